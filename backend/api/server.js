@@ -4,6 +4,17 @@ const { verifyVC } = require('./verifyVC');
 const { fetchVC } = require('./fetchVC');
 const cors = require('cors');
 
+// ZKP checks run in process through the zkp-backend addon
+// (`cd zkp-backend/node && npm run build`); without it every credential
+// with proofs fails its ZKP check.
+const ADDON_UNAVAILABLE = { verified: false, status: 'failed', error: 'addon unavailable', checks: [] };
+let verifyVcProofs = () => ADDON_UNAVAILABLE;
+try {
+  ({ verifyVcProofs } = require('zkp-node'));
+} catch (error) {
+  console.warn('⚠️ zkp-node addon not built, /verify-vc will fail ZKP checks:', error.message);
+}
+
 const app = express();
 const port = 5000;
 
@@ -33,6 +44,9 @@ app.post('/verify-vc', async (req, res) => {
       message: 'VC verification complete.',
       issuer: verificationResult.issuer,
       holder: verificationResult.holder,
+      // { verified, status, checks: [{ name, verified, error? }] }, or
+      // ADDON_UNAVAILABLE; null for certificates (they carry no proofs)
+      zkp: isCertificate ? null : verifyVcProofs(vc),
     });
   } catch (error) {
     console.error('Error verifying VC:', error);
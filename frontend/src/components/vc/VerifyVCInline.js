@@ -3,7 +3,7 @@ import { verifyVCWithServer } from "../../utils/verifyVc";
 import VCViewer from "./VCViewer";
 import VerificationBox from "./VerifyVCTab-Enhanced";
 import ZKPVerificationBox from "./ZKPVerificationBox";
-import { extractZKPProof, extractTxHashCommitment, extractPurchaseTxHashCommitment, verifyTxHashCommitment, verifyBindingTagsMatch, verifyTransactionOnChain, verifyPurchaseTransactionOnChain } from "../../utils/verifyZKP";
+import { extractZKPProof, extractTxHashCommitment, verifyVcProofs, extractPurchaseTxHashCommitment, verifyTxHashCommitment, verifyBindingTagsMatch, verifyTransactionOnChain, verifyPurchaseTransactionOnChain } from "../../utils/verifyZKP";
 import { verifyCommitmentMatch } from "../../utils/commitmentUtils";
 import { ethers } from "ethers";
 import ProductEscrowABI from "../../abis/ProductEscrow_Initializer.json";
@@ -70,39 +70,24 @@ const VerifyVCInline = ({ vc, cid, provider, contractAddress }) => {
     const stageLabel = vc?.credentialSubject?.previousCredential ? 'post-purchase VC (Stage 2/3)' : 'listing VC (Stage 0)';
     console.log(`[Flow][Audit] Running ZKP verification for ${stageLabel}.`);
     try {
-      const { commitment, proof, protocol, proofType, bindingTag } = extractZKPProof(vc);
-      const endpoint =
-        proofType === "zkRangeProof-v1" || protocol === "bulletproofs-pedersen"
-          ? "verify-value-commitment"
-          : "verify";
+      // One call verifies the price proof and both TX hash commitments against
+      // the binding tags recomputed from this VC.
       const zkpBackendUrl = process.env.REACT_APP_ZKP_BACKEND_URL || 'http://localhost:5010';
-      
-      // ✅ Include binding tag in verification request if available
-      const requestBody = {
-        commitment,
-        proof,
-        ...(bindingTag && { binding_tag_hex: bindingTag }),
-      };
-      
-      const res = await fetch(`${zkpBackendUrl}/zkp/${endpoint}`, {
-        method: "POST",
-        headers: { "Content-Type": "application/json" },
-        body: JSON.stringify(requestBody),
-      });
-      const data = await res.json();
+      const data = await verifyVcProofs(vc, zkpBackendUrl, contractAddress);
       setZkpResult(data);
-      
+
       if (data?.verified) {
-        console.log(`[Flow][Audit] ZKP verified ✔︎ – commitment proves the hidden price is within the allowed range for the ${stageLabel}.`);
+        console.log(`[Flow][Audit] ZKP verified ✔︎ – every proof in the ${stageLabel} validates against its binding tag.`);
       } else {
-        console.warn(`[Flow][Audit] ZKP verification failed ✖︎ – proof does not validate for the ${stageLabel}.`, data);
+        console.warn(`[Flow][Audit] ZKP verification failed ✖︎ – ${data?.error || "a proof does not validate"} for the ${stageLabel}.`, data);
       }
-      
-      // Log binding tag verification
-      if (bindingTag) {
-        console.log("✅ ZKP verification with binding tag:", bindingTag);
-      } else {
-        console.warn("⚠️ ZKP verification without binding tag (backward compatible)");
+
+      const unbound = (data?.checks || []).filter((check) => check.binding && check.binding !== "recomputed");
+      if (unbound.length > 0) {
+        console.warn("⚠️ Binding tags not recomputed (VC has no binding context):", unbound.map((check) => check.name));
+      }
+      for (const check of data?.signatures || []) {
+        console.log(`[Flow][Audit] ${check.name}: ${check.verified ? `signed by ${check.signer}` : check.error}`);
       }
     } catch (err) {
       console.error("❌ ZKP verify error:", err);
@@ -299,6 +299,66 @@ export function extractPurchaseTxHashCommitment(vc) {
   };
 }
 
+/**
+ * Verifies every proof in a VC with one ZKP backend call (`/zkp/verify-vc`).
+ * The backend recomputes each binding tag from the VC's binding context, so
+ * a proof copied from another product or stage fails even with its own tag.
+ * It also checks the issuer and holder EIP-712 signatures, returned apart as
+ * `signatures`; a VC signed by its issuer only (before Stage 3) is checked as
+ * a certificate, without the holder's. `verified` and `status` are the
+ * backend's verdict, taken as is: a proof whose tag could not be recomputed
+ * (an older VC) may come from another VC, so such a VC is never `verified`;
+ * its `status` is "unbound".
+ * @param {Object} vc - The whole VC, as stored
+ * @param {string} zkpBackendUrl - The ZKP backend URL (default: http://localhost:5010)
+ * @param {string} [contractAddress] - Escrow the signatures may be bound to (verifyingContract)
+ * @returns {Promise<{verified: boolean, status: "verified"|"unbound"|"failed", checks: Array<{name: string, verified: boolean, error?: string, binding?: "recomputed"|"stored"|"unbound", binding_tag?: string}>, signatures: Array<{name: string, verified: boolean, error?: string, signer?: string}>, error?: string}>}
+ */
+export async function verifyVcProofs(vc, zkpBackendUrl = 'http://localhost:5010', contractAddress = null) {
+  try {
+    const params = new URLSearchParams();
+    if (contractAddress) params.set("contract", contractAddress);
+    const proofs = Array.isArray(vc?.proof) ? vc.proof : [vc?.proof].filter(Boolean);
+    if (proofs.length < 2) params.set("certificate", "true");
+    const query = params.toString() ? `?${params}` : "";
+    const response = await fetch(`${zkpBackendUrl}/zkp/verify-vc${query}`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify(vc),
+    });
+
+    if (!response.ok) {
+      const errorData = await response.json().catch(() => ({}));
+      throw new Error(errorData.error || `HTTP ${response.status}`);
+    }
+
+    const report = await response.json();
+    const isSignature = (check) => check.name.endsWith("_signature");
+    // Only explains the verdict; it is never re-derived from the checks.
+    const failed = report.checks.find((check) => !check.verified);
+    const unbound = report.checks.find((check) => check.binding && check.binding !== "recomputed");
+    return {
+      verified: report.verified === true,
+      status: report.status,
+      checks: report.checks.filter((check) => !isSignature(check)),
+      signatures: report.checks.filter(isSignature),
+      error: report.status === "failed"
+        ? `${failed?.name ?? "verify-vc"}: ${failed?.error || "proof verification failed"}`
+        : report.status === "unbound"
+          ? `${unbound?.name ?? "verify-vc"}: not bound to this VC`
+          : undefined,
+    };
+  } catch (error) {
+    return {
+      verified: false,
+      status: "failed",
+      checks: [],
+      signatures: [],
+      error: error.message || "Failed to verify VC proofs",
+    };
+  }
+}
+
 /**
  * Verifies a TX hash commitment proof using the ZKP backend (Step 6).
  * Feature 2: Supports optional binding tag for verification.
@@ -0,0 +1,86 @@
+//! Why a call failed.
+
+use std::fmt;
+
+use bulletproof_demo::api::ErrorResponse;
+use bulletproof_demo::zk::ZkError;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The server (or the mock) rejected the request with
+    /// `{ "error", "code" }`; `code` is the server's stable identifier.
+    Api { status: u16, code: String, message: String },
+    /// No response within the configured timeout.
+    Timeout,
+    /// The request could not be sent or its response not read.
+    Transport(reqwest::Error),
+    /// A successful response whose body is not the expected one.
+    Decode(String),
+    /// The client configuration is unusable (base URL, API key).
+    Config(String),
+}
+
+impl Error {
+    /// The server's error code, for [`Error::Api`].
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::Api { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+
+    /// A non-2xx response. Bodies without a code (proxies, load balancers)
+    /// get `http_error`.
+    pub(crate) fn from_response(status: u16, body: &str) -> Self {
+        match serde_json::from_str::<ErrorResponse>(body) {
+            Ok(ErrorResponse { error, code }) => Self::Api { status, code, message: error },
+            Err(_) => Self::Api { status, code: "http_error".into(), message: body.into() },
+        }
+    }
+
+    /// What the server would have answered for `e`. A fresh proof failing
+    /// its own verification is a server-side fault there too.
+    pub(crate) fn from_zk(e: ZkError) -> Self {
+        match e {
+            ZkError::TranscriptMismatch => Self::Api {
+                status: 500,
+                code: "self_check_failed".into(),
+                message: "proof failed its own verification".into(),
+            },
+            e => Self::Api { status: e.http_status(), code: e.code().into(), message: e.to_string() },
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            Self::Timeout
+        } else if e.is_decode() {
+            Self::Decode(e.to_string())
+        } else {
+            Self::Transport(e)
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Api { status, code, message } => write!(f, "{status} {code}: {message}"),
+            Self::Timeout => write!(f, "no response within the timeout"),
+            Self::Transport(e) => write!(f, "request failed: {e}"),
+            Self::Decode(detail) => write!(f, "unexpected response body: {detail}"),
+            Self::Config(detail) => write!(f, "invalid client configuration: {detail}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Typed async client for the ZKP backend's `/v1` API.
+//!
+//! Request and response bodies are the server's own ([`api`]), so a field
+//! added there reaches every caller at compile time. Each endpoint is one
+//! method and every failure is an [`Error`]. Rate limiting (429), a
+//! saturated proof pool (503), connection failures and timeouts are retried
+//! per [`RetryPolicy`], honouring `Retry-After`; except that verifications
+//! are not repeated after a timeout. The server may have verified and
+//! recorded the proof before the answer was lost, and with a replay registry
+//! (`replay_scope = "once"`) the repeat would report the caller's own first
+//! presentation as a replay. Such a timeout is returned as [`Error::Timeout`]
+//! for the caller to decide.
+//!
+//! [`Client::mock`] answers in process with the library code the server
+//! runs, for tests of services that have no server to talk to.
+//!
+//! ```no_run
+//! use zkp_client::api::ValueCommitmentRequest;
+//! use zkp_client::{Client, ClientConfig};
+//!
+//! # async fn run() -> Result<(), zkp_client::Error> {
+//! let client = Client::new(ClientConfig::new("http://localhost:5010"))?;
+//! let request = ValueCommitmentRequest { value: 1500, blinding_hex: None, binding_tag_hex: None, bit_range: 64 };
+//! let commitment = client.create_value_commitment(&request).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod error;
+
+use std::time::Duration;
+
+pub use bulletproof_demo::api;
+use api::{
+    IssuedList, IssuedQuery, PurgeResponse, RangeProofRequest, RangeProofResponse, RangeProofVerifyRequest, TxHashCommitmentRequest, TxHashCommitmentResponse,
+    TxHashCommitmentVerifyRequest, ValueCommitmentRequest, ValueCommitmentResponse, ValueCommitmentVerifyRequest,
+    VerifyResponse,
+};
+use bulletproof_demo::zk::ZkError;
+use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::debug;
+
+pub use error::Error;
+
+/// How often and how patiently failed calls are repeated. Verifications
+/// time out without retries whatever the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first one.
+    pub max_retries: u32,
+    /// Wait before the first retry; doubled for each further one.
+    pub initial_backoff: Duration,
+    /// Cap on any single wait, including the server's `Retry-After`.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 3, initial_backoff: Duration::from_millis(200), max_backoff: Duration::from_secs(5) }
+    }
+}
+
+impl RetryPolicy {
+    /// Fail on the first error.
+    pub fn none() -> Self {
+        Self { max_retries: 0, ..Self::default() }
+    }
+
+    fn backoff(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let doubled = self.initial_backoff.saturating_mul(1 << retry.min(16));
+        retry_after.unwrap_or(doubled).min(self.max_backoff)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Scheme, host and port of the server, e.g. `http://localhost:5010`.
+    pub base_url: String,
+    /// Sent as `X-Api-Key`; the server needs one for generation when it
+    /// runs with `auth`.
+    pub api_key: Option<String>,
+    /// Sent as `X-Zkp-Verifier`: who verifications are presented to, for a
+    /// server whose replay registry accepts each proof once per verifier.
+    pub verifier: Option<String>,
+    /// Limit on each attempt, from connecting to reading the body.
+    pub timeout: Duration,
+    pub retry: RetryPolicy,
+}
+
+impl ClientConfig {
+    /// Defaults: no API key or verifier, 30 s per attempt, [`RetryPolicy::default`].
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            api_key: None,
+            verifier: None,
+            timeout: Duration::from_secs(30),
+            retry: RetryPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    backend: Backend,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    Http { http: reqwest::Client, base_url: String, retry: RetryPolicy },
+    Mock,
+}
+
+impl Client {
+    pub fn new(config: ClientConfig) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = &config.api_key {
+            let key = HeaderValue::from_str(key).map_err(|_| Error::Config("api_key is not a valid header value".into()))?;
+            headers.insert("x-api-key", key);
+        }
+        if let Some(verifier) = &config.verifier {
+            let verifier = HeaderValue::from_str(verifier).map_err(|_| Error::Config("verifier is not a valid header value".into()))?;
+            headers.insert("x-zkp-verifier", verifier);
+        }
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .default_headers(headers)
+            .build()
+            .map_err(|e| Error::Config(e.to_string()))?;
+        let base_url = config.base_url.trim_end_matches('/').to_string();
+        if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+            return Err(Error::Config(format!("base_url {base_url:?} must start with http:// or https://")));
+        }
+        Ok(Self { backend: Backend::Http { http, base_url, retry: config.retry } })
+    }
+
+    /// A client that proves and verifies in process, with the server's
+    /// results and error codes, and never touches the network.
+    pub fn mock() -> Self {
+        Self { backend: Backend::Mock }
+    }
+
+    /// `POST /v1/value-commitments`
+    pub async fn create_value_commitment(&self, req: &ValueCommitmentRequest) -> Result<ValueCommitmentResponse, Error> {
+        self.call("/v1/value-commitments", req, Timeouts::Retry, || req.prove()).await
+    }
+
+    /// `POST /v1/value-commitments/verify`
+    pub async fn verify_value_commitment(&self, req: &ValueCommitmentVerifyRequest) -> Result<VerifyResponse, Error> {
+        self.call("/v1/value-commitments/verify", req, Timeouts::Fail, || req.verify()).await
+    }
+
+    /// `POST /v1/tx-hash-commitments`
+    pub async fn create_tx_hash_commitment(&self, req: &TxHashCommitmentRequest) -> Result<TxHashCommitmentResponse, Error> {
+        self.call("/v1/tx-hash-commitments", req, Timeouts::Retry, || req.prove()).await
+    }
+
+    /// `POST /v1/tx-hash-commitments/verify`
+    pub async fn verify_tx_hash_commitment(&self, req: &TxHashCommitmentVerifyRequest) -> Result<VerifyResponse, Error> {
+        self.call("/v1/tx-hash-commitments/verify", req, Timeouts::Fail, || req.verify()).await
+    }
+
+    /// `POST /v1/range-proofs`
+    pub async fn create_range_proof(&self, req: &RangeProofRequest) -> Result<RangeProofResponse, Error> {
+        self.call("/v1/range-proofs", req, Timeouts::Retry, || req.prove()).await
+    }
+
+    /// `POST /v1/range-proofs/verify`
+    pub async fn verify_range_proof(&self, req: &RangeProofVerifyRequest) -> Result<VerifyResponse, Error> {
+        self.call("/v1/range-proofs/verify", req, Timeouts::Fail, || req.verify()).await
+    }
+
+    /// `GET /v1/issued`: the commitments the server recorded, newest first.
+    /// The mock keeps no record.
+    pub async fn list_issued(&self, query: &IssuedQuery) -> Result<IssuedList, Error> {
+        self.issued(Method::GET, query).await
+    }
+
+    /// `DELETE /v1/issued`: remove the matching records.
+    pub async fn purge_issued(&self, query: &IssuedQuery) -> Result<PurgeResponse, Error> {
+        self.issued(Method::DELETE, query).await
+    }
+
+    async fn issued<T: DeserializeOwned>(&self, method: Method, query: &IssuedQuery) -> Result<T, Error> {
+        match &self.backend {
+            Backend::Http { http, base_url, retry } => {
+                let url = format!("{base_url}/v1/issued");
+                send(|| http.request(method.clone(), &url).query(query), &url, retry, Timeouts::Retry).await
+            },
+            Backend::Mock => Err(Error::Api {
+                status: 404,
+                code: "issued_store_disabled".into(),
+                message: "the mock keeps no issued commitment record".into(),
+            }),
+        }
+    }
+
+    async fn call<B, T>(&self, path: &str, body: &B, timeouts: Timeouts, local: impl FnOnce() -> Result<T, ZkError>) -> Result<T, Error>
+    where
+        B: Serialize,
+        T: DeserializeOwned,
+    {
+        match &self.backend {
+            Backend::Http { http, base_url, retry } => {
+                let url = format!("{base_url}{path}");
+                send(|| http.post(&url).json(body), &url, retry, timeouts).await
+            },
+            Backend::Mock => local().map_err(Error::from_zk),
+        }
+    }
+}
+
+/// Whether a call that timed out is sent again. A connection that failed
+/// never reached the server and is retried either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Timeouts {
+    Retry,
+    /// The server may have handled the call already, as for verifications.
+    Fail,
+}
+
+/// 429 and 503 mean "not now"; everything else is the answer.
+fn retryable(status: StatusCode) -> bool {
+    matches!(status, StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE)
+}
+
+/// Send the request `build` makes, building it afresh for each attempt.
+async fn send<T: DeserializeOwned>(
+    build: impl Fn() -> RequestBuilder,
+    url: &str,
+    policy: &RetryPolicy,
+    timeouts: Timeouts,
+) -> Result<T, Error> {
+    let mut retry = 0;
+    loop {
+        let (error, retry_after) = match build().send().await {
+            Ok(response) if response.status().is_success() => return Ok(response.json().await?),
+            Ok(response) => {
+                let status = response.status();
+                let retry_after = response
+                    .headers()
+                    .get(RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok()?.parse().ok())
+                    .map(Duration::from_secs);
+                let error = Error::from_response(status.as_u16(), &response.text().await?);
+                if !retryable(status) {
+                    return Err(error);
+                }
+                (error, retry_after)
+            },
+            Err(e) if e.is_connect() || (e.is_timeout() && timeouts == Timeouts::Retry) => (Error::from(e), None),
+            Err(e) => return Err(e.into()),
+        };
+        if retry >= policy.max_retries {
+            return Err(error);
+        }
+        let wait = policy.backoff(retry, retry_after);
+        debug!(url, retry = retry + 1, wait_ms = wait.as_millis() as u64, %error, "retrying");
+        tokio::time::sleep(wait).await;
+        retry += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let policy = RetryPolicy { max_retries: 5, initial_backoff: Duration::from_millis(100), max_backoff: Duration::from_secs(1) };
+        let waits: Vec<_> = (0..5).map(|retry| policy.backoff(retry, None).as_millis()).collect();
+        assert_eq!(waits, [100, 200, 400, 800, 1000]);
+        assert_eq!(policy.backoff(0, Some(Duration::from_secs(60))), Duration::from_secs(1));
+        assert_eq!(policy.backoff(3, Some(Duration::ZERO)), Duration::ZERO);
+    }
+}
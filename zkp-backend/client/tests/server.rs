@@ -0,0 +1,166 @@
+//! The client against an in-process server, and the mock against both.
+
+use std::net::{SocketAddr, TcpListener};
+use std::time::{Duration, Instant};
+
+use bulletproof_demo::server::config::{EndpointGroup, ReplayScope};
+use bulletproof_demo::server::{self, Config};
+use zkp_client::api::{
+    IssuedQuery, RangeProofRequest, Scheme, TxHashCommitmentRequest, TxHashCommitmentVerifyRequest, ValueCommitmentRequest,
+    ValueCommitmentVerifyRequest,
+};
+use zkp_client::{Client, ClientConfig, Error, RetryPolicy};
+
+/// Serve `config` on a free port from its own actix system.
+fn serve(config: Config) -> String {
+    let (addr_tx, addr_rx) = std::sync::mpsc::channel::<SocketAddr>();
+    std::thread::spawn(move || {
+        actix_web::rt::System::new().block_on(async move {
+            let (server, addrs) = server::start(config).unwrap();
+            addr_tx.send(addrs[0]).unwrap();
+            server.await
+        })
+    });
+    format!("http://{}", addr_rx.recv().unwrap())
+}
+
+fn config() -> Config {
+    Config {
+        listen: SocketAddr::from(([127, 0, 0, 1], 0)),
+        workers: 1,
+        proof_workers: 2,
+        endpoints: vec![EndpointGroup::V1],
+        ..Config::default()
+    }
+}
+
+fn tag(byte: u8) -> Option<String> {
+    Some(hex::encode([byte; 32]))
+}
+
+async fn round_trip(client: &Client) {
+    let value = ValueCommitmentRequest { value: 1500, blinding_hex: None, binding_tag_hex: tag(1), bit_range: 32 };
+    let value = client.create_value_commitment(&value).await.unwrap();
+    let mut verify = ValueCommitmentVerifyRequest {
+        commitment: value.commitment,
+        proof: value.proof,
+        binding_tag_hex: tag(1),
+        bit_range: 32,
+    };
+    assert!(client.verify_value_commitment(&verify).await.unwrap().verified);
+    verify.binding_tag_hex = tag(2);
+    assert!(!client.verify_value_commitment(&verify).await.unwrap().verified);
+
+    let tx_hash = TxHashCommitmentRequest { tx_hash: hex::encode([0x3c; 32]), binding_tag_hex: tag(1) };
+    let tx_hash = client.create_tx_hash_commitment(&tx_hash).await.unwrap();
+    let verify = TxHashCommitmentVerifyRequest { commitment: tx_hash.commitment, proof: tx_hash.proof, binding_tag_hex: tag(1) };
+    assert!(client.verify_tx_hash_commitment(&verify).await.unwrap().verified);
+
+    let range = RangeProofRequest { tx_hash: hex::encode([0x3c; 32]), scheme: Scheme::BulletproofsPlus };
+    let range = client.create_range_proof(&range).await.unwrap();
+    let verify = serde_json::from_value(serde_json::to_value(range).unwrap()).unwrap();
+    assert!(client.verify_range_proof(&verify).await.unwrap().verified);
+}
+
+async fn rejection(client: &Client) -> (u16, String) {
+    let bad = ValueCommitmentVerifyRequest { commitment: "00".repeat(32), proof: "zz".into(), binding_tag_hex: None, bit_range: 64 };
+    match client.verify_value_commitment(&bad).await {
+        Err(Error::Api { status, code, .. }) => (status, code),
+        other => panic!("expected a rejection, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn endpoints_round_trip_through_the_server() {
+    let client = Client::new(ClientConfig::new(serve(config()))).unwrap();
+    round_trip(&client).await;
+    assert_eq!(rejection(&client).await, (400, "invalid_parameters".into()));
+}
+
+#[tokio::test]
+async fn mock_matches_the_server() {
+    let client = Client::mock();
+    round_trip(&client).await;
+    assert_eq!(rejection(&client).await, (400, "invalid_parameters".into()));
+}
+
+#[tokio::test]
+async fn issued_commitments_are_listed_and_purged() {
+    let dir = tempfile::tempdir().unwrap();
+    let client = Client::new(ClientConfig::new(serve(Config { issued_store: Some(dir.path().into()), ..config() }))).unwrap();
+    round_trip(&client).await;
+
+    let all = client.list_issued(&IssuedQuery::default()).await.unwrap().records;
+    let kinds: Vec<_> = all.iter().map(|r| r.kind.as_str()).collect();
+    assert_eq!(kinds, ["range_proof", "tx_hash_commitment", "value_commitment"]);
+    let tagged = IssuedQuery { binding_tag: tag(1), ..IssuedQuery::default() };
+    assert_eq!(client.list_issued(&tagged).await.unwrap().records.len(), 2);
+    assert_eq!(client.purge_issued(&tagged).await.unwrap().purged, 2);
+    assert_eq!(client.purge_issued(&IssuedQuery::default()).await.unwrap_err().code(), Some("invalid_parameters"));
+
+    let error = Client::mock().list_issued(&IssuedQuery::default()).await.unwrap_err();
+    assert_eq!(error.code(), Some("issued_store_disabled"));
+}
+
+#[tokio::test]
+async fn replays_are_reported_per_verifier() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = serve(Config { replay_store: Some(dir.path().into()), replay_scope: ReplayScope::PerVerifier, ..config() });
+    let client = |verifier: &str| Client::new(ClientConfig { verifier: Some(verifier.into()), ..ClientConfig::new(&url) }).unwrap();
+
+    let create = TxHashCommitmentRequest { tx_hash: hex::encode([0x3c; 32]), binding_tag_hex: tag(1) };
+    let created = client("issuer").create_tx_hash_commitment(&create).await.unwrap();
+    let verify = TxHashCommitmentVerifyRequest { commitment: created.commitment, proof: created.proof, binding_tag_hex: tag(1) };
+    let accepted = |verifier: &'static str| {
+        let (client, verify) = (client(verifier), verify.clone());
+        async move { client.verify_tx_hash_commitment(&verify).await.unwrap().replay.unwrap().accepted }
+    };
+    assert!(accepted("alice").await);
+    assert!(accepted("bob").await);
+    assert!(!accepted("alice").await);
+    assert_eq!(Client::mock().verify_tx_hash_commitment(&verify).await.unwrap().replay, None);
+}
+
+#[tokio::test]
+async fn rate_limits_are_retried_after_their_wait() {
+    // One verification per second and no burst.
+    let url = serve(Config { verify_rate: Some(60), verify_burst: Some(1), ..config() });
+    let verify = TxHashCommitmentVerifyRequest { commitment: "00".repeat(32), proof: "00".into(), binding_tag_hex: None };
+
+    let impatient = Client::new(ClientConfig { retry: RetryPolicy::none(), ..ClientConfig::new(&url) }).unwrap();
+    let _ = impatient.verify_tx_hash_commitment(&verify).await;
+    let error = impatient.verify_tx_hash_commitment(&verify).await.unwrap_err();
+    assert_eq!(error.code(), Some("rate_limited"));
+
+    let patient = Client::new(ClientConfig::new(&url)).unwrap();
+    let started = Instant::now();
+    let error = patient.verify_tx_hash_commitment(&verify).await.unwrap_err();
+    assert_ne!(error.code(), Some("rate_limited"), "{error}");
+    assert!(started.elapsed() >= Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn silent_servers_time_out() {
+    // Accepts connections and never answers.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let retry = RetryPolicy { max_retries: 1, initial_backoff: Duration::from_millis(10), ..RetryPolicy::default() };
+    let client = Client::new(ClientConfig { timeout: Duration::from_millis(200), retry, ..ClientConfig::new(url) }).unwrap();
+
+    let started = Instant::now();
+    let create = TxHashCommitmentRequest { tx_hash: hex::encode([0x3c; 32]), binding_tag_hex: None };
+    assert!(matches!(client.create_tx_hash_commitment(&create).await, Err(Error::Timeout)));
+    assert!(started.elapsed() >= Duration::from_millis(400), "the timeout is retried once");
+
+    // A verification may have been recorded as presented, so it is not repeated.
+    let started = Instant::now();
+    let verify = TxHashCommitmentVerifyRequest { commitment: "00".repeat(32), proof: "00".into(), binding_tag_hex: None };
+    assert!(matches!(client.verify_tx_hash_commitment(&verify).await, Err(Error::Timeout)));
+    assert!(started.elapsed() < Duration::from_millis(400), "the timeout is not retried");
+    drop(listener);
+}
+
+#[test]
+fn base_urls_need_a_scheme() {
+    assert!(matches!(Client::new(ClientConfig::new("localhost:5010")), Err(Error::Config(_))));
+}
@@ -0,0 +1,42 @@
+// Run `npm run build` (or `build:debug`) first.
+const test = require("node:test");
+const assert = require("node:assert");
+const zkp = require("..");
+
+// Made by the native `zkp` CLI; shared with the WebAssembly tests.
+const server = require("../../wasm/tests/server_proofs.json");
+const TAG = "5a".repeat(32);
+const OTHER_TAG = "00".repeat(32);
+
+test("server proofs verify in process", () => {
+  assert.deepStrictEqual(zkp.verifyValue(server.value_commitment), { verified: true });
+  assert.deepStrictEqual(zkp.verifyValue({ ...server.value_commitment, binding_tag_hex: OTHER_TAG }), { verified: false });
+  assert.deepStrictEqual(zkp.verifyTxHash(server.tx_hash_commitment), { verified: true });
+  assert.deepStrictEqual(zkp.verifyRange(server.range_proof), { verified: true });
+});
+
+test("commitments match the server", () => {
+  const value = zkp.commitValue({ value: 42, blinding_hex: "07" + "00".repeat(31), binding_tag_hex: TAG });
+  assert.strictEqual(value.commitment, server.value_commitment.commitment);
+  assert.deepStrictEqual(zkp.verifyValue({ ...value, binding_tag_hex: TAG }), { verified: true });
+
+  const txHash = zkp.commitTxHash({ tx_hash: "3c".repeat(32), binding_tag_hex: TAG });
+  assert.deepStrictEqual(zkp.verifyTxHash({ ...txHash, binding_tag_hex: TAG }), { verified: true });
+});
+
+test("credential proofs are reported one by one", () => {
+  const zkpProof = { ...server.value_commitment, bindingTag: TAG, proofType: "zkRangeProof-v1" };
+  const vc = { credentialSubject: { price: JSON.stringify({ hidden: true, zkpProof }) } };
+  const check = { name: "price_proof", verified: true, binding: "stored", binding_tag: TAG };
+  // Nothing in the credential recomputes the tag, so the proof may belong to another one.
+  assert.deepStrictEqual(zkp.verifyVcProofs(vc), { verified: false, status: "unbound", checks: [check] });
+
+  zkpProof.bindingTag = OTHER_TAG;
+  vc.credentialSubject.price = JSON.stringify({ hidden: true, zkpProof });
+  assert.strictEqual(zkp.verifyVcProofs(vc).status, "failed");
+});
+
+test("errors carry the server codes", () => {
+  assert.throws(() => zkp.verifyValue({ ...server.value_commitment, proof: "zz" }), { code: "invalid_parameters" });
+  assert.throws(() => zkp.commitTxHash({}), { code: "invalid_request" });
+});
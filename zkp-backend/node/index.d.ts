@@ -0,0 +1,73 @@
+// Bodies are those of the matching `/v1` endpoints of zkp-backend.
+
+export interface VerifyResponse {
+  verified: boolean;
+}
+
+export interface ValueCommitmentRequest {
+  value: number;
+  blinding_hex?: string;
+  binding_tag_hex?: string;
+  bit_range?: 8 | 16 | 32 | 64;
+}
+
+export interface ValueCommitmentResponse {
+  commitment: string;
+  proof: string;
+  blinding_hex: string;
+  bit_range: number;
+}
+
+export interface ValueCommitmentVerifyRequest {
+  commitment: string;
+  proof: string;
+  binding_tag_hex?: string;
+  bit_range?: 8 | 16 | 32 | 64;
+}
+
+export interface TxHashCommitmentRequest {
+  tx_hash: string;
+  binding_tag_hex?: string;
+}
+
+export interface TxHashCommitmentResponse {
+  commitment: string;
+  proof: string;
+}
+
+export interface TxHashCommitmentVerifyRequest {
+  commitment: string;
+  proof: string;
+  binding_tag_hex?: string;
+}
+
+export interface RangeProofVerifyRequest {
+  commitments: string[];
+  proof: string;
+  scheme?: "bulletproofs_plus" | "bulletproofs";
+}
+
+export interface VcReport {
+  /** True only when `status` is "verified". */
+  verified: boolean;
+  /** "unbound": every check passed, but a proof (the price proof or a
+   * transaction hash commitment) is bound only to its stored tag, or to
+   * none, and may come from another credential. */
+  status: "verified" | "unbound" | "failed";
+  checks: {
+    name: string;
+    verified: boolean;
+    error?: string;
+    /** How the proof's binding tag was established. */
+    binding?: "recomputed" | "stored" | "unbound";
+    binding_tag?: string;
+  }[];
+}
+
+/** Errors thrown by every function carry the server's error `code`. */
+export function commitValue(body: ValueCommitmentRequest): ValueCommitmentResponse;
+export function verifyValue(body: ValueCommitmentVerifyRequest): VerifyResponse;
+export function commitTxHash(body: TxHashCommitmentRequest): TxHashCommitmentResponse;
+export function verifyTxHash(body: TxHashCommitmentVerifyRequest): VerifyResponse;
+export function verifyRange(body: RangeProofVerifyRequest): VerifyResponse;
+export function verifyVcProofs(vc: object): VcReport;
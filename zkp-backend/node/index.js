@@ -0,0 +1,2 @@
+// Built by `npm run build` (see scripts/build.js).
+module.exports = require("./zkp-node.node");
@@ -0,0 +1,18 @@
+// Build the addon and copy it to ./zkp-node.node, where index.js loads it.
+// `node scripts/build.js --debug` skips the release profile.
+const { execFileSync } = require("child_process");
+const fs = require("fs");
+const path = require("path");
+
+const debug = process.argv.includes("--debug");
+const root = path.join(__dirname, "..");
+
+execFileSync("cargo", ["build", ...(debug ? [] : ["--release"])], { cwd: root, stdio: "inherit" });
+
+const library = {
+  win32: "zkp_node.dll",
+  darwin: "libzkp_node.dylib",
+}[process.platform] || "libzkp_node.so";
+const built = path.join(root, "target", debug ? "debug" : "release", library);
+fs.copyFileSync(built, path.join(root, "zkp-node.node"));
+console.log(`zkp-node.node <- ${path.relative(root, built)}`);
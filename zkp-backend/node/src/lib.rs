@@ -0,0 +1,78 @@
+//! The verifier and commitment helpers as a Node.js addon.
+//!
+//! Each function takes the same object as the JSON body of its `/v1`
+//! endpoint and returns the same response object, so `backend/api` can run
+//! the checks in process instead of calling the server. Failures throw an
+//! `Error` whose `code` is the server's error code.
+//!
+//! ```js
+//! const { verifyVcProofs } = require("zkp-node");
+//! verifyVcProofs(vc).verified;
+//! ```
+
+use bulletproof_demo::api::{
+    RangeProofVerifyRequest, TxHashCommitmentRequest, TxHashCommitmentVerifyRequest, ValueCommitmentRequest,
+    ValueCommitmentVerifyRequest,
+};
+use bulletproof_demo::vc;
+use bulletproof_demo::zk::ZkError;
+use napi::Error;
+use napi_derive::napi;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+type Result<T> = napi::Result<T, String>;
+
+fn error(message: impl ToString, code: &str) -> Error<String> {
+    Error::new(code.to_string(), message.to_string())
+}
+
+fn zk_error(e: ZkError) -> Error<String> {
+    error(&e, e.code())
+}
+
+fn request<T: DeserializeOwned>(body: Value) -> Result<T> {
+    serde_json::from_value(body).map_err(|e| error(e, "invalid_request"))
+}
+
+fn response(body: impl Serialize) -> Result<Value> {
+    Ok(serde_json::to_value(body).expect("response bodies always serialize"))
+}
+
+/// Commit to a value with a range proof (`POST /v1/value-commitments`).
+#[napi]
+pub fn commit_value(body: Value) -> Result<Value> {
+    response(request::<ValueCommitmentRequest>(body)?.prove().map_err(zk_error)?)
+}
+
+/// Verify a value commitment's range proof (`POST /v1/value-commitments/verify`).
+#[napi]
+pub fn verify_value(body: Value) -> Result<Value> {
+    response(request::<ValueCommitmentVerifyRequest>(body)?.verify().map_err(zk_error)?)
+}
+
+/// Commit to a transaction hash (`POST /v1/tx-hash-commitments`).
+#[napi]
+pub fn commit_tx_hash(body: Value) -> Result<Value> {
+    response(request::<TxHashCommitmentRequest>(body)?.prove().map_err(zk_error)?)
+}
+
+/// Verify a transaction hash commitment (`POST /v1/tx-hash-commitments/verify`).
+#[napi]
+pub fn verify_tx_hash(body: Value) -> Result<Value> {
+    response(request::<TxHashCommitmentVerifyRequest>(body)?.verify().map_err(zk_error)?)
+}
+
+/// Verify a four-limb range proof (`POST /v1/range-proofs/verify`).
+#[napi]
+pub fn verify_range(body: Value) -> Result<Value> {
+    response(request::<RangeProofVerifyRequest>(body)?.verify().map_err(zk_error)?)
+}
+
+/// Verify every zero-knowledge proof embedded in a Verifiable Credential:
+/// `{ verified, checks: [{ name, verified, error? }] }`.
+#[napi]
+pub fn verify_vc_proofs(vc: Value) -> Result<Value> {
+    response(vc::verify_proofs(&vc))
+}
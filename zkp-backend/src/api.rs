@@ -0,0 +1,422 @@
+//! Request and response bodies of the versioned `/v1` HTTP API.
+//!
+//! Every resource uses the same field names: hex strings (an optional `0x`
+//! prefix is accepted) for `commitment(s)`, `proof`, `blinding_hex` and
+//! `binding_tag_hex`, and a `{ "verified": bool }` body for every
+//! verification. Errors are always `{ "error", "code" }`.
+
+use std::ops::RangeInclusive;
+
+use curve25519_dalek_ng::ristretto::CompressedRistretto as NgCompressed;
+use curve25519_dalek_ng::scalar::Scalar;
+use hex::FromHex;
+use serde::{Deserialize, Serialize};
+
+use crate::zk::r1cs_statement::random_blinding;
+use crate::zk::{bp_plus_pedersen, pedersen, txid_pedersen_proof, ZkError};
+
+pub use crate::zk::generators::Scheme;
+
+fn default_bit_range() -> usize {
+    64
+}
+
+fn default_scheme() -> Scheme {
+    Scheme::BulletproofsPlus
+}
+
+/// `POST /v1/value-commitments`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueCommitmentRequest {
+    pub value: u64,
+    /// 32-byte blinding factor; a random one is drawn if omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blinding_hex: Option<String>,
+    /// 32-byte tag bound into the proof transcript.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_tag_hex: Option<String>,
+    /// 8, 16, 32 or 64.
+    #[serde(default = "default_bit_range")]
+    pub bit_range: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueCommitmentResponse {
+    pub commitment: String,
+    pub proof: String,
+    /// Needed to open the commitment or combine it with others later.
+    pub blinding_hex: String,
+    pub bit_range: usize,
+}
+
+/// `POST /v1/value-commitments/verify`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueCommitmentVerifyRequest {
+    pub commitment: String,
+    pub proof: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_tag_hex: Option<String>,
+    #[serde(default = "default_bit_range")]
+    pub bit_range: usize,
+}
+
+/// `POST /v1/tx-hash-commitments`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxHashCommitmentRequest {
+    /// 32-byte transaction hash.
+    pub tx_hash: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_tag_hex: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxHashCommitmentResponse {
+    pub commitment: String,
+    pub proof: String,
+}
+
+/// `POST /v1/tx-hash-commitments/verify`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxHashCommitmentVerifyRequest {
+    pub commitment: String,
+    pub proof: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_tag_hex: Option<String>,
+}
+
+/// `POST /v1/range-proofs`: the transaction hash is split into four 64-bit
+/// limbs, each committed and range-proven in one aggregated proof.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeProofRequest {
+    pub tx_hash: String,
+    #[serde(default = "default_scheme")]
+    pub scheme: Scheme,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeProofResponse {
+    pub scheme: Scheme,
+    pub commitments: Vec<String>,
+    pub proof: String,
+}
+
+/// `POST /v1/range-proofs/verify`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RangeProofVerifyRequest {
+    #[serde(default = "default_scheme")]
+    pub scheme: Scheme,
+    pub commitments: Vec<String>,
+    pub proof: String,
+}
+
+/// Outcome of every `/verify` call. Only a proof that fails to verify is
+/// `false`; undecodable inputs are errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifyResponse {
+    pub verified: bool,
+    /// Earlier presentations of the proof, when the server keeps a replay
+    /// registry and the proof verified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ReplayReport>,
+}
+
+/// How often a verified proof was presented before, under the same binding
+/// tag. A proof its scope allows only once is still `verified` the second
+/// time, but not `accepted`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayReport {
+    /// Whether the server's replay scope allows this presentation.
+    pub accepted: bool,
+    /// Earlier presentations, to any verifier.
+    pub previous_presentations: u64,
+    /// Earlier presentations to the verifier named by `X-Zkp-Verifier`.
+    pub previous_by_verifier: u64,
+    /// Unix seconds of the first presentation; absent on the first one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_presented_at: Option<u64>,
+}
+
+/// Filters of `GET /v1/issued` (all optional) and `DELETE /v1/issued`
+/// (at least one of them), as query parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedQuery {
+    /// Product (escrow) address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commitment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+    /// Only records issued before this unix time (seconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<u64>,
+    /// Listing only: how many records, newest first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// A commitment the server issued. Its opening (value, blinding factor or
+/// transaction hash) is never recorded.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedRecord {
+    pub id: String,
+    /// `value_commitment`, `tx_hash_commitment` or `range_proof`.
+    pub kind: String,
+    /// `bulletproofs`, `r1cs_4limb` or `bulletproofs_plus`.
+    pub scheme: String,
+    pub commitments: Vec<String>,
+    pub proof: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding_tag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub product: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage: Option<String>,
+    /// Unix seconds.
+    pub issued_at: u64,
+}
+
+/// `GET /v1/issued`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssuedList {
+    pub records: Vec<IssuedRecord>,
+}
+
+/// `DELETE /v1/issued`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PurgeResponse {
+    pub purged: usize,
+}
+
+/// Error body. `code` is stable; `error` is for humans.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub code: String,
+}
+
+// ─── Decoding ──────────────────────────────────────────────────────────────
+
+/// Longest proof accepted for verification. The largest real ones, BBS+
+/// presentations hiding every attribute, are about 4 KiB.
+pub const MAX_PROOF_BYTES: usize = 16 * 1024;
+
+/// Both tx-hash range proofs commit to the four 64-bit limbs of the hash.
+pub const TX_HASH_LIMBS: usize = 4;
+
+/// Reject a list whose length is outside `allowed`, before any of it is decoded.
+pub fn check_count(what: &str, len: usize, allowed: RangeInclusive<usize>) -> Result<(), ZkError> {
+    if allowed.contains(&len) {
+        return Ok(());
+    }
+    let expected = if allowed.start() == allowed.end() {
+        allowed.start().to_string()
+    } else {
+        format!("{} to {}", allowed.start(), allowed.end())
+    };
+    Err(ZkError::InvalidParameters(format!("expected {expected} {what}, got {len}")))
+}
+
+/// Hex-decode a proof, turning away oversized ones without decoding them.
+pub fn proof_bytes(hex: &str) -> Result<Vec<u8>, ZkError> {
+    check_proof_len(hex)?;
+    hex_bytes(hex, "proof")
+}
+
+/// Turn away a hex proof longer than [`MAX_PROOF_BYTES`].
+pub fn check_proof_len(hex: &str) -> Result<(), ZkError> {
+    if hex.trim_start_matches("0x").len() > 2 * MAX_PROOF_BYTES {
+        return Err(ZkError::InvalidParameters(format!("proof is longer than {MAX_PROOF_BYTES} bytes")));
+    }
+    Ok(())
+}
+
+/// Hex-decode `what`; a `0x` prefix is accepted.
+pub fn hex_bytes(hex: &str, what: &str) -> Result<Vec<u8>, ZkError> {
+    Vec::from_hex(hex.trim_start_matches("0x")).map_err(|_| ZkError::InvalidParameters(format!("{what} is not valid hex")))
+}
+
+/// Hex-decode exactly 32 bytes.
+pub fn hex32(hex: &str, what: &str) -> Result<[u8; 32], ZkError> {
+    <[u8; 32]>::from_hex(hex.trim_start_matches("0x"))
+        .map_err(|_| ZkError::InvalidParameters(format!("{what} must be 32 bytes (64 hex chars)")))
+}
+
+/// The optional `binding_tag_hex` of a request.
+pub fn binding_tag(hex: &Option<String>) -> Result<Option<[u8; 32]>, ZkError> {
+    hex.as_deref().map(|h| hex32(h, "binding_tag_hex")).transpose()
+}
+
+/// A 32-byte blinding factor reduced mod the group order, or a random one.
+pub fn blinding(hex: &Option<String>, what: &str) -> Result<Scalar, ZkError> {
+    match hex {
+        None => Ok(random_blinding()),
+        Some(h) => hex32(h, what).map(Scalar::from_bytes_mod_order),
+    }
+}
+
+/// Decode a list of 32-byte commitments whose length is in `allowed`.
+pub fn commitments_hex(commitments: &[String], allowed: RangeInclusive<usize>) -> Result<Vec<[u8; 32]>, ZkError> {
+    check_count("commitments", commitments.len(), allowed)?;
+    commitments.iter().map(|c| hex32(c, "commitment")).collect()
+}
+
+// ─── Local execution ───────────────────────────────────────────────────────
+//
+// The same work the `/v1` handlers do, without a server. A freshly generated
+// proof that fails its own verification is reported as
+// `ZkError::TranscriptMismatch`.
+
+impl VerifyResponse {
+    /// Only a proof that does not verify is `verified: false`; undecodable
+    /// inputs stay errors.
+    pub fn from_result(result: Result<(), ZkError>) -> Result<Self, ZkError> {
+        match result {
+            Ok(()) => Ok(Self { verified: true, replay: None }),
+            Err(ZkError::TranscriptMismatch) => Ok(Self { verified: false, replay: None }),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn self_checked<T>((output, verified): (T, bool)) -> Result<T, ZkError> {
+    if verified {
+        Ok(output)
+    } else {
+        Err(ZkError::TranscriptMismatch)
+    }
+}
+
+impl ValueCommitmentRequest {
+    pub fn prove(&self) -> Result<ValueCommitmentResponse, ZkError> {
+        let blinding = blinding(&self.blinding_hex, "blinding_hex")?;
+        let tag = binding_tag(&self.binding_tag_hex)?;
+        let (commitment, proof, verified) = pedersen::prove_value_commitment_with_binding_and_range(
+            self.value,
+            blinding,
+            tag.as_ref().map(|t| &t[..]),
+            self.bit_range,
+        )?;
+        let (commitment, proof) = self_checked(((commitment, proof), verified))?;
+        Ok(ValueCommitmentResponse {
+            commitment: hex::encode(commitment.as_bytes()),
+            proof: hex::encode(proof),
+            blinding_hex: hex::encode(blinding.as_bytes()),
+            bit_range: self.bit_range,
+        })
+    }
+}
+
+impl ValueCommitmentVerifyRequest {
+    pub fn verify(&self) -> Result<VerifyResponse, ZkError> {
+        let commitment = NgCompressed(hex32(&self.commitment, "commitment")?);
+        let proof = proof_bytes(&self.proof)?;
+        let tag = binding_tag(&self.binding_tag_hex)?;
+        VerifyResponse::from_result(pedersen::verify_value_commitment_with_binding_and_range(
+            commitment,
+            proof,
+            tag.as_ref().map(|t| &t[..]),
+            self.bit_range,
+        ))
+    }
+}
+
+impl TxHashCommitmentRequest {
+    pub fn prove(&self) -> Result<TxHashCommitmentResponse, ZkError> {
+        let tx_id = Scalar::from_bytes_mod_order(hex32(self.tx_hash.trim(), "tx_hash")?);
+        let tag = binding_tag(&self.binding_tag_hex)?;
+        let (commitment, proof, verified) = txid_pedersen_proof::prove_txid_commitment_with_binding(tx_id, tag.as_ref().map(|t| &t[..]))?;
+        let (commitment, proof) = self_checked(((commitment, proof), verified))?;
+        Ok(TxHashCommitmentResponse { commitment: hex::encode(commitment.as_bytes()), proof: hex::encode(proof) })
+    }
+}
+
+impl TxHashCommitmentVerifyRequest {
+    pub fn verify(&self) -> Result<VerifyResponse, ZkError> {
+        let commitment = NgCompressed(hex32(&self.commitment, "commitment")?);
+        let proof = proof_bytes(&self.proof)?;
+        let tag = binding_tag(&self.binding_tag_hex)?;
+        VerifyResponse::from_result(txid_pedersen_proof::verify_txid_commitment_with_binding(
+            commitment,
+            proof,
+            tag.as_ref().map(|t| &t[..]),
+        ))
+    }
+}
+
+impl RangeProofRequest {
+    pub fn prove(&self) -> Result<RangeProofResponse, ZkError> {
+        let tx_hash = hex32(self.tx_hash.trim(), "tx_hash")?;
+        let (commitments, proof) = match self.scheme {
+            Scheme::Bulletproofs => {
+                let (commitments, proof, verified) = txid_pedersen_proof::prove_txid_commitment_4limb(tx_hash)?;
+                self_checked(((commitments.iter().map(|c| hex::encode(c.as_bytes())).collect(), proof), verified))?
+            },
+            Scheme::BulletproofsPlus => {
+                let (commitments, proof) = bp_plus_pedersen::prove_txid_commitment(tx_hash)?;
+                (commitments.iter().map(|c| hex::encode(c.as_bytes())).collect(), proof)
+            },
+        };
+        Ok(RangeProofResponse { scheme: self.scheme, commitments, proof: hex::encode(proof) })
+    }
+}
+
+impl RangeProofVerifyRequest {
+    pub fn verify(&self) -> Result<VerifyResponse, ZkError> {
+        let commitments = commitments_hex(&self.commitments, TX_HASH_LIMBS..=TX_HASH_LIMBS)?;
+        let proof = proof_bytes(&self.proof)?;
+        VerifyResponse::from_result(match self.scheme {
+            Scheme::Bulletproofs => {
+                let commitments: Vec<_> = commitments.into_iter().map(NgCompressed).collect();
+                txid_pedersen_proof::verify_txid_commitment_4limb(&commitments, &proof)
+            },
+            Scheme::BulletproofsPlus => {
+                let commitments = commitments.into_iter().map(curve25519_dalek::ristretto::CompressedRistretto).collect();
+                bp_plus_pedersen::verify_txid_commitment(commitments, proof)
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn optional_fields_take_their_defaults() {
+        let req: ValueCommitmentRequest = serde_json::from_value(json!({ "value": 5 })).unwrap();
+        assert_eq!((req.bit_range, req.blinding_hex, req.binding_tag_hex), (64, None, None));
+        let req: RangeProofRequest = serde_json::from_value(json!({ "tx_hash": "00" })).unwrap();
+        assert_eq!(req.scheme, Scheme::BulletproofsPlus);
+        let req: RangeProofRequest = serde_json::from_value(json!({ "tx_hash": "00", "scheme": "bulletproofs" })).unwrap();
+        assert_eq!(req.scheme, Scheme::Bulletproofs);
+    }
+
+    #[test]
+    fn requests_run_locally() {
+        let tag = Some("0x".to_string() + &"ab".repeat(32));
+        let req = ValueCommitmentRequest { value: 42, blinding_hex: None, binding_tag_hex: tag.clone(), bit_range: 16 };
+        let resp = req.prove().unwrap();
+        let mut verify = ValueCommitmentVerifyRequest {
+            commitment: resp.commitment,
+            proof: resp.proof,
+            binding_tag_hex: tag,
+            bit_range: 16,
+        };
+        assert_eq!(verify.verify(), Ok(VerifyResponse { verified: true, replay: None }));
+        verify.binding_tag_hex = None;
+        assert_eq!(verify.verify(), Ok(VerifyResponse { verified: false, replay: None }));
+        verify.proof = "zz".into();
+        assert_eq!(verify.verify().unwrap_err().code(), "invalid_parameters");
+
+        let req = RangeProofRequest { tx_hash: "11".repeat(32), scheme: Scheme::BulletproofsPlus };
+        let resp = req.prove().unwrap();
+        let verify = RangeProofVerifyRequest { scheme: resp.scheme, commitments: resp.commitments, proof: resp.proof };
+        assert_eq!(verify.verify(), Ok(VerifyResponse { verified: true, replay: None }));
+        let short = RangeProofVerifyRequest { commitments: verify.commitments[..3].to_vec(), ..verify };
+        assert!(short.verify().is_err());
+    }
+}
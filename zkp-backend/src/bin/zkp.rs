@@ -0,0 +1,250 @@
+//! `zkp`: prove and verify commitments without a server.
+//!
+//! Every subcommand takes the same JSON body as its `/v1` endpoint, from
+//! `--input <file>` (`-` for stdin), from flags named after its fields, or
+//! both (flags win). The result is printed as JSON on stdout; errors are
+//! `{ "error", "code" }` on stderr.
+//!
+//! Exit status: 0 when done or verified, 1 when a proof (or a credential)
+//! does not verify, 2 for unusable input.
+
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use bulletproof_demo::api::{
+    ErrorResponse, RangeProofRequest, RangeProofVerifyRequest, TxHashCommitmentRequest, TxHashCommitmentVerifyRequest,
+    ValueCommitmentRequest, ValueCommitmentVerifyRequest, VerifyResponse,
+};
+use bulletproof_demo::eth::parse_address;
+use bulletproof_demo::vc::{self, signature};
+use clap::{Args, Parser, Subcommand};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Parser)]
+#[command(name = "zkp", version, about = "Offline commitments, range proofs and credential checks")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Commit to a value with a range proof (as POST /v1/value-commitments)
+    CommitValue(Request<ValueFlags>),
+    /// Commit to a transaction hash (as POST /v1/tx-hash-commitments)
+    CommitTxHash(Request<TxHashFlags>),
+    /// Verify a value commitment's range proof
+    VerifyValue(Request<VerifyValueFlags>),
+    /// Verify a transaction hash commitment's proof
+    VerifyTxHash(Request<VerifyTxHashFlags>),
+    /// Range-prove the four 64-bit limbs of a transaction hash (as POST /v1/range-proofs)
+    ProvePlus(Request<RangeFlags>),
+    /// Verify a four-limb range proof
+    VerifyPlus(Request<VerifyRangeFlags>),
+    /// Verify the signatures and every zero-knowledge proof of a Verifiable Credential
+    VerifyVc {
+        /// Credential JSON file; `-` reads stdin
+        credential: PathBuf,
+        /// Escrow the signatures may be bound to (EIP-712 verifyingContract)
+        #[arg(long)]
+        contract: Option<String>,
+        /// Only the issuer signs (certificates)
+        #[arg(long)]
+        certificate: bool,
+        /// Chain of DIDs that name none
+        #[arg(long, default_value_t = signature::DEFAULT_CHAIN_ID)]
+        chain_id: u64,
+    },
+}
+
+/// A JSON body from `--input`, overridden field by field by the flags.
+#[derive(Args)]
+struct Request<F: Args> {
+    /// JSON request body; `-` reads stdin
+    #[arg(long, short)]
+    input: Option<PathBuf>,
+    #[command(flatten)]
+    flags: F,
+}
+
+// Flags carry the field names of the request bodies, so they serialize
+// straight into them.
+
+#[derive(Args, Serialize)]
+struct ValueFlags {
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<u64>,
+    /// 32-byte hex blinding factor (random if omitted)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blinding_hex: Option<String>,
+    /// 32-byte hex tag bound into the proof
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    binding_tag_hex: Option<String>,
+    /// 8, 16, 32 or 64 (default 64)
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bit_range: Option<usize>,
+}
+
+#[derive(Args, Serialize)]
+struct TxHashFlags {
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tx_hash: Option<String>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    binding_tag_hex: Option<String>,
+}
+
+#[derive(Args, Serialize)]
+struct VerifyValueFlags {
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commitment: Option<String>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<String>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    binding_tag_hex: Option<String>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bit_range: Option<usize>,
+}
+
+#[derive(Args, Serialize)]
+struct VerifyTxHashFlags {
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commitment: Option<String>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<String>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    binding_tag_hex: Option<String>,
+}
+
+#[derive(Args, Serialize)]
+struct RangeFlags {
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tx_hash: Option<String>,
+    /// bulletproofs_plus (default) or bulletproofs
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheme: Option<String>,
+}
+
+#[derive(Args, Serialize)]
+struct VerifyRangeFlags {
+    /// The four limb commitments, comma separated
+    #[arg(long, value_delimiter = ',')]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commitments: Option<Vec<String>>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proof: Option<String>,
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scheme: Option<String>,
+}
+
+/// Why a command stopped: printed as an [`ErrorResponse`].
+struct Failure {
+    error: String,
+    code: &'static str,
+}
+
+impl From<bulletproof_demo::zk::ZkError> for Failure {
+    fn from(e: bulletproof_demo::zk::ZkError) -> Self {
+        Self { error: e.to_string(), code: e.code() }
+    }
+}
+
+fn invalid_request(error: impl ToString) -> Failure {
+    Failure { error: error.to_string(), code: "invalid_request" }
+}
+
+fn read_json(path: &Path) -> Result<Value, Failure> {
+    let mut text = String::new();
+    let read = if path == Path::new("-") {
+        std::io::stdin().read_to_string(&mut text).map(drop)
+    } else {
+        std::fs::read_to_string(path).map(|t| text = t)
+    };
+    read.map_err(|e| invalid_request(format!("{}: {e}", path.display())))?;
+    serde_json::from_str(&text).map_err(|e| invalid_request(format!("{}: {e}", path.display())))
+}
+
+impl<F: Args + Serialize> Request<F> {
+    fn body<T: DeserializeOwned>(&self) -> Result<T, Failure> {
+        let mut body = match &self.input {
+            Some(path) => read_json(path)?,
+            None => Value::Object(Default::default()),
+        };
+        let Value::Object(fields) = &mut body else {
+            return Err(invalid_request("the request body must be a JSON object"));
+        };
+        if let Value::Object(flags) = serde_json::to_value(&self.flags).map_err(invalid_request)? {
+            fields.extend(flags);
+        }
+        serde_json::from_value(body).map_err(invalid_request)
+    }
+}
+
+/// Output and whether it counts as success.
+type Outcome = (Value, bool);
+
+fn done(output: impl Serialize) -> Result<Outcome, Failure> {
+    Ok((serde_json::to_value(output).map_err(invalid_request)?, true))
+}
+
+fn checked(response: VerifyResponse) -> Result<Outcome, Failure> {
+    Ok((serde_json::to_value(response).map_err(invalid_request)?, response.verified))
+}
+
+fn run(command: Command) -> Result<Outcome, Failure> {
+    match command {
+        Command::CommitValue(req) => done(req.body::<ValueCommitmentRequest>()?.prove()?),
+        Command::CommitTxHash(req) => done(req.body::<TxHashCommitmentRequest>()?.prove()?),
+        Command::VerifyValue(req) => checked(req.body::<ValueCommitmentVerifyRequest>()?.verify()?),
+        Command::VerifyTxHash(req) => checked(req.body::<TxHashCommitmentVerifyRequest>()?.verify()?),
+        Command::ProvePlus(req) => done(req.body::<RangeProofRequest>()?.prove()?),
+        Command::VerifyPlus(req) => checked(req.body::<RangeProofVerifyRequest>()?.verify()?),
+        Command::VerifyVc { credential, contract, certificate, chain_id } => {
+            let verifying_contract = match contract {
+                Some(contract) => Some(parse_address(&contract).map_err(|e| Failure { error: e.to_string(), code: e.code() })?),
+                None => None,
+            };
+            let options = signature::SignatureOptions { chain_id, verifying_contract, certificate };
+            let report = vc::verify(&read_json(&credential)?, &options);
+            let verified = report.verified;
+            Ok((serde_json::to_value(report).map_err(invalid_request)?, verified))
+        },
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok((output, success)) => {
+            println!("{}", serde_json::to_string_pretty(&output).expect("JSON values always serialize"));
+            if success {
+                ExitCode::SUCCESS
+            } else {
+                ExitCode::from(1)
+            }
+        },
+        Err(Failure { error, code }) => {
+            let body = ErrorResponse { error, code: code.into() };
+            eprintln!("{}", serde_json::to_string_pretty(&body).expect("JSON values always serialize"));
+            ExitCode::from(2)
+        },
+    }
+}
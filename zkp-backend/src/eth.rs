@@ -0,0 +1,236 @@
+//! Ethereum signatures: Keccak-256, addresses, EIP-191 personal messages,
+//! EIP-712 typed data and signer recovery.
+//!
+//! Signatures are the 65-byte `r ‖ s ‖ v` produced by wallets (`v` is 27/28
+//! or 0/1). High-`s` signatures are rejected as Ethereum does since EIP-2.
+
+use std::fmt;
+
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+pub type Address = [u8; 20];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EthError {
+    /// Not `0x` followed by 40 hex digits.
+    InvalidAddress(String),
+    /// Not 65 bytes of hex, or not a valid low-`s` ECDSA signature.
+    InvalidSignature(&'static str),
+    /// No public key can be recovered from the signature and digest.
+    Unrecoverable,
+}
+
+impl EthError {
+    /// Stable machine-readable identifier, in the style of `ZkError::code`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::InvalidAddress(_) => "invalid_address",
+            Self::InvalidSignature(_) | Self::Unrecoverable => "invalid_signature",
+        }
+    }
+}
+
+impl fmt::Display for EthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAddress(address) => write!(f, "{address:?} is not an Ethereum address"),
+            Self::InvalidSignature(why) => write!(f, "invalid signature: {why}"),
+            Self::Unrecoverable => write!(f, "no signer can be recovered from the signature"),
+        }
+    }
+}
+
+impl std::error::Error for EthError {}
+
+pub fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
+
+/// Parse a `0x`-prefixed address. The EIP-55 checksum is not enforced:
+/// config files and headers are often lower-cased.
+pub fn parse_address(s: &str) -> Result<Address, EthError> {
+    let digits = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")).ok_or_else(|| EthError::InvalidAddress(s.into()))?;
+    <Address as hex::FromHex>::from_hex(digits).map_err(|_| EthError::InvalidAddress(s.into()))
+}
+
+/// EIP-55 mixed-case checksum encoding.
+pub fn format_address(address: &Address) -> String {
+    let lower = hex::encode(address);
+    let hash = keccak256(lower.as_bytes());
+    let mut out = String::with_capacity(42);
+    out.push_str("0x");
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0x0f;
+        out.push(if nibble >= 8 { c.to_ascii_uppercase() } else { c });
+    }
+    out
+}
+
+/// Digest signed by `personal_sign` / `eth_sign` (EIP-191 version `0x45`).
+pub fn eip191_hash(message: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(format!("\x19Ethereum Signed Message:\n{}", message.len()));
+    hasher.update(message);
+    hasher.finalize().into()
+}
+
+/// Digest signed by `eth_signTypedData_v4`: `keccak256(0x1901 ‖ domain ‖ struct)`.
+pub fn eip712_hash(domain_separator: &[u8; 32], struct_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update([0x19, 0x01]);
+    hasher.update(domain_separator);
+    hasher.update(struct_hash);
+    hasher.finalize().into()
+}
+
+/// An EIP-712 domain with the fields this backend uses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Eip712Domain {
+    pub name: String,
+    pub version: String,
+    pub chain_id: Option<u64>,
+    pub verifying_contract: Option<Address>,
+}
+
+impl Eip712Domain {
+    /// `hashStruct(EIP712Domain)` over the fields that are present.
+    pub fn separator(&self) -> [u8; 32] {
+        let mut ty = String::from("EIP712Domain(string name,string version");
+        let mut encoded = vec![keccak256(self.name.as_bytes()), keccak256(self.version.as_bytes())];
+        if let Some(chain_id) = self.chain_id {
+            ty.push_str(",uint256 chainId");
+            encoded.push(encode_uint(chain_id));
+        }
+        if let Some(contract) = &self.verifying_contract {
+            ty.push_str(",address verifyingContract");
+            encoded.push(encode_address(contract));
+        }
+        ty.push(')');
+        hash_struct(&ty, &encoded)
+    }
+}
+
+/// `keccak256(typeHash ‖ encodeData)` for already-encoded 32-byte members.
+pub fn hash_struct(type_string: &str, members: &[[u8; 32]]) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(keccak256(type_string.as_bytes()));
+    for member in members {
+        hasher.update(member);
+    }
+    hasher.finalize().into()
+}
+
+/// ABI word for an unsigned integer.
+pub fn encode_uint(value: u64) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[24..].copy_from_slice(&value.to_be_bytes());
+    word
+}
+
+/// ABI word for an address.
+pub fn encode_address(address: &Address) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    word[12..].copy_from_slice(address);
+    word
+}
+
+/// Decode a `0x`-prefixed 65-byte signature.
+pub fn parse_signature(s: &str) -> Result<[u8; 65], EthError> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    <[u8; 65] as hex::FromHex>::from_hex(digits).map_err(|_| EthError::InvalidSignature("expected 65 bytes of hex"))
+}
+
+/// The address whose key produced `signature` over `digest`.
+pub fn recover(digest: &[u8; 32], signature: &[u8; 65]) -> Result<Address, EthError> {
+    let recovery_id = match signature[64] {
+        0 | 27 => 0,
+        1 | 28 => 1,
+        _ => return Err(EthError::InvalidSignature("v must be 27 or 28")),
+    };
+    let sig = Signature::from_slice(&signature[..64]).map_err(|_| EthError::InvalidSignature("r or s out of range"))?;
+    if sig.normalize_s().is_some() {
+        return Err(EthError::InvalidSignature("s is in the upper half of the curve order"));
+    }
+    let recovery_id = RecoveryId::from_byte(recovery_id).expect("0 and 1 are valid recovery ids");
+    let key = VerifyingKey::recover_from_prehash(digest, &sig, recovery_id).map_err(|_| EthError::Unrecoverable)?;
+    Ok(address_of(&key))
+}
+
+/// Address of a secp256k1 public key: the last 20 bytes of the Keccak-256
+/// of its uncompressed encoding.
+pub fn address_of(key: &VerifyingKey) -> Address {
+    let point = key.to_encoded_point(false);
+    let hash = keccak256(&point.as_bytes()[1..]);
+    hash[12..].try_into().expect("20 bytes")
+}
+
+/// Sign `digest` the way a wallet does: `r ‖ s ‖ v` with `v` in {27, 28}.
+pub fn sign(key: &k256::ecdsa::SigningKey, digest: &[u8; 32]) -> [u8; 65] {
+    let (sig, recovery_id) = key.sign_prehash_recoverable(digest).expect("a 32-byte digest can always be signed");
+    let mut out = [0u8; 65];
+    out[..64].copy_from_slice(&sig.to_bytes());
+    out[64] = 27 + recovery_id.to_byte();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    /// Hardhat/Ganache account #0.
+    const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    fn key() -> SigningKey {
+        SigningKey::from_slice(&hex::decode(KEY).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn addresses_round_trip_with_checksum() {
+        assert_eq!(format_address(&address_of(key().verifying_key())), ADDRESS);
+        assert_eq!(format_address(&parse_address(&ADDRESS.to_lowercase()).unwrap()), ADDRESS);
+        assert!(parse_address("f39fd6e51aad88f6f4ce6ab8827279cfffb92266").is_err());
+        assert!(parse_address("0x1234").is_err());
+    }
+
+    #[test]
+    fn personal_sign_recovers_the_signer() {
+        let digest = eip191_hash(b"hello");
+        // ethers.js `hashMessage("hello")`.
+        assert_eq!(hex::encode(digest), "50b2c43fd39106bafbba0da34fc430e1f91e3c96ea2acee2bc34119f92b37750");
+        let signature = sign(&key(), &digest);
+        // RFC 6979 nonces: identical to ethers.js.
+        assert_eq!(
+            hex::encode(signature),
+            "f16ea9a3478698f695fd1401bfe27e9e4a7e8e3da94aa72b021125e31fa899cc573c48ea3fe1d4ab61a9db10c19032026e3ed2dbccba5a178235ac27f94504311c"
+        );
+        assert_eq!(format_address(&recover(&digest, &signature).unwrap()), ADDRESS);
+        assert_ne!(format_address(&recover(&eip191_hash(b"hellO"), &signature).unwrap()), ADDRESS);
+    }
+
+    #[test]
+    fn malleable_and_malformed_signatures_are_rejected() {
+        let digest = eip191_hash(b"hello");
+        let mut signature = sign(&key(), &digest);
+        let sig = Signature::from_slice(&signature[..64]).unwrap();
+        let (r, s) = sig.split_scalars();
+        let flipped = Signature::from_scalars(r.to_bytes(), (-*s).to_bytes()).unwrap();
+        signature[..64].copy_from_slice(&flipped.to_bytes());
+        signature[64] ^= 1;
+        assert!(matches!(recover(&digest, &signature), Err(EthError::InvalidSignature(_))));
+
+        let mut bad_v = sign(&key(), &digest);
+        bad_v[64] = 29;
+        assert!(recover(&digest, &bad_v).is_err());
+        assert!(parse_signature("0x1234").is_err());
+    }
+
+    #[test]
+    fn domain_separator_matches_ethers() {
+        // ethers.js `TypedDataEncoder.hashDomain({ name: "Test", version: "1", chainId: 1337 })`.
+        let domain = Eip712Domain { name: "Test".into(), version: "1".into(), chain_id: Some(1337), verifying_contract: None };
+        assert_eq!(hex::encode(domain.separator()), "58ebed1022b7fdc84ad724750e5a6b831df36619cdc5cdf92ea1c0b4f6f14c01");
+    }
+}
@@ -1,4 +1,8 @@
 //! Library crate exposing ZKP modules for testing
 
+pub mod api;
+pub mod eth;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod vc;
 pub mod zk;
-
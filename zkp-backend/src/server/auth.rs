@@ -0,0 +1,740 @@
+//! Optional request authentication (`auth = true`).
+//!
+//! Verifications and reads stay open, except the issued commitment record
+//! (`/v1/issued`). Every other request (proof and commitment generation)
+//! needs one of:
+//!
+//! * `X-Api-Key` carrying one of the configured `api_keys`, for
+//!   service-to-service calls;
+//! * a TLS client certificate verified against `tls_client_ca`
+//!   (see [`tls`](super::tls));
+//! * an Ethereum signature over the request. Its headers are
+//!   `X-Zkp-Signer` (the address), `X-Zkp-Signature` (65-byte hex),
+//!   `X-Zkp-Nonce` (1–64 of `[A-Za-z0-9_-]`), `X-Zkp-Timestamp` (unix
+//!   seconds, within five minutes of the server clock) and, optionally,
+//!   `X-Zkp-Escrow` and `X-Zkp-Signature-Type` (`eip191`, the default,
+//!   or `eip712`).
+//!
+//! With `eip191` the signer `personal_sign`s the text
+//!
+//! ```text
+//! ZKP backend request
+//! method: POST
+//! path: /v1/value-commitments
+//! body: 0x<keccak256 of the raw body>
+//! escrow: 0x<escrow address, lower case; zero address if none>
+//! nonce: <nonce>
+//! timestamp: <timestamp>
+//! ```
+//!
+//! With `eip712` the same fields are signed as `ZkpRequest` typed data
+//! in the domain `{ name: "ZKP Backend", version: "1", chainId }`.
+//!
+//! The signer must be in `auth_allowlist`. Otherwise it must be the seller
+//! (`owner()`) or `buyer()` of the escrow named in `X-Zkp-Escrow`; those
+//! roles are read over `escrow_rpc_url`. Each (signer, nonce) pair is
+//! accepted once while its timestamp is fresh, so a captured request cannot
+//! be replayed.
+//!
+//! Issuing BBS+ credentials, adding or removing revocation handles and
+//! handing out membership witnesses ([`ISSUER_PATHS`]) is for the issuer
+//! alone: those requests need one of the `issuer_api_keys` or a signature by
+//! an address in `issuer_allowlist`. Purging the issued record
+//! (`DELETE /v1/issued`) needs one of the `operator_api_keys`. Issuer and
+//! operator keys also pass wherever the others do.
+//!
+//! A signed request carries its verified escrow as a [`SignedEscrow`]
+//! extension, which the issued record takes as the product.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::web::{Bytes, BytesMut};
+use actix_web::{web, HttpMessage, HttpResponse};
+use crate::eth::{self, Address, Eip712Domain};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tracing::{debug, info};
+
+use super::config::Config;
+use super::issued;
+use super::limits::{budget, Budget};
+use super::tls::ClientCertificate;
+
+pub const API_KEY: &str = "x-api-key";
+pub const SIGNER: &str = "x-zkp-signer";
+pub const SIGNATURE: &str = "x-zkp-signature";
+pub const SIGNATURE_TYPE: &str = "x-zkp-signature-type";
+pub const NONCE: &str = "x-zkp-nonce";
+pub const TIMESTAMP: &str = "x-zkp-timestamp";
+pub const ESCROW: &str = "x-zkp-escrow";
+
+/// Largest accepted difference between `X-Zkp-Timestamp` and the server clock.
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+const EIP712_TYPE: &str =
+    "ZkpRequest(string method,string path,bytes32 bodyHash,address escrow,string nonce,uint256 timestamp)";
+
+/// Routes that sign credentials, change the revocation registry or reveal
+/// whether a handle is live (witnesses go to holders through the issuer).
+pub const ISSUER_PATHS: [&str; 4] = ["/zkp/bbs/issue", "/zkp/revocation/add", "/zkp/revocation/remove", "/zkp/revocation/witness"];
+
+/// Who may make a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    /// Reads and verifications: anyone.
+    Open,
+    /// Proof and commitment generation, and the issued record: any
+    /// authenticated client.
+    Client,
+    /// [`ISSUER_PATHS`]: the issuer.
+    Issuer,
+    /// Purging the issued record: an operator.
+    Operator,
+}
+
+fn access(req: &ServiceRequest) -> Access {
+    if ISSUER_PATHS.contains(&req.path()) {
+        Access::Issuer
+    } else if req.path() == issued::PATH && req.method() == Method::DELETE {
+        Access::Operator
+    } else if budget(req.method(), req.path()) == Some(Budget::Prove) || req.path() == issued::PATH {
+        Access::Client
+    } else {
+        Access::Open
+    }
+}
+
+/// How long an escrow role lookup may take.
+const RPC_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Credentials and replay state, shared by every worker.
+pub struct Auth {
+    /// SHA-256 of each API key, so keys are compared in constant time and
+    /// never kept in the clear.
+    api_keys: Vec<[u8; 32]>,
+    allowlist: HashSet<Address>,
+    issuer_api_keys: Vec<[u8; 32]>,
+    issuer_allowlist: HashSet<Address>,
+    operator_api_keys: Vec<[u8; 32]>,
+    escrows: Option<EscrowRoles>,
+    domain_separator: [u8; 32],
+    body_limit: usize,
+    /// (signer, nonce) → unix second after which the entry may be forgotten.
+    nonces: Mutex<HashMap<(Address, String), u64>>,
+}
+
+impl Auth {
+    pub fn new(config: &Config) -> Result<Self, String> {
+        let allowlist = config.auth_allowlist.iter().map(|a| eth::parse_address(a)).collect::<Result<_, _>>();
+        let issuer_allowlist = config.issuer_allowlist.iter().map(|a| eth::parse_address(a)).collect::<Result<_, _>>();
+        let escrows = match &config.escrow_rpc_url {
+            Some(url) => Some(EscrowRoles::new(url)?),
+            None => None,
+        };
+        let domain = Eip712Domain {
+            name: "ZKP Backend".into(),
+            version: "1".into(),
+            chain_id: Some(config.chain_id),
+            verifying_contract: None,
+        };
+        Ok(Self {
+            api_keys: config.api_keys.iter().map(|k| Sha256::digest(k).into()).collect(),
+            allowlist: allowlist.map_err(|e| e.to_string())?,
+            issuer_api_keys: config.issuer_api_keys.iter().map(|k| Sha256::digest(k).into()).collect(),
+            issuer_allowlist: issuer_allowlist.map_err(|e| e.to_string())?,
+            operator_api_keys: config.operator_api_keys.iter().map(|k| Sha256::digest(k).into()).collect(),
+            escrows,
+            domain_separator: domain.separator(),
+            body_limit: config.json_limit,
+            nonces: Mutex::default(),
+        })
+    }
+
+    /// Whether `key` is one of `keys`.
+    fn api_key_in(keys: &[[u8; 32]], key: &[u8]) -> bool {
+        let digest: [u8; 32] = Sha256::digest(key).into();
+        // No early exit: every configured key is compared.
+        keys.iter().fold(0u8, |found, k| found | k.ct_eq(&digest).unwrap_u8()) == 1
+    }
+
+    /// Remember `nonce` for `signer` until `expires`; false if it was already used.
+    fn claim_nonce(&self, signer: Address, nonce: &str, expires: u64, now: u64) -> bool {
+        let mut nonces = self.nonces.lock().unwrap();
+        nonces.retain(|_, until| *until >= now);
+        match nonces.entry((signer, nonce.to_owned())) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(expires);
+                true
+            },
+        }
+    }
+}
+
+/// The escrow (`X-Zkp-Escrow`) a request signed, if it named one; only
+/// present on requests authenticated by signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignedEscrow(pub Option<Address>);
+
+/// Why a request was turned away.
+#[derive(Debug)]
+struct Rejection {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+impl Rejection {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into() }
+    }
+
+    fn unauthenticated(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "unauthenticated", message)
+    }
+}
+
+/// Middleware (for `middleware::from_fn`); a no-op unless [`Auth`] is in the
+/// app data.
+pub async fn authenticate(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let Some(auth) = req.app_data::<web::Data<Auth>>().cloned() else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+    let access = access(&req);
+    if access == Access::Open {
+        return Ok(next.call(req).await?.map_into_left_body());
+    }
+    match check(&auth, &mut req, access).await {
+        Ok(()) => Ok(next.call(req).await?.map_into_left_body()),
+        Err(rejection) => {
+            info!(code = rejection.code, error = %rejection.message, "request rejected");
+            let response = HttpResponse::build(rejection.status).json(json!({
+                "error": rejection.message,
+                "code": rejection.code,
+            }));
+            Ok(req.into_response(response).map_into_right_body())
+        },
+    }
+}
+
+fn header<'a>(req: &'a ServiceRequest, name: &str) -> Result<Option<&'a str>, Rejection> {
+    req.headers()
+        .get(name)
+        .map(|v| v.to_str().map_err(|_| Rejection::unauthenticated(format!("{name} is not valid text"))))
+        .transpose()
+}
+
+fn required<'a>(req: &'a ServiceRequest, name: &str) -> Result<&'a str, Rejection> {
+    header(req, name)?.ok_or_else(|| Rejection::unauthenticated(format!("{name} is missing")))
+}
+
+fn not_issuer(who: &str) -> Rejection {
+    Rejection::new(StatusCode::FORBIDDEN, "forbidden", format!("{who} may not issue credentials or change revocations"))
+}
+
+fn not_operator(who: &str) -> Rejection {
+    Rejection::new(StatusCode::FORBIDDEN, "forbidden", format!("{who} may not purge issued commitments"))
+}
+
+/// The rejection for credentials that pass only [`Access::Client`] requests.
+fn client_only(who: &str, access: Access) -> Result<(), Rejection> {
+    match access {
+        Access::Issuer => Err(not_issuer(who)),
+        Access::Operator => Err(not_operator(who)),
+        Access::Open | Access::Client => Ok(()),
+    }
+}
+
+async fn check(auth: &Auth, req: &mut ServiceRequest, access: Access) -> Result<(), Rejection> {
+    if let Some(key) = req.headers().get(API_KEY) {
+        let key = key.as_bytes();
+        let role = if Auth::api_key_in(&auth.issuer_api_keys, key) {
+            Access::Issuer
+        } else if Auth::api_key_in(&auth.operator_api_keys, key) {
+            Access::Operator
+        } else if Auth::api_key_in(&auth.api_keys, key) {
+            Access::Client
+        } else {
+            return Err(Rejection::new(StatusCode::UNAUTHORIZED, "invalid_api_key", "unknown API key"));
+        };
+        if role != access {
+            client_only("this API key", access)?;
+        }
+        debug!(role = ?role, "authenticated by API key");
+        return Ok(());
+    }
+    if req.conn_data::<ClientCertificate>().is_some() && !req.headers().contains_key(SIGNATURE) {
+        client_only("a client certificate", access)?;
+        debug!("authenticated by client certificate");
+        return Ok(());
+    }
+    if !req.headers().contains_key(SIGNATURE) {
+        return Err(Rejection::unauthenticated(format!("requires {API_KEY} or a signed request ({SIGNATURE})")));
+    }
+
+    let signer = eth::parse_address(required(req, SIGNER)?).map_err(|e| Rejection::unauthenticated(e.to_string()))?;
+    let signature = eth::parse_signature(required(req, SIGNATURE)?).map_err(invalid_signature)?;
+    let kind = match header(req, SIGNATURE_TYPE)?.unwrap_or("eip191") {
+        "eip191" => SignatureType::Eip191,
+        "eip712" => SignatureType::Eip712,
+        other => return Err(Rejection::unauthenticated(format!("unknown {SIGNATURE_TYPE} {other:?}"))),
+    };
+    let nonce = required(req, NONCE)?.to_owned();
+    if nonce.is_empty() || nonce.len() > 64 || !nonce.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_') {
+        return Err(Rejection::unauthenticated(format!("{NONCE} must be 1 to 64 letters, digits, '-' or '_'")));
+    }
+    let timestamp: u64 = required(req, TIMESTAMP)?
+        .parse()
+        .map_err(|_| Rejection::unauthenticated(format!("{TIMESTAMP} must be unix seconds")))?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    if timestamp.abs_diff(now) > MAX_CLOCK_SKEW.as_secs() {
+        return Err(Rejection::new(
+            StatusCode::UNAUTHORIZED,
+            "stale_request",
+            format!("{TIMESTAMP} is more than {} seconds from the server clock", MAX_CLOCK_SKEW.as_secs()),
+        ));
+    }
+    let escrow = header(req, ESCROW)?
+        .map(eth::parse_address)
+        .transpose()
+        .map_err(|e| Rejection::unauthenticated(e.to_string()))?;
+
+    let body = read_body(req, auth.body_limit).await?;
+    let claims = RequestClaims {
+        method: req.method().as_str().to_owned(),
+        path: req.uri().path_and_query().map_or_else(|| req.path().to_owned(), |p| p.as_str().to_owned()),
+        body_hash: eth::keccak256(&body),
+        escrow: escrow.unwrap_or_default(),
+        nonce,
+        timestamp,
+    };
+    req.set_payload(Payload::from(body));
+
+    let digest = claims.digest(kind, &auth.domain_separator);
+    let recovered = eth::recover(&digest, &signature).map_err(invalid_signature)?;
+    if recovered != signer {
+        return Err(Rejection::new(
+            StatusCode::UNAUTHORIZED,
+            "invalid_signature",
+            format!("signature is not by {}", eth::format_address(&signer)),
+        ));
+    }
+
+    authorize(auth, &signer, escrow.as_ref(), access).await?;
+
+    if !auth.claim_nonce(signer, &claims.nonce, timestamp + MAX_CLOCK_SKEW.as_secs(), now) {
+        return Err(Rejection::new(StatusCode::UNAUTHORIZED, "nonce_reused", format!("{NONCE} was already used")));
+    }
+    req.extensions_mut().insert(SignedEscrow(escrow));
+    debug!(signer = %eth::format_address(&signer), "authenticated by signature");
+    Ok(())
+}
+
+fn invalid_signature(e: eth::EthError) -> Rejection {
+    Rejection::new(StatusCode::UNAUTHORIZED, e.code(), e.to_string())
+}
+
+/// The issuer allowlist, which passes everything but purges (operator keys
+/// only); for other requests the allowlist, then, for requests naming an
+/// escrow, its seller and buyer.
+async fn authorize(auth: &Auth, signer: &Address, escrow: Option<&Address>, access: Access) -> Result<(), Rejection> {
+    if access == Access::Operator {
+        return Err(not_operator(&eth::format_address(signer)));
+    }
+    if auth.issuer_allowlist.contains(signer) {
+        return Ok(());
+    }
+    if access == Access::Issuer {
+        return Err(not_issuer(&eth::format_address(signer)));
+    }
+    if auth.allowlist.contains(signer) {
+        return Ok(());
+    }
+    let forbidden = |why: &str| {
+        Rejection::new(StatusCode::FORBIDDEN, "forbidden", format!("{} {why}", eth::format_address(signer)))
+    };
+    let (Some(escrow), Some(escrows)) = (escrow, &auth.escrows) else {
+        return Err(forbidden("is not allowed to generate proofs"));
+    };
+    let parties = escrows.parties(escrow).await.map_err(|e| {
+        Rejection::new(StatusCode::BAD_GATEWAY, "escrow_lookup_failed", format!("escrow {}: {e}", eth::format_address(escrow)))
+    })?;
+    if parties.contains(signer) {
+        Ok(())
+    } else {
+        Err(forbidden(&format!("is neither seller nor buyer of escrow {}", eth::format_address(escrow))))
+    }
+}
+
+/// The whole body, up to `limit` bytes.
+async fn read_body(req: &mut ServiceRequest, limit: usize) -> Result<Bytes, Rejection> {
+    let mut payload = req.take_payload();
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|e| Rejection::new(StatusCode::BAD_REQUEST, "invalid_body", e.to_string()))?;
+        if body.len() + chunk.len() > limit {
+            return Err(Rejection::new(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                "payload_too_large",
+                format!("body is larger than {limit} bytes"),
+            ));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SignatureType {
+    Eip191,
+    Eip712,
+}
+
+/// What a request signature covers.
+#[derive(Debug, Clone)]
+struct RequestClaims {
+    method: String,
+    /// Path and query string.
+    path: String,
+    body_hash: [u8; 32],
+    /// The zero address when the request names no escrow.
+    escrow: Address,
+    nonce: String,
+    timestamp: u64,
+}
+
+impl RequestClaims {
+    fn message(&self) -> String {
+        format!(
+            "ZKP backend request\nmethod: {}\npath: {}\nbody: 0x{}\nescrow: 0x{}\nnonce: {}\ntimestamp: {}",
+            self.method,
+            self.path,
+            hex::encode(self.body_hash),
+            hex::encode(self.escrow),
+            self.nonce,
+            self.timestamp
+        )
+    }
+
+    fn digest(&self, kind: SignatureType, domain_separator: &[u8; 32]) -> [u8; 32] {
+        match kind {
+            SignatureType::Eip191 => eth::eip191_hash(self.message().as_bytes()),
+            SignatureType::Eip712 => {
+                let struct_hash = eth::hash_struct(
+                    EIP712_TYPE,
+                    &[
+                        eth::keccak256(self.method.as_bytes()),
+                        eth::keccak256(self.path.as_bytes()),
+                        self.body_hash,
+                        eth::encode_address(&self.escrow),
+                        eth::keccak256(self.nonce.as_bytes()),
+                        eth::encode_uint(self.timestamp),
+                    ],
+                );
+                eth::eip712_hash(domain_separator, &struct_hash)
+            },
+        }
+    }
+}
+
+/// Reads escrow roles with `eth_call`.
+struct EscrowRoles {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl EscrowRoles {
+    fn new(url: &str) -> Result<Self, String> {
+        let client = reqwest::Client::builder().timeout(RPC_TIMEOUT).build().map_err(|e| e.to_string())?;
+        Ok(Self { client, url: url.to_owned() })
+    }
+
+    /// The seller (`owner()`) and `buyer()`. An unset buyer is the zero
+    /// address, which no signature recovers to.
+    async fn parties(&self, escrow: &Address) -> Result<[Address; 2], String> {
+        Ok([self.call_address(escrow, "owner()").await?, self.call_address(escrow, "buyer()").await?])
+    }
+
+    async fn call_address(&self, contract: &Address, function: &str) -> Result<Address, String> {
+        let selector = &eth::keccak256(function.as_bytes())[..4];
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "eth_call",
+            "params": [{ "to": format!("0x{}", hex::encode(contract)), "data": format!("0x{}", hex::encode(selector)) }, "latest"],
+        });
+        let response: Value = self
+            .client
+            .post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("{function}: {error}"));
+        }
+        let word = response["result"]
+            .as_str()
+            .and_then(|r| hex::decode(r.strip_prefix("0x").unwrap_or(r)).ok())
+            .filter(|w| w.len() == 32)
+            .ok_or_else(|| format!("{function} did not return an address"))?;
+        Ok(word[12..].try_into().expect("20 bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::test::{call_service, init_service, read_body, read_body_json, TestRequest};
+    use actix_web::{App, HttpRequest, HttpServer};
+    use k256::ecdsa::SigningKey;
+
+    const KEY: &str = "0123456789abcdef-service";
+    const ISSUER_KEY: &str = "0123456789abcdef-issuer";
+    const OPERATOR_KEY: &str = "0123456789abcdef-operator";
+    /// Hardhat/Ganache accounts #0, #1 and #2.
+    const ALICE: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const BOB: &str = "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+    const ISSUER: &str = "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a";
+
+    fn key(hex_key: &str) -> SigningKey {
+        SigningKey::from_slice(&hex::decode(hex_key).unwrap()).unwrap()
+    }
+
+    fn config(escrow_rpc_url: Option<String>) -> Config {
+        Config {
+            auth: true,
+            api_keys: vec![KEY.into()],
+            auth_allowlist: vec![eth::format_address(&eth::address_of(key(ALICE).verifying_key()))],
+            issuer_api_keys: vec![ISSUER_KEY.into()],
+            issuer_allowlist: vec![eth::format_address(&eth::address_of(key(ISSUER).verifying_key()))],
+            operator_api_keys: vec![OPERATOR_KEY.into()],
+            escrow_rpc_url,
+            ..Config::default()
+        }
+    }
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    /// A POST to `path` signed by `signer`.
+    fn signed(signer: &SigningKey, path: &str, body: &str, nonce: &str, kind: SignatureType, escrow: Option<Address>) -> TestRequest {
+        let timestamp = now();
+        let claims = RequestClaims {
+            method: "POST".into(),
+            path: path.into(),
+            body_hash: eth::keccak256(body.as_bytes()),
+            escrow: escrow.unwrap_or_default(),
+            nonce: nonce.into(),
+            timestamp,
+        };
+        let domain_separator = Auth::new(&config(None)).unwrap().domain_separator;
+        let signature = eth::sign(signer, &claims.digest(kind, &domain_separator));
+        let mut req = TestRequest::post()
+            .uri(path)
+            .insert_header((SIGNER, eth::format_address(&eth::address_of(signer.verifying_key()))))
+            .insert_header((SIGNATURE, format!("0x{}", hex::encode(signature))))
+            .insert_header((SIGNATURE_TYPE, if kind == SignatureType::Eip712 { "eip712" } else { "eip191" }))
+            .insert_header((NONCE, nonce))
+            .insert_header((TIMESTAMP, timestamp.to_string()))
+            .set_payload(body.to_owned());
+        if let Some(escrow) = escrow {
+            req = req.insert_header((ESCROW, format!("0x{}", hex::encode(escrow))));
+        }
+        req
+    }
+
+    macro_rules! app {
+        ($config:expr) => {
+            init_service(
+                App::new()
+                    .wrap(from_fn(authenticate))
+                    .app_data(web::Data::new(Auth::new(&$config).unwrap()))
+                    .route("/zkp/generate", web::post().to(|body: Bytes| async move { HttpResponse::Ok().body(body) }))
+                    .route("/zkp/bbs/issue", web::post().to(HttpResponse::Ok))
+                    .route("/zkp/verify", web::post().to(HttpResponse::Ok))
+                    .route("/zkp/pool", web::get().to(HttpResponse::Ok))
+                    .route(issued::PATH, web::delete().to(HttpResponse::Ok))
+                    .route("/signed-escrow", web::post().to(|req: HttpRequest| async move {
+                        HttpResponse::Ok().json(req.extensions().get::<SignedEscrow>().map(|e| e.0.map(hex::encode)))
+                    })),
+            )
+            .await
+        };
+    }
+
+    async fn code(resp: ServiceResponse<impl MessageBody>) -> String {
+        let body: Value = read_body_json(resp).await;
+        body["code"].as_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn digests_match_ethers() {
+        let claims = RequestClaims {
+            method: "POST".into(),
+            path: "/v1/value-commitments".into(),
+            body_hash: eth::keccak256(br#"{"value":42}"#),
+            escrow: Address::default(),
+            nonce: "n-1".into(),
+            timestamp: 1_700_000_000,
+        };
+        let domain_separator = Auth::new(&config(None)).unwrap().domain_separator;
+        // ethers.js `hashMessage(message)` and `TypedDataEncoder.hash(domain, types, value)`.
+        assert_eq!(hex::encode(claims.digest(SignatureType::Eip191, &domain_separator)), "1c150ec53ccf311d185c15c0b6742bb287fa2353501d019d9394ee1c307b36b4");
+        assert_eq!(hex::encode(claims.digest(SignatureType::Eip712, &domain_separator)), "cb33bdb624617f6bcad57b79aff063ce6f76b31e5da021cc7a631f0cb5c365f5");
+    }
+
+    #[actix_web::test]
+    async fn api_keys_guard_generation_only() {
+        let app = app!(config(None));
+
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/generate").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(code(resp).await, "unauthenticated");
+
+        let req = TestRequest::post().uri("/zkp/generate").insert_header((API_KEY, "0123456789abcdef-wrong"));
+        let resp = call_service(&app, req.to_request()).await;
+        assert_eq!(code(resp).await, "invalid_api_key");
+
+        let req = TestRequest::post().uri("/zkp/generate").insert_header((API_KEY, KEY)).set_payload("{}");
+        let resp = call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        for req in [TestRequest::post().uri("/zkp/verify"), TestRequest::get().uri("/zkp/pool")] {
+            assert_eq!(call_service(&app, req.to_request()).await.status(), StatusCode::OK);
+        }
+    }
+
+    #[actix_web::test]
+    async fn signed_requests_are_checked_and_not_replayable() {
+        let app = app!(config(None));
+        let body = r#"{"value":42}"#;
+
+        for (nonce, kind) in [("a-1", SignatureType::Eip191), ("a-2", SignatureType::Eip712)] {
+            let resp = call_service(&app, signed(&key(ALICE), "/zkp/generate", body, nonce, kind, None).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            // The handler still sees the body the signature covered.
+            assert_eq!(read_body(resp).await, body.as_bytes());
+        }
+
+        let replay = signed(&key(ALICE), "/zkp/generate", body, "a-1", SignatureType::Eip191, None);
+        assert_eq!(code(call_service(&app, replay.to_request()).await).await, "nonce_reused");
+
+        let tampered = signed(&key(ALICE), "/zkp/generate", body, "a-3", SignatureType::Eip191, None).set_payload(r#"{"value":43}"#);
+        assert_eq!(code(call_service(&app, tampered.to_request()).await).await, "invalid_signature");
+
+        let stale = signed(&key(ALICE), "/zkp/generate", body, "a-4", SignatureType::Eip191, None)
+            .insert_header((TIMESTAMP, (now() - 3600).to_string()));
+        assert_eq!(code(call_service(&app, stale.to_request()).await).await, "stale_request");
+
+        let stranger = signed(&key(BOB), "/zkp/generate", body, "b-1", SignatureType::Eip191, None);
+        let resp = call_service(&app, stranger.to_request()).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        assert_eq!(code(resp).await, "forbidden");
+    }
+
+    #[actix_web::test]
+    async fn only_the_issuer_issues() {
+        let bob = eth::address_of(key(BOB).verifying_key());
+        let app = app!(config(Some(rpc_node(bob).await)));
+        let issue = || TestRequest::post().uri("/zkp/bbs/issue");
+
+        let resp = call_service(&app, issue().insert_header((API_KEY, KEY)).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        for (signer, nonce, escrow) in [(ALICE, "a-1", None), (BOB, "b-1", Some([0x42; 20]))] {
+            let resp = call_service(&app, signed(&key(signer), "/zkp/bbs/issue", "{}", nonce, SignatureType::Eip191, escrow).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{signer}");
+        }
+
+        let resp = call_service(&app, issue().insert_header((API_KEY, ISSUER_KEY)).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = call_service(&app, signed(&key(ISSUER), "/zkp/bbs/issue", "{}", "i-1", SignatureType::Eip712, None).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        // Issuer credentials pass everywhere else too.
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/generate").insert_header((API_KEY, ISSUER_KEY)).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn only_operators_purge() {
+        let app = app!(config(None));
+        let purge = || TestRequest::delete().uri("/v1/issued?before=1");
+
+        for key in [KEY, ISSUER_KEY] {
+            let resp = call_service(&app, purge().insert_header((API_KEY, key)).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN, "{key}");
+        }
+        let resp = call_service(&app, purge().insert_header((API_KEY, OPERATOR_KEY)).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/generate").insert_header((API_KEY, OPERATOR_KEY)).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/bbs/issue").insert_header((API_KEY, OPERATOR_KEY)).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[actix_web::test]
+    async fn signed_requests_carry_their_escrow() {
+        let app = app!(config(None));
+        let resp = call_service(&app, signed(&key(ALICE), "/signed-escrow", "{}", "e-1", SignatureType::Eip191, Some([0x42; 20])).to_request()).await;
+        let escrow: Value = read_body_json(resp).await;
+        assert_eq!(escrow, json!("42".repeat(20)));
+        let resp = call_service(&app, TestRequest::post().uri("/signed-escrow").insert_header((API_KEY, KEY)).insert_header((ESCROW, format!("0x{}", "42".repeat(20)))).to_request()).await;
+        let escrow: Value = read_body_json(resp).await;
+        assert_eq!(escrow, Value::Null, "an API key does not vouch for X-Zkp-Escrow");
+    }
+
+    /// A JSON-RPC node that knows a single escrow, sold by `owner` to nobody yet.
+    async fn rpc_node(owner: Address) -> String {
+        let server = HttpServer::new(move || {
+            App::new().route(
+                "/",
+                web::post().to(move |call: web::Json<Value>| async move {
+                    let word = match call["params"][0]["data"].as_str() {
+                        Some("0x8da5cb5b") => eth::encode_address(&owner),
+                        _ => [0u8; 32],
+                    };
+                    HttpResponse::Ok().json(json!({ "jsonrpc": "2.0", "id": 1, "result": format!("0x{}", hex::encode(word)) }))
+                }),
+            )
+        })
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap();
+        let url = format!("http://{}", server.addrs()[0]);
+        actix_web::rt::spawn(server.run());
+        url
+    }
+
+    #[actix_web::test]
+    async fn escrow_parties_may_sign() {
+        let bob = eth::address_of(key(BOB).verifying_key());
+        let app = app!(config(Some(rpc_node(bob).await)));
+        let escrow = Some([0x42; 20]);
+
+        let resp = call_service(&app, signed(&key(BOB), "/zkp/generate", "{}", "b-1", SignatureType::Eip712, escrow).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // The zero buyer of an escrow nobody bought yet matches no one.
+        let carol = SigningKey::from_slice(&[0x11; 32]).unwrap();
+        let resp = call_service(&app, signed(&carol, "/zkp/generate", "{}", "c-1", SignatureType::Eip712, escrow).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+}
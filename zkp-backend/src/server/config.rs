@@ -0,0 +1,753 @@
+//! Server configuration.
+//!
+//! Settings come from three layers, later ones winning: a TOML file
+//! (`--config` / `ZKP_CONFIG`), `ZKP_*` environment variables, and command
+//! line flags. Anything left unset keeps the historical default, so a bare
+//! `cargo run` still serves everything on `127.0.0.1:5010` to any origin,
+//! except the BBS+ and revocation endpoints: those need the issuer key
+//! `ZKP_BBS_ISSUER_KEY_HEX` and the accumulator key `ZKP_REVOCATION_KEY_HEX`
+//! and are only served when theirs is set. The keys are never read from the
+//! file, and neither they nor the API keys are part of the printed config.
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use actix_web::http::Uri;
+use crate::eth::parse_address;
+use clap::{Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+
+/// Groups of routes that can be switched on and off together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum EndpointGroup {
+    /// Deprecated `/zkp/*` commitment and range proof routes.
+    Legacy,
+    /// The versioned `/v1` API.
+    V1,
+    /// Multi-party aggregated range proofs.
+    Aggregation,
+    /// R1CS statement proofs.
+    Statements,
+    /// Homomorphic commitment arithmetic.
+    Commitments,
+    /// BBS+ credentials.
+    Bbs,
+    /// Revocation accumulator.
+    Revocation,
+    /// Whole-credential proof verification (`/zkp/verify-vc`).
+    Credentials,
+    /// Asynchronous proof jobs.
+    Jobs,
+    /// Proof pool and generator status.
+    Status,
+    /// Prometheus `/metrics`.
+    Metrics,
+    /// `/healthz` and `/readyz` probes.
+    Health,
+}
+
+impl EndpointGroup {
+    pub const ALL: [Self; 12] = [
+        Self::Legacy,
+        Self::V1,
+        Self::Aggregation,
+        Self::Statements,
+        Self::Commitments,
+        Self::Bbs,
+        Self::Revocation,
+        Self::Credentials,
+        Self::Jobs,
+        Self::Status,
+        Self::Metrics,
+        Self::Health,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// One human-readable line per event.
+    Pretty,
+    /// One JSON object per event, for log shippers.
+    Json,
+}
+
+/// Whether TLS clients must present a certificate signed by `tls_client_ca`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientAuth {
+    /// Refuse the handshake without a valid client certificate.
+    Required,
+    /// Accept anonymous clients; certificates that are presented must verify.
+    Optional,
+}
+
+/// Which presentations of a verified proof the replay registry accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum ReplayScope {
+    /// The first presentation only.
+    Once,
+    /// The first presentation to each verifier (`X-Zkp-Verifier`).
+    PerVerifier,
+    /// Every presentation; earlier ones are still reported.
+    Unlimited,
+}
+
+/// Length of the hex BBS+ issuer secret key: a 32-byte little-endian scalar
+/// below the BLS12-381 group order.
+const BBS_ISSUER_KEY_HEX_LEN: usize = 64;
+/// Shortest hex accumulator key: 32 bytes of seed material.
+const MIN_REVOCATION_KEY_HEX_LEN: usize = 64;
+
+/// Command line flags; each one can also be given through its `ZKP_*` variable.
+#[derive(Debug, Default, Parser)]
+#[command(name = "bulletproof-demo", version, about = "Zero-knowledge proof backend")]
+pub struct Args {
+    /// TOML file with any of the settings below
+    #[arg(long, env = "ZKP_CONFIG")]
+    pub config: Option<PathBuf>,
+    /// Address to listen on
+    #[arg(long, env = "ZKP_LISTEN")]
+    pub listen: Option<SocketAddr>,
+    /// HTTP worker threads
+    #[arg(long, env = "ZKP_HTTP_WORKERS")]
+    pub workers: Option<usize>,
+    /// Proof worker threads
+    #[arg(long, env = "ZKP_PROOF_WORKERS")]
+    pub proof_workers: Option<usize>,
+    /// Proof jobs allowed to wait for a worker
+    #[arg(long, env = "ZKP_PROOF_QUEUE")]
+    pub proof_queue: Option<usize>,
+    /// Allowed CORS origins, comma separated; `*` allows any
+    #[arg(long = "cors-origin", env = "ZKP_CORS_ORIGINS", value_delimiter = ',')]
+    pub cors_origins: Option<Vec<String>>,
+    /// Largest accepted JSON body, in bytes
+    #[arg(long, env = "ZKP_JSON_LIMIT")]
+    pub json_limit: Option<usize>,
+    /// Largest accepted body for proof generation, in bytes
+    #[arg(long, env = "ZKP_PROVE_JSON_LIMIT")]
+    pub prove_json_limit: Option<usize>,
+    /// Proof generation requests per client and minute (unlimited if unset)
+    #[arg(long, env = "ZKP_PROVE_RATE")]
+    pub prove_rate: Option<u32>,
+    /// Proof generation requests a client may burst (default: the per-minute rate)
+    #[arg(long, env = "ZKP_PROVE_BURST")]
+    pub prove_burst: Option<u32>,
+    /// Verification requests per client and minute (unlimited if unset)
+    #[arg(long, env = "ZKP_VERIFY_RATE")]
+    pub verify_rate: Option<u32>,
+    /// Verification requests a client may burst (default: the per-minute rate)
+    #[arg(long, env = "ZKP_VERIFY_BURST")]
+    pub verify_burst: Option<u32>,
+    /// Endpoint groups to serve, comma separated
+    #[arg(long, env = "ZKP_ENDPOINTS", value_delimiter = ',')]
+    pub endpoints: Option<Vec<EndpointGroup>>,
+    /// Server log verbosity; `RUST_LOG` takes precedence when set
+    #[arg(long, env = "ZKP_LOG_LEVEL")]
+    pub log_level: Option<LogLevel>,
+    /// Log output format
+    #[arg(long, env = "ZKP_LOG_FORMAT")]
+    pub log_format: Option<LogFormat>,
+    /// Directory of the proof job store
+    #[arg(long, env = "ZKP_JOB_STORE")]
+    pub job_store: Option<PathBuf>,
+    /// Directory of the issued commitment record; off unless set
+    #[arg(long, env = "ZKP_ISSUED_STORE")]
+    pub issued_store: Option<PathBuf>,
+    /// Days issued commitment records are kept (default: forever)
+    #[arg(long, env = "ZKP_ISSUED_RETENTION_DAYS")]
+    pub issued_retention_days: Option<u32>,
+    /// Directory of the revocation registry
+    #[arg(long, env = "ZKP_REVOCATION_STORE")]
+    pub revocation_store: Option<PathBuf>,
+    /// Directory of the registry of presented proofs; off unless set
+    #[arg(long, env = "ZKP_REPLAY_STORE")]
+    pub replay_store: Option<PathBuf>,
+    /// Which presentations of one proof the replay registry accepts
+    #[arg(long, env = "ZKP_REPLAY_SCOPE")]
+    pub replay_scope: Option<ReplayScope>,
+    /// Require signed requests or an API key for proof generation
+    #[arg(long, env = "ZKP_AUTH", num_args = 0..=1, default_missing_value = "true")]
+    pub auth: Option<bool>,
+    /// Static API keys for service-to-service calls, comma separated
+    #[arg(long = "api-key", env = "ZKP_API_KEYS", value_delimiter = ',', hide_env_values = true)]
+    pub api_keys: Option<Vec<String>>,
+    /// Addresses whose signed requests are accepted, comma separated
+    #[arg(long = "auth-allow", env = "ZKP_AUTH_ALLOWLIST", value_delimiter = ',')]
+    pub auth_allowlist: Option<Vec<String>>,
+    /// API keys that may also issue credentials and change the revocation registry
+    #[arg(long = "issuer-api-key", env = "ZKP_ISSUER_API_KEYS", value_delimiter = ',', hide_env_values = true)]
+    pub issuer_api_keys: Option<Vec<String>>,
+    /// Addresses that may also issue credentials and change the revocation registry
+    #[arg(long = "issuer-allow", env = "ZKP_ISSUER_ALLOWLIST", value_delimiter = ',')]
+    pub issuer_allowlist: Option<Vec<String>>,
+    /// API keys that may also purge the issued commitment record
+    #[arg(long = "operator-api-key", env = "ZKP_OPERATOR_API_KEYS", value_delimiter = ',', hide_env_values = true)]
+    pub operator_api_keys: Option<Vec<String>>,
+    /// JSON-RPC endpoint used to look up an escrow's seller and buyer
+    #[arg(long, env = "ZKP_ESCROW_RPC_URL")]
+    pub escrow_rpc_url: Option<String>,
+    /// Chain id in the EIP-712 domain of signed requests
+    #[arg(long, env = "ZKP_CHAIN_ID")]
+    pub chain_id: Option<u64>,
+    /// PEM certificate chain; serves HTTPS together with `--tls-key`
+    #[arg(long, env = "ZKP_TLS_CERT")]
+    pub tls_cert: Option<PathBuf>,
+    /// PEM private key of the certificate
+    #[arg(long, env = "ZKP_TLS_KEY")]
+    pub tls_key: Option<PathBuf>,
+    /// PEM CA certificates that sign client certificates (enables mutual TLS)
+    #[arg(long, env = "ZKP_TLS_CLIENT_CA")]
+    pub tls_client_ca: Option<PathBuf>,
+    /// Whether clients must present a certificate when `--tls-client-ca` is set
+    #[arg(long, env = "ZKP_TLS_CLIENT_AUTH")]
+    pub tls_client_auth: Option<ClientAuth>,
+    /// BBS+ issuer secret key (hex); the bbs endpoints are served only with one
+    #[arg(long, env = "ZKP_BBS_ISSUER_KEY_HEX", hide_env_values = true)]
+    pub bbs_issuer_key_hex: Option<String>,
+    /// Revocation accumulator key (hex seed); the revocation endpoints are served only with one
+    #[arg(long, env = "ZKP_REVOCATION_KEY_HEX", hide_env_values = true)]
+    pub revocation_key_hex: Option<String>,
+}
+
+/// The TOML file: the same settings as [`Args`], all optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    listen: Option<SocketAddr>,
+    workers: Option<usize>,
+    proof_workers: Option<usize>,
+    proof_queue: Option<usize>,
+    cors_origins: Option<Vec<String>>,
+    json_limit: Option<usize>,
+    prove_json_limit: Option<usize>,
+    prove_rate: Option<u32>,
+    prove_burst: Option<u32>,
+    verify_rate: Option<u32>,
+    verify_burst: Option<u32>,
+    endpoints: Option<Vec<EndpointGroup>>,
+    log_level: Option<LogLevel>,
+    log_format: Option<LogFormat>,
+    job_store: Option<PathBuf>,
+    issued_store: Option<PathBuf>,
+    issued_retention_days: Option<u32>,
+    revocation_store: Option<PathBuf>,
+    replay_store: Option<PathBuf>,
+    replay_scope: Option<ReplayScope>,
+    auth: Option<bool>,
+    api_keys: Option<Vec<String>>,
+    auth_allowlist: Option<Vec<String>>,
+    issuer_api_keys: Option<Vec<String>>,
+    issuer_allowlist: Option<Vec<String>>,
+    operator_api_keys: Option<Vec<String>>,
+    escrow_rpc_url: Option<String>,
+    chain_id: Option<u64>,
+    tls_cert: Option<PathBuf>,
+    tls_key: Option<PathBuf>,
+    tls_client_ca: Option<PathBuf>,
+    tls_client_auth: Option<ClientAuth>,
+}
+
+impl FileConfig {
+    fn read(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        toml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+}
+
+/// Largest `json_limit` accepted; proofs and statements are far smaller.
+pub const MAX_JSON_LIMIT: usize = 64 * 1024 * 1024;
+
+const DEFAULT_PROVE_JSON_LIMIT: usize = 256 * 1024;
+
+/// Shortest accepted API key.
+pub const MIN_API_KEY_LEN: usize = 16;
+
+/// Effective configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Config {
+    pub listen: SocketAddr,
+    pub workers: usize,
+    pub proof_workers: usize,
+    pub proof_queue: usize,
+    pub cors_origins: Vec<String>,
+    pub json_limit: usize,
+    /// Body limit of proof generation requests; verifications get `json_limit`.
+    pub prove_json_limit: usize,
+    pub prove_rate: Option<u32>,
+    pub prove_burst: Option<u32>,
+    pub verify_rate: Option<u32>,
+    pub verify_burst: Option<u32>,
+    pub endpoints: Vec<EndpointGroup>,
+    pub log_level: LogLevel,
+    pub log_format: LogFormat,
+    pub job_store: PathBuf,
+    pub issued_store: Option<PathBuf>,
+    pub issued_retention_days: Option<u32>,
+    pub revocation_store: PathBuf,
+    pub replay_store: Option<PathBuf>,
+    pub replay_scope: ReplayScope,
+    pub auth: bool,
+    #[serde(skip_serializing)]
+    pub api_keys: Vec<String>,
+    pub auth_allowlist: Vec<String>,
+    /// Issuing BBS+ credentials and adding or removing revocation handles
+    /// take one of these keys or addresses; see [`auth`](super::auth).
+    #[serde(skip_serializing)]
+    pub issuer_api_keys: Vec<String>,
+    pub issuer_allowlist: Vec<String>,
+    /// Purging the issued record (`DELETE /v1/issued`) takes one of these.
+    #[serde(skip_serializing)]
+    pub operator_api_keys: Vec<String>,
+    pub escrow_rpc_url: Option<String>,
+    pub chain_id: u64,
+    pub tls_cert: Option<PathBuf>,
+    pub tls_key: Option<PathBuf>,
+    pub tls_client_ca: Option<PathBuf>,
+    pub tls_client_auth: ClientAuth,
+    /// Signs every BBS+ credential; without it the bbs group is refused, so
+    /// issued credentials never depend on a key lost at the next restart.
+    #[serde(skip_serializing)]
+    pub bbs_issuer_key_hex: Option<String>,
+    /// Derives the accumulator trapdoor, so its value, public key and
+    /// witnesses outlive restarts; without it the revocation group is refused.
+    #[serde(skip_serializing)]
+    pub revocation_key_hex: Option<String>,
+}
+
+fn available_cores() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let cores = available_cores();
+        Self {
+            listen: SocketAddr::from(([127, 0, 0, 1], 5010)),
+            workers: cores,
+            proof_workers: cores,
+            proof_queue: cores * 8,
+            cors_origins: vec!["*".into()],
+            json_limit: 2 * 1024 * 1024,
+            // Tx hashes, values and statements are tiny; a full batch of
+            // jobs is the largest legitimate body.
+            prove_json_limit: DEFAULT_PROVE_JSON_LIMIT,
+            prove_rate: None,
+            prove_burst: None,
+            verify_rate: None,
+            verify_burst: None,
+            // Every group but those that need a key.
+            endpoints: EndpointGroup::ALL.into_iter().filter(|g| !matches!(g, EndpointGroup::Bbs | EndpointGroup::Revocation)).collect(),
+            log_level: LogLevel::Info,
+            log_format: LogFormat::Pretty,
+            job_store: PathBuf::from("data/jobs"),
+            issued_store: None,
+            issued_retention_days: None,
+            revocation_store: PathBuf::from("data/revocation"),
+            replay_store: None,
+            replay_scope: ReplayScope::Once,
+            auth: false,
+            api_keys: Vec::new(),
+            auth_allowlist: Vec::new(),
+            issuer_api_keys: Vec::new(),
+            issuer_allowlist: Vec::new(),
+            operator_api_keys: Vec::new(),
+            escrow_rpc_url: None,
+            // The Hardhat/Ganache development chain.
+            chain_id: 1337,
+            tls_cert: None,
+            tls_key: None,
+            tls_client_ca: None,
+            tls_client_auth: ClientAuth::Required,
+            bbs_issuer_key_hex: None,
+            revocation_key_hex: None,
+        }
+    }
+}
+
+impl Config {
+    /// Parse the command line and environment, read the config file they
+    /// name, and validate the result.
+    pub fn load() -> Result<Self, String> {
+        Self::from_args(Args::parse())
+    }
+
+    pub fn from_args(args: Args) -> Result<Self, String> {
+        let file = match &args.config {
+            Some(path) => FileConfig::read(path)?,
+            None => FileConfig::default(),
+        };
+        let defaults = Self::default();
+        let proof_workers = args.proof_workers.or(file.proof_workers).unwrap_or(defaults.proof_workers);
+        let json_limit = args.json_limit.or(file.json_limit).unwrap_or(defaults.json_limit);
+        let key = |k: Option<String>| k.map(|k| k.trim().trim_start_matches("0x").to_owned());
+        let (bbs_issuer_key_hex, revocation_key_hex) = (key(args.bbs_issuer_key_hex), key(args.revocation_key_hex));
+        let mut endpoints = args.endpoints.or(file.endpoints).unwrap_or_else(|| {
+            let keyed = [(EndpointGroup::Bbs, &bbs_issuer_key_hex), (EndpointGroup::Revocation, &revocation_key_hex)];
+            let keyed = keyed.into_iter().filter(|(_, key)| key.is_some()).map(|(group, _)| group);
+            defaults.endpoints.iter().copied().chain(keyed).collect()
+        });
+        endpoints.sort();
+        endpoints.dedup();
+        let config = Self {
+            listen: args.listen.or(file.listen).unwrap_or(defaults.listen),
+            workers: args.workers.or(file.workers).unwrap_or(defaults.workers),
+            proof_workers,
+            // The queue follows the worker count unless set explicitly.
+            proof_queue: args.proof_queue.or(file.proof_queue).unwrap_or(proof_workers * 8),
+            cors_origins: args.cors_origins.or(file.cors_origins).unwrap_or(defaults.cors_origins),
+            json_limit,
+            // Never more than the general limit unless asked for (and then rejected).
+            prove_json_limit: args
+                .prove_json_limit
+                .or(file.prove_json_limit)
+                .unwrap_or(defaults.prove_json_limit.min(json_limit)),
+            prove_rate: args.prove_rate.or(file.prove_rate),
+            prove_burst: args.prove_burst.or(file.prove_burst),
+            verify_rate: args.verify_rate.or(file.verify_rate),
+            verify_burst: args.verify_burst.or(file.verify_burst),
+            endpoints,
+            log_level: args.log_level.or(file.log_level).unwrap_or(defaults.log_level),
+            log_format: args.log_format.or(file.log_format).unwrap_or(defaults.log_format),
+            job_store: args.job_store.or(file.job_store).unwrap_or(defaults.job_store),
+            issued_store: args.issued_store.or(file.issued_store),
+            issued_retention_days: args.issued_retention_days.or(file.issued_retention_days),
+            revocation_store: args.revocation_store.or(file.revocation_store).unwrap_or(defaults.revocation_store),
+            replay_store: args.replay_store.or(file.replay_store),
+            replay_scope: args.replay_scope.or(file.replay_scope).unwrap_or(defaults.replay_scope),
+            auth: args.auth.or(file.auth).unwrap_or(defaults.auth),
+            api_keys: args.api_keys.or(file.api_keys).unwrap_or(defaults.api_keys),
+            auth_allowlist: args.auth_allowlist.or(file.auth_allowlist).unwrap_or(defaults.auth_allowlist),
+            issuer_api_keys: args.issuer_api_keys.or(file.issuer_api_keys).unwrap_or(defaults.issuer_api_keys),
+            issuer_allowlist: args.issuer_allowlist.or(file.issuer_allowlist).unwrap_or(defaults.issuer_allowlist),
+            operator_api_keys: args.operator_api_keys.or(file.operator_api_keys).unwrap_or(defaults.operator_api_keys),
+            escrow_rpc_url: args.escrow_rpc_url.or(file.escrow_rpc_url),
+            chain_id: args.chain_id.or(file.chain_id).unwrap_or(defaults.chain_id),
+            tls_cert: args.tls_cert.or(file.tls_cert),
+            tls_key: args.tls_key.or(file.tls_key),
+            tls_client_ca: args.tls_client_ca.or(file.tls_client_ca),
+            tls_client_auth: args.tls_client_auth.or(file.tls_client_auth).unwrap_or(defaults.tls_client_auth),
+            bbs_issuer_key_hex,
+            revocation_key_hex,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Every problem with the configuration, joined into one message.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        for (name, value) in [("workers", self.workers), ("proof_workers", self.proof_workers), ("proof_queue", self.proof_queue)] {
+            if value == 0 {
+                problems.push(format!("{name} must be at least 1"));
+            }
+        }
+        if !(1024..=MAX_JSON_LIMIT).contains(&self.json_limit) {
+            problems.push(format!("json_limit must be between 1024 and {MAX_JSON_LIMIT} bytes, got {}", self.json_limit));
+        }
+        if !(1024..=self.json_limit).contains(&self.prove_json_limit) {
+            problems.push(format!(
+                "prove_json_limit must be between 1024 bytes and json_limit ({}), got {}",
+                self.json_limit, self.prove_json_limit
+            ));
+        }
+        for (name, rate, burst) in [
+            ("prove", self.prove_rate, self.prove_burst),
+            ("verify", self.verify_rate, self.verify_burst),
+        ] {
+            if rate == Some(0) || burst == Some(0) {
+                problems.push(format!("{name}_rate and {name}_burst must be at least 1"));
+            }
+            if rate.is_none() && burst.is_some() {
+                problems.push(format!("{name}_burst needs {name}_rate"));
+            }
+        }
+        if self.endpoints.is_empty() {
+            problems.push("endpoints must enable at least one group".into());
+        }
+        if self.cors_origins.iter().any(|o| o == "*") && self.cors_origins.len() > 1 {
+            problems.push("cors_origins: `*` cannot be combined with specific origins".into());
+        }
+        problems.extend(self.cors_origins.iter().filter(|o| *o != "*").filter_map(|o| check_origin(o).err()));
+        for (name, keys) in [("api_keys", &self.api_keys), ("issuer_api_keys", &self.issuer_api_keys), ("operator_api_keys", &self.operator_api_keys)] {
+            if keys.iter().any(|k| k.len() < MIN_API_KEY_LEN) {
+                problems.push(format!("{name} must be at least {MIN_API_KEY_LEN} characters long"));
+            }
+        }
+        for (name, addresses) in [("auth_allowlist", &self.auth_allowlist), ("issuer_allowlist", &self.issuer_allowlist)] {
+            problems.extend(addresses.iter().filter_map(|a| parse_address(a).err()).map(|e| format!("{name}: {e}")));
+        }
+        if let Some(url) = &self.escrow_rpc_url {
+            let uri: Option<Uri> = url.parse().ok();
+            if !uri.as_ref().is_some_and(|u| matches!(u.scheme_str(), Some("http" | "https")) && u.host().is_some()) {
+                problems.push(format!("escrow_rpc_url {url:?} needs an http(s) scheme and a host"));
+            }
+        }
+        if self.tls_cert.is_some() != self.tls_key.is_some() {
+            problems.push("tls_cert and tls_key must be set together".into());
+        }
+        if self.tls_client_ca.is_some() && self.tls_cert.is_none() {
+            problems.push("tls_client_ca needs tls_cert and tls_key".into());
+        }
+        match &self.bbs_issuer_key_hex {
+            None if self.serves(EndpointGroup::Bbs) => {
+                problems.push("the bbs endpoints need a BBS+ issuer key (ZKP_BBS_ISSUER_KEY_HEX)".into());
+            },
+            Some(key) if key.len() != BBS_ISSUER_KEY_HEX_LEN || !key.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                problems.push(format!("bbs_issuer_key_hex must be {BBS_ISSUER_KEY_HEX_LEN} hex digits"));
+            },
+            _ => {},
+        }
+        match &self.revocation_key_hex {
+            None if self.serves(EndpointGroup::Revocation) => {
+                problems.push("the revocation endpoints need an accumulator key (ZKP_REVOCATION_KEY_HEX)".into());
+            },
+            Some(key) if key.len() < MIN_REVOCATION_KEY_HEX_LEN || hex::decode(key).is_err() => {
+                problems.push(format!("revocation_key_hex must be at least {MIN_REVOCATION_KEY_HEX_LEN} hex digits"));
+            },
+            _ => {},
+        }
+        match self.issued_retention_days {
+            Some(0) => problems.push("issued_retention_days must be at least 1".into()),
+            Some(_) if self.issued_store.is_none() => problems.push("issued_retention_days needs issued_store".into()),
+            _ => {},
+        }
+        if self.auth
+            && self.api_keys.is_empty()
+            && self.auth_allowlist.is_empty()
+            && self.issuer_api_keys.is_empty()
+            && self.issuer_allowlist.is_empty()
+            && self.operator_api_keys.is_empty()
+            && self.escrow_rpc_url.is_none()
+            && self.tls_client_ca.is_none()
+        {
+            problems.push("auth needs api_keys, auth_allowlist, escrow_rpc_url or tls_client_ca, or no request could pass".into());
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+
+    pub fn serves_tls(&self) -> bool {
+        self.tls_cert.is_some()
+    }
+
+    pub fn serves(&self, group: EndpointGroup) -> bool {
+        self.endpoints.contains(&group)
+    }
+
+    pub fn allows_any_origin(&self) -> bool {
+        self.cors_origins.iter().any(|o| o == "*")
+    }
+}
+
+/// A browser `Origin`: scheme and host, optional port, nothing else.
+fn check_origin(origin: &str) -> Result<(), String> {
+    let uri: Uri = origin.parse().map_err(|_| format!("cors origin {origin:?} is not a URL"))?;
+    if !matches!(uri.scheme_str(), Some("http" | "https")) || uri.host().is_none() {
+        return Err(format!("cors origin {origin:?} needs an http(s) scheme and a host"));
+    }
+    if uri.path_and_query().is_some_and(|p| p.as_str() != "/") || origin.ends_with('/') {
+        return Err(format!("cors origin {origin:?} must not have a path"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::try_parse_from(std::iter::once("bulletproof-demo").chain(args.iter().copied())).unwrap()
+    }
+
+    #[test]
+    fn defaults_keep_the_historical_behaviour() {
+        let config = Config::from_args(Args::default()).unwrap();
+        assert_eq!(config.listen, "127.0.0.1:5010".parse().unwrap());
+        assert!(config.allows_any_origin());
+        let keyed = [EndpointGroup::Bbs, EndpointGroup::Revocation];
+        assert!(EndpointGroup::ALL.iter().all(|g| config.serves(*g) != keyed.contains(g)));
+        assert_eq!(config.proof_queue, config.proof_workers * 8);
+    }
+
+    #[test]
+    fn flags_override_the_file() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(
+            file,
+            "listen = \"0.0.0.0:8080\"\nproof_workers = 2\ncors_origins = [\"https://app.example.com\"]\nendpoints = [\"v1\", \"status\"]"
+        )
+        .unwrap();
+        let path = file.path().to_str().unwrap();
+        let config = Config::from_args(parse(&["--config", path, "--listen", "127.0.0.1:9000"])).unwrap();
+        assert_eq!(config.listen, "127.0.0.1:9000".parse().unwrap());
+        assert_eq!((config.proof_workers, config.proof_queue), (2, 16));
+        assert_eq!(config.cors_origins, ["https://app.example.com"]);
+        assert!(config.serves(EndpointGroup::V1) && !config.serves(EndpointGroup::Legacy));
+
+        let config = Config::from_args(parse(&["--config", path, "--endpoints", "jobs,v1,jobs"])).unwrap();
+        assert_eq!(config.endpoints, [EndpointGroup::V1, EndpointGroup::Jobs]);
+    }
+
+    #[test]
+    fn invalid_settings_are_all_reported() {
+        let err = Config::from_args(parse(&[
+            "--proof-workers",
+            "0",
+            "--json-limit",
+            "10",
+            "--verify-burst",
+            "5",
+            "--cors-origin",
+            "https://ok.example,ftp://x.example,https://y.example/app",
+        ]))
+        .unwrap_err();
+        for expected in ["proof_workers", "json_limit", "verify_burst needs", "ftp://x.example", "must not have a path"] {
+            assert!(err.contains(expected), "{expected} missing from {err}");
+        }
+        assert!(Config::from_args(parse(&["--cors-origin", "*,https://a.example"])).is_err());
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "listen_port = 1").unwrap();
+        let err = Config::from_args(parse(&["--config", file.path().to_str().unwrap()])).unwrap_err();
+        assert!(err.contains("listen_port"), "{err}");
+    }
+
+    #[test]
+    fn auth_needs_a_way_in() {
+        assert!(!Config::from_args(Args::default()).unwrap().auth);
+        let err = Config::from_args(parse(&["--auth"])).unwrap_err();
+        assert!(err.contains("auth needs"), "{err}");
+
+        let err = Config::from_args(parse(&["--auth", "--api-key", "short", "--auth-allow", "0x1234"])).unwrap_err();
+        assert!(err.contains("api_keys") && err.contains("0x1234"), "{err}");
+
+        let config = Config::from_args(parse(&[
+            "--auth",
+            "--api-key",
+            "0123456789abcdef",
+            "--auth-allow",
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+        ]))
+        .unwrap();
+        assert!(config.auth);
+        assert!(!serde_json::to_string(&config).unwrap().contains("0123456789abcdef"));
+
+        let err = Config::from_args(parse(&["--auth", "--issuer-api-key", "short", "--issuer-allow", "0x1234"])).unwrap_err();
+        assert!(err.contains("issuer_api_keys") && err.contains("issuer_allowlist"), "{err}");
+        let config = Config::from_args(parse(&["--auth", "--issuer-api-key", "issuer-0123456789"])).unwrap();
+        assert!(!serde_json::to_string(&config).unwrap().contains("issuer-0123456789"));
+        let config = Config::from_args(parse(&["--auth", "--operator-api-key", "operator-0123456789"])).unwrap();
+        assert!(!serde_json::to_string(&config).unwrap().contains("operator-0123456789"));
+    }
+
+    #[test]
+    fn tls_needs_both_halves() {
+        assert!(!Config::from_args(Args::default()).unwrap().serves_tls());
+        let err = Config::from_args(parse(&["--tls-cert", "cert.pem", "--tls-client-ca", "ca.pem"])).unwrap_err();
+        assert!(err.contains("tls_cert and tls_key"), "{err}");
+        let err = Config::from_args(parse(&["--tls-client-ca", "ca.pem"])).unwrap_err();
+        assert!(err.contains("tls_client_ca needs"), "{err}");
+
+        let config = Config::from_args(parse(&[
+            "--tls-cert",
+            "cert.pem",
+            "--tls-key",
+            "key.pem",
+            "--tls-client-ca",
+            "ca.pem",
+            "--tls-client-auth",
+            "optional",
+            "--auth",
+        ]))
+        .unwrap();
+        assert!(config.serves_tls());
+        assert_eq!(config.tls_client_auth, ClientAuth::Optional);
+    }
+
+    #[test]
+    fn bbs_needs_an_issuer_key() {
+        let key = format!("{}00", "ab".repeat(31));
+        let config = Config::from_args(parse(&["--bbs-issuer-key-hex", &key])).unwrap();
+        assert!(config.serves(EndpointGroup::Bbs) && !config.serves(EndpointGroup::Revocation));
+        assert!(!serde_json::to_string(&config).unwrap().contains(&key));
+
+        let err = Config::from_args(parse(&["--endpoints", "v1,bbs"])).unwrap_err();
+        assert!(err.contains("ZKP_BBS_ISSUER_KEY_HEX"), "{err}");
+        let err = Config::from_args(parse(&["--endpoints", "bbs", "--bbs-issuer-key-hex", "abcd"])).unwrap_err();
+        assert!(err.contains("64 hex digits"), "{err}");
+    }
+
+    #[test]
+    fn revocation_needs_an_accumulator_key() {
+        let key = "5e".repeat(32);
+        let config = Config::from_args(parse(&["--revocation-key-hex", &key])).unwrap();
+        assert!(config.serves(EndpointGroup::Revocation));
+        assert_eq!(config.revocation_store, PathBuf::from("data/revocation"));
+        assert!(!serde_json::to_string(&config).unwrap().contains(&key));
+
+        let err = Config::from_args(parse(&["--endpoints", "revocation"])).unwrap_err();
+        assert!(err.contains("ZKP_REVOCATION_KEY_HEX"), "{err}");
+        let err = Config::from_args(parse(&["--revocation-key-hex", "5e5e"])).unwrap_err();
+        assert!(err.contains("at least 64 hex digits"), "{err}");
+    }
+
+    #[test]
+    fn issued_store_is_opt_in() {
+        assert_eq!(Config::from_args(Args::default()).unwrap().issued_store, None);
+        let err = Config::from_args(parse(&["--issued-store", "data/issued", "--issued-retention-days", "0"])).unwrap_err();
+        assert!(err.contains("at least 1"), "{err}");
+        let err = Config::from_args(parse(&["--issued-retention-days", "30"])).unwrap_err();
+        assert!(err.contains("needs issued_store"), "{err}");
+        let config = Config::from_args(parse(&["--issued-store", "data/issued", "--endpoints", "legacy"])).unwrap();
+        assert_eq!(config.issued_store, Some(PathBuf::from("data/issued")));
+
+        let config = Config::from_args(parse(&["--issued-store", "data/issued", "--issued-retention-days", "90"])).unwrap();
+        assert_eq!(config.issued_store, Some(PathBuf::from("data/issued")));
+        assert_eq!(config.issued_retention_days, Some(90));
+    }
+
+    #[test]
+    fn replay_registry_is_opt_in() {
+        let config = Config::from_args(Args::default()).unwrap();
+        assert_eq!((config.replay_store, config.replay_scope), (None, ReplayScope::Once));
+        let config = Config::from_args(parse(&["--replay-store", "data/replay", "--endpoints", "legacy"])).unwrap();
+        assert_eq!(config.replay_store, Some(PathBuf::from("data/replay")));
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        writeln!(file, "replay_store = \"data/replay\"\nreplay_scope = \"per_verifier\"").unwrap();
+        let config = Config::from_args(parse(&["--config", file.path().to_str().unwrap()])).unwrap();
+        assert_eq!(config.replay_scope, ReplayScope::PerVerifier);
+        let config = Config::from_args(parse(&["--config", file.path().to_str().unwrap(), "--replay-scope", "unlimited"])).unwrap();
+        assert_eq!(config.replay_scope, ReplayScope::Unlimited);
+    }
+}
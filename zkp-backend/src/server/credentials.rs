@@ -0,0 +1,199 @@
+//! Verification of a whole product credential (`/zkp/verify-vc`).
+//!
+//! The frontend used to pick one proof endpoint per field of the credential
+//! and trust the binding tags stored next to the proofs. This takes the
+//! credential JSON as it is, verifies the price proof and both transaction
+//! hash commitments against the tags recomputed from the credential
+//! ([`vc::verify_proofs`]) and the issuer and holder signatures
+//! ([`vc::signature`]), and answers with one report: `verified`, a `status`
+//! (`unbound` when the checks pass but a proof is not bound to this
+//! credential) and a check per proof, each saying how its
+//! binding tag was established, and per signature, naming its signer.
+//!
+//! As with `/verify-vc` of the Node service, `?contract=0x…` names the
+//! escrow signatures may be bound to and `?certificate=true` skips the
+//! holder; `?chain_id=` replaces Sepolia for DIDs without a chain.
+//!
+//! With a replay registry, each proof that verified is recorded as a
+//! presentation to `X-Zkp-Verifier` and its check carries the `replay`
+//! report, so a proof shown here is not a first use at `/v1` afterwards.
+
+use actix_web::{post, web, HttpRequest, HttpResponse, Responder};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::{error, info};
+
+use super::proof_pool::ProofPool;
+use super::replay::{Presentation, ReplayRegistry};
+use super::{metrics, offload, zk_error};
+use crate::eth::parse_address;
+use crate::vc::{self, signature::SignatureOptions, VcReport};
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(verify_vc);
+}
+
+#[derive(Deserialize)]
+struct VerifyVcQuery {
+    contract: Option<String>,
+    #[serde(default)]
+    certificate: bool,
+    chain_id: Option<u64>,
+}
+
+#[post("/zkp/verify-vc")]
+async fn verify_vc(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    query: web::Query<VerifyVcQuery>,
+    credential: web::Json<Value>,
+) -> impl Responder {
+    let credential = credential.into_inner();
+    if !credential.is_object() {
+        return HttpResponse::BadRequest()
+            .json(json!({ "error": "the body must be a credential (a JSON object)", "code": "invalid_request" }));
+    }
+    let verifier = match registry.as_ref().map(|_| Presentation::verifier(http.headers())).transpose() {
+        Ok(verifier) => verifier.unwrap_or_default(),
+        Err(e) => return zk_error(&e),
+    };
+    let mut options = SignatureOptions { certificate: query.certificate, ..Default::default() };
+    if let Some(chain_id) = query.chain_id {
+        options.chain_id = chain_id;
+    }
+    if let Some(contract) = &query.contract {
+        match parse_address(contract) {
+            Ok(contract) => options.verifying_contract = Some(contract),
+            Err(e) => return HttpResponse::BadRequest().json(json!({ "error": e.to_string(), "code": e.code() })),
+        }
+    }
+    let mut report = match offload(&pool, move || vc::verify(&credential, &options)).await {
+        Ok(report) => report,
+        Err(resp) => return resp,
+    };
+    if let Some(registry) = &registry {
+        if let Err(e) = present(registry, &mut report, &verifier) {
+            error!(error = %e, "recording a proof presentation failed");
+            return HttpResponse::InternalServerError().json(json!({ "error": format!("replay registry: {e}"), "code": "store_error" }));
+        }
+    }
+    metrics::record_outcome(if report.verified { "verified" } else { "invalid" });
+    let failed: Vec<_> = report.checks.iter().filter(|c| !c.verified).map(|c| c.name).collect();
+    info!(verified = report.verified, checks = report.checks.len(), ?failed, "credential verification");
+    HttpResponse::Ok().json(report)
+}
+
+/// Record the proofs of `report` that verified, each under its binding tag.
+fn present(registry: &ReplayRegistry, report: &mut VcReport, verifier: &str) -> sled::Result<()> {
+    for check in report.checks.iter_mut().filter(|c| c.verified) {
+        let Some(proof) = &check.proof else { continue };
+        let tag = check.binding_tag.as_deref().and_then(|t| hex::decode(t).ok());
+        check.replay = Some(registry.present(&Presentation::new(proof, tag.as_deref(), verifier.to_string()))?);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::App;
+    use crate::api::ValueCommitmentRequest;
+    use crate::vc::signature::{sign, Role, DEFAULT_CHAIN_ID};
+    use k256::ecdsa::SigningKey;
+
+    const ESCROW: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+
+    /// A certificate with a price proof, signed by its issuer (Hardhat account #2).
+    fn certificate(tag: &str) -> Value {
+        let price = ValueCommitmentRequest { value: 1500, blinding_hex: None, binding_tag_hex: Some(tag.into()), bit_range: 64 }
+            .prove()
+            .unwrap();
+        let mut credential = json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "id": "urn:uuid:1",
+            "type": ["VerifiableCredential"],
+            "schemaVersion": "1.0",
+            "issuer": { "id": "did:ethr:11155111:0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC", "name": "Seller" },
+            "holder": { "id": "did:ethr:11155111:0x70997970C51812dc3A010C7d01b50e0d17dc79C8", "name": "Buyer" },
+            "issuanceDate": "2025-01-15T10:00:00.000Z",
+            "credentialSubject": {
+                "id": "did:ethr:11155111:0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+                "productName": "Battery Module",
+                "batch": "B-42",
+                "quantity": 1,
+                "price": {
+                    "hidden": true,
+                    "zkpProof": { "commitment": price.commitment, "proof": price.proof, "bindingTag": tag, "proofType": vc::RANGE_PROOF_TYPE },
+                },
+            },
+        });
+        let key = SigningKey::from_slice(&hex::decode("5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a").unwrap()).unwrap();
+        let proof = sign(&credential, &key, Role::Issuer, DEFAULT_CHAIN_ID, Some(parse_address(ESCROW).unwrap())).unwrap();
+        credential["proof"] = json!([proof]);
+        credential
+    }
+
+    #[actix_web::test]
+    async fn credentials_get_one_report() {
+        let app = init_service(App::new().app_data(web::Data::new(ProofPool::new(1, 4).unwrap())).configure(configure)).await;
+        let mut credential = certificate(&format!("0x{}", "11".repeat(32)));
+
+        let verify = |query: &str, body: &Value| TestRequest::post().uri(&format!("/zkp/verify-vc{query}")).set_json(body).to_request();
+        let certified = format!("?certificate=true&contract={ESCROW}");
+        let report: Value = read_body_json(call_service(&app, verify(&certified, &credential)).await).await;
+        // Every check passes, but a certificate has no context to bind its price proof to.
+        assert_eq!((report["verified"].as_bool(), report["status"].as_str()), (Some(false), Some("unbound")), "{report}");
+        assert_eq!(report["checks"][0]["binding"], "stored");
+        assert_eq!(report["checks"][1]["signer"], "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC");
+
+        // Without the escrow the signature recovers someone else; a product
+        // credential also needs its holder's.
+        let report: Value = read_body_json(call_service(&app, verify("?certificate=true", &credential)).await).await;
+        assert_eq!((report["status"].as_str(), report["checks"][1]["verified"].as_bool()), (Some("failed"), Some(false)));
+        let report: Value = read_body_json(call_service(&app, verify(&format!("?contract={ESCROW}"), &credential)).await).await;
+        assert_eq!(report["checks"][2]["name"], "holder_signature");
+        assert_eq!(report["status"], "failed");
+
+        credential["credentialSubject"]["price"]["zkpProof"]["bindingTag"] = json!("22".repeat(32));
+        let report: Value = read_body_json(call_service(&app, verify(&certified, &credential)).await).await;
+        assert_eq!((report["status"].as_str(), report["checks"][0]["verified"].as_bool()), (Some("failed"), Some(false)));
+
+        let resp = call_service(&app, verify("", &json!(["not", "a", "credential"]))).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp = call_service(&app, verify("?contract=0x1234", &credential)).await;
+        let body: Value = read_body_json(resp).await;
+        assert_eq!(body["code"], "invalid_address");
+    }
+
+    #[actix_web::test]
+    async fn verified_proofs_are_presentations() {
+        use super::super::config::ReplayScope;
+
+        let dir = tempfile::tempdir().unwrap();
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(ProofPool::new(1, 4).unwrap()))
+                .app_data(web::Data::new(ReplayRegistry::open(dir.path(), ReplayScope::Once).unwrap()))
+                .configure(configure)
+                .configure(|cfg| super::super::v1::configure(cfg, 1 << 20)),
+        )
+        .await;
+        let tag = "11".repeat(32);
+        let credential = certificate(&format!("0x{tag}"));
+        let verify = || TestRequest::post().uri(&format!("/zkp/verify-vc?certificate=true&contract={ESCROW}")).set_json(&credential).to_request();
+
+        let report: Value = read_body_json(call_service(&app, verify()).await).await;
+        assert_eq!((&report["checks"][0]["replay"]["accepted"], &report["checks"][1]["replay"]), (&json!(true), &Value::Null));
+        let report: Value = read_body_json(call_service(&app, verify()).await).await;
+        assert_eq!(report["checks"][0]["replay"]["accepted"], false);
+
+        let zkp = &credential["credentialSubject"]["price"]["zkpProof"];
+        let body = json!({ "commitment": zkp["commitment"], "proof": zkp["proof"], "bit_range": 64, "binding_tag_hex": tag });
+        let resp = call_service(&app, TestRequest::post().uri("/v1/value-commitments/verify").set_json(body).to_request()).await;
+        let verified: Value = read_body_json(resp).await;
+        assert_eq!((&verified["replay"]["accepted"], &verified["replay"]["previous_presentations"]), (&json!(false), &json!(2)));
+    }
+}
@@ -0,0 +1,162 @@
+//! Liveness and readiness probes.
+//!
+//! `/healthz` only says the process answers HTTP. `/readyz` reports the last
+//! known-answer self-test ([`zk::self_test`]), run once at startup and again
+//! on `GET /readyz?rerun=true`: an instance whose commitments no longer match
+//! the fixed vectors, or whose proofs stop verifying, answers 503 and should
+//! not receive traffic.
+//!
+//! The probe is unauthenticated, so reruns are coalesced and limited to one
+//! per [`RERUN_INTERVAL`]: concurrent or earlier requests get the last report.
+
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use actix_web::http::StatusCode;
+use actix_web::{get, web, HttpResponse, Responder};
+use crate::zk;
+use serde::Deserialize;
+use serde_json::json;
+use tracing::{error, info};
+
+use super::offload;
+use super::proof_pool::ProofPool;
+use zk::self_test::{self, SelfTestReport};
+
+/// The shortest time between two reruns of the self-test.
+pub const RERUN_INTERVAL: Duration = Duration::from_secs(30);
+
+/// The last self-test report.
+pub struct Readiness {
+    report: RwLock<SelfTestReport>,
+    /// When the report was last rerun; held during a rerun, so concurrent
+    /// requests wait for it and share its report.
+    rerun: tokio::sync::Mutex<Option<Instant>>,
+}
+
+impl Readiness {
+    /// Run the self-test now, on the calling thread.
+    pub fn check() -> Self {
+        Self::new(logged(self_test::run()))
+    }
+
+    fn new(report: SelfTestReport) -> Self {
+        Self { report: RwLock::new(report), rerun: tokio::sync::Mutex::new(None) }
+    }
+}
+
+fn logged(report: SelfTestReport) -> SelfTestReport {
+    if report.passed {
+        info!(checks = report.checks.len(), "self-test passed");
+    } else {
+        for check in report.checks.iter().filter(|c| !c.passed) {
+            error!(check = check.name, error = check.error.as_deref().unwrap_or_default(), "self-test failed");
+        }
+    }
+    report
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(healthz).service(readyz);
+}
+
+#[get("/healthz")]
+async fn healthz() -> impl Responder {
+    HttpResponse::Ok().json(json!({ "status": "ok" }))
+}
+
+#[derive(Deserialize)]
+struct ReadyQuery {
+    #[serde(default)]
+    rerun: bool,
+}
+
+#[get("/readyz")]
+async fn readyz(pool: web::Data<ProofPool>, readiness: web::Data<Readiness>, query: web::Query<ReadyQuery>) -> impl Responder {
+    if query.rerun {
+        let mut rerun = readiness.rerun.lock().await;
+        if rerun.is_none_or(|at| at.elapsed() >= RERUN_INTERVAL) {
+            match offload(&pool, self_test::run).await {
+                Ok(report) => *readiness.report.write().unwrap() = logged(report),
+                Err(resp) => return resp,
+            }
+            *rerun = Some(Instant::now());
+        }
+    }
+    let report = readiness.report.read().unwrap().clone();
+    let status = if report.passed { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    HttpResponse::build(status).json(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::App;
+    use serde_json::Value;
+    use zk::self_test::SelfTestCheck;
+
+    #[actix_web::test]
+    async fn readiness_follows_the_self_test() {
+        let failed = SelfTestReport {
+            passed: false,
+            checks: vec![SelfTestCheck { name: "pedersen_vector", passed: false, error: Some("changed".into()) }],
+        };
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(ProofPool::new(1, 1).unwrap()))
+                .app_data(web::Data::new(Readiness::new(failed)))
+                .configure(configure),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::get().uri("/healthz").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = call_service(&app, TestRequest::get().uri("/readyz").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: Value = read_body_json(resp).await;
+        assert_eq!(body["checks"][0]["error"], "changed");
+
+        let resp = call_service(&app, TestRequest::get().uri("/readyz?rerun=true").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: Value = read_body_json(resp).await;
+        assert_eq!(body["passed"], true);
+
+        let resp = call_service(&app, TestRequest::get().uri("/readyz").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_web::test]
+    async fn reruns_are_coalesced_and_rate_limited() {
+        let failed = || SelfTestReport { passed: false, checks: Vec::new() };
+        let pool = web::Data::new(ProofPool::new(1, 1).unwrap());
+        let readiness = web::Data::new(Readiness::new(failed()));
+        let app = init_service(App::new().app_data(pool.clone()).app_data(readiness.clone()).configure(configure)).await;
+        let rerun = || call_service(&app, TestRequest::get().uri("/readyz?rerun=true").to_request());
+
+        // With the worker busy, the first rerun fills the queue; the second
+        // waits for it instead of being refused.
+        let (release, gate) = std::sync::mpsc::channel::<()>();
+        let worker = pool.clone();
+        let blocked = actix_web::rt::spawn(async move { worker.run(move || gate.recv().unwrap()).await });
+        while pool.status().active == 0 {
+            actix_web::rt::time::sleep(Duration::from_millis(1)).await;
+        }
+        let unblock = async {
+            while pool.status().queued == 0 {
+                actix_web::rt::time::sleep(Duration::from_millis(1)).await;
+            }
+            release.send(()).unwrap();
+        };
+        let (first, second, ()) = futures_util::join!(rerun(), rerun(), unblock);
+        assert_eq!((first.status(), second.status()), (StatusCode::OK, StatusCode::OK));
+        blocked.await.unwrap().unwrap();
+
+        // Within the interval the last report stands.
+        *readiness.report.write().unwrap() = failed();
+        assert_eq!(rerun().await.status(), StatusCode::SERVICE_UNAVAILABLE);
+        *readiness.rerun.lock().await = Some(Instant::now() - RERUN_INTERVAL);
+        assert_eq!(rerun().await.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,393 @@
+//! Record of issued commitments (`issued_store`).
+//!
+//! Every commitment `/v1` generates is recorded with its proof, scheme and
+//! binding tag, the product (escrow) address and stage it was made for, and
+//! the time, so a lost or disputed credential can be traced back to what the
+//! backend issued. Openings (values, blinding factors, transaction hashes)
+//! are never stored.
+//!
+//! The product is the escrow a signed request signed for; otherwise it comes
+//! from `X-Zkp-Escrow` or `X-Zkp-Product`. A product header that disagrees
+//! with the escrow is refused rather than recorded. The stage comes from
+//! `X-Zkp-Stage`. Records are listed by `GET /v1/issued` and removed by
+//! `DELETE /v1/issued` (with an operator key when `auth` is on), filtered by
+//! product, commitment, binding tag, stage and age. With
+//! `issued_retention_days`, older records are purged on startup and hourly.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use actix_web::http::header::HeaderMap;
+use tracing::{info, warn};
+
+use super::auth::{SignedEscrow, ESCROW};
+use crate::api::{IssuedQuery, IssuedRecord};
+use crate::eth::parse_address;
+use crate::zk::ZkError;
+
+/// Listing and purging; both need authentication when `auth` is on.
+pub const PATH: &str = "/v1/issued";
+/// Product (escrow) address a commitment is issued for; must match the
+/// escrow, if the request names one.
+pub const PRODUCT: &str = "x-zkp-product";
+/// Lifecycle stage a commitment is issued for, e.g. `purchase` or `delivery`.
+pub const STAGE: &str = "x-zkp-stage";
+/// Most records one listing returns.
+pub const MAX_LIST_LIMIT: usize = 1000;
+const DEFAULT_LIST_LIMIT: usize = 100;
+const MAX_STAGE_LEN: usize = 64;
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn normalize_hex(hex: &str) -> String {
+    hex.trim().trim_start_matches("0x").to_ascii_lowercase()
+}
+
+fn normalize_product(product: &str) -> Result<String, ZkError> {
+    let address = parse_address(product.trim()).map_err(|e| ZkError::InvalidParameters(format!("product: {e}")))?;
+    Ok(format!("0x{}", hex::encode(address)))
+}
+
+fn normalize_stage(stage: &str) -> Result<String, ZkError> {
+    let stage = stage.trim();
+    if stage.is_empty() || stage.len() > MAX_STAGE_LEN || !stage.bytes().all(|b| b.is_ascii_alphanumeric() || b"_-.".contains(&b)) {
+        return Err(ZkError::InvalidParameters(format!(
+            "stage must be 1-{MAX_STAGE_LEN} of [A-Za-z0-9_.-]"
+        )));
+    }
+    Ok(stage.to_ascii_lowercase())
+}
+
+/// Where a commitment is issued, from the request headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IssueContext {
+    pub product: Option<String>,
+    pub stage: Option<String>,
+}
+
+impl IssueContext {
+    /// `signed` is the request's [`SignedEscrow`], if it was signed.
+    pub fn from_headers(headers: &HeaderMap, signed: Option<&SignedEscrow>) -> Result<Self, ZkError> {
+        let header = |name: &str| -> Result<Option<&str>, ZkError> {
+            headers
+                .get(name)
+                .map(|v| v.to_str().map_err(|_| ZkError::InvalidParameters(format!("{name} is not text"))))
+                .transpose()
+        };
+        let claimed = header(PRODUCT)?.map(normalize_product).transpose()?;
+        let escrow = match signed {
+            Some(SignedEscrow(escrow)) => escrow.map(|address| format!("0x{}", hex::encode(address))),
+            None => header(ESCROW)?.map(normalize_product).transpose()?,
+        };
+        let product = match (claimed, escrow) {
+            (Some(claimed), escrow) if escrow.as_ref() != Some(&claimed) && (escrow.is_some() || signed.is_some()) => {
+                return Err(ZkError::InvalidParameters(match escrow {
+                    Some(escrow) => format!("{PRODUCT} {claimed} conflicts with the escrow {escrow}"),
+                    None => format!("{PRODUCT} is not signed; name the product in {ESCROW}"),
+                }));
+            },
+            (claimed, escrow) => escrow.or(claimed),
+        };
+        Ok(Self { product, stage: header(STAGE)?.map(normalize_stage).transpose()? })
+    }
+}
+
+/// A commitment just issued, before it is recorded.
+#[derive(Debug, Clone)]
+pub struct Issue {
+    pub kind: &'static str,
+    pub scheme: &'static str,
+    pub commitments: Vec<String>,
+    pub proof: String,
+    pub binding_tag: Option<[u8; 32]>,
+}
+
+/// [`IssuedQuery`] with its values normalized the way they are stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Filter {
+    product: Option<String>,
+    commitment: Option<String>,
+    binding_tag: Option<String>,
+    stage: Option<String>,
+    before: Option<u64>,
+}
+
+impl Filter {
+    fn new(query: &IssuedQuery) -> Result<Self, ZkError> {
+        Ok(Self {
+            product: query.product.as_deref().map(normalize_product).transpose()?,
+            commitment: query.commitment.as_deref().map(normalize_hex),
+            binding_tag: query.binding_tag.as_deref().map(normalize_hex),
+            stage: query.stage.as_deref().map(normalize_stage).transpose()?,
+            before: query.before,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn matches(&self, record: &IssuedRecord) -> bool {
+        fn field(wanted: &Option<String>, actual: &Option<String>) -> bool {
+            wanted.is_none() || wanted == actual
+        }
+        field(&self.product, &record.product)
+            && field(&self.binding_tag, &record.binding_tag)
+            && field(&self.stage, &record.stage)
+            && self.commitment.as_ref().is_none_or(|c| record.commitments.contains(c))
+            && self.before.is_none_or(|before| record.issued_at < before)
+    }
+
+    /// The most selective index entry prefix for this filter, if any.
+    fn index_prefix(&self) -> Option<Vec<u8>> {
+        let (field, value) = [("c", &self.commitment), ("t", &self.binding_tag), ("p", &self.product)]
+            .into_iter()
+            .find_map(|(field, value)| Some((field, value.as_ref()?)))?;
+        Some(index_key(field, value, &[]))
+    }
+}
+
+/// `<field>\0<value>\0<record id>`: records by commitment (`c`), binding
+/// tag (`t`) and product (`p`).
+fn index_key(field: &str, value: &str, id: &[u8]) -> Vec<u8> {
+    [field.as_bytes(), b"\0", value.as_bytes(), b"\0", id].concat()
+}
+
+fn index_keys(record: &IssuedRecord, id: &[u8]) -> Vec<Vec<u8>> {
+    let mut keys: Vec<_> = record.commitments.iter().map(|c| index_key("c", c, id)).collect();
+    keys.extend(record.binding_tag.iter().map(|t| index_key("t", t, id)));
+    keys.extend(record.product.iter().map(|p| index_key("p", p, id)));
+    keys
+}
+
+pub struct IssuedStore {
+    db: sled::Db,
+    /// Records by id; ids increase with time.
+    records: sled::Tree,
+    index: sled::Tree,
+}
+
+impl IssuedStore {
+    /// Open (or create) the store at `path`.
+    pub fn open(path: impl AsRef<std::path::Path>) -> sled::Result<Self> {
+        let db = sled::open(path)?;
+        Ok(Self { records: db.open_tree("records")?, index: db.open_tree("index")?, db })
+    }
+
+    pub fn record(&self, issue: Issue, context: &IssueContext) -> sled::Result<IssuedRecord> {
+        let id = self.db.generate_id()?.to_be_bytes();
+        let record = IssuedRecord {
+            id: hex::encode(id),
+            kind: issue.kind.into(),
+            scheme: issue.scheme.into(),
+            commitments: issue.commitments.iter().map(|c| normalize_hex(c)).collect(),
+            proof: issue.proof,
+            binding_tag: issue.binding_tag.map(hex::encode),
+            product: context.product.clone(),
+            stage: context.stage.clone(),
+            issued_at: now(),
+        };
+        let bytes = serde_json::to_vec(&record).expect("issued records always serialize");
+        self.records.insert(id, bytes)?;
+        for key in index_keys(&record, &id) {
+            self.index.insert(key, &[])?;
+        }
+        Ok(record)
+    }
+
+    fn get(&self, id: &[u8]) -> Option<IssuedRecord> {
+        let bytes = self.records.get(id).ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Ids of the records `filter` may match, newest first.
+    fn candidates(&self, filter: &Filter) -> Box<dyn Iterator<Item = sled::Result<sled::IVec>> + '_> {
+        match filter.index_prefix() {
+            Some(prefix) => Box::new(self.index.scan_prefix(&prefix).keys().rev().map(move |key| {
+                key.map(|key| sled::IVec::from(&key[prefix.len()..]))
+            })),
+            None => Box::new(self.records.iter().keys().rev()),
+        }
+    }
+
+    /// Matching records, newest first.
+    pub fn list(&self, query: &IssuedQuery) -> Result<Vec<IssuedRecord>, ZkError> {
+        let filter = Filter::new(query)?;
+        let limit = query.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+        if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+            return Err(ZkError::InvalidParameters(format!("limit must be between 1 and {MAX_LIST_LIMIT}")));
+        }
+        Ok(self
+            .candidates(&filter)
+            .filter_map(|id| self.get(&id.ok()?))
+            .filter(|record| filter.matches(record))
+            .take(limit)
+            .collect())
+    }
+
+    /// Remove the matching records; an empty filter is refused rather than
+    /// wiping the store.
+    pub fn purge(&self, query: &IssuedQuery) -> Result<usize, PurgeError> {
+        let filter = Filter::new(query).map_err(PurgeError::Invalid)?;
+        if filter.is_empty() {
+            return Err(PurgeError::Invalid(ZkError::InvalidParameters(
+                "purging needs product, commitment, binding_tag, stage or before".into(),
+            )));
+        }
+        let matching: Vec<IssuedRecord> = self
+            .candidates(&filter)
+            .filter_map(|id| self.get(&id.ok()?))
+            .filter(|record| filter.matches(record))
+            .collect();
+        for record in &matching {
+            let id = hex::decode(&record.id).expect("record ids are hex");
+            for key in index_keys(record, &id) {
+                self.index.remove(key).map_err(PurgeError::Store)?;
+            }
+            self.records.remove(&id).map_err(PurgeError::Store)?;
+        }
+        Ok(matching.len())
+    }
+
+    /// Purge records older than `retention` now and every hour from a
+    /// background thread.
+    pub fn retain(store: &Arc<Self>, retention: Duration) {
+        let store = Arc::downgrade(store);
+        std::thread::spawn(move || {
+            while let Some(store) = store.upgrade() {
+                let query = IssuedQuery { before: Some(now().saturating_sub(retention.as_secs())), ..IssuedQuery::default() };
+                match store.purge(&query) {
+                    Ok(0) => {},
+                    Ok(purged) => info!(purged, "purged expired issued commitments"),
+                    Err(e) => warn!(error = %e, "purging expired issued commitments failed"),
+                }
+                drop(store);
+                std::thread::sleep(PURGE_INTERVAL);
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+pub enum PurgeError {
+    Invalid(ZkError),
+    Store(sled::Error),
+}
+
+impl std::fmt::Display for PurgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Invalid(e) => e.fmt(f),
+            Self::Store(e) => write!(f, "issued store: {e}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    const PRODUCT_A: &str = "0xAbCdEf0123456789abcdef0123456789ABCDEF01";
+    const PRODUCT_B: &str = "0x2222222222222222222222222222222222222222";
+
+    fn issue(commitment: u8, tag: Option<u8>) -> Issue {
+        Issue {
+            kind: "value_commitment",
+            scheme: "bulletproofs",
+            commitments: vec![format!("0x{}", hex::encode([commitment; 32]).to_uppercase())],
+            proof: "00".repeat(8),
+            binding_tag: tag.map(|t| [t; 32]),
+        }
+    }
+
+    fn context(product: &str, stage: &str) -> IssueContext {
+        IssueContext { product: Some(normalize_product(product).unwrap()), stage: Some(stage.into()) }
+    }
+
+    fn query(f: impl FnOnce(&mut IssuedQuery)) -> IssuedQuery {
+        let mut query = IssuedQuery::default();
+        f(&mut query);
+        query
+    }
+
+    #[test]
+    fn records_are_found_by_product_commitment_and_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = IssuedStore::open(dir.path()).unwrap();
+        let first = store.record(issue(1, Some(9)), &context(PRODUCT_A, "purchase")).unwrap();
+        store.record(issue(2, Some(9)), &context(PRODUCT_A, "delivery")).unwrap();
+        store.record(issue(3, None), &context(PRODUCT_B, "purchase")).unwrap();
+        assert_eq!(first.commitments, [hex::encode([1; 32])]);
+        assert_eq!(first.product.as_deref(), Some(PRODUCT_A.to_lowercase().as_str()));
+
+        let ids = |q: IssuedQuery| -> Vec<String> { store.list(&q).unwrap().into_iter().map(|r| r.commitments[0][..2].to_string()).collect() };
+        assert_eq!(ids(IssuedQuery::default()), ["03", "02", "01"]);
+        assert_eq!(ids(query(|q| q.product = Some(PRODUCT_A.to_lowercase()))), ["02", "01"]);
+        assert_eq!(ids(query(|q| q.binding_tag = Some(format!("0x{}", "09".repeat(32))))), ["02", "01"]);
+        assert_eq!(ids(query(|q| q.commitment = Some("01".repeat(32)))), ["01"]);
+        assert_eq!(ids(query(|q| q.stage = Some("purchase".into()))), ["03", "01"]);
+        assert_eq!(ids(query(|q| q.limit = Some(1))), ["03"]);
+        assert!(store.list(&query(|q| q.product = Some("0x12".into()))).is_err());
+
+        // Survives reopening.
+        drop(store);
+        let store = IssuedStore::open(dir.path()).unwrap();
+        assert_eq!(store.list(&IssuedQuery::default()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn purging_removes_records_and_their_index_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = IssuedStore::open(dir.path()).unwrap();
+        store.record(issue(1, Some(9)), &context(PRODUCT_A, "purchase")).unwrap();
+        store.record(issue(2, Some(8)), &context(PRODUCT_B, "purchase")).unwrap();
+
+        assert!(matches!(store.purge(&IssuedQuery::default()), Err(PurgeError::Invalid(_))));
+        assert_eq!(store.purge(&query(|q| q.before = Some(0))).unwrap(), 0);
+        assert_eq!(store.purge(&query(|q| q.product = Some(PRODUCT_A.into()))).unwrap(), 1);
+        assert!(store.list(&query(|q| q.binding_tag = Some("09".repeat(32)))).unwrap().is_empty());
+        assert_eq!(store.index.len(), 3);
+        assert_eq!(store.purge(&query(|q| q.before = Some(now() + 1))).unwrap(), 1);
+        assert!(store.records.is_empty() && store.index.is_empty());
+    }
+
+    #[test]
+    fn context_comes_from_the_headers() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(IssueContext::from_headers(&headers, None).unwrap(), IssueContext::default());
+        headers.insert(HeaderName::from_static(PRODUCT), HeaderValue::from_static(PRODUCT_A));
+        assert_eq!(IssueContext::from_headers(&headers, None).unwrap().product, Some(PRODUCT_A.to_lowercase()));
+        headers.remove(PRODUCT);
+        headers.insert(HeaderName::from_static(ESCROW), HeaderValue::from_static(PRODUCT_B));
+        headers.insert(HeaderName::from_static(STAGE), HeaderValue::from_static("Delivery"));
+        assert_eq!(IssueContext::from_headers(&headers, None).unwrap(), context(PRODUCT_B, "delivery"));
+
+        headers.insert(HeaderName::from_static(STAGE), HeaderValue::from_static("no spaces"));
+        assert_eq!(IssueContext::from_headers(&headers, None).unwrap_err().code(), "invalid_parameters");
+    }
+
+    #[test]
+    fn the_product_is_the_signed_escrow() {
+        let escrow = parse_address(PRODUCT_B).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(HeaderName::from_static(ESCROW), HeaderValue::from_static(PRODUCT_B));
+        assert_eq!(IssueContext::from_headers(&headers, Some(&SignedEscrow(Some(escrow)))).unwrap().product, Some(PRODUCT_B.to_lowercase()));
+
+        // Another product than the escrow, signed or not, is refused.
+        headers.insert(HeaderName::from_static(PRODUCT), HeaderValue::from_static(PRODUCT_A));
+        for signed in [None, Some(SignedEscrow(Some(escrow)))] {
+            let err = IssueContext::from_headers(&headers, signed.as_ref()).unwrap_err();
+            assert!(err.to_string().contains("conflicts with the escrow"), "{err}");
+        }
+        // A signed request without an escrow cannot name an unsigned product.
+        headers.remove(ESCROW);
+        let err = IssueContext::from_headers(&headers, Some(&SignedEscrow(None))).unwrap_err();
+        assert!(err.to_string().contains("not signed"), "{err}");
+        headers.insert(HeaderName::from_static(PRODUCT), HeaderValue::from_static(PRODUCT_B));
+        assert!(IssueContext::from_headers(&headers, Some(&SignedEscrow(Some(escrow)))).is_ok());
+    }
+}
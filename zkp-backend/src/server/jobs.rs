@@ -0,0 +1,978 @@
+//! Asynchronous proof jobs.
+//!
+//! `POST /zkp/jobs` stores the request and answers with a job id straight
+//! away; the work runs on the [`ProofPool`] and the job record is updated as
+//! it progresses. Records and requests live in a sled database, so jobs that
+//! were still queued or running when the server stopped are resumed on
+//! startup, and finished results stay readable until they expire.
+//!
+//! Secrets never reach the store. A request that carries the values behind
+//! its commitments (see [`JobRequest::holds_secrets`]) is kept in memory only
+//! and fails as `job_lost` after a restart; blinding factors in a result are
+//! left out of the stored record and handed out once, by the first
+//! [`JobManager::fetch`] of the finished job.
+//!
+//! With an issued store, commit steps are recorded as issued for the
+//! submitter's product and stage. With a replay registry, verify steps whose
+//! proof verified are recorded as presentations to the submitter's
+//! `X-Zkp-Verifier`, and their result carries the `replay` report, as in the
+//! synchronous responses.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::api::{binding_tag, blinding, check_count, check_proof_len, commitments_hex, hex32, proof_bytes, TX_HASH_LIMBS};
+use crate::zk;
+use curve25519_dalek_ng::ristretto::CompressedRistretto as NgCompressed;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use tracing::{info, warn};
+
+use super::issued::{Issue, IssueContext, IssuedStore};
+use super::proof_pool::{PoolError, ProofPool};
+use super::replay::{Presentation, ReplayRegistry};
+use zk::aggregated_range_proof::MAX_PARTIES;
+use zk::r1cs_statement::{prove_statement, verify_statement, Opening, Statement, MAX_VARIABLES};
+use zk::ZkError;
+
+/// Jobs that are queued or running at once; further submissions are refused.
+pub const MAX_PENDING_JOBS: usize = 256;
+/// Jobs queued or running for one client, so no client holds the whole queue.
+pub const MAX_PENDING_JOBS_PER_CLIENT: usize = 32;
+/// Sub-jobs in one batch.
+pub const MAX_BATCH_JOBS: usize = 64;
+/// Finished jobs are dropped from the store after this long.
+pub const JOB_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+/// Delay before retrying a job step the saturated pool turned away.
+const SATURATED_BACKOFF: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobOpening {
+    pub value: u64,
+    #[serde(default)]
+    pub blinding_hex: Option<String>, // random if omitted
+}
+
+fn default_bit_range() -> usize {
+    64
+}
+
+/// What a job does. Field names follow the matching synchronous endpoints.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JobRequest {
+    CommitValue {
+        value: u64,
+        #[serde(default)]
+        blinding_hex: Option<String>,
+        #[serde(default)]
+        binding_tag_hex: Option<String>,
+        #[serde(default = "default_bit_range")]
+        bit_range: usize,
+    },
+    VerifyValueCommitment {
+        commitment: String,
+        proof: String,
+        #[serde(default)]
+        binding_tag_hex: Option<String>,
+    },
+    CommitTxHash {
+        tx_hash: String,
+        #[serde(default)]
+        binding_tag_hex: Option<String>,
+    },
+    VerifyTxHash {
+        commitment: String,
+        proof: String,
+        #[serde(default)]
+        binding_tag_hex: Option<String>,
+    },
+    ProvePlus {
+        tx_hash: String,
+    },
+    VerifyPlus {
+        commitments: Vec<String>,
+        proof: String,
+    },
+    VerifyAggregated {
+        commitments: Vec<String>,
+        proof: String,
+        #[serde(default = "default_bit_range")]
+        bit_range: usize,
+        #[serde(default)]
+        binding_tag_hex: Option<String>,
+    },
+    ProveStatement {
+        statement: Statement,
+        openings: HashMap<String, JobOpening>,
+        #[serde(default)]
+        binding_tag_hex: Option<String>,
+    },
+    VerifyStatement {
+        statement: Statement,
+        statement_hash: String,
+        commitments: Vec<String>,
+        proof: String,
+        #[serde(default)]
+        binding_tag_hex: Option<String>,
+    },
+    /// Independent jobs run one after another; progress counts finished ones.
+    Batch {
+        jobs: Vec<JobRequest>,
+    },
+}
+
+impl JobRequest {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::CommitValue { .. } => "commit_value",
+            Self::VerifyValueCommitment { .. } => "verify_value_commitment",
+            Self::CommitTxHash { .. } => "commit_tx_hash",
+            Self::VerifyTxHash { .. } => "verify_tx_hash",
+            Self::ProvePlus { .. } => "prove_plus",
+            Self::VerifyPlus { .. } => "verify_plus",
+            Self::VerifyAggregated { .. } => "verify_aggregated",
+            Self::ProveStatement { .. } => "prove_statement",
+            Self::VerifyStatement { .. } => "verify_statement",
+            Self::Batch { .. } => "batch",
+        }
+    }
+
+    /// Whether the request carries secret values, blindings or openings,
+    /// which must not be written to disk.
+    pub fn holds_secrets(&self) -> bool {
+        match self {
+            Self::CommitValue { .. } | Self::CommitTxHash { .. } | Self::ProvePlus { .. } | Self::ProveStatement { .. } => true,
+            Self::Batch { jobs } => jobs.iter().any(Self::holds_secrets),
+            _ => false,
+        }
+    }
+
+    /// The proof and binding tag a verify step presents.
+    fn presented(&self) -> Option<(Vec<u8>, Option<[u8; 32]>)> {
+        let (proof, tag) = match self {
+            Self::VerifyValueCommitment { proof, binding_tag_hex, .. }
+            | Self::VerifyTxHash { proof, binding_tag_hex, .. }
+            | Self::VerifyAggregated { proof, binding_tag_hex, .. }
+            | Self::VerifyStatement { proof, binding_tag_hex, .. } => (proof, binding_tag_hex.clone()),
+            Self::VerifyPlus { proof, .. } => (proof, None),
+            _ => return None,
+        };
+        Some((proof_bytes(proof).ok()?, binding_tag(&tag).ok()?))
+    }
+
+    /// The kind an issued commitment of a commit step is recorded as, and its binding tag.
+    fn issued(&self) -> Option<(&'static str, Option<[u8; 32]>)> {
+        let (kind, tag) = match self {
+            Self::CommitValue { binding_tag_hex, .. } => ("value_commitment", binding_tag_hex),
+            Self::CommitTxHash { binding_tag_hex, .. } => ("tx_hash_commitment", binding_tag_hex),
+            _ => return None,
+        };
+        Some((kind, binding_tag(tag).ok()?))
+    }
+
+    /// The individual proof steps this job is made of.
+    fn steps(&self) -> Vec<JobRequest> {
+        match self {
+            Self::Batch { jobs } => jobs.clone(),
+            other => vec![other.clone()],
+        }
+    }
+
+    /// How many steps [`Self::steps`] would give; what a job is rate limited by.
+    pub fn step_count(&self) -> usize {
+        match self {
+            Self::Batch { jobs } => jobs.len(),
+            _ => 1,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if let Self::Batch { jobs } = self {
+            if jobs.is_empty() || jobs.len() > MAX_BATCH_JOBS {
+                return Err(format!("a batch holds 1 to {MAX_BATCH_JOBS} jobs, got {}", jobs.len()));
+            }
+            if jobs.iter().any(|j| matches!(j, Self::Batch { .. })) {
+                return Err("batches cannot be nested".into());
+            }
+        }
+        self.check_sizes().map_err(|e| e.to_string())
+    }
+
+    /// Commitment list and proof lengths, so an oversized job is refused
+    /// before it is stored; [`execute`] checks them again before decoding.
+    fn check_sizes(&self) -> Result<(), ZkError> {
+        let (commitments, allowed, proof) = match self {
+            Self::VerifyValueCommitment { proof, .. } | Self::VerifyTxHash { proof, .. } => return check_proof_len(proof),
+            Self::VerifyPlus { commitments, proof } => (commitments, TX_HASH_LIMBS..=TX_HASH_LIMBS, proof),
+            Self::VerifyAggregated { commitments, proof, .. } => (commitments, 1..=MAX_PARTIES.next_power_of_two(), proof),
+            Self::VerifyStatement { commitments, proof, .. } => (commitments, 0..=MAX_VARIABLES, proof),
+            Self::Batch { jobs } => return jobs.iter().try_for_each(Self::check_sizes),
+            _ => return Ok(()),
+        };
+        check_count("commitments", commitments.len(), allowed)?;
+        check_proof_len(proof)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, Self::Succeeded | Self::Failed)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Same `{ "error", "code" }` shape as the synchronous endpoints.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobError {
+    pub error: String,
+    pub code: String,
+}
+
+impl From<&ZkError> for JobError {
+    fn from(e: &ZkError) -> Self {
+        Self { error: e.to_string(), code: e.code().into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub id: String,
+    pub kind: String,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    pub created_at: u64, // unix seconds
+    pub updated_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JobError>,
+}
+
+#[derive(Debug)]
+pub enum SubmitError {
+    Invalid(String),
+    TooManyPending,
+    TooManyPendingForClient,
+    Store(sled::Error),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(msg) => write!(f, "invalid job: {msg}"),
+            Self::TooManyPending => write!(f, "too many pending jobs (max {MAX_PENDING_JOBS})"),
+            Self::TooManyPendingForClient => write!(f, "too many pending jobs for this client (max {MAX_PENDING_JOBS_PER_CLIENT})"),
+            Self::Store(e) => write!(f, "job store: {e}"),
+        }
+    }
+}
+
+/// Result fields that open a commitment and are never stored.
+const SECRET_FIELDS: &[&str] = &["blinding_hex"];
+
+/// `result` without [`SECRET_FIELDS`], at any depth; `None` if it had none.
+fn redact(result: &Value) -> Option<Value> {
+    match result {
+        Value::Object(fields) if fields.keys().any(|k| SECRET_FIELDS.contains(&k.as_str())) || fields.values().any(|v| redact(v).is_some()) => {
+            Some(Value::Object(
+                fields
+                    .iter()
+                    .filter(|(k, _)| !SECRET_FIELDS.contains(&k.as_str()))
+                    .map(|(k, v)| (k.clone(), redact(v).unwrap_or_else(|| v.clone())))
+                    .collect(),
+            ))
+        },
+        Value::Array(items) if items.iter().any(|v| redact(v).is_some()) => {
+            Some(Value::Array(items.iter().map(|v| redact(v).unwrap_or_else(|| v.clone())).collect()))
+        },
+        _ => None,
+    }
+}
+
+/// Who a job was submitted by.
+#[derive(Debug, Clone, Default)]
+pub struct Submitter {
+    /// The rate-limit client, holding a share of the queue; resumed jobs have none.
+    pub client: Option<String>,
+    /// `X-Zkp-Verifier`, whom the verify steps present their proofs to.
+    pub verifier: String,
+    /// Where commit steps issue for, when the issued store is kept; not
+    /// stored, as commit requests hold secrets and are never resumed.
+    pub issue: Option<IssueContext>,
+}
+
+/// A request as stored, with the verifier it is presented to.
+#[derive(Serialize, Deserialize)]
+struct StoredRequest {
+    #[serde(flatten)]
+    request: JobRequest,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    verifier: String,
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub struct JobManager {
+    records: sled::Tree,
+    requests: sled::Tree,
+    /// Requests that hold secrets, by job id, until they finish.
+    secret_requests: Mutex<HashMap<String, JobRequest>>,
+    /// Unredacted results of finished jobs, until [`Self::fetch`] hands them out.
+    secret_results: Mutex<HashMap<String, Value>>,
+    events: broadcast::Sender<JobRecord>,
+    pending: AtomicUsize,
+    /// Pending jobs by the client that submitted them; resumed jobs have none.
+    pending_by_client: Mutex<HashMap<String, usize>>,
+    pool: Arc<ProofPool>,
+    issued: Option<Arc<IssuedStore>>,
+    replay: Option<Arc<ReplayRegistry>>,
+}
+
+impl JobManager {
+    /// Open (or create) the job store at `path`.
+    pub fn open(path: impl AsRef<Path>, pool: Arc<ProofPool>) -> sled::Result<Self> {
+        let db = sled::open(path)?;
+        let (events, _) = broadcast::channel(256);
+        let manager = Self {
+            records: db.open_tree("records")?,
+            requests: db.open_tree("requests")?,
+            secret_requests: Mutex::default(),
+            secret_results: Mutex::default(),
+            events,
+            pending: AtomicUsize::new(0),
+            pending_by_client: Mutex::default(),
+            pool,
+            issued: None,
+            replay: None,
+        };
+        manager.prune()?;
+        Ok(manager)
+    }
+
+    /// Record the commitments of commit steps in `store`.
+    pub fn with_issued_store(mut self, store: Arc<IssuedStore>) -> Self {
+        self.issued = Some(store);
+        self
+    }
+
+    /// Record the presentations of verify steps in `registry`.
+    pub fn with_replay_registry(mut self, registry: Arc<ReplayRegistry>) -> Self {
+        self.replay = Some(registry);
+        self
+    }
+
+    /// Jobs queued or running.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobRecord> {
+        let bytes = self.records.get(id).ok()??;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// The record for a client: the first read of a finished job also carries
+    /// the secret fields of its result, which are then forgotten.
+    pub fn fetch(&self, id: &str) -> Option<JobRecord> {
+        let mut record = self.get(id)?;
+        if record.status.is_finished() {
+            if let Some(result) = self.secret_results.lock().expect("job secrets lock").remove(id) {
+                record.result = Some(result);
+            }
+        }
+        Some(record)
+    }
+
+    /// Updates for every job; callers filter by id.
+    pub fn subscribe(&self) -> broadcast::Receiver<JobRecord> {
+        self.events.subscribe()
+    }
+
+    /// Store `request` as a queued job for `submitter` and start running it.
+    pub fn submit(manager: &Arc<Self>, request: JobRequest, submitter: Submitter) -> Result<JobRecord, SubmitError> {
+        request.validate().map_err(SubmitError::Invalid)?;
+        let client = submitter.client.as_deref();
+        if let Some(client) = client {
+            let mut by_client = manager.pending_by_client.lock().expect("pending jobs lock");
+            let pending = by_client.entry(client.to_string()).or_default();
+            if *pending >= MAX_PENDING_JOBS_PER_CLIENT {
+                return Err(SubmitError::TooManyPendingForClient);
+            }
+            *pending += 1;
+        }
+        let reserved = manager.pending.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+            (n < MAX_PENDING_JOBS).then_some(n + 1)
+        });
+        if reserved.is_err() {
+            manager.release_client(client);
+            return Err(SubmitError::TooManyPending);
+        }
+
+        let mut id = [0u8; 16];
+        OsRng.fill_bytes(&mut id);
+        let id = hex::encode(id);
+        let created_at = now();
+        let record = JobRecord {
+            id: id.clone(),
+            kind: request.kind().into(),
+            status: JobStatus::Queued,
+            progress: JobProgress { done: 0, total: request.steps().len() },
+            created_at,
+            updated_at: created_at,
+            result: None,
+            error: None,
+        };
+        let stored = if request.holds_secrets() {
+            manager.secret_requests.lock().expect("job secrets lock").insert(id.clone(), request.clone());
+            Ok(())
+        } else {
+            serde_json::to_vec(&StoredRequest { request: request.clone(), verifier: submitter.verifier.clone() })
+                .map_err(|e| sled::Error::Unsupported(e.to_string()))
+                .and_then(|bytes| manager.requests.insert(id.as_bytes(), bytes).map(drop))
+        };
+        let stored = stored.and_then(|_| manager.save(&record));
+        if let Err(e) = stored {
+            manager.secret_requests.lock().expect("job secrets lock").remove(&id);
+            manager.pending.fetch_sub(1, Ordering::SeqCst);
+            manager.release_client(client);
+            return Err(SubmitError::Store(e));
+        }
+        if let Err(e) = manager.prune() {
+            warn!(error = %e, "pruning expired jobs failed");
+        }
+        actix_web::rt::spawn(Self::run(manager.clone(), id, request, submitter));
+        Ok(record)
+    }
+
+    /// Restart jobs left queued or running by a previous process.
+    pub fn resume(manager: &Arc<Self>) -> usize {
+        let unfinished: Vec<JobRecord> = manager
+            .records
+            .iter()
+            .values()
+            .filter_map(|v| serde_json::from_slice::<JobRecord>(&v.ok()?).ok())
+            .filter(|r| !r.status.is_finished())
+            .collect();
+        let mut resumed = 0;
+        for mut record in unfinished {
+            let request = manager
+                .requests
+                .get(record.id.as_bytes())
+                .ok()
+                .flatten()
+                .and_then(|bytes| serde_json::from_slice::<StoredRequest>(&bytes).ok());
+            let Some(StoredRequest { request, verifier }) = request else {
+                record.status = JobStatus::Failed;
+                // Requests holding secrets were never stored.
+                let error = "job request was lost, or held secrets and was not stored".into();
+                record.error = Some(JobError { error, code: "job_lost".into() });
+                record.updated_at = now();
+                let _ = manager.save(&record);
+                continue;
+            };
+            // Steps are not checkpointed: a resumed job starts over.
+            record.status = JobStatus::Queued;
+            record.progress.done = 0;
+            record.updated_at = now();
+            if manager.save(&record).is_ok() {
+                manager.pending.fetch_add(1, Ordering::SeqCst);
+                actix_web::rt::spawn(Self::run(manager.clone(), record.id, request, Submitter { verifier, ..Submitter::default() }));
+                resumed += 1;
+            }
+        }
+        resumed
+    }
+
+    fn release_client(&self, client: Option<&str>) {
+        let Some(client) = client else { return };
+        let mut by_client = self.pending_by_client.lock().expect("pending jobs lock");
+        if let Some(pending) = by_client.get_mut(client) {
+            *pending -= 1;
+            if *pending == 0 {
+                by_client.remove(client);
+            }
+        }
+    }
+
+    fn save(&self, record: &JobRecord) -> sled::Result<()> {
+        let bytes = serde_json::to_vec(record).expect("job records always serialize");
+        self.records.insert(record.id.as_bytes(), bytes)?;
+        // Nobody listening is fine.
+        let _ = self.events.send(record.clone());
+        Ok(())
+    }
+
+    fn update(&self, id: &str, f: impl FnOnce(&mut JobRecord)) {
+        let Some(mut record) = self.get(id) else { return };
+        f(&mut record);
+        record.updated_at = now();
+        if let Err(e) = self.save(&record) {
+            warn!(job_id = id, error = %e, "saving job failed");
+        }
+    }
+
+    /// Drop finished jobs older than [`JOB_RETENTION`].
+    fn prune(&self) -> sled::Result<()> {
+        let cutoff = now().saturating_sub(JOB_RETENTION.as_secs());
+        for entry in self.records.iter() {
+            let (key, value) = entry?;
+            let expired = serde_json::from_slice::<JobRecord>(&value)
+                .map(|r| r.status.is_finished() && r.updated_at < cutoff)
+                .unwrap_or(true);
+            if expired {
+                self.records.remove(&key)?;
+                self.requests.remove(&key)?;
+                self.secret_results.lock().expect("job secrets lock").remove(&*String::from_utf8_lossy(&key));
+            }
+        }
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "job", skip_all, fields(job_id = %id, kind = %request.kind()))]
+    async fn run(manager: Arc<Self>, id: String, request: JobRequest, submitter: Submitter) {
+        info!("proof job started");
+        manager.update(&id, |r| r.status = JobStatus::Running);
+        let steps = request.steps();
+        let mut outcomes = Vec::with_capacity(steps.len());
+        for step in steps {
+            let outcome = manager.run_step(step, &submitter).await;
+            outcomes.push(outcome);
+            manager.update(&id, |r| r.progress.done += 1);
+        }
+
+        let (status, result, error) = match request {
+            JobRequest::Batch { .. } => {
+                let results = outcomes
+                    .into_iter()
+                    .map(|o| o.unwrap_or_else(|e| json!(e)))
+                    .collect::<Vec<_>>();
+                (JobStatus::Succeeded, Some(json!({ "results": results })), None)
+            },
+            _ => match outcomes.pop().expect("a single job has one step") {
+                Ok(value) => (JobStatus::Succeeded, Some(value), None),
+                Err(e) => (JobStatus::Failed, None, Some(e)),
+            },
+        };
+        info!(status = ?status, "proof job finished");
+        let stored = result.map(|full| match redact(&full) {
+            Some(redacted) => {
+                manager.secret_results.lock().expect("job secrets lock").insert(id.clone(), full);
+                redacted
+            },
+            None => full,
+        });
+        manager.update(&id, |r| {
+            r.status = status;
+            r.result = stored;
+            r.error = error;
+        });
+        manager.requests.remove(id.as_bytes()).ok();
+        manager.secret_requests.lock().expect("job secrets lock").remove(&id);
+        manager.pending.fetch_sub(1, Ordering::SeqCst);
+        manager.release_client(submitter.client.as_deref());
+    }
+
+    /// Run one step on the proof pool, waiting out saturation instead of failing.
+    async fn run_step(&self, step: JobRequest, submitter: &Submitter) -> Result<Value, JobError> {
+        loop {
+            let job = step.clone();
+            match self.pool.run(move || execute(&job)).await {
+                Ok(result) => {
+                    let result = result.map_err(|e| JobError::from(&e))?;
+                    self.record_issue(&step, &result, submitter);
+                    return self.present(&step, result, &submitter.verifier);
+                },
+                Err(PoolError::Saturated) => actix_web::rt::time::sleep(SATURATED_BACKOFF).await,
+                Err(e @ PoolError::Aborted) => {
+                    return Err(JobError { error: e.to_string(), code: "proof_aborted".into() });
+                },
+            }
+        }
+    }
+
+    /// Record the commitment of a commit step. It is already the
+    /// submitter's, so a store failure is only logged.
+    fn record_issue(&self, step: &JobRequest, result: &Value, submitter: &Submitter) {
+        let (Some(store), Some(context), Some((kind, tag))) = (&self.issued, &submitter.issue, step.issued()) else {
+            return;
+        };
+        let (Some(commitment), Some(proof)) = (result["commitment"].as_str(), result["proof"].as_str()) else {
+            return;
+        };
+        let issue = Issue { kind, scheme: "bulletproofs", commitments: vec![commitment.into()], proof: proof.into(), binding_tag: tag };
+        if let Err(e) = store.record(issue, context) {
+            warn!(error = %e, "recording an issued commitment failed");
+        }
+    }
+
+    /// Record a verified proof of a verify step and report its earlier
+    /// presentations. As in the synchronous responses, the registry failing
+    /// fails the step.
+    fn present(&self, step: &JobRequest, mut result: Value, verifier: &str) -> Result<Value, JobError> {
+        let (Some(registry), Some((proof, tag))) = (&self.replay, step.presented()) else {
+            return Ok(result);
+        };
+        if result["verified"] != json!(true) {
+            return Ok(result);
+        }
+        let presentation = Presentation::new(&proof, tag.as_ref().map(|t| &t[..]), verifier.to_string());
+        match registry.present(&presentation) {
+            Ok(report) => {
+                result["replay"] = json!(report);
+                Ok(result)
+            },
+            Err(e) => Err(JobError { error: format!("replay registry: {e}"), code: "store_error".into() }),
+        }
+    }
+}
+
+// ─── Execution ─────────────────────────────────────────────────────────────
+
+/// `{ "verified": bool }`; only a proof that fails to verify is `false`.
+fn verified(result: Result<(), ZkError>) -> Result<Value, ZkError> {
+    match result {
+        Ok(()) => Ok(json!({ "verified": true })),
+        Err(ZkError::TranscriptMismatch) => Ok(json!({ "verified": false })),
+        Err(e) => Err(e),
+    }
+}
+
+/// Run a single (non-batch) job step to completion.
+pub fn execute(request: &JobRequest) -> Result<Value, ZkError> {
+    use zk::pedersen::{prove_value_commitment_with_binding_and_range, verify_value_commitment_with_binding};
+    use zk::txid_pedersen_proof::{prove_txid_commitment_from_hex_with_binding, verify_txid_commitment_with_binding};
+
+    match request {
+        JobRequest::CommitValue { value, blinding_hex, binding_tag_hex, bit_range } => {
+            let blinding = blinding(blinding_hex, "blinding_hex")?;
+            let tag = binding_tag(binding_tag_hex)?;
+            let (commitment, proof, verified) =
+                prove_value_commitment_with_binding_and_range(*value, blinding, tag.as_ref().map(|t| &t[..]), *bit_range)?;
+            Ok(json!({
+                "commitment": hex::encode(commitment.as_bytes()),
+                "proof": hex::encode(proof),
+                "blinding_hex": hex::encode(blinding.as_bytes()),
+                "verified": verified,
+            }))
+        },
+        JobRequest::VerifyValueCommitment { commitment, proof, binding_tag_hex } => {
+            let commitment = NgCompressed(hex32(commitment, "commitment")?);
+            let proof = proof_bytes(proof)?;
+            let tag = binding_tag(binding_tag_hex)?;
+            verified(verify_value_commitment_with_binding(commitment, proof, tag.as_ref().map(|t| &t[..])))
+        },
+        JobRequest::CommitTxHash { tx_hash, binding_tag_hex } => {
+            let tag = binding_tag(binding_tag_hex)?;
+            let (commitment, proof, verified) =
+                prove_txid_commitment_from_hex_with_binding(tx_hash.trim(), tag.as_ref().map(|t| &t[..]))?;
+            Ok(json!({
+                "commitment": hex::encode(commitment.as_bytes()),
+                "proof": hex::encode(proof),
+                "verified": verified,
+            }))
+        },
+        JobRequest::VerifyTxHash { commitment, proof, binding_tag_hex } => {
+            let commitment = NgCompressed(hex32(commitment, "commitment")?);
+            let proof = proof_bytes(proof)?;
+            let tag = binding_tag(binding_tag_hex)?;
+            verified(verify_txid_commitment_with_binding(commitment, proof, tag.as_ref().map(|t| &t[..])))
+        },
+        JobRequest::ProvePlus { tx_hash } => {
+            let (commitments, proof) = zk::bp_plus_pedersen::prove_txid_commitment(hex32(tx_hash, "tx_hash")?)?;
+            Ok(json!({
+                "commitments": commitments.iter().map(|c| hex::encode(c.as_bytes())).collect::<Vec<_>>(),
+                "proof": hex::encode(proof),
+            }))
+        },
+        JobRequest::VerifyPlus { commitments, proof } => {
+            let commitments = commitments_hex(commitments, TX_HASH_LIMBS..=TX_HASH_LIMBS)?
+                .into_iter()
+                .map(curve25519_dalek::ristretto::CompressedRistretto)
+                .collect();
+            verified(zk::bp_plus_pedersen::verify_txid_commitment(commitments, proof_bytes(proof)?))
+        },
+        JobRequest::VerifyAggregated { commitments, proof, bit_range, binding_tag_hex } => {
+            let commitments: Vec<_> =
+                commitments_hex(commitments, 1..=MAX_PARTIES.next_power_of_two())?.into_iter().map(NgCompressed).collect();
+            let tag = binding_tag(binding_tag_hex)?;
+            verified(zk::aggregated_range_proof::verify_aggregated_range_proof(
+                &commitments,
+                &proof_bytes(proof)?,
+                *bit_range,
+                tag.as_ref().map(|t| &t[..]),
+            ))
+        },
+        JobRequest::ProveStatement { statement, openings, binding_tag_hex } => {
+            let tag = binding_tag(binding_tag_hex)?;
+            let openings = openings
+                .iter()
+                .map(|(name, o)| Ok((name.clone(), Opening { value: o.value, blinding: blinding(&o.blinding_hex, "blinding_hex")? })))
+                .collect::<Result<HashMap<_, _>, ZkError>>()?;
+            let proof = prove_statement(statement, &openings, tag.as_ref().map(|t| &t[..]))?;
+            Ok(json!({
+                "statement_hash": hex::encode(proof.statement_hash),
+                "commitments": proof.commitments.iter().map(|c| hex::encode(c.as_bytes())).collect::<Vec<_>>(),
+                "proof": hex::encode(&proof.proof),
+            }))
+        },
+        JobRequest::VerifyStatement { statement, statement_hash, commitments, proof, binding_tag_hex } => {
+            let statement_hash = hex32(statement_hash, "statement_hash")?;
+            let commitments: Vec<_> = commitments_hex(commitments, 0..=MAX_VARIABLES)?.into_iter().map(NgCompressed).collect();
+            let tag = binding_tag(binding_tag_hex)?;
+            verified(verify_statement(
+                statement,
+                &statement_hash,
+                &commitments,
+                &proof_bytes(proof)?,
+                tag.as_ref().map(|t| &t[..]),
+            ))
+        },
+        JobRequest::Batch { .. } => Err(ZkError::InvalidParameters("batches run step by step".into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::MAX_PROOF_BYTES;
+
+    fn manager(dir: &tempfile::TempDir) -> Arc<JobManager> {
+        let pool = Arc::new(ProofPool::new(2, 8).unwrap());
+        Arc::new(JobManager::open(dir.path().to_str().unwrap(), pool).unwrap())
+    }
+
+    fn client(ip: &str) -> Submitter {
+        Submitter { client: Some(ip.into()), ..Submitter::default() }
+    }
+
+    async fn finished(manager: &JobManager, id: &str) -> JobRecord {
+        loop {
+            let record = manager.get(id).unwrap();
+            if record.status.is_finished() {
+                return record;
+            }
+            actix_web::rt::time::sleep(Duration::from_millis(5)).await;
+        }
+    }
+
+    #[actix_web::test]
+    async fn batch_job_runs_every_step_and_reports_each_outcome() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+
+        let commit = JobManager::submit(&manager, serde_json::from_value(json!({ "kind": "commit_value", "value": 7 })).unwrap(), Submitter::default()).unwrap();
+        let committed = finished(&manager, &commit.id).await;
+        assert_eq!(committed.status, JobStatus::Succeeded);
+        let result = manager.fetch(&commit.id).unwrap().result.unwrap();
+
+        let batch = serde_json::from_value(json!({ "kind": "batch", "jobs": [
+            { "kind": "verify_value_commitment", "commitment": result["commitment"], "proof": result["proof"] },
+            { "kind": "verify_value_commitment", "commitment": result["commitment"], "proof": "00" },
+        ]}))
+        .unwrap();
+        let batch = JobManager::submit(&manager, batch, Submitter::default()).unwrap();
+        let record = finished(&manager, &batch.id).await;
+        assert_eq!(record.progress, JobProgress { done: 2, total: 2 });
+        let results = &record.result.unwrap()["results"];
+        assert_eq!(results[0], json!({ "verified": true }));
+        assert_eq!(results[1]["code"], "proof_deserialization");
+    }
+
+    #[actix_web::test]
+    async fn clients_hold_a_bounded_share_of_the_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+        let verify = || serde_json::from_value::<JobRequest>(json!({ "kind": "verify_tx_hash", "commitment": "00", "proof": "00" })).unwrap();
+        let ids: Vec<_> = (0..MAX_PENDING_JOBS_PER_CLIENT)
+            .map(|_| JobManager::submit(&manager, verify(), client("ip:10.0.0.1")).unwrap().id)
+            .collect();
+        assert!(matches!(
+            JobManager::submit(&manager, verify(), client("ip:10.0.0.1")),
+            Err(SubmitError::TooManyPendingForClient)
+        ));
+        assert!(JobManager::submit(&manager, verify(), client("ip:10.0.0.2")).is_ok());
+
+        for id in ids {
+            finished(&manager, &id).await;
+        }
+        assert!(JobManager::submit(&manager, verify(), client("ip:10.0.0.1")).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn failed_job_keeps_the_error_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+        let request = serde_json::from_value(json!({ "kind": "commit_value", "value": 300, "bit_range": 8 })).unwrap();
+        let record = finished(&manager, &JobManager::submit(&manager, request, Submitter::default()).unwrap().id).await;
+        assert_eq!(record.status, JobStatus::Failed);
+        assert_eq!(record.error.unwrap().code, "range_overflow");
+    }
+
+    #[actix_web::test]
+    async fn secrets_are_never_stored_and_handed_out_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = manager(&dir);
+        let blinding = format!("2a{}", "00".repeat(31));
+        let request = json!({ "kind": "batch", "jobs": [{ "kind": "commit_value", "value": 7, "blinding_hex": blinding }] });
+        let record = JobManager::submit(&manager, serde_json::from_value(request).unwrap(), Submitter::default()).unwrap();
+        assert!(manager.requests.get(&record.id).unwrap().is_none(), "openings stay in memory");
+
+        let stored = finished(&manager, &record.id).await.result.unwrap();
+        assert!(stored["results"][0]["commitment"].is_string());
+        assert!(stored["results"][0].get("blinding_hex").is_none());
+        let fetched = manager.fetch(&record.id).unwrap().result.unwrap();
+        assert_eq!(fetched["results"][0]["blinding_hex"], blinding);
+        assert!(manager.fetch(&record.id).unwrap().result.unwrap()["results"][0].get("blinding_hex").is_none());
+        let on_disk = manager.records.get(&record.id).unwrap().unwrap();
+        assert!(!String::from_utf8_lossy(&on_disk).contains(&blinding));
+    }
+
+    #[actix_web::test]
+    async fn verify_steps_are_presentations() {
+        use super::super::config::ReplayScope;
+
+        let dir = tempfile::tempdir().unwrap();
+        let registry = Arc::new(ReplayRegistry::open(dir.path().join("replay"), ReplayScope::PerVerifier).unwrap());
+        let pool = Arc::new(ProofPool::new(2, 8).unwrap());
+        let manager = Arc::new(JobManager::open(dir.path().join("jobs"), pool).unwrap().with_replay_registry(registry));
+        let tag = Some("11".repeat(32));
+        let created = execute(&JobRequest::CommitTxHash { tx_hash: "ab".repeat(32), binding_tag_hex: tag.clone() }).unwrap();
+        let verify = JobRequest::VerifyTxHash {
+            commitment: created["commitment"].as_str().unwrap().into(),
+            proof: created["proof"].as_str().unwrap().into(),
+            binding_tag_hex: tag,
+        };
+
+        let mut reports = Vec::new();
+        for verifier in ["alice", "alice"] {
+            let submitter = Submitter { verifier: verifier.into(), ..Submitter::default() };
+            let record = JobManager::submit(&manager, verify.clone(), submitter).unwrap();
+            let result = finished(&manager, &record.id).await.result.unwrap();
+            reports.push((result["verified"].clone(), result["replay"]["accepted"].clone(), result["replay"]["previous_by_verifier"].clone()));
+        }
+        assert_eq!(reports, [(json!(true), json!(true), json!(0)), (json!(true), json!(false), json!(1))]);
+
+        // Resumed jobs keep their verifier; requests stored without one still load.
+        let stored = serde_json::to_value(StoredRequest { request: verify, verifier: "alice".into() }).unwrap();
+        assert_eq!((stored["kind"].as_str(), stored["verifier"].as_str()), (Some("verify_tx_hash"), Some("alice")));
+        let mut unnamed = stored.clone();
+        unnamed.as_object_mut().unwrap().remove("verifier");
+        assert_eq!(serde_json::from_value::<StoredRequest>(unnamed).unwrap().verifier, "");
+    }
+
+    #[actix_web::test]
+    async fn commit_steps_are_issued() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(IssuedStore::open(dir.path().join("issued")).unwrap());
+        let pool = Arc::new(ProofPool::new(2, 8).unwrap());
+        let manager = Arc::new(JobManager::open(dir.path().join("jobs"), pool).unwrap().with_issued_store(store.clone()));
+        let request = serde_json::from_value(json!({ "kind": "batch", "jobs": [
+            { "kind": "commit_value", "value": 7, "binding_tag_hex": "11".repeat(32) },
+            { "kind": "commit_tx_hash", "tx_hash": "ab".repeat(32) },
+        ]}))
+        .unwrap();
+        let issue = IssueContext { product: None, stage: Some("purchase".into()) };
+        let record = JobManager::submit(&manager, request, Submitter { issue: Some(issue), ..Submitter::default() }).unwrap();
+        finished(&manager, &record.id).await;
+        let result = manager.fetch(&record.id).unwrap().result.unwrap();
+
+        let records = store.list(&crate::api::IssuedQuery::default()).unwrap();
+        let issued: Vec<_> = records.iter().map(|r| (r.kind.as_str(), r.commitments[0].as_str(), r.stage.as_deref())).collect();
+        assert_eq!(issued, [
+            ("tx_hash_commitment", result["results"][1]["commitment"].as_str().unwrap(), Some("purchase")),
+            ("value_commitment", result["results"][0]["commitment"].as_str().unwrap(), Some("purchase")),
+        ]);
+        assert_eq!(records[1].binding_tag, Some("11".repeat(32)));
+    }
+
+    #[actix_web::test]
+    async fn jobs_holding_secrets_are_lost_on_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let manager = manager(&dir);
+            let record = JobRecord {
+                id: "interrupted".into(),
+                kind: "commit_value".into(),
+                status: JobStatus::Running,
+                progress: JobProgress { done: 0, total: 1 },
+                created_at: now(),
+                updated_at: now(),
+                result: None,
+                error: None,
+            };
+            manager.save(&record).unwrap();
+            manager.records.flush().unwrap();
+        }
+        let manager = manager(&dir);
+        assert_eq!(JobManager::resume(&manager), 0);
+        assert_eq!(manager.get("interrupted").unwrap().error.unwrap().code, "job_lost");
+    }
+
+    #[actix_web::test]
+    async fn unfinished_jobs_resume_after_reopening_the_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let request: JobRequest =
+            serde_json::from_value(json!({ "kind": "verify_tx_hash", "commitment": "00".repeat(32), "proof": "00" })).unwrap();
+        assert!(!request.holds_secrets());
+        {
+            // Simulate a crash: the job is recorded but never ran.
+            let manager = manager(&dir);
+            let record = JobRecord {
+                id: "interrupted".into(),
+                kind: request.kind().into(),
+                status: JobStatus::Running,
+                progress: JobProgress { done: 0, total: 1 },
+                created_at: now(),
+                updated_at: now(),
+                result: None,
+                error: None,
+            };
+            manager.requests.insert("interrupted", serde_json::to_vec(&request).unwrap()).unwrap();
+            manager.save(&record).unwrap();
+            manager.records.flush().unwrap();
+        }
+        let manager = manager(&dir);
+        assert_eq!(JobManager::resume(&manager), 1);
+        assert!(finished(&manager, "interrupted").await.status.is_finished());
+    }
+
+    #[test]
+    fn nested_batches_are_rejected() {
+        let request: JobRequest =
+            serde_json::from_value(json!({ "kind": "batch", "jobs": [{ "kind": "batch", "jobs": [] }] })).unwrap();
+        assert!(request.validate().is_err());
+        assert!(JobRequest::Batch { jobs: vec![] }.validate().is_err());
+    }
+
+    #[test]
+    fn oversized_lists_and_proofs_are_refused_at_submission() {
+        let oversized = JobRequest::VerifyPlus { commitments: vec!["00".into(); 5], proof: String::new() };
+        assert!(oversized.validate().unwrap_err().contains("expected 4 commitments, got 5"));
+        let oversized = JobRequest::VerifyTxHash { commitment: String::new(), proof: "ab".repeat(MAX_PROOF_BYTES + 1), binding_tag_hex: None };
+        assert!(JobRequest::Batch { jobs: vec![oversized] }.validate().is_err());
+    }
+}
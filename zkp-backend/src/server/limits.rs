@@ -0,0 +1,376 @@
+//! Per-client rate limits and the proof generation body limit.
+//!
+//! Requests are charged to one of two budgets (see [`budget`]): proof
+//! generation, which is anything other than a read or a verification, and
+//! verification. Each client has a token bucket per budget. The client is the
+//! verified TLS client certificate if there is one, then the API key when
+//! the request carries one of the configured `api_keys`, `issuer_api_keys`
+//! or `operator_api_keys`, and the peer IP otherwise, so made-up keys cannot
+//! buy fresh buckets. An empty bucket answers 429 with `Retry-After`. Reads are
+//! never limited.
+//!
+//! A request costs one token. Handlers whose requests do more than one
+//! proof's work charge the rest with [`Limits::charge`]: a proof job costs a
+//! token per step. A charge larger than the bucket takes a full one and
+//! leaves the client in debt until it has refilled.
+//!
+//! Proof generation bodies are also held to `prove_json_limit`, far below the
+//! `json_limit` verifications need.
+
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::error::PayloadError;
+use actix_web::http::header::{CONTENT_LENGTH, RETRY_AFTER};
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use futures_util::StreamExt;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use super::auth;
+use super::config::Config;
+use super::tls::ClientCertificate;
+
+/// Buckets kept before idle ones are dropped. A bucket that has refilled is
+/// the same as a fresh one, so dropping it changes nothing for its client.
+const PRUNE_AT: usize = 16 * 1024;
+
+/// What a request is charged to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Budget {
+    Prove,
+    Verify,
+}
+
+/// The budget of a request, or `None` for reads. Verification routes are
+/// the ones whose last path segment starts with `verify`.
+pub fn budget(method: &Method, path: &str) -> Option<Budget> {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        None
+    } else if path.rsplit('/').next().is_some_and(|segment| segment.starts_with("verify")) {
+        Some(Budget::Verify)
+    } else {
+        Some(Budget::Prove)
+    }
+}
+
+/// Who a request is charged to; handlers find it in the request extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Client {
+    /// SHA-256 of a configured API key.
+    ApiKey([u8; 32]),
+    /// SHA-256 of a verified TLS client certificate.
+    Certificate([u8; 32]),
+    Ip(IpAddr),
+}
+
+impl std::fmt::Display for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ApiKey(digest) => write!(f, "key:{}", hex::encode(digest)),
+            Self::Certificate(digest) => write!(f, "cert:{}", hex::encode(digest)),
+            Self::Ip(ip) => write!(f, "ip:{ip}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rate {
+    per_second: f64,
+    capacity: f64,
+}
+
+impl Rate {
+    fn new(per_minute: Option<u32>, burst: Option<u32>) -> Option<Self> {
+        let per_minute = per_minute?;
+        Some(Self { per_second: f64::from(per_minute) / 60.0, capacity: f64::from(burst.unwrap_or(per_minute)) })
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+pub struct Limits {
+    prove: Option<Rate>,
+    verify: Option<Rate>,
+    prove_body_limit: usize,
+    api_keys: HashSet<[u8; 32]>,
+    buckets: Mutex<HashMap<(Budget, Client), Bucket>>,
+}
+
+impl Limits {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            prove: Rate::new(config.prove_rate, config.prove_burst),
+            verify: Rate::new(config.verify_rate, config.verify_burst),
+            prove_body_limit: config.prove_json_limit,
+            api_keys: config.api_keys.iter().chain(&config.issuer_api_keys).chain(&config.operator_api_keys).map(|k| Sha256::digest(k).into()).collect(),
+            buckets: Mutex::default(),
+        }
+    }
+
+    fn client(&self, req: &ServiceRequest) -> Client {
+        if let Some(cert) = req.conn_data::<ClientCertificate>() {
+            return Client::Certificate(cert.fingerprint);
+        }
+        if let Some(key) = req.headers().get(auth::API_KEY) {
+            let digest: [u8; 32] = Sha256::digest(key.as_bytes()).into();
+            if self.api_keys.contains(&digest) {
+                return Client::ApiKey(digest);
+            }
+        }
+        Client::Ip(req.peer_addr().map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |a| a.ip()))
+    }
+
+    /// Take `cost` tokens from `client`'s `budget` bucket, or say how long
+    /// until they are available.
+    fn take(&self, budget: Budget, client: Client, now: Instant, cost: f64) -> Result<(), Duration> {
+        let rate = match budget {
+            Budget::Prove => self.prove,
+            Budget::Verify => self.verify,
+        };
+        let Some(rate) = rate else {
+            return Ok(());
+        };
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= PRUNE_AT {
+            buckets.retain(|(budget, _), bucket| {
+                let rate = if *budget == Budget::Prove { self.prove } else { self.verify };
+                rate.is_some_and(|r| bucket.tokens + now.duration_since(bucket.updated).as_secs_f64() * r.per_second < r.capacity)
+            });
+        }
+        let bucket = buckets.entry((budget, client)).or_insert(Bucket { tokens: rate.capacity, updated: now });
+        let refill = now.duration_since(bucket.updated).as_secs_f64() * rate.per_second;
+        bucket.tokens = (bucket.tokens + refill).min(rate.capacity);
+        bucket.updated = now;
+        let needed = cost.min(rate.capacity);
+        if bucket.tokens >= needed {
+            bucket.tokens -= cost;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((needed - bucket.tokens) / rate.per_second))
+        }
+    }
+
+    /// Charge the client of `req` (as the middleware saw it) `tokens` more
+    /// from `budget`; the 429 to answer if its bucket cannot pay.
+    pub fn charge(&self, req: &HttpRequest, budget: Budget, tokens: usize) -> Result<(), HttpResponse> {
+        let Some(client) = req.extensions().get::<Client>().cloned() else {
+            return Ok(());
+        };
+        self.take(budget, client, Instant::now(), tokens as f64).map_err(|wait| {
+            let (message, response) = rate_limited(budget, wait);
+            info!(code = "rate_limited", error = %message, "request rejected");
+            response
+        })
+    }
+}
+
+/// The message and 429 for a client that must wait `wait`.
+fn rate_limited(budget: Budget, wait: Duration) -> (String, HttpResponse) {
+    let retry_after = wait.as_secs() + 1;
+    let what = if budget == Budget::Prove { "proof generation" } else { "verification" };
+    let message = format!("{what} rate limit exceeded; retry in {retry_after} s");
+    let response = HttpResponse::TooManyRequests()
+        .insert_header((RETRY_AFTER, retry_after))
+        .json(json!({ "error": message, "code": "rate_limited" }));
+    (message, response)
+}
+
+/// Middleware (for `middleware::from_fn`); a no-op unless [`Limits`] is in
+/// the app data.
+pub async fn enforce(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let Some(limits) = req.app_data::<web::Data<Limits>>().cloned() else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+    let Some(budget) = budget(req.method(), req.path()) else {
+        return Ok(next.call(req).await?.map_into_left_body());
+    };
+
+    if budget == Budget::Prove {
+        let limit = limits.prove_body_limit;
+        let declared = req.headers().get(CONTENT_LENGTH).and_then(|v| v.to_str().ok()?.parse::<usize>().ok());
+        if declared.is_some_and(|length| length > limit) {
+            let message = format!("proof generation bodies are limited to {limit} bytes");
+            return Ok(reject(req, StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", message));
+        }
+        // Chunked bodies declare no length: cut them off as they stream in.
+        let mut seen = 0;
+        let limited = req.take_payload().map(move |chunk| {
+            let chunk = chunk?;
+            seen += chunk.len();
+            if seen > limit {
+                Err(PayloadError::Overflow)
+            } else {
+                Ok(chunk)
+            }
+        });
+        req.set_payload(Payload::Stream { payload: Box::pin(limited) });
+    }
+
+    let client = limits.client(&req);
+    req.extensions_mut().insert(client.clone());
+    if let Err(wait) = limits.take(budget, client, Instant::now(), 1.0) {
+        let (message, response) = rate_limited(budget, wait);
+        info!(code = "rate_limited", error = %message, "request rejected");
+        return Ok(req.into_response(response).map_into_right_body());
+    }
+    Ok(next.call(req).await?.map_into_left_body())
+}
+
+fn reject<B>(req: ServiceRequest, status: StatusCode, code: &str, message: String) -> ServiceResponse<EitherBody<B>> {
+    info!(code, error = %message, "request rejected");
+    req.into_response(HttpResponse::build(status).json(json!({ "error": message, "code": code }))).map_into_right_body()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::web::Bytes;
+    use actix_web::App;
+    use serde_json::Value;
+
+    const KEY: &str = "0123456789abcdef-service";
+
+    fn config() -> Config {
+        Config {
+            api_keys: vec![KEY.into()],
+            prove_json_limit: 1024,
+            prove_rate: Some(2),
+            verify_rate: Some(60),
+            verify_burst: Some(3),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn requests_fall_into_budgets() {
+        assert_eq!(budget(&Method::POST, "/v1/range-proofs"), Some(Budget::Prove));
+        assert_eq!(budget(&Method::POST, "/v1/range-proofs/verify"), Some(Budget::Verify));
+        assert_eq!(budget(&Method::POST, "/zkp/verify_plus"), Some(Budget::Verify));
+        assert_eq!(budget(&Method::GET, "/zkp/jobs/1"), None);
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let limits = Limits::new(&config());
+        let (start, ip) = (Instant::now(), Client::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        for _ in 0..3 {
+            assert!(limits.take(Budget::Verify, ip.clone(), start, 1.0).is_ok());
+        }
+        let wait = limits.take(Budget::Verify, ip.clone(), start, 1.0).unwrap_err();
+        assert_eq!(wait, Duration::from_secs(1));
+        assert!(limits.take(Budget::Verify, ip.clone(), start + wait, 1.0).is_ok());
+        // Budgets and clients are independent.
+        assert!(limits.take(Budget::Prove, ip, start, 1.0).is_ok());
+        assert!(limits.take(Budget::Verify, Client::Ip(IpAddr::V4(Ipv4Addr::BROADCAST)), start, 1.0).is_ok());
+    }
+
+    #[test]
+    fn charges_beyond_the_bucket_leave_a_debt() {
+        let limits = Limits::new(&config());
+        let (start, ip) = (Instant::now(), Client::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert_eq!(limits.take(Budget::Verify, ip.clone(), start, 2.0), Ok(()));
+        // Two more than the bucket holds: it must be full, then owes two.
+        assert_eq!(limits.take(Budget::Verify, ip.clone(), start, 5.0), Err(Duration::from_secs(2)));
+        let full = start + Duration::from_secs(2);
+        assert_eq!(limits.take(Budget::Verify, ip.clone(), full, 5.0), Ok(()));
+        assert_eq!(limits.take(Budget::Verify, ip, full, 1.0), Err(Duration::from_secs(3)));
+    }
+
+    #[actix_web::test]
+    async fn limits_are_per_client_and_budget() {
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(enforce))
+                .app_data(web::Data::new(Limits::new(&config())))
+                .route("/zkp/generate", web::post().to(|body: Bytes| async move { HttpResponse::Ok().body(body) }))
+                .route("/zkp/verify", web::post().to(HttpResponse::Ok))
+                .route("/zkp/pool", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        let from = |ip: &str| TestRequest::post().uri("/zkp/generate").peer_addr(format!("{ip}:1000").parse().unwrap());
+
+        for _ in 0..2 {
+            assert_eq!(call_service(&app, from("10.0.0.1").to_request()).await.status(), StatusCode::OK);
+        }
+        let resp = call_service(&app, from("10.0.0.1").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(resp.headers().get(RETRY_AFTER).unwrap(), "30");
+        let body: Value = read_body_json(resp).await;
+        assert_eq!(body["code"], "rate_limited");
+
+        // Another IP, a configured key on the same IP, and the verify budget all still pass;
+        // an unknown key is just the IP.
+        assert_eq!(call_service(&app, from("10.0.0.2").to_request()).await.status(), StatusCode::OK);
+        let keyed = from("10.0.0.1").insert_header((auth::API_KEY, KEY));
+        assert_eq!(call_service(&app, keyed.to_request()).await.status(), StatusCode::OK);
+        let unknown = from("10.0.0.1").insert_header((auth::API_KEY, "0123456789abcdef-unknown"));
+        assert_eq!(call_service(&app, unknown.to_request()).await.status(), StatusCode::TOO_MANY_REQUESTS);
+        let verify = TestRequest::post().uri("/zkp/verify").peer_addr("10.0.0.1:1000".parse().unwrap());
+        assert_eq!(call_service(&app, verify.to_request()).await.status(), StatusCode::OK);
+        for _ in 0..5 {
+            assert_eq!(call_service(&app, TestRequest::get().uri("/zkp/pool").to_request()).await.status(), StatusCode::OK);
+        }
+    }
+
+    #[actix_web::test]
+    async fn handlers_charge_their_extra_work() {
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(enforce))
+                .app_data(web::Data::new(Limits::new(&config())))
+                .route("/zkp/jobs", web::post().to(|limits: web::Data<Limits>, req: HttpRequest| async move {
+                    limits.charge(&req, Budget::Prove, 1).map_or_else(|resp| resp, |()| HttpResponse::Accepted().finish())
+                })),
+        )
+        .await;
+        // Two tokens a minute: the first request pays both.
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/jobs").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::ACCEPTED);
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/jobs").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(resp.headers().contains_key(RETRY_AFTER));
+    }
+
+    #[actix_web::test]
+    async fn proof_generation_bodies_are_capped() {
+        let app = init_service(
+            App::new()
+                .wrap(from_fn(enforce))
+                .app_data(web::Data::new(Limits::new(&config())))
+                .route("/zkp/generate", web::post().to(|body: Bytes| async move { HttpResponse::Ok().body(body) }))
+                .route("/zkp/verify", web::post().to(|body: Bytes| async move { HttpResponse::Ok().body(body) })),
+        )
+        .await;
+        let big = "x".repeat(2048);
+
+        let declared = TestRequest::post().uri("/zkp/generate").insert_header((CONTENT_LENGTH, big.len())).set_payload(big.clone());
+        let resp = call_service(&app, declared.to_request()).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body: Value = read_body_json(resp).await;
+        assert_eq!(body["code"], "payload_too_large");
+
+        // Without a Content-Length the body is cut off while it is read.
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/generate").set_payload(big.clone()).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        let resp = call_service(&app, TestRequest::post().uri("/zkp/verify").set_payload(big).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,256 @@
+//! Prometheus metrics, served at `GET /metrics`.
+//!
+//! [`track`] runs every request with an empty [`Sample`] that the proof
+//! helpers fill in as they go: `offload` notes the time spent on the proof
+//! pool, [`super::telemetry::record_proof`] the scheme and proof size, and
+//! `verify_response` the verification outcome. When the response is ready
+//! the sample becomes observations labelled by endpoint (the route pattern,
+//! never the raw path) and scheme. Pool and job gauges are read at scrape
+//! time.
+
+use std::cell::RefCell;
+use std::future::Future;
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+use actix_web::http::StatusCode;
+use prometheus::core::Collector;
+use prometheus::proto::MetricFamily;
+use prometheus::{exponential_buckets, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+
+use super::proof_pool::PoolStatus;
+
+/// Seconds; proofs take from about a millisecond (BP⁺ verification) to
+/// several seconds (large aggregations on a busy pool).
+const DURATION_BUCKETS: &[f64] = &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Label for requests that matched no route, so unknown paths cannot blow
+/// up the label space.
+const UNMATCHED: &str = "unmatched";
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+tokio::task_local! {
+    static SAMPLE: RefCell<Sample>;
+}
+
+/// What the handler of one request reported about its proof.
+#[derive(Debug, Default)]
+struct Sample {
+    scheme: Option<&'static str>,
+    proof_size: Option<usize>,
+    proof_time: Option<Duration>,
+    /// Set by verifications only: `verified`, `invalid` or a `ZkError` code.
+    outcome: Option<&'static str>,
+}
+
+struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    request_duration: HistogramVec,
+    proof_duration: HistogramVec,
+    proof_size: HistogramVec,
+    verifications: IntCounterVec,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let requests = IntCounterVec::new(
+            Opts::new("zkp_http_requests_total", "HTTP requests by endpoint, method and status."),
+            &["endpoint", "method", "status"],
+        )
+        .unwrap();
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new("zkp_http_request_duration_seconds", "Time to answer an HTTP request.")
+                .buckets(DURATION_BUCKETS.to_vec()),
+            &["endpoint", "method"],
+        )
+        .unwrap();
+        let proof_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "zkp_proof_duration_seconds",
+                "Time spent on the proof pool, queueing included, per proof generation or verification.",
+            )
+            .buckets(DURATION_BUCKETS.to_vec()),
+            &["endpoint", "scheme", "operation"],
+        )
+        .unwrap();
+        let proof_size = HistogramVec::new(
+            HistogramOpts::new("zkp_proof_size_bytes", "Size of generated and verified proofs.")
+                .buckets(exponential_buckets(128.0, 2.0, 10).unwrap()),
+            &["scheme", "operation"],
+        )
+        .unwrap();
+        let verifications = IntCounterVec::new(
+            Opts::new(
+                "zkp_verifications_total",
+                "Verification outcomes: verified, invalid, or the error code that stopped verification.",
+            ),
+            &["endpoint", "scheme", "outcome"],
+        )
+        .unwrap();
+
+        let registry = Registry::new();
+        for collector in [
+            Box::new(requests.clone()) as Box<dyn Collector>,
+            Box::new(request_duration.clone()),
+            Box::new(proof_duration.clone()),
+            Box::new(proof_size.clone()),
+            Box::new(verifications.clone()),
+        ] {
+            registry.register(collector).expect("metric names are unique");
+        }
+        Self { registry, requests, request_duration, proof_duration, proof_size, verifications }
+    }
+
+    fn observe(&self, endpoint: &str, method: &str, status: StatusCode, elapsed: Duration, sample: Sample) {
+        self.requests.with_label_values(&[endpoint, method, status.as_str()]).inc();
+        self.request_duration.with_label_values(&[endpoint, method]).observe(elapsed.as_secs_f64());
+        let Some(scheme) = sample.scheme else { return };
+        let operation = if sample.outcome.is_some() { "verify" } else { "prove" };
+        if let Some(time) = sample.proof_time {
+            self.proof_duration.with_label_values(&[endpoint, scheme, operation]).observe(time.as_secs_f64());
+        }
+        if let Some(size) = sample.proof_size {
+            self.proof_size.with_label_values(&[scheme, operation]).observe(size as f64);
+        }
+        if let Some(outcome) = sample.outcome {
+            self.verifications.with_label_values(&[endpoint, scheme, outcome]).inc();
+        }
+    }
+}
+
+fn with_sample(f: impl FnOnce(&mut Sample)) {
+    // Outside a tracked request (tests, background jobs) there is nothing to record.
+    let _ = SAMPLE.try_with(|sample| f(&mut sample.borrow_mut()));
+}
+
+/// Note the proof scheme and size of the current request.
+pub fn record_proof(scheme: &'static str, proof_size: usize) {
+    with_sample(|s| {
+        s.scheme = Some(scheme);
+        s.proof_size = Some(proof_size);
+    });
+}
+
+/// Note the time the current request spent on the proof pool.
+pub fn record_proof_time(elapsed: Duration) {
+    with_sample(|s| s.proof_time = Some(elapsed));
+}
+
+/// Note the outcome of the current request's verification.
+pub fn record_outcome(outcome: &'static str) {
+    with_sample(|s| s.outcome = Some(outcome));
+}
+
+/// Middleware (for `App::wrap_fn`) counting and timing every request and
+/// collecting what its handler reported about proofs.
+pub fn track<S, B>(req: ServiceRequest, srv: &S) -> impl Future<Output = Result<ServiceResponse<B>, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+{
+    let endpoint = req.match_pattern().unwrap_or_else(|| UNMATCHED.to_owned());
+    let method = req.method().to_string();
+    let started = Instant::now();
+    let response = srv.call(req);
+    SAMPLE.scope(RefCell::default(), async move {
+        let response = response.await;
+        let status = match &response {
+            Ok(response) => response.status(),
+            Err(e) => e.as_response_error().status_code(),
+        };
+        let sample = SAMPLE.with(RefCell::take);
+        METRICS.observe(&endpoint, &method, status, started.elapsed(), sample);
+        response
+    })
+}
+
+/// Everything in the Prometheus text format, plus the current pool and job
+/// queue state.
+pub fn render(pool: &PoolStatus, jobs_pending: Option<usize>) -> String {
+    let mut families = METRICS.registry.gather();
+    families.extend(pool_families(pool));
+    if let Some(pending) = jobs_pending {
+        families.extend(gauge("zkp_jobs_pending", "Asynchronous proof jobs queued or running.", pending));
+    }
+    TextEncoder::new().encode_to_string(&families).expect("text encoding never fails")
+}
+
+fn pool_families(status: &PoolStatus) -> Vec<MetricFamily> {
+    let mut families = Vec::new();
+    families.extend(gauge("zkp_proof_pool_workers", "Proof pool worker threads.", status.workers));
+    families.extend(gauge("zkp_proof_pool_queue_capacity", "Proof jobs that may wait for a worker.", status.queue_capacity));
+    families.extend(gauge("zkp_proof_pool_queued", "Proof jobs waiting for a worker.", status.queued));
+    families.extend(gauge("zkp_proof_pool_active", "Proof jobs running.", status.active));
+    families.extend(counter("zkp_proof_pool_completed_total", "Proof jobs run to completion.", status.completed));
+    families.extend(counter("zkp_proof_pool_rejected_total", "Proof jobs turned away because the pool was saturated.", status.rejected));
+    families
+}
+
+fn gauge(name: &str, help: &str, value: usize) -> Vec<MetricFamily> {
+    let gauge = IntGauge::new(name, help).unwrap();
+    gauge.set(value as i64);
+    gauge.collect()
+}
+
+fn counter(name: &str, help: &str, value: u64) -> Vec<MetricFamily> {
+    let counter = IntCounter::new(name, help).unwrap();
+    counter.inc_by(value);
+    counter.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+
+    fn pool_status() -> PoolStatus {
+        PoolStatus { workers: 2, queue_capacity: 16, queued: 3, active: 2, completed: 40, rejected: 1, saturated: false }
+    }
+
+    #[actix_web::test]
+    async fn requests_are_labelled_by_route_and_scheme() {
+        let app = init_service(
+            App::new()
+                .wrap_fn(track)
+                .route(
+                    "/metrics-test/{id}/verify",
+                    web::post().to(|| async {
+                        record_proof("bulletproofs_plus", 700);
+                        record_proof_time(Duration::from_millis(3));
+                        record_outcome("invalid");
+                        HttpResponse::Ok().finish()
+                    }),
+                ),
+        )
+        .await;
+        for id in ["a", "b"] {
+            let req = TestRequest::post().uri(&format!("/metrics-test/{id}/verify")).to_request();
+            assert!(call_service(&app, req).await.status().is_success());
+        }
+        call_service(&app, TestRequest::get().uri("/metrics-test/nowhere").to_request()).await;
+
+        let text = render(&pool_status(), Some(5));
+        for line in [
+            r#"zkp_http_requests_total{endpoint="/metrics-test/{id}/verify",method="POST",status="200"} 2"#,
+            r#"zkp_verifications_total{endpoint="/metrics-test/{id}/verify",outcome="invalid",scheme="bulletproofs_plus"} 2"#,
+            r#"zkp_proof_size_bytes_count{operation="verify",scheme="bulletproofs_plus"} 2"#,
+            r#"zkp_proof_duration_seconds_bucket{endpoint="/metrics-test/{id}/verify",operation="verify",scheme="bulletproofs_plus",le="0.005"} 2"#,
+            "zkp_proof_pool_queued 3",
+            "zkp_proof_pool_rejected_total 1",
+            "zkp_jobs_pending 5",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {line} in\n{text}");
+        }
+        assert!(text.contains(r#"endpoint="unmatched",method="GET",status="404""#), "{text}");
+        assert!(!text.contains("nowhere"), "{text}");
+    }
+
+    #[test]
+    fn recording_outside_a_request_is_a_no_op() {
+        record_proof("bulletproofs", 1);
+        record_outcome("verified");
+    }
+}
@@ -0,0 +1,2294 @@
+//! Actix‑web server exposing classic Bulletproof (BP) **and** Bulletproofs‑Plus (BP⁺) endpoints.
+//!
+//! `main.rs` loads the [`Config`] and runs [`start`]; tests (and the client
+//! crate's) start it in process on port 0.
+
+use crate::zk;
+
+use actix_cors::Cors;
+use actix_web::{dev::{Server, Service}, get, http::StatusCode, middleware, post, web, App, HttpMessage, HttpRequest, HttpResponse, HttpServer, Responder};
+use curve25519_dalek_ng::{scalar::Scalar, ristretto::CompressedRistretto as NgCompressed}; // Dalek‑NG (classic BP)
+use hex::{decode as hex_decode, FromHex, ToHex};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, TryLockError};
+use std::time::{Duration, Instant};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use tracing::{debug, error, info, warn, Span};
+
+// ─── Local circuits ────────────────────────────────────────────────────────
+use zk::bp_plus_pedersen::{prove_txid_commitment as prove_plus, verify_txid_commitment as verify_plus};
+use zk::txid_pedersen_proof::{prove_txid_commitment, prove_txid_commitment_from_hex_with_binding, verify_txid_commitment, verify_txid_commitment_with_binding};
+use zk::pedersen::{prove_value_commitment, prove_value_commitment_with_blinding, prove_value_commitment_with_binding, verify_value_commitment, verify_value_commitment_with_binding};
+use zk::aggregated_range_proof::{verify_aggregated_range_proof, AggregationSession, SessionStatus, MAX_PARTIES};
+use bulletproofs::range_proof_mpc::messages::{BitCommitment, PolyCommitment, ProofShare};
+use zk::commitment_ops::{add_blindings, add_commitments, scale_blinding, scale_commitment, sub_blindings, sub_commitments};
+use zk::r1cs_statement::{prove_statement, random_blinding, verify_statement, Opening, Statement, StatementError, MAX_VARIABLES};
+use zk::bbs_plus::{self, Presentation};
+mod auth;
+pub mod config;
+mod credentials;
+mod health;
+mod issued;
+mod jobs;
+mod limits;
+mod metrics;
+mod proof_pool;
+mod replay;
+mod revocation;
+pub mod telemetry;
+mod tls;
+mod v1;
+
+use auth::Auth;
+use limits::{Budget, Limits};
+pub use config::Config;
+use config::EndpointGroup;
+use crate::api::{check_count, check_proof_len, hex32, ReplayReport, VerifyResponse, TX_HASH_LIMBS};
+use auth::SignedEscrow;
+use issued::{Issue, IssueContext, IssuedStore};
+use replay::ReplayRegistry;
+use jobs::{JobManager, JobRequest, SubmitError, Submitter};
+use proof_pool::{PoolError, ProofPool};
+use zk::generators::{GeneratorKey, GeneratorRegistry, DEFAULT_SHAPES};
+use zk::ZkError;
+use zk::revocation_accumulator::{
+    blinding_from_bytes, blinding_to_bytes, commitment_from_bytes, commitment_to_bytes, handle_element, prove_non_revocation, random_blinding as random_accumulator_blinding,
+    verify_non_revocation, MembershipWitness, NonRevocationProof, RevocationRegistry,
+};
+
+
+fn bad_req(msg: &str) -> HttpResponse {
+    HttpResponse::BadRequest().json(json!({ "error": msg }))
+}
+
+/// Parse an optional 32-byte `binding_tag_hex` field.
+fn parse_binding_tag(binding_tag_hex: &Option<String>) -> Result<Option<Vec<u8>>, HttpResponse> {
+    match binding_tag_hex {
+        None => Ok(None),
+        Some(binding_hex) => match hex_decode(binding_hex.trim_start_matches("0x")) {
+            Ok(b) if b.len() == 32 => Ok(Some(b)),
+            _ => Err(bad_req("invalid binding_tag_hex: must be 32 bytes (64 hex chars)")),
+        },
+    }
+}
+
+/// Map a [`ZkError`] to a stable status and a `{ "error", "code" }` body.
+fn zk_error(e: &ZkError) -> HttpResponse {
+    info!(code = e.code(), error = %e, "request rejected");
+    let (status, body) = zk_error_body(e);
+    HttpResponse::build(status).json(body)
+}
+
+fn zk_error_body(e: &ZkError) -> (StatusCode, serde_json::Value) {
+    let status = StatusCode::from_u16(e.http_status()).expect("ZkError statuses are valid");
+    let mut body = json!({ "error": e.to_string(), "code": e.code() });
+    if let ZkError::Statement(StatementError::Unsatisfied { kind, index, label }) = e {
+        body["constraint"] = json!({ "kind": kind, "index": index, "label": label });
+    }
+    (status, body)
+}
+
+/// `{ "verified": bool }` for a verification outcome. Only a proof that fails
+/// to verify is `false`; undecodable inputs are errors.
+fn verify_response(result: Result<(), ZkError>, what: &str) -> HttpResponse {
+    replayed_verify_response(result, what, None)
+}
+
+/// [`verify_response`] for a verified proof whose presentation the replay
+/// registry has already reported on.
+fn replayed_verify_response(result: Result<(), ZkError>, what: &str, replay: Option<ReplayReport>) -> HttpResponse {
+    match result {
+        Ok(()) => {
+            let replayed = replay.is_some_and(|r| !r.accepted);
+            metrics::record_outcome(if replayed { "replayed" } else { "verified" });
+            info!(verified = true, replayed, "{} verification", what);
+            HttpResponse::Ok().json(VerifyResponse { verified: true, replay })
+        },
+        Err(ZkError::TranscriptMismatch) => {
+            metrics::record_outcome("invalid");
+            info!(verified = false, "{} verification", what);
+            HttpResponse::Ok().json(VerifyResponse { verified: false, replay: None })
+        },
+        Err(e) => {
+            metrics::record_outcome(e.code());
+            zk_error(&e)
+        },
+    }
+}
+
+/// The presentation of a proof about to be verified, when the replay
+/// registry is kept. Read before verifying, so a malformed header costs no
+/// verification.
+fn presentation(
+    registry: &Option<web::Data<ReplayRegistry>>,
+    req: &HttpRequest,
+    proof: &[u8],
+    tag: Option<&[u8]>,
+) -> Result<Option<replay::Presentation>, ZkError> {
+    registry
+        .as_ref()
+        .map(|_| Ok(replay::Presentation::new(proof, tag, replay::Presentation::verifier(req.headers())?)))
+        .transpose()
+}
+
+/// [`verify_response`], reporting earlier presentations of a proof that
+/// verified. Unlike the issued record, the registry failing fails the
+/// request: a replay it cannot rule out must not pass as a first use.
+fn presented_response(
+    registry: &Option<web::Data<ReplayRegistry>>,
+    presentation: Option<replay::Presentation>,
+    result: Result<(), ZkError>,
+    what: &str,
+) -> HttpResponse {
+    match (registry, presentation) {
+        (Some(registry), Some(presentation)) if result.is_ok() => match registry.present(&presentation) {
+            Ok(report) => replayed_verify_response(result, what, Some(report)),
+            Err(e) => {
+                error!(error = %e, "recording a proof presentation failed");
+                HttpResponse::InternalServerError().json(json!({ "error": format!("replay registry: {e}"), "code": "store_error" }))
+            },
+        },
+        _ => verify_response(result, what),
+    }
+}
+
+/// Where a commitment about to be generated is issued for, when the record
+/// is kept. Read before proving, so a malformed header costs no proof.
+fn issue_context(store: &Option<web::Data<IssuedStore>>, req: &HttpRequest) -> Result<Option<IssueContext>, ZkError> {
+    store.as_ref().map(|_| IssueContext::from_headers(req.headers(), req.extensions().get::<SignedEscrow>())).transpose()
+}
+
+/// The commitment is already the caller's; a store failure is only logged.
+fn record_issue(store: &Option<web::Data<IssuedStore>>, context: Option<IssueContext>, issue: Issue) {
+    if let (Some(store), Some(context)) = (store, context) {
+        if let Err(e) = store.record(issue, &context) {
+            warn!(error = %e, "recording an issued commitment failed");
+        }
+    }
+}
+
+/// Seconds a client should wait before retrying when the proof pool is full.
+const PROOF_RETRY_AFTER_SECS: u64 = 1;
+
+/// Run proof work on the blocking proof pool. A full queue answers 503 with
+/// `Retry-After` so clients back off instead of piling up. Time spent waiting
+/// for and running the job is recorded as `proof_ms` on the request span and
+/// in its metrics sample.
+async fn offload<T, F>(pool: &ProofPool, job: F) -> Result<T, HttpResponse>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let started = Instant::now();
+    let result = pool.run(job).await;
+    let elapsed = started.elapsed();
+    Span::current().record("proof_ms", elapsed.as_millis() as u64);
+    metrics::record_proof_time(elapsed);
+    result.map_err(|e| {
+        let status = pool.status();
+        warn!(error = %e, active = status.active, queued = status.queued, queue_capacity = status.queue_capacity,
+              rejected = status.rejected, "proof job not run");
+        match e {
+            PoolError::Saturated => HttpResponse::ServiceUnavailable()
+                .insert_header(("Retry-After", PROOF_RETRY_AFTER_SECS.to_string()))
+                .json(json!({ "error": e.to_string(), "code": "pool_saturated" })),
+            PoolError::Aborted => {
+                HttpResponse::InternalServerError().json(json!({ "error": e.to_string(), "code": "proof_aborted" }))
+            },
+        }
+    })
+}
+
+// =============================================================================
+// Classic Bulletproof (R1CS) endpoints
+// =============================================================================
+
+#[derive(Deserialize)]
+struct ZkpRequest { tx_hash: String }
+
+#[derive(Serialize)]
+struct ProofResponse { commitments: Vec<String>, proof: String }
+impl ProofResponse {
+    fn new<T>(coms: &[T], proof: Vec<u8>) -> Self
+    where
+        T: ProofCommitmentBytes,
+    {
+        Self {
+            commitments: coms.iter().map(|c| hex::encode(c.commitment_bytes())).collect(),
+            proof: proof.encode_hex::<String>(),
+        }
+    }
+}
+
+trait ProofCommitmentBytes {
+    fn commitment_bytes(&self) -> &[u8];
+}
+
+impl ProofCommitmentBytes for curve25519_dalek::ristretto::CompressedRistretto {
+    fn commitment_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl ProofCommitmentBytes for [u8; 32] {
+    fn commitment_bytes(&self) -> &[u8] {
+        self.as_ref()
+    }
+}
+
+impl ProofCommitmentBytes for curve25519_dalek_ng::ristretto::CompressedRistretto {
+    fn commitment_bytes(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[post("/zkp/generate")]
+async fn generate_zkp(pool: web::Data<ProofPool>, tx: web::Json<ZkpRequest>) -> impl Responder {
+    let value = match hex32(tx.tx_hash.trim(), "tx_hash") {
+        Ok(h) => Scalar::from_bytes_mod_order(h),
+        Err(e) => return zk_error(&e),
+    };
+
+    let result = match offload(&pool, move || {
+        let (commitment, proof_bytes, _) = prove_txid_commitment(value)?;
+        verify_txid_commitment(commitment, proof_bytes.clone())?;
+        Ok((commitment, proof_bytes))
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (commitment, proof_bytes) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("bulletproofs", proof_bytes.len());
+
+    HttpResponse::Ok().json(ProofResponse::new(&[commitment.to_bytes()], proof_bytes))
+}
+
+#[derive(Deserialize)]
+struct ZkpVerifyInput { 
+    commitment: String, 
+    proof: String,
+    #[serde(default)]
+    binding_tag_hex: Option<String>, // Feature 2: Optional binding tag for linkable commitments
+}
+
+#[post("/zkp/verify")]
+async fn verify_zkp(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    input: web::Json<ZkpVerifyInput>,
+) -> impl Responder {
+    if let Err(e) = check_proof_len(&input.proof) {
+        return zk_error(&e);
+    }
+    let com_bytes: [u8; 32] = match <[u8;32]>::from_hex(&input.commitment) {
+        Ok(b) => b,
+        Err(e) => {
+            debug!("Failed to parse commitment: {:?}", e);
+            return HttpResponse::BadRequest().json(json!({"error":"bad commitment"}));
+        },
+    };
+    
+    let proof_bytes = match Vec::from_hex(&input.proof) {
+        Ok(p) => p,
+        Err(e) => {
+            debug!("Failed to parse proof: {:?}", e);
+            return HttpResponse::BadRequest().json(json!({"error":"bad proof"}));
+        },
+    };
+    
+    // Parse binding tag if provided (Feature 2: Linkable Commitment)
+    let binding_tag = if let Some(ref binding_hex) = input.binding_tag_hex {
+        debug!(binding_tag_hex = %binding_hex, "binding tag provided for verification");
+        match hex::decode(binding_hex.strip_prefix("0x").unwrap_or(binding_hex)) {
+            Ok(bytes) if bytes.len() == 32 => {
+                Some(bytes)
+            },
+            Ok(bytes) => {
+                debug!("Invalid binding tag length: expected 32 bytes, got {}", bytes.len());
+                return HttpResponse::BadRequest().json(json!({
+                    "error": "invalid binding_tag_hex: must be 32 bytes (64 hex chars)"
+                }));
+            },
+            Err(e) => {
+                debug!("Failed to decode binding tag hex: {:?}", e);
+                return HttpResponse::BadRequest().json(json!({
+                    "error": "invalid binding_tag_hex: must be valid hex"
+                }));
+            },
+        }
+    } else {
+        None
+    };
+    
+    let presentation = match presentation(&registry, &http, &proof_bytes, binding_tag.as_deref()) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    // Verify with optional binding tag
+    telemetry::record_proof("bulletproofs", proof_bytes.len());
+    let result = match offload(&pool, move || {
+        verify_txid_commitment_with_binding(NgCompressed(com_bytes), proof_bytes, binding_tag.as_deref())
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "TX hash commitment")
+}
+
+// =============================================================================
+// Transaction Hash Commitment endpoints (for privacy)
+// =============================================================================
+
+#[derive(Deserialize)]
+struct TxHashCommitRequest { 
+    tx_hash: String,
+    #[serde(default)]
+    binding_tag_hex: Option<String>, // Feature 2: Optional binding tag for linkable commitments
+}
+
+#[derive(Serialize)]
+struct TxHashCommitResponse {
+    commitment: String,
+    proof: String,
+    verified: bool,
+}
+
+/// Generate a Pedersen commitment to a transaction hash
+/// This hides the transaction hash in the VC, making it harder to link to Etherscan
+/// Feature 2: Supports optional binding_tag_hex to link purchase and delivery TX commitments
+#[post("/zkp/commit-tx-hash")]
+async fn commit_tx_hash(
+    pool: web::Data<ProofPool>,
+    store: Option<web::Data<IssuedStore>>,
+    http: HttpRequest,
+    req: web::Json<TxHashCommitRequest>,
+) -> impl Responder {
+    let tx_hash = req.tx_hash.trim();
+    debug!(tx_hash = %tx_hash, "TX hash commitment requested");
+    
+    // Validate hex format
+    let tx_hash_clean = tx_hash.strip_prefix("0x").unwrap_or(tx_hash);
+    if tx_hash_clean.len() != 64 {
+        debug!("Invalid tx_hash format: expected 64 hex chars, got {}", tx_hash_clean.len());
+        return HttpResponse::BadRequest().json(json!({
+            "error": "Invalid tx_hash format. Expected 64 hex characters (32 bytes)"
+        }));
+    }
+    
+    // Parse binding tag if provided (Feature 2: Linkable Commitment)
+    let binding_tag = if let Some(ref binding_hex) = req.binding_tag_hex {
+        debug!(binding_tag_hex = %binding_hex, "binding tag provided");
+        match hex::decode(binding_hex.strip_prefix("0x").unwrap_or(binding_hex)) {
+            Ok(bytes) if bytes.len() == 32 => {
+                Some(bytes)
+            },
+            Ok(bytes) => {
+                debug!("Invalid binding tag length: expected 32 bytes, got {}", bytes.len());
+                return HttpResponse::BadRequest().json(json!({
+                    "error": "invalid binding_tag_hex: must be 32 bytes (64 hex chars)"
+                }));
+            },
+            Err(e) => {
+                debug!("Failed to decode binding tag hex: {:?}", e);
+                return HttpResponse::BadRequest().json(json!({
+                    "error": "invalid binding_tag_hex: must be valid hex"
+                }));
+            },
+        }
+    } else {
+        None
+    };
+    
+    let context = match issue_context(&store, &http) {
+        Ok(c) => c,
+        Err(e) => return zk_error(&e),
+    };
+    
+    // Generate commitment with optional binding tag
+    let tx_hash = tx_hash.to_owned();
+    let tag = binding_tag.as_deref().map(|t| <[u8; 32]>::try_from(t).expect("checked to be 32 bytes"));
+    let result = match offload(&pool, move || {
+        prove_txid_commitment_from_hex_with_binding(&tx_hash, binding_tag.as_deref())
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (commitment, proof_bytes, verified) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    
+    telemetry::record_proof("bulletproofs", proof_bytes.len());
+    
+    let response = TxHashCommitResponse {
+        commitment: hex::encode(commitment.as_bytes()),
+        proof: hex::encode(&proof_bytes),
+        verified,
+    };
+    let issue = Issue {
+        kind: "tx_hash_commitment",
+        scheme: "bulletproofs",
+        commitments: vec![response.commitment.clone()],
+        proof: response.proof.clone(),
+        binding_tag: tag,
+    };
+    record_issue(&store, context, issue);
+    HttpResponse::Ok().json(response)
+}
+
+// =============================================================================
+// Bulletproofs‑Plus endpoints (64‑bit range proof)
+// =============================================================================
+
+#[derive(Deserialize)]
+struct TxHashPayload { tx_hash: String }
+
+#[post("/zkp/prove_plus")]
+async fn prove_plus_ep(pool: web::Data<ProofPool>, payload: web::Json<TxHashPayload>) -> impl Responder {
+    // Exactly 32 bytes: longer input used to be silently truncated.
+    let arr = match hex32(payload.tx_hash.trim(), "tx_hash") {
+        Ok(h) => h,
+        Err(e) => return zk_error(&e),
+    };
+    let result = match offload(&pool, move || prove_plus(arr)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (coms, proof) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("bulletproofs_plus", proof.len());
+    HttpResponse::Ok().json(ProofResponse::new(&coms, proof))
+}
+
+#[derive(Deserialize)]
+struct ProofVerifyPayload {
+    commitments: Vec<String>,
+    proof: String,
+}
+
+#[post("/zkp/verify_plus")]
+async fn verify_plus_ep(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    payload: web::Json<ProofVerifyPayload>,
+) -> impl Responder {
+    use curve25519_dalek::ristretto::CompressedRistretto;
+    if let Err(e) = check_count("commitments", payload.commitments.len(), TX_HASH_LIMBS..=TX_HASH_LIMBS).and_then(|()| check_proof_len(&payload.proof)) {
+        return zk_error(&e);
+    }
+    let commitments: Result<Vec<_>, _> = payload.commitments.iter()
+        .map(|hex| <[u8;32]>::from_hex(hex).map(CompressedRistretto))
+        .collect();
+    let commitments = match commitments {
+        Ok(c) => c,
+        Err(_) => {
+            debug!("Failed to parse commitments");
+            return HttpResponse::BadRequest().json(json!({"error":"bad commitments"}));
+        },
+    };
+    let proof = match Vec::from_hex(&payload.proof) {
+        Ok(p) => p,
+        _ => {
+            debug!("Failed to parse proof");
+            return HttpResponse::BadRequest().json(json!({"error":"bad proof"}));
+        },
+    };
+    let presentation = match presentation(&registry, &http, &proof, None) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("bulletproofs_plus", proof.len());
+    let result = match offload(&pool, move || verify_plus(commitments, proof)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "BP+")
+}
+
+#[post("/zkp/generate_bp4")]
+async fn generate_bp4(pool: web::Data<ProofPool>, tx: web::Json<ZkpRequest>) -> impl Responder {
+    let arr = match hex32(tx.tx_hash.trim(), "tx_hash") {
+        Ok(h) => h,
+        Err(e) => return zk_error(&e),
+    };
+    let result = match offload(&pool, move || zk::txid_pedersen_proof::prove_txid_commitment_4limb(arr)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (commitments, proof_bytes, verified) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    if !verified {
+        warn!("4-limb proof failed its own verification");
+        return HttpResponse::InternalServerError().json(json!({"error":"proof failed"}));
+    }
+    telemetry::record_proof("r1cs_4limb", proof_bytes.len());
+    HttpResponse::Ok().json(ProofResponse::new(&commitments, proof_bytes))
+}
+
+#[derive(Deserialize)]
+struct BP4VerifyInput {
+    commitments: Vec<String>,
+    proof: String,
+}
+
+#[post("/zkp/verify_bp4")]
+async fn verify_bp4(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    input: web::Json<BP4VerifyInput>,
+) -> impl Responder {
+    use curve25519_dalek_ng::ristretto::CompressedRistretto;
+    if let Err(e) = check_count("commitments", input.commitments.len(), TX_HASH_LIMBS..=TX_HASH_LIMBS).and_then(|()| check_proof_len(&input.proof)) {
+        return zk_error(&e);
+    }
+    let commitments: Result<Vec<_>, _> = input.commitments.iter()
+        .map(|hex| <[u8;32]>::from_hex(hex).map(CompressedRistretto))
+        .collect();
+    let commitments = match commitments {
+        Ok(c) => c,
+        Err(_) => {
+            debug!("Failed to parse commitments");
+            return HttpResponse::BadRequest().json(json!({"error":"bad commitments"}));
+        },
+    };
+    let proof = match Vec::from_hex(&input.proof) {
+        Ok(p) => p,
+        _ => {
+            debug!("Failed to parse proof");
+            return HttpResponse::BadRequest().json(json!({"error":"bad proof"}));
+        },
+    };
+    let presentation = match presentation(&registry, &http, &proof, None) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("r1cs_4limb", proof.len());
+    let result = match offload(&pool, move || zk::txid_pedersen_proof::verify_txid_commitment_4limb(&commitments, &proof)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "4-limb")
+}
+
+// =============================================================================
+// Server bootstrap
+// =============================================================================
+
+#[derive(Deserialize)]
+struct ValueCommitRequest { value: u64 }
+
+#[derive(Serialize)]
+struct ValueCommitResponse { commitment: String, proof: String }
+
+#[post("/zkp/commit-value")]
+async fn commit_value(
+    pool: web::Data<ProofPool>,
+    store: Option<web::Data<IssuedStore>>,
+    http: HttpRequest,
+    req: web::Json<ValueCommitRequest>,
+) -> impl Responder {
+    debug!(value = req.value, "value commitment requested");
+    let context = match issue_context(&store, &http) {
+        Ok(c) => c,
+        Err(e) => return zk_error(&e),
+    };
+    let value = req.value;
+    let result = match offload(&pool, move || prove_value_commitment(value)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (commitment, proof_bytes, _verified) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    let commitment_hex = hex::encode(commitment.as_bytes());
+    let proof_len = proof_bytes.len();
+    let proof_hex = hex::encode(&proof_bytes);
+    telemetry::record_proof("bulletproofs", proof_len);
+    let issue = Issue {
+        kind: "value_commitment",
+        scheme: "bulletproofs",
+        commitments: vec![commitment_hex.clone()],
+        proof: proof_hex.clone(),
+        binding_tag: None,
+    };
+    record_issue(&store, context, issue);
+    HttpResponse::Ok().json(ValueCommitResponse {
+        commitment: commitment_hex,
+        proof: proof_hex,
+    })
+}
+
+#[derive(Deserialize)]
+struct ValueVerifyRequest { commitment: String, proof: String }
+
+#[post("/zkp/verify-value")]
+async fn verify_value(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    req: web::Json<ValueVerifyRequest>,
+) -> impl Responder {
+    if let Err(e) = check_proof_len(&req.proof) {
+        return zk_error(&e);
+    }
+    let com_bytes = match <[u8;32]>::from_hex(&req.commitment) {
+        Ok(b) => b,
+        Err(_) => {
+            debug!("Failed to parse commitment");
+            return HttpResponse::BadRequest().json(json!({"error":"bad commitment"}));
+        },
+    };
+    let proof_bytes = match Vec::from_hex(&req.proof) {
+        Ok(p) => p,
+        Err(_) => {
+            debug!("Failed to parse proof");
+            return HttpResponse::BadRequest().json(json!({"error":"bad proof"}));
+        },
+    };
+    let presentation = match presentation(&registry, &http, &proof_bytes, None) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("bulletproofs", proof_bytes.len());
+    let result = match offload(&pool, move || verify_value_commitment(NgCompressed(com_bytes), proof_bytes)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "Value")
+}
+
+#[derive(Deserialize)]
+struct ValueCommitmentRequest {
+    value: u64,
+}
+
+#[derive(Serialize)]
+struct ValueCommitmentResponse {
+    commitment: String, // hex-encoded
+    proof: String,      // hex-encoded
+    verified: bool,
+}
+
+#[post("/zkp/generate-value-commitment")]
+async fn generate_value_commitment(pool: web::Data<ProofPool>, req: web::Json<ValueCommitmentRequest>) -> impl Responder {
+    debug!(value = req.value, "value commitment requested");
+    let value = req.value;
+    let result = match offload(&pool, move || prove_value_commitment(value)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (commitment, proof_bytes, verified) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    let proof_len = proof_bytes.len();
+    telemetry::record_proof("bulletproofs", proof_len);
+    HttpResponse::Ok().json(ValueCommitmentResponse {
+        commitment: hex::encode(commitment.as_bytes()),
+        proof: hex::encode(proof_bytes),
+        verified,
+    })
+}
+
+#[derive(Deserialize)]
+struct ValueCommitmentWithBlindingRequest {
+    value: u64,
+    blinding_hex: String, // 32-byte hex string (64 hex chars)
+}
+
+#[post("/zkp/generate-value-commitment-with-blinding")]
+async fn generate_value_commitment_with_blinding_ep(
+    pool: web::Data<ProofPool>,
+    store: Option<web::Data<IssuedStore>>,
+    http: HttpRequest,
+    req: web::Json<ValueCommitmentWithBlindingRequest>,
+) -> impl Responder {
+    debug!(value = req.value, "value commitment with blinding requested");
+    // Parse blinding factor from hex string
+    let blinding_bytes = match hex_decode(req.blinding_hex.trim_start_matches("0x")) {
+        Ok(b) if b.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&b[..32]);
+            arr
+        },
+        _ => {
+            debug!("Invalid blinding_hex format");
+            return HttpResponse::BadRequest().json(json!({ "error": "invalid blinding_hex: must be 32 bytes (64 hex chars)" }));
+        },
+    };
+    
+    let blinding = Scalar::from_bytes_mod_order(blinding_bytes);
+    let context = match issue_context(&store, &http) {
+        Ok(c) => c,
+        Err(e) => return zk_error(&e),
+    };
+    
+    let value = req.value;
+    let result = match offload(&pool, move || prove_value_commitment_with_blinding(value, blinding)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (commitment, proof_bytes, verified) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    let proof_len = proof_bytes.len();
+    telemetry::record_proof("bulletproofs", proof_len);
+    let response = ValueCommitmentResponse {
+        commitment: hex::encode(commitment.as_bytes()),
+        proof: hex::encode(proof_bytes),
+        verified,
+    };
+    let issue = Issue {
+        kind: "value_commitment",
+        scheme: "bulletproofs",
+        commitments: vec![response.commitment.clone()],
+        proof: response.proof.clone(),
+        binding_tag: None,
+    };
+    record_issue(&store, context, issue);
+    HttpResponse::Ok().json(response)
+}
+
+#[derive(Deserialize)]
+struct ValueCommitmentWithBindingRequest {
+    value: u64,
+    blinding_hex: String, // 32-byte hex string (64 hex chars)
+    binding_tag_hex: Option<String>, // Optional 32-byte hex string (64 hex chars)
+}
+
+#[post("/zkp/generate-value-commitment-with-binding")]
+async fn generate_value_commitment_with_binding_ep(
+    pool: web::Data<ProofPool>,
+    store: Option<web::Data<IssuedStore>>,
+    http: HttpRequest,
+    req: web::Json<ValueCommitmentWithBindingRequest>,
+) -> impl Responder {
+    debug!(value = req.value, "value commitment with binding requested");
+    // Parse blinding factor from hex string
+    let blinding_bytes = match hex_decode(req.blinding_hex.trim_start_matches("0x")) {
+        Ok(b) if b.len() == 32 => {
+            let mut arr = [0u8; 32];
+            arr.copy_from_slice(&b[..32]);
+            arr
+        },
+        _ => {
+            debug!("Invalid blinding_hex format");
+            return HttpResponse::BadRequest().json(json!({ "error": "invalid blinding_hex: must be 32 bytes (64 hex chars)" }));
+        },
+    };
+    
+    let blinding = Scalar::from_bytes_mod_order(blinding_bytes);
+    
+    // Parse binding tag if provided
+    let binding_tag = if let Some(ref binding_hex) = req.binding_tag_hex {
+        match hex_decode(binding_hex.trim_start_matches("0x")) {
+            Ok(b) if b.len() == 32 => {
+                Some(b)
+            },
+            _ => {
+                debug!("Invalid binding_tag_hex format");
+                return HttpResponse::BadRequest().json(json!({ "error": "invalid binding_tag_hex: must be 32 bytes (64 hex chars)" }));
+            },
+        }
+    } else {
+        None
+    };
+    let context = match issue_context(&store, &http) {
+        Ok(c) => c,
+        Err(e) => return zk_error(&e),
+    };
+    
+    let value = req.value;
+    let tag = binding_tag.as_deref().map(|t| <[u8; 32]>::try_from(t).expect("checked to be 32 bytes"));
+    let result = match offload(&pool, move || prove_value_commitment_with_binding(value, blinding, binding_tag.as_deref())).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (commitment, proof_bytes, verified) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    let proof_len = proof_bytes.len();
+    telemetry::record_proof("bulletproofs", proof_len);
+    let response = ValueCommitmentResponse {
+        commitment: hex::encode(commitment.as_bytes()),
+        proof: hex::encode(proof_bytes),
+        verified,
+    };
+    let issue = Issue {
+        kind: "value_commitment",
+        scheme: "bulletproofs",
+        commitments: vec![response.commitment.clone()],
+        proof: response.proof.clone(),
+        binding_tag: tag,
+    };
+    record_issue(&store, context, issue);
+    HttpResponse::Ok().json(response)
+}
+
+#[derive(Deserialize)]
+struct ValueVerifyInput {
+    commitment: String, // hex
+    proof: String,      // hex
+    binding_tag_hex: Option<String>, // Optional 32-byte hex string (64 hex chars)
+}
+
+#[post("/zkp/verify-value-commitment")]
+async fn verify_value_commitment_ep(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    input: web::Json<ValueVerifyInput>,
+) -> impl Responder {
+    if let Err(e) = check_proof_len(&input.proof) {
+        return zk_error(&e);
+    }
+    let com_bytes = match <[u8;32]>::from_hex(&input.commitment) {
+        Ok(b) => b,
+        Err(_) => {
+            debug!("Failed to parse commitment");
+            return HttpResponse::BadRequest().json(json!({ "error": "bad commitment" }));
+        },
+    };
+    let proof_bytes = match Vec::from_hex(&input.proof) {
+        Ok(p) => p,
+        Err(_) => {
+            debug!("Failed to parse proof");
+            return HttpResponse::BadRequest().json(json!({ "error": "bad proof" }));
+        },
+    };
+    
+    // Parse binding tag if provided
+    let binding_tag = if let Some(ref binding_hex) = input.binding_tag_hex {
+        match hex_decode(binding_hex.trim_start_matches("0x")) {
+            Ok(b) if b.len() == 32 => {
+                Some(b)
+            },
+            _ => {
+                debug!("Invalid binding tag format");
+                return HttpResponse::BadRequest().json(json!({ "error": "invalid binding_tag_hex: must be 32 bytes (64 hex chars)" }));
+            },
+        }
+    } else {
+        None
+    };
+    
+    let presentation = match presentation(&registry, &http, &proof_bytes, binding_tag.as_deref()) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("bulletproofs", proof_bytes.len());
+    let result = match offload(&pool, move || {
+        verify_value_commitment_with_binding(NgCompressed(com_bytes), proof_bytes, binding_tag.as_deref())
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "Value commitment")
+}
+
+// =============================================================================
+// Multi-party aggregated range proof (dealer side of the BP MPC protocol)
+// =============================================================================
+
+/// Sessions older than this are dropped when a new one is opened.
+const AGGREGATION_SESSION_TTL: Duration = Duration::from_secs(15 * 60);
+const MAX_AGGREGATION_SESSIONS: usize = 1024;
+
+/// Each session has its own lock so a party's proving work never stalls the other sessions.
+type AggregationSessions = Mutex<HashMap<String, Arc<Mutex<AggregationSession>>>>;
+
+#[derive(Deserialize)]
+struct AggregationSessionRequest {
+    parties: usize,
+    #[serde(default = "default_bit_range")]
+    bit_range: usize,
+    #[serde(default)]
+    binding_tag_hex: Option<String>,
+}
+
+fn default_bit_range() -> usize { 64 }
+
+#[derive(Serialize)]
+struct AggregationSessionResponse {
+    session_id: String,
+    #[serde(flatten)]
+    status: SessionStatus,
+}
+
+#[derive(Deserialize)]
+struct BitCommitmentSubmission { party: usize, bit_commitment: BitCommitment }
+
+#[derive(Deserialize)]
+struct PolyCommitmentSubmission { party: usize, poly_commitment: PolyCommitment }
+
+#[derive(Deserialize)]
+struct ProofShareSubmission { party: usize, proof_share: ProofShare }
+
+fn session_response(session_id: &str, session: &AggregationSession) -> HttpResponse {
+    status_response(session_id, session.status())
+}
+
+fn status_response(session_id: &str, status: SessionStatus) -> HttpResponse {
+    HttpResponse::Ok().json(AggregationSessionResponse { session_id: session_id.to_string(), status })
+}
+
+fn session_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(json!({ "error": "unknown aggregation session" }))
+}
+
+/// Open an aggregation session. Each party then submits its messages round by round
+/// and polls the session for the dealer's challenges.
+#[post("/zkp/aggregate/sessions")]
+async fn create_aggregation_session(
+    sessions: web::Data<AggregationSessions>,
+    req: web::Json<AggregationSessionRequest>,
+) -> impl Responder {
+    debug!(parties = req.parties, bit_range = req.bit_range, "opening aggregation session");
+    let binding_tag = match parse_binding_tag(&req.binding_tag_hex) {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let session = match AggregationSession::new(req.parties, req.bit_range, binding_tag.as_deref()) {
+        Ok(s) => s,
+        Err(e) => return zk_error(&e),
+    };
+
+    let mut sessions = sessions.lock().unwrap();
+    // A session busy with a submission is in use, so it is kept.
+    sessions.retain(|_, s| match s.try_lock() {
+        Ok(s) => s.created_at().elapsed() < AGGREGATION_SESSION_TTL,
+        Err(TryLockError::Poisoned(_)) => false,
+        Err(TryLockError::WouldBlock) => true,
+    });
+    if sessions.len() >= MAX_AGGREGATION_SESSIONS {
+        warn!("too many open aggregation sessions");
+        return HttpResponse::ServiceUnavailable().json(json!({ "error": "too many open aggregation sessions" }));
+    }
+    let mut id = [0u8; 16];
+    rand::RngCore::fill_bytes(&mut OsRng, &mut id);
+    let session_id = hex::encode(id);
+    let resp = session_response(&session_id, &session);
+    sessions.insert(session_id.clone(), Arc::new(Mutex::new(session)));
+    info!(session = %session_id, "aggregation session opened");
+    resp
+}
+
+#[get("/zkp/aggregate/sessions/{id}")]
+async fn get_aggregation_session(
+    sessions: web::Data<AggregationSessions>,
+    id: web::Path<String>,
+) -> impl Responder {
+    let Some(session) = sessions.lock().unwrap().get(id.as_str()).cloned() else {
+        return session_not_found();
+    };
+    let session = session.lock().unwrap();
+    session_response(&id, &session)
+}
+
+/// Apply one party message to a session and answer with the updated status.
+/// The dealer's checks and the padding parties' proving run on the proof pool,
+/// holding only the session's own lock.
+async fn submit_to_session<T>(
+    pool: &ProofPool,
+    sessions: &AggregationSessions,
+    id: &str,
+    submit: impl FnOnce(&mut AggregationSession) -> Result<T, ZkError> + Send + 'static,
+) -> HttpResponse {
+    let Some(session) = sessions.lock().unwrap().get(id).cloned() else {
+        return session_not_found();
+    };
+    let submitted = offload(pool, move || {
+        let mut session = session.lock().unwrap();
+        let result = submit(&mut session).map(drop);
+        (result, session.status())
+    })
+    .await;
+    match submitted {
+        Ok((Ok(()), status)) => {
+            debug!(session = %id, phase = ?status.phase, "aggregation session advanced");
+            status_response(id, status)
+        },
+        Ok((Err(e), status)) => {
+            info!(session = %id, error = %e, "aggregation message rejected");
+            let (code, mut body) = zk_error_body(&e);
+            body["phase"] = json!(status.phase);
+            HttpResponse::build(code).json(body)
+        },
+        Err(resp) => resp,
+    }
+}
+
+#[post("/zkp/aggregate/sessions/{id}/bit-commitment")]
+async fn submit_bit_commitment(
+    pool: web::Data<ProofPool>,
+    sessions: web::Data<AggregationSessions>,
+    id: web::Path<String>,
+    msg: web::Json<BitCommitmentSubmission>,
+) -> impl Responder {
+    debug!(session = %id, party = msg.party, "bit commitment received");
+    let msg = msg.into_inner();
+    submit_to_session(&pool, &sessions, &id, move |s| s.submit_bit_commitment(msg.party, msg.bit_commitment)).await
+}
+
+#[post("/zkp/aggregate/sessions/{id}/poly-commitment")]
+async fn submit_poly_commitment(
+    pool: web::Data<ProofPool>,
+    sessions: web::Data<AggregationSessions>,
+    id: web::Path<String>,
+    msg: web::Json<PolyCommitmentSubmission>,
+) -> impl Responder {
+    debug!(session = %id, party = msg.party, "poly commitment received");
+    let msg = msg.into_inner();
+    submit_to_session(&pool, &sessions, &id, move |s| s.submit_poly_commitment(msg.party, msg.poly_commitment)).await
+}
+
+#[post("/zkp/aggregate/sessions/{id}/proof-share")]
+async fn submit_proof_share(
+    pool: web::Data<ProofPool>,
+    sessions: web::Data<AggregationSessions>,
+    id: web::Path<String>,
+    msg: web::Json<ProofShareSubmission>,
+) -> impl Responder {
+    debug!(session = %id, party = msg.party, "proof share received");
+    let msg = msg.into_inner();
+    submit_to_session(&pool, &sessions, &id, move |s| s.submit_proof_share(msg.party, msg.proof_share)).await
+}
+
+#[derive(Deserialize)]
+struct AggregatedVerifyInput {
+    commitments: Vec<String>,
+    proof: String,
+    #[serde(default = "default_bit_range")]
+    bit_range: usize,
+    #[serde(default)]
+    binding_tag_hex: Option<String>,
+}
+
+#[post("/zkp/verify-aggregated")]
+async fn verify_aggregated(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    input: web::Json<AggregatedVerifyInput>,
+) -> impl Responder {
+    debug!(commitments = input.commitments.len(), "verifying aggregated proof");
+    let sizes = check_count("commitments", input.commitments.len(), 1..=MAX_PARTIES.next_power_of_two())
+        .and_then(|()| check_proof_len(&input.proof));
+    if let Err(e) = sizes {
+        return zk_error(&e);
+    }
+    let commitments: Result<Vec<_>, _> = input.commitments.iter()
+        .map(|hex| <[u8;32]>::from_hex(hex).map(NgCompressed))
+        .collect();
+    let commitments = match commitments {
+        Ok(c) => c,
+        Err(_) => {
+            debug!("Failed to parse commitments");
+            return bad_req("bad commitments");
+        },
+    };
+    let proof = match Vec::from_hex(&input.proof) {
+        Ok(p) => p,
+        Err(_) => {
+            debug!("Failed to parse proof");
+            return bad_req("bad proof");
+        },
+    };
+    let binding_tag = match parse_binding_tag(&input.binding_tag_hex) {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let presentation = match presentation(&registry, &http, &proof, binding_tag.as_deref()) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    let bit_range = input.bit_range;
+    telemetry::record_proof("bulletproofs", proof.len());
+    let result = match offload(&pool, move || {
+        verify_aggregated_range_proof(&commitments, &proof, bit_range, binding_tag.as_deref())
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "Aggregated range proof")
+}
+
+// =============================================================================
+// Declarative R1CS statements
+// =============================================================================
+
+#[derive(Deserialize)]
+struct StatementOpeningInput {
+    value: u64,
+    #[serde(default)]
+    blinding_hex: Option<String>, // Optional 32-byte hex; random if omitted
+}
+
+#[derive(Deserialize)]
+struct StatementProveRequest {
+    statement: Statement,
+    openings: HashMap<String, StatementOpeningInput>,
+    #[serde(default)]
+    binding_tag_hex: Option<String>,
+}
+
+#[derive(Serialize)]
+struct StatementProveResponse {
+    statement_hash: String,
+    commitments: Vec<String>, // in statement.variables order
+    proof: String,
+    verified: bool,
+}
+
+#[derive(Deserialize)]
+struct StatementVerifyRequest {
+    statement: Statement,
+    statement_hash: String,
+    commitments: Vec<String>,
+    proof: String,
+    #[serde(default)]
+    binding_tag_hex: Option<String>,
+}
+
+#[post("/zkp/r1cs/prove")]
+async fn prove_r1cs_statement(pool: web::Data<ProofPool>, req: web::Json<StatementProveRequest>) -> impl Responder {
+    debug!(
+        variables = req.statement.variables.len(),
+        linear = req.statement.linear.len(),
+        multiplications = req.statement.multiplications.len(),
+        "proving statement"
+    );
+    let binding_tag = match parse_binding_tag(&req.binding_tag_hex) {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let mut openings = HashMap::with_capacity(req.openings.len());
+    for (name, input) in &req.openings {
+        let blinding = match &input.blinding_hex {
+            None => random_blinding(),
+            Some(hex) => match <[u8; 32]>::from_hex(hex.trim_start_matches("0x")) {
+                Ok(b) => Scalar::from_bytes_mod_order(b),
+                Err(_) => return bad_req(&format!("invalid blinding_hex for {name}: must be 32 bytes (64 hex chars)")),
+            },
+        };
+        openings.insert(name.clone(), Opening { value: input.value, blinding });
+    }
+
+    let statement = req.into_inner().statement;
+    let result = match offload(&pool, move || {
+        let proof = prove_statement(&statement, &openings, binding_tag.as_deref())?;
+        let verified = verify_statement(&statement, &proof.statement_hash, &proof.commitments, &proof.proof, binding_tag.as_deref())
+            .is_ok();
+        Ok((proof, verified))
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    let (proof, verified) = match result {
+        Ok(r) => r,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("r1cs", proof.proof.len());
+    debug!(verified, "statement proof generated");
+    HttpResponse::Ok().json(StatementProveResponse {
+        statement_hash: hex::encode(proof.statement_hash),
+        commitments: proof.commitments.iter().map(|c| hex::encode(c.as_bytes())).collect(),
+        proof: hex::encode(&proof.proof),
+        verified,
+    })
+}
+
+#[post("/zkp/r1cs/verify")]
+async fn verify_r1cs_statement(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    req: web::Json<StatementVerifyRequest>,
+) -> impl Responder {
+    debug!(commitments = req.commitments.len(), "verifying statement proof");
+    let sizes = check_count("commitments", req.commitments.len(), 0..=MAX_VARIABLES).and_then(|()| check_proof_len(&req.proof));
+    if let Err(e) = sizes {
+        return zk_error(&e);
+    }
+    let statement_hash = match <[u8; 32]>::from_hex(req.statement_hash.trim_start_matches("0x")) {
+        Ok(h) => h,
+        Err(_) => return bad_req("invalid statement_hash: must be 32 bytes (64 hex chars)"),
+    };
+    let commitments: Result<Vec<_>, _> = req.commitments.iter()
+        .map(|hex| <[u8;32]>::from_hex(hex).map(NgCompressed))
+        .collect();
+    let commitments = match commitments {
+        Ok(c) => c,
+        Err(_) => return bad_req("bad commitments"),
+    };
+    let proof = match Vec::from_hex(&req.proof) {
+        Ok(p) => p,
+        Err(_) => return bad_req("bad proof"),
+    };
+    let binding_tag = match parse_binding_tag(&req.binding_tag_hex) {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    let presentation = match presentation(&registry, &http, &proof, binding_tag.as_deref()) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    let statement = req.into_inner().statement;
+    telemetry::record_proof("r1cs", proof.len());
+    let result = match offload(&pool, move || {
+        verify_statement(&statement, &statement_hash, &commitments, &proof, binding_tag.as_deref())
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "Statement")
+}
+
+// =============================================================================
+// Homomorphic commitment arithmetic
+// =============================================================================
+
+#[derive(Deserialize)]
+struct CommitmentPairRequest {
+    lhs: String, // hex compressed Ristretto
+    rhs: String, // hex compressed Ristretto
+    // Optional blindings (32-byte hex, reduced mod the group order like the
+    // generate-* endpoints). Supply both to get the blinding of the result.
+    #[serde(default)]
+    lhs_blinding_hex: Option<String>,
+    #[serde(default)]
+    rhs_blinding_hex: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CommitmentScaleRequest {
+    commitment: String,
+    factor: u64,
+    #[serde(default)]
+    blinding_hex: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CommitmentOpResponse {
+    commitment: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    blinding_hex: Option<String>,
+}
+
+fn parse_point_hex(hex: &str, field: &str) -> Result<[u8; 32], HttpResponse> {
+    <[u8; 32]>::from_hex(hex.trim_start_matches("0x"))
+        .map_err(|_| bad_req(&format!("invalid {field}: must be 32 bytes (64 hex chars)")))
+}
+
+fn parse_blinding_hex(hex: &str, field: &str) -> Result<Scalar, HttpResponse> {
+    <[u8; 32]>::from_hex(hex.trim_start_matches("0x"))
+        .map(Scalar::from_bytes_mod_order)
+        .map_err(|_| bad_req(&format!("invalid {field}: must be 32 bytes (64 hex chars)")))
+}
+
+fn commitment_op_response(
+    result: Result<NgCompressed, ZkError>,
+    blinding: Option<Scalar>,
+) -> HttpResponse {
+    match result {
+        Ok(commitment) => HttpResponse::Ok().json(CommitmentOpResponse {
+            commitment: hex::encode(commitment.as_bytes()),
+            blinding_hex: blinding.map(|b| hex::encode(b.as_bytes())),
+        }),
+        Err(e) => zk_error(&e),
+    }
+}
+
+struct CommitmentPair {
+    lhs: [u8; 32],
+    rhs: [u8; 32],
+    blindings: Option<(Scalar, Scalar)>,
+}
+
+/// Shared parsing for `add`/`sub`: both commitments plus optional blinding pair.
+fn parse_commitment_pair(req: &CommitmentPairRequest) -> Result<CommitmentPair, HttpResponse> {
+    let lhs = parse_point_hex(&req.lhs, "lhs")?;
+    let rhs = parse_point_hex(&req.rhs, "rhs")?;
+    let blindings = match (&req.lhs_blinding_hex, &req.rhs_blinding_hex) {
+        (None, None) => None,
+        (Some(l), Some(r)) => Some((parse_blinding_hex(l, "lhs_blinding_hex")?, parse_blinding_hex(r, "rhs_blinding_hex")?)),
+        _ => return Err(bad_req("supply both lhs_blinding_hex and rhs_blinding_hex, or neither")),
+    };
+    Ok(CommitmentPair { lhs, rhs, blindings })
+}
+
+#[post("/zkp/commitment/add")]
+async fn commitment_add(req: web::Json<CommitmentPairRequest>) -> impl Responder {
+    let pair = match parse_commitment_pair(&req) {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    commitment_op_response(add_commitments(&pair.lhs, &pair.rhs), pair.blindings.map(|(l, r)| add_blindings(&l, &r)))
+}
+
+#[post("/zkp/commitment/sub")]
+async fn commitment_sub(req: web::Json<CommitmentPairRequest>) -> impl Responder {
+    let pair = match parse_commitment_pair(&req) {
+        Ok(p) => p,
+        Err(resp) => return resp,
+    };
+    commitment_op_response(sub_commitments(&pair.lhs, &pair.rhs), pair.blindings.map(|(l, r)| sub_blindings(&l, &r)))
+}
+
+#[post("/zkp/commitment/scale")]
+async fn commitment_scale(req: web::Json<CommitmentScaleRequest>) -> impl Responder {
+    debug!(factor = req.factor, "scaling commitment");
+    let commitment = match parse_point_hex(&req.commitment, "commitment") {
+        Ok(c) => c,
+        Err(resp) => return resp,
+    };
+    let blinding = match req.blinding_hex.as_deref().map(|h| parse_blinding_hex(h, "blinding_hex")).transpose() {
+        Ok(b) => b,
+        Err(resp) => return resp,
+    };
+    commitment_op_response(scale_commitment(&commitment, req.factor), blinding.map(|b| scale_blinding(&b, req.factor)))
+}
+
+// =============================================================================
+// BBS+ selective-disclosure credentials
+// =============================================================================
+
+/// Issuer key pair for BBS+ credentials, from `ZKP_BBS_ISSUER_KEY_HEX`
+/// (see [`Config::bbs_issuer_key_hex`]), so signatures survive restarts.
+struct BbsIssuer {
+    secret_key: bbs_plus::SecretKey,
+    public_key: bbs_plus::PublicKey,
+}
+
+impl BbsIssuer {
+    fn new(secret_key_hex: &str) -> Result<Self, ZkError> {
+        let secret_key = decode_hex_with(secret_key_hex, "secret key", bbs_plus::SecretKey::from_bytes)?;
+        let public_key = secret_key.public_key();
+        Ok(Self { secret_key, public_key })
+    }
+}
+
+#[derive(Serialize)]
+struct BbsPublicKeyResponse { public_key: String }
+
+#[derive(Deserialize)]
+struct BbsIssueRequest {
+    credential_subject: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize)]
+struct BbsIssueResponse {
+    signature: String,
+    public_key: String,
+    attributes: Vec<String>, // signed attribute names, in message order
+}
+
+#[derive(Deserialize)]
+struct BbsDeriveRequest {
+    credential_subject: serde_json::Map<String, serde_json::Value>,
+    signature: String,
+    reveal: Vec<String>,
+    #[serde(default)]
+    nonce_hex: String, // verifier-chosen presentation nonce
+}
+
+#[derive(Deserialize)]
+struct BbsVerifyRequest {
+    presentation: Presentation,
+    #[serde(default)]
+    nonce_hex: String,
+    // Issuer public key; defaults to this server's issuer key.
+    #[serde(default)]
+    public_key: Option<String>,
+}
+
+/// Hex-decode `hex` and hand the bytes to a `from_bytes` style decoder.
+fn decode_hex_with<T>(hex: &str, what: &str, f: impl FnOnce(&[u8]) -> Result<T, ZkError>) -> Result<T, ZkError> {
+    Vec::from_hex(hex.trim_start_matches("0x"))
+        .map_err(|_| ZkError::InvalidParameters(format!("{what} is not valid hex")))
+        .and_then(|bytes| f(&bytes))
+}
+
+fn parse_nonce_hex(nonce_hex: &str) -> Result<Vec<u8>, HttpResponse> {
+    Vec::from_hex(nonce_hex.trim_start_matches("0x")).map_err(|_| bad_req("invalid nonce_hex"))
+}
+
+#[get("/zkp/bbs/public-key")]
+async fn bbs_public_key(issuer: web::Data<BbsIssuer>) -> impl Responder {
+    HttpResponse::Ok().json(BbsPublicKeyResponse { public_key: hex::encode(issuer.public_key.to_bytes()) })
+}
+
+#[post("/zkp/bbs/issue")]
+async fn bbs_issue(pool: web::Data<ProofPool>, issuer: web::Data<BbsIssuer>, req: web::Json<BbsIssueRequest>) -> impl Responder {
+    debug!(attributes = req.credential_subject.len(), "issuing BBS+ credential");
+    let attributes = match bbs_plus::encode_attributes(&req.credential_subject) {
+        Ok(a) => a,
+        Err(e) => return zk_error(&e),
+    };
+    let job_issuer = issuer.clone();
+    let subject = req.into_inner().credential_subject;
+    let result = match offload(&pool, move || bbs_plus::issue_credential(&job_issuer.secret_key, &subject, &mut OsRng)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok(signature) => {
+            info!("BBS+ credential issued");
+            HttpResponse::Ok().json(BbsIssueResponse {
+                signature: hex::encode(signature.to_bytes()),
+                public_key: hex::encode(issuer.public_key.to_bytes()),
+                attributes: attributes.into_iter().map(|(name, _)| name).collect(),
+            })
+        },
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[post("/zkp/bbs/derive-proof")]
+async fn bbs_derive_proof(pool: web::Data<ProofPool>, issuer: web::Data<BbsIssuer>, req: web::Json<BbsDeriveRequest>) -> impl Responder {
+    debug!(revealed = req.reveal.len(), attributes = req.credential_subject.len(), "deriving BBS+ presentation");
+    if let Err(e) = check_count("revealed attributes", req.reveal.len(), 0..=bbs_plus::MAX_ATTRIBUTES) {
+        return zk_error(&e);
+    }
+    let nonce = match parse_nonce_hex(&req.nonce_hex) {
+        Ok(n) => n,
+        Err(resp) => return resp,
+    };
+    let signature = match decode_hex_with(&req.signature, "signature", bbs_plus::Signature::from_bytes) {
+        Ok(s) => s,
+        Err(e) => return zk_error(&e),
+    };
+    let req = req.into_inner();
+    let result = match offload(&pool, move || {
+        bbs_plus::derive_presentation(&issuer.public_key, &req.credential_subject, &signature, &req.reveal, &nonce, &mut OsRng)
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok(presentation) => {
+            debug!("BBS+ presentation derived");
+            HttpResponse::Ok().json(presentation)
+        },
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[post("/zkp/bbs/verify")]
+async fn bbs_verify(
+    pool: web::Data<ProofPool>,
+    issuer: web::Data<BbsIssuer>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    req: web::Json<BbsVerifyRequest>,
+) -> impl Responder {
+    debug!(revealed = req.presentation.revealed.len(), "verifying BBS+ presentation");
+    let sizes = check_count("revealed attributes", req.presentation.revealed.len(), 0..=bbs_plus::MAX_ATTRIBUTES)
+        .and_then(|()| check_proof_len(&req.presentation.proof));
+    if let Err(e) = sizes {
+        return zk_error(&e);
+    }
+    let nonce = match parse_nonce_hex(&req.nonce_hex) {
+        Ok(n) => n,
+        Err(resp) => return resp,
+    };
+    let public_key = match &req.public_key {
+        None => issuer.public_key,
+        Some(hex) => match decode_hex_with(hex, "public key", bbs_plus::PublicKey::from_bytes) {
+            Ok(pk) => pk,
+            Err(e) => return zk_error(&e),
+        },
+    };
+    // The nonce is the context a presentation is bound to.
+    let proof = hex_decode(req.presentation.proof.trim_start_matches("0x")).unwrap_or_default();
+    let presented = match presentation(&registry, &http, &proof, Some(&nonce[..])) {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    let presentation = req.into_inner().presentation;
+    telemetry::record_proof("bbs_plus", presentation.proof.len() / 2);
+    let result = match offload(&pool, move || bbs_plus::verify_presentation(&public_key, &presentation, &nonce)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presented, result, "BBS+ presentation")
+}
+
+// =============================================================================
+// Revocation accumulator
+// =============================================================================
+
+use revocation::{RevocationError, RevocationStore};
+
+fn revocation_error(e: &RevocationError) -> HttpResponse {
+    match e {
+        RevocationError::Registry(e) => zk_error(e),
+        RevocationError::Store(_) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
+    }
+}
+
+#[derive(Deserialize)]
+struct RevocationHandleRequest {
+    handle: String, // credential handle, e.g. the VC id
+}
+
+#[derive(Serialize)]
+struct AccumulatorResponse {
+    accumulator: String,
+    public_key: String,
+    epoch: u64,
+    digest: String, // 0x-prefixed sha256 of the accumulator bytes, for on-chain anchoring
+}
+
+#[derive(Serialize)]
+struct WitnessResponse {
+    witness: String,
+    accumulator: String,
+    epoch: u64,
+}
+
+#[derive(Deserialize)]
+struct NonRevocationProveRequest {
+    handle: String,
+    witness: String,
+    #[serde(default)]
+    blinding_hex: Option<String>, // random if omitted
+    #[serde(default)]
+    nonce_hex: String,
+}
+
+#[derive(Serialize)]
+struct NonRevocationProveResponse {
+    commitment: String,
+    blinding_hex: String,
+    proof: String,
+    accumulator: String,
+    epoch: u64,
+}
+
+#[derive(Deserialize)]
+struct NonRevocationVerifyRequest {
+    commitment: String,
+    proof: String,
+    // Epoch the holder proved against. Only the current one is accepted: an
+    // older accumulator still contains the handles revoked since.
+    #[serde(default)]
+    epoch: Option<u64>,
+    #[serde(default)]
+    nonce_hex: String,
+}
+
+#[derive(Serialize)]
+struct NonRevocationVerifyResponse {
+    verified: bool,
+    epoch: u64, // the accumulator epoch the proof was checked against
+}
+
+fn accumulator_response(registry: &RevocationRegistry) -> HttpResponse {
+    let accumulator = registry.value().to_bytes();
+    HttpResponse::Ok().json(AccumulatorResponse {
+        digest: format!("0x{}", hex::encode(Sha256::digest(&accumulator))),
+        accumulator: hex::encode(accumulator),
+        public_key: hex::encode(registry.public_key().to_bytes()),
+        epoch: registry.epoch(),
+    })
+}
+
+
+#[get("/zkp/revocation/accumulator")]
+async fn revocation_accumulator(store: web::Data<RevocationStore>) -> impl Responder {
+    let registry = store.registry();
+    accumulator_response(&registry)
+}
+
+#[post("/zkp/revocation/add")]
+async fn revocation_add(store: web::Data<RevocationStore>, req: web::Json<RevocationHandleRequest>) -> impl Responder {
+    match store.add(handle_element(&req.handle)) {
+        Ok(registry) => {
+            info!(epoch = registry.epoch(), "revocation handle accumulated");
+            accumulator_response(&registry)
+        },
+        Err(e) => revocation_error(&e),
+    }
+}
+
+#[post("/zkp/revocation/remove")]
+async fn revocation_remove(store: web::Data<RevocationStore>, req: web::Json<RevocationHandleRequest>) -> impl Responder {
+    match store.remove(&handle_element(&req.handle)) {
+        Ok(registry) => {
+            info!(epoch = registry.epoch(), "revocation handle removed");
+            accumulator_response(&registry)
+        },
+        Err(e) => revocation_error(&e),
+    }
+}
+
+// Issuer-only (auth::ISSUER_PATHS): a witness, or its absence, tells whether
+// a handle is live. Unknown and revoked handles get the same NotMember error.
+#[post("/zkp/revocation/witness")]
+async fn revocation_witness(store: web::Data<RevocationStore>, req: web::Json<RevocationHandleRequest>) -> impl Responder {
+    let registry = store.registry();
+    match registry.witness(&handle_element(&req.handle)) {
+        Ok(witness) => HttpResponse::Ok().json(WitnessResponse {
+            witness: hex::encode(witness.to_bytes()),
+            accumulator: hex::encode(registry.value().to_bytes()),
+            epoch: registry.epoch(),
+        }),
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[post("/zkp/revocation/prove")]
+async fn revocation_prove(pool: web::Data<ProofPool>, store: web::Data<RevocationStore>, req: web::Json<NonRevocationProveRequest>) -> impl Responder {
+    let nonce = match parse_nonce_hex(&req.nonce_hex) {
+        Ok(n) => n,
+        Err(resp) => return resp,
+    };
+    let witness = match decode_hex_with(&req.witness, "membership witness", MembershipWitness::from_bytes) {
+        Ok(w) => w,
+        Err(e) => return zk_error(&e),
+    };
+    let blinding = match &req.blinding_hex {
+        None => random_accumulator_blinding(&mut OsRng),
+        Some(hex) => match <[u8; 32]>::from_hex(hex.trim_start_matches("0x")) {
+            Ok(b) => blinding_from_bytes(&b),
+            Err(_) => return bad_req("invalid blinding_hex: must be 32 bytes (64 hex chars)"),
+        },
+    };
+    let (public_key, value, epoch) = {
+        let registry = store.registry();
+        (registry.public_key(), registry.value(), registry.epoch())
+    };
+    let y = handle_element(&req.handle);
+    let result = match offload(&pool, move || {
+        prove_non_revocation(&public_key, &value, &y, &witness, &blinding, &nonce, &mut OsRng)
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok((commitment, proof)) => {
+            debug!(epoch, "non-revocation proof generated");
+            HttpResponse::Ok().json(NonRevocationProveResponse {
+                commitment: hex::encode(commitment_to_bytes(&commitment)),
+                blinding_hex: hex::encode(blinding_to_bytes(&blinding)),
+                proof: hex::encode(proof.to_bytes()),
+                accumulator: hex::encode(value.to_bytes()),
+                epoch,
+            })
+        },
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[post("/zkp/revocation/verify")]
+async fn revocation_verify(pool: web::Data<ProofPool>, store: web::Data<RevocationStore>, req: web::Json<NonRevocationVerifyRequest>) -> impl Responder {
+    let nonce = match parse_nonce_hex(&req.nonce_hex) {
+        Ok(n) => n,
+        Err(resp) => return resp,
+    };
+    let parsed = decode_hex_with(&req.commitment, "handle commitment", commitment_from_bytes).and_then(|commitment| {
+        Ok((commitment, decode_hex_with(&req.proof, "non-revocation proof", NonRevocationProof::from_bytes)?))
+    });
+    let (commitment, proof) = match parsed {
+        Ok(p) => p,
+        Err(e) => return zk_error(&e),
+    };
+    let (public_key, value, epoch) = {
+        let registry = store.registry();
+        (registry.public_key(), registry.value(), registry.epoch())
+    };
+    if let Some(requested) = req.epoch.filter(|&e| e != epoch) {
+        return zk_error(&ZkError::InvalidParameters(format!("accumulator epoch {requested} is not the current epoch {epoch}")));
+    }
+    telemetry::record_proof("accumulator", req.proof.trim_start_matches("0x").len() / 2);
+    let result = match offload(&pool, move || verify_non_revocation(&public_key, &value, &commitment, &proof, &nonce)).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok(()) | Err(ZkError::TranscriptMismatch) => {
+            let verified = result.is_ok();
+            metrics::record_outcome(if verified { "verified" } else { "invalid" });
+            info!(verified, epoch, "Non-revocation verification");
+            HttpResponse::Ok().json(NonRevocationVerifyResponse { verified, epoch })
+        },
+        Err(e) => {
+            metrics::record_outcome(e.code());
+            zk_error(&e)
+        },
+    }
+}
+
+// =============================================================================
+// Proof worker pool
+// =============================================================================
+
+#[get("/zkp/pool")]
+async fn pool_status(pool: web::Data<ProofPool>) -> impl Responder {
+    HttpResponse::Ok().json(pool.status())
+}
+
+// =============================================================================
+// Metrics
+// =============================================================================
+
+#[get("/metrics")]
+async fn metrics_endpoint(pool: web::Data<ProofPool>, jobs: Option<web::Data<JobManager>>) -> impl Responder {
+    HttpResponse::Ok()
+        .content_type(prometheus::TEXT_FORMAT)
+        .body(metrics::render(&pool.status(), jobs.map(|jobs| jobs.pending())))
+}
+
+// =============================================================================
+// Asynchronous proof jobs
+// =============================================================================
+
+#[post("/zkp/jobs")]
+async fn submit_job(
+    jobs: web::Data<JobManager>,
+    limits: Option<web::Data<Limits>>,
+    store: Option<web::Data<IssuedStore>>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    req: web::Json<JobRequest>,
+) -> impl Responder {
+    let request = req.into_inner();
+    if let Err(e) = request.validate() {
+        return job_rejected(SubmitError::Invalid(e));
+    }
+    // The rate limit took one token for the request; a job pays one per step.
+    if let Some(limits) = &limits {
+        if let Err(resp) = limits.charge(&http, Budget::Prove, request.step_count() - 1) {
+            return resp;
+        }
+    }
+    let verifier = match registry.as_ref().map(|_| replay::Presentation::verifier(http.headers())).transpose() {
+        Ok(verifier) => verifier.unwrap_or_default(),
+        Err(e) => return zk_error(&e),
+    };
+    let issue = match issue_context(&store, &http) {
+        Ok(issue) => issue,
+        Err(e) => return zk_error(&e),
+    };
+    let client = http.extensions().get::<limits::Client>().map(ToString::to_string);
+    match JobManager::submit(&jobs.into_inner(), request, Submitter { client, verifier, issue }) {
+        Ok(record) => {
+            info!(job_id = %record.id, kind = %record.kind, "proof job queued");
+            HttpResponse::Accepted()
+                .insert_header(("Location", format!("/zkp/jobs/{}", record.id)))
+                .json(record)
+        },
+        Err(e) => job_rejected(e),
+    }
+}
+
+fn job_rejected(e: SubmitError) -> HttpResponse {
+    info!(error = %e, "proof job rejected");
+    match e {
+        SubmitError::Invalid(_) => bad_req(&e.to_string()),
+        SubmitError::TooManyPending => HttpResponse::ServiceUnavailable()
+            .insert_header(("Retry-After", PROOF_RETRY_AFTER_SECS.to_string()))
+            .json(json!({ "error": e.to_string(), "code": "too_many_jobs" })),
+        SubmitError::TooManyPendingForClient => HttpResponse::TooManyRequests()
+            .insert_header(("Retry-After", PROOF_RETRY_AFTER_SECS.to_string()))
+            .json(json!({ "error": e.to_string(), "code": "too_many_jobs" })),
+        SubmitError::Store(_) => HttpResponse::InternalServerError().json(json!({ "error": e.to_string() })),
+    }
+}
+
+fn job_not_found() -> HttpResponse {
+    HttpResponse::NotFound().json(json!({ "error": "unknown job" }))
+}
+
+#[get("/zkp/jobs/{id}")]
+async fn get_job(jobs: web::Data<JobManager>, id: web::Path<String>) -> impl Responder {
+    match jobs.fetch(&id) {
+        Some(record) => HttpResponse::Ok().json(record),
+        None => job_not_found(),
+    }
+}
+
+/// Server-sent events for one job: a `progress` event per update and a final
+/// `complete` event, each carrying the job record. Ends after `complete`.
+#[get("/zkp/jobs/{id}/events")]
+async fn job_events(jobs: web::Data<JobManager>, id: web::Path<String>) -> impl Responder {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let jobs = jobs.into_inner();
+    let id = id.into_inner();
+    // Subscribe before reading the record so no update falls in between.
+    let rx = jobs.subscribe();
+    let Some(current) = jobs.get(&id) else {
+        return job_not_found();
+    };
+    let stream = futures_util::stream::unfold((rx, Some(current), false), move |(mut rx, pending, done)| {
+        let (jobs, id) = (jobs.clone(), id.clone());
+        async move {
+            if done {
+                return None;
+            }
+            let record = match pending {
+                Some(record) => record,
+                None => loop {
+                    match rx.recv().await {
+                        Ok(record) if record.id == id => break record,
+                        Ok(_) => continue,
+                        Err(RecvError::Lagged(_)) => break jobs.get(&id)?,
+                        Err(RecvError::Closed) => return None,
+                    }
+                },
+            };
+            let finished = record.status.is_finished();
+            // The complete event is a read like GET: it may carry the secrets.
+            let record = if finished { jobs.fetch(&id).unwrap_or(record) } else { record };
+            let frame = format!(
+                "event: {}\ndata: {}\n\n",
+                if finished { "complete" } else { "progress" },
+                serde_json::to_string(&record).expect("job records always serialize"),
+            );
+            Some((Ok::<_, actix_web::Error>(web::Bytes::from(frame)), (rx, None, finished)))
+        }
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(stream)
+}
+
+// =============================================================================
+// Shared generators
+// =============================================================================
+
+// Every prover and verifier draws from the same process-wide registry.
+type Generators = &'static GeneratorRegistry;
+
+#[derive(Serialize)]
+struct GeneratorsResponse {
+    cached: Vec<GeneratorKey>,
+}
+
+#[get("/zkp/generators")]
+async fn generators_status(generators: web::Data<Generators>) -> impl Responder {
+    HttpResponse::Ok().json(GeneratorsResponse { cached: generators.cached() })
+}
+
+/// Register the routes of every enabled endpoint group.
+fn configure_endpoints(cfg: &mut web::ServiceConfig, config: &Config) {
+    for group in &config.endpoints {
+        match group {
+            EndpointGroup::Legacy => {
+                cfg.service(generate_zkp)
+                    .service(verify_zkp)
+                    .service(commit_tx_hash)
+                    .service(prove_plus_ep)
+                    .service(verify_plus_ep)
+                    .service(generate_bp4)
+                    .service(verify_bp4)
+                    .service(commit_value)
+                    .service(verify_value)
+                    .service(generate_value_commitment)
+                    .service(generate_value_commitment_with_blinding_ep)
+                    .service(generate_value_commitment_with_binding_ep)
+                    .service(verify_value_commitment_ep);
+            },
+            EndpointGroup::V1 => v1::configure(cfg, config.json_limit),
+            EndpointGroup::Aggregation => {
+                cfg.service(create_aggregation_session)
+                    .service(get_aggregation_session)
+                    .service(submit_bit_commitment)
+                    .service(submit_poly_commitment)
+                    .service(submit_proof_share)
+                    .service(verify_aggregated);
+            },
+            EndpointGroup::Statements => {
+                cfg.service(prove_r1cs_statement).service(verify_r1cs_statement);
+            },
+            EndpointGroup::Commitments => {
+                cfg.service(commitment_add).service(commitment_sub).service(commitment_scale);
+            },
+            EndpointGroup::Bbs => {
+                cfg.service(bbs_public_key).service(bbs_issue).service(bbs_derive_proof).service(bbs_verify);
+            },
+            EndpointGroup::Revocation => {
+                cfg.service(revocation_accumulator)
+                    .service(revocation_add)
+                    .service(revocation_remove)
+                    .service(revocation_witness)
+                    .service(revocation_prove)
+                    .service(revocation_verify);
+            },
+            EndpointGroup::Credentials => credentials::configure(cfg),
+            EndpointGroup::Jobs => {
+                cfg.service(submit_job).service(get_job).service(job_events);
+            },
+            EndpointGroup::Status => {
+                cfg.service(generators_status).service(pool_status);
+            },
+            EndpointGroup::Metrics => {
+                cfg.service(metrics_endpoint);
+            },
+            EndpointGroup::Health => health::configure(cfg),
+        }
+    }
+}
+
+fn cors(config: &Config) -> Cors {
+    let cors = Cors::default()
+        .allow_any_method()
+        .allow_any_header()
+        .expose_headers(["Deprecation", "Link", "Retry-After", "Location", "X-Request-Id"]);
+    if config.allows_any_origin() {
+        cors.allow_any_origin()
+    } else {
+        config.cors_origins.iter().fold(cors, |cors, origin| cors.allowed_origin(origin))
+    }
+}
+
+/// Bind the server described by `config`. The [`Server`] serves once it is
+/// awaited or spawned; the addresses are the ones actually bound, so
+/// `listen` may use port 0.
+pub fn start(config: Config) -> std::io::Result<(Server, Vec<SocketAddr>)> {
+    info!(
+        listen = %config.listen,
+        workers = config.workers,
+        proof_workers = config.proof_workers,
+        proof_queue = config.proof_queue,
+        cors_origins = ?config.cors_origins,
+        json_limit = config.json_limit,
+        prove_json_limit = config.prove_json_limit,
+        prove_rate = ?config.prove_rate,
+        verify_rate = ?config.verify_rate,
+        endpoints = ?config.endpoints,
+        job_store = %config.job_store.display(),
+        "starting ZKP backend server"
+    );
+    let aggregation_sessions = web::Data::new(AggregationSessions::default());
+    // Only served with a configured key (checked by `Config::validate`).
+    let bbs_issuer = match &config.bbs_issuer_key_hex {
+        Some(key) if config.serves(EndpointGroup::Bbs) => {
+            let issuer = BbsIssuer::new(key)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("ZKP_BBS_ISSUER_KEY_HEX: {e}")))?;
+            Some(web::Data::new(issuer))
+        },
+        None if config.serves(EndpointGroup::Bbs) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "the bbs endpoints need ZKP_BBS_ISSUER_KEY_HEX"));
+        },
+        _ => None,
+    };
+    // Likewise the revocation registry, whose trapdoor comes from its key.
+    let revocation = match &config.revocation_key_hex {
+        Some(key) if config.serves(EndpointGroup::Revocation) => {
+            let seed = hex::decode(key)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("ZKP_REVOCATION_KEY_HEX: {e}")))?;
+            let store = RevocationStore::open(&config.revocation_store, &seed)
+                .map_err(|e| std::io::Error::other(format!("revocation store {}: {e}", config.revocation_store.display())))?;
+            info!(epoch = store.registry().epoch(), "revocation registry restored");
+            Some(web::Data::new(store))
+        },
+        None if config.serves(EndpointGroup::Revocation) => {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "the revocation endpoints need ZKP_REVOCATION_KEY_HEX"));
+        },
+        _ => None,
+    };
+    let generators: Generators = GeneratorRegistry::shared();
+    let started = Instant::now();
+    generators.warm_up(DEFAULT_SHAPES)
+        .map_err(|e| std::io::Error::other(format!("generator warm-up: {e}")))?;
+    info!(shapes = DEFAULT_SHAPES.len(), elapsed_ms = started.elapsed().as_millis() as u64, "generators ready");
+    // A failed self-test keeps the server up (for /readyz and diagnosis) but not ready.
+    let readiness = web::Data::new(health::Readiness::check());
+    let generators = web::Data::new(generators);
+    let pool = Arc::new(ProofPool::new(config.proof_workers, config.proof_queue)?);
+    let status = pool.status();
+    info!(workers = status.workers, queue = status.queue_capacity, "proof pool ready");
+    let replay = match &config.replay_store {
+        Some(path) => {
+            let registry = ReplayRegistry::open(path, config.replay_scope)
+                .map_err(|e| std::io::Error::other(format!("replay store {}: {e}", path.display())))?;
+            info!(path = %path.display(), scope = ?config.replay_scope, "recording presented proofs");
+            Some(web::Data::new(registry))
+        },
+        None => None,
+    };
+    let issued = match &config.issued_store {
+        Some(path) => {
+            let store = IssuedStore::open(path)
+                .map_err(|e| std::io::Error::other(format!("issued store {}: {e}", path.display())))?;
+            let store = Arc::new(store);
+            if let Some(days) = config.issued_retention_days {
+                IssuedStore::retain(&store, Duration::from_secs(u64::from(days) * 24 * 60 * 60));
+            }
+            info!(path = %path.display(), retention_days = ?config.issued_retention_days, "recording issued commitments");
+            Some(web::Data::from(store))
+        },
+        None => None,
+    };
+    // The job store is only opened (and unfinished jobs resumed) when jobs are served.
+    let jobs = if config.serves(EndpointGroup::Jobs) {
+        let mut jobs = JobManager::open(&config.job_store, pool.clone())
+            .map_err(|e| std::io::Error::other(format!("job store {}: {e}", config.job_store.display())))?;
+        if let Some(issued) = &issued {
+            jobs = jobs.with_issued_store(issued.clone().into_inner());
+        }
+        if let Some(replay) = &replay {
+            jobs = jobs.with_replay_registry(replay.clone().into_inner());
+        }
+        let jobs = Arc::new(jobs);
+        let resumed = JobManager::resume(&jobs);
+        if resumed > 0 {
+            info!(resumed, "resumed unfinished proof jobs");
+        }
+        Some(web::Data::from(jobs))
+    } else {
+        None
+    };
+    let auth = if config.auth {
+        let auth = Auth::new(&config).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        info!(
+            api_keys = config.api_keys.len(),
+            allowlist = config.auth_allowlist.len(),
+            issuer_allowlist = config.issuer_allowlist.len(),
+            escrow_rpc = config.escrow_rpc_url.as_deref().unwrap_or("none"),
+            "request authentication enabled"
+        );
+        Some(web::Data::new(auth))
+    } else {
+        None
+    };
+    let limits = web::Data::new(Limits::new(&config));
+    let tls = if config.serves_tls() {
+        let (tls, cert) = tls::server_config(&config).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        cert.watch();
+        info!(
+            cert = ?config.tls_cert,
+            client_ca = ?config.tls_client_ca,
+            client_auth = ?config.tls_client_auth,
+            "HTTPS enabled"
+        );
+        Some(tls)
+    } else {
+        None
+    };
+    let pool = web::Data::from(pool);
+    let (listen, workers) = (config.listen, config.workers);
+    let config = web::Data::new(config);
+    let server = HttpServer::new(move || {
+        App::new()
+            // Legacy routes answer as before but point clients at their /v1 successor.
+            .wrap_fn(|req, srv| {
+                let successor = v1::successor_of(req.path());
+                let response = srv.call(req);
+                async move {
+                    let mut response = response.await?;
+                    if let Some(successor) = successor {
+                        v1::mark_deprecated(response.headers_mut(), successor);
+                    }
+                    Ok(response)
+                }
+            })
+            // Inside CORS, so rejections still carry the CORS headers browsers need.
+            .wrap(middleware::from_fn(auth::authenticate))
+            // Before authentication, so floods never reach signature checks.
+            .wrap(middleware::from_fn(limits::enforce))
+            .wrap(cors(&config))
+            .wrap_fn(metrics::track)
+            // Outermost, so the request span covers CORS rejections too.
+            .wrap_fn(telemetry::trace_request)
+            .app_data(web::JsonConfig::default().limit(config.json_limit))
+            .app_data(aggregation_sessions.clone())
+            .app_data(generators.clone())
+            .app_data(pool.clone())
+            .app_data(readiness.clone())
+            .app_data(limits.clone())
+            .configure(|cfg| {
+                if let Some(jobs) = &jobs {
+                    cfg.app_data(jobs.clone());
+                }
+                if let Some(bbs_issuer) = &bbs_issuer {
+                    cfg.app_data(bbs_issuer.clone());
+                }
+                if let Some(revocation) = &revocation {
+                    cfg.app_data(revocation.clone());
+                }
+                if let Some(auth) = &auth {
+                    cfg.app_data(auth.clone());
+                }
+                if let Some(issued) = &issued {
+                    cfg.app_data(issued.clone());
+                }
+                if let Some(replay) = &replay {
+                    cfg.app_data(replay.clone());
+                }
+                configure_endpoints(cfg, &config);
+            })
+    })
+    .workers(workers)
+    .on_connect(tls::on_connect);
+    let server = match tls {
+        Some(tls) => server.bind_rustls_0_23(listen, tls)?,
+        None => server.bind(listen)?,
+    };
+    let addrs = server.addrs();
+    Ok((server.run(), addrs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use serde_json::Value;
+    use zk::aggregated_range_proof::{AggregationParams, PartySecret};
+
+    #[actix_web::test]
+    async fn saturated_pool_rejects_aggregation_messages() {
+        let pool = web::Data::new(ProofPool::new(1, 1).unwrap());
+        let app = init_service(
+            App::new()
+                .app_data(pool.clone())
+                .app_data(web::Data::new(AggregationSessions::default()))
+                .service(create_aggregation_session)
+                .service(submit_bit_commitment)
+                .service(submit_poly_commitment)
+                .service(submit_proof_share),
+        )
+        .await;
+        macro_rules! post {
+            ($path:expr, $body:expr) => {{
+                let resp = call_service(&app, TestRequest::post().uri(&$path).set_json($body).to_request()).await;
+                let retry_after = resp.headers().get("Retry-After").map(|v| v.to_str().unwrap().to_string());
+                (resp.status(), retry_after, read_body_json::<Value, _>(resp).await)
+            }};
+        }
+
+        let (_, _, session) = post!("/zkp/aggregate/sessions", json!({ "parties": 1, "bit_range": 8 }));
+        let path = |round: &str| format!("/zkp/aggregate/sessions/{}/{round}", session["session_id"].as_str().unwrap());
+        let params: AggregationParams = serde_json::from_value(session["params"].clone()).unwrap();
+        let secret = PartySecret::new(7, Scalar::from(3u64));
+        let (_, _, status) = post!(path("bit-commitment"), json!({ "party": 0, "bit_commitment": secret.bit_commitment(&params, 0).unwrap() }));
+        let bit_challenge = serde_json::from_value(status["bit_challenge"].clone()).unwrap();
+        let poly = secret.poly_commitment(&params, 0, &bit_challenge).unwrap();
+        let (_, _, status) = post!(path("poly-commitment"), json!({ "party": 0, "poly_commitment": poly }));
+        let poly_challenge = serde_json::from_value(status["poly_challenge"].clone()).unwrap();
+        let share = json!({ "party": 0, "proof_share": secret.proof_share(&params, 0, &bit_challenge, &poly_challenge).unwrap() });
+
+        // One job occupies the worker, the next one fills the queue.
+        let (release, gate) = std::sync::mpsc::channel::<()>();
+        let gate = Arc::new(Mutex::new(gate));
+        let mut blocked = Vec::new();
+        for queued in [0, 1] {
+            let (worker, gate) = (pool.clone(), gate.clone());
+            blocked.push(actix_web::rt::spawn(async move { worker.run(move || gate.lock().unwrap().recv().unwrap()).await }));
+            while pool.status().active + pool.status().queued <= queued {
+                actix_web::rt::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+
+        let (status, retry_after, body) = post!(path("proof-share"), share.clone());
+        assert_eq!((status, body["code"].as_str()), (StatusCode::SERVICE_UNAVAILABLE, Some("pool_saturated")));
+        assert_eq!(retry_after, Some(PROOF_RETRY_AFTER_SECS.to_string()));
+
+        for job in blocked {
+            release.send(()).unwrap();
+            job.await.unwrap().unwrap();
+        }
+        let (status, _, body) = post!(path("proof-share"), share);
+        assert_eq!((status, body["phase"].as_str()), (StatusCode::OK, Some("complete")));
+    }
+
+    #[actix_web::test]
+    async fn legacy_verifications_are_presentations() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(ProofPool::new(2, 8).unwrap()))
+                .app_data(web::Data::new(ReplayRegistry::open(dir.path(), config::ReplayScope::Once).unwrap()))
+                .service(verify_zkp)
+                .configure(|cfg| v1::configure(cfg, 1 << 20)),
+        )
+        .await;
+        macro_rules! post {
+            ($path:expr, $body:expr) => {
+                read_body_json::<Value, _>(call_service(&app, TestRequest::post().uri($path).set_json($body).to_request()).await).await
+            };
+        }
+
+        let tag = "11".repeat(32);
+        let created = post!("/v1/tx-hash-commitments", json!({ "tx_hash": "ab".repeat(32), "binding_tag_hex": tag }));
+        let proof = json!({ "commitment": created["commitment"], "proof": created["proof"], "binding_tag_hex": tag });
+        let legacy = post!("/zkp/verify", &proof);
+        assert_eq!((&legacy["verified"], &legacy["replay"]["accepted"]), (&json!(true), &json!(true)));
+        let again = post!("/v1/tx-hash-commitments/verify", &proof);
+        assert_eq!((&again["replay"]["accepted"], &again["replay"]["previous_presentations"]), (&json!(false), &json!(1)));
+    }
+
+    #[actix_web::test]
+    async fn legacy_commitments_are_issued() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(ProofPool::new(2, 8).unwrap()))
+                .app_data(web::Data::new(IssuedStore::open(dir.path()).unwrap()))
+                .service(commit_tx_hash)
+                .service(generate_value_commitment_with_binding_ep)
+                .configure(|cfg| v1::configure(cfg, 1 << 20)),
+        )
+        .await;
+        let product = "0x00000000000000000000000000000000000000AA";
+        let create = |path: &str, body: Value| {
+            TestRequest::post().uri(path).insert_header(("X-Zkp-Product", product)).insert_header(("X-Zkp-Stage", "delivery")).set_json(body).to_request()
+        };
+
+        let tag = "11".repeat(32);
+        let resp = call_service(&app, create("/zkp/commit-tx-hash", json!({ "tx_hash": "ab".repeat(32), "binding_tag_hex": tag }))).await;
+        let created: Value = read_body_json(resp).await;
+        let body = json!({ "value": 7, "blinding_hex": "02".repeat(32), "binding_tag_hex": tag });
+        let resp = call_service(&app, create("/zkp/generate-value-commitment-with-binding", body)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let list = call_service(&app, TestRequest::get().uri(&format!("/v1/issued?binding_tag={tag}")).to_request()).await;
+        let list: Value = read_body_json(list).await;
+        let records = list["records"].as_array().unwrap();
+        let kinds: Vec<_> = records.iter().map(|r| r["kind"].as_str().unwrap()).collect();
+        assert_eq!(kinds, ["value_commitment", "tx_hash_commitment"]);
+        let record = &records[1];
+        assert_eq!((&record["commitments"][0], &record["proof"]), (&created["commitment"], &created["proof"]));
+        assert_eq!((record["product"].as_str(), record["stage"].as_str()), (Some(product.to_lowercase().as_str()), Some("delivery")));
+    }
+}
@@ -0,0 +1,195 @@
+//! Bounded pool of blocking threads for proving and verification.
+//!
+//! Bulletproof proving takes tens of milliseconds of pure CPU. Running it on
+//! the actix workers lets a burst of proofs stall every other request, so the
+//! handlers hand that work to a fixed set of threads behind a bounded queue.
+//! When the queue is full the job is rejected straight away rather than
+//! queued behind work that will not finish in time.
+
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// Why a job did not produce a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolError {
+    /// Every worker is busy and the queue is full.
+    Saturated,
+    /// The job panicked or the pool shut down before it ran.
+    Aborted,
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Saturated => write!(f, "proof workers are saturated"),
+            Self::Aborted => write!(f, "proof job aborted"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    queued: AtomicUsize,
+    active: AtomicUsize,
+    completed: AtomicU64,
+    rejected: AtomicU64,
+}
+
+/// Point-in-time view of the pool, for the status endpoint and logs.
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStatus {
+    pub workers: usize,
+    pub queue_capacity: usize,
+    pub queued: usize,
+    pub active: usize,
+    pub completed: u64,
+    pub rejected: u64,
+    /// Every worker busy and the queue full: new jobs are being rejected.
+    pub saturated: bool,
+}
+
+pub struct ProofPool {
+    sender: SyncSender<Job>,
+    workers: usize,
+    queue_capacity: usize,
+    counters: Arc<Counters>,
+}
+
+impl ProofPool {
+    /// Start `workers` threads sharing a queue of `queue_capacity` pending jobs.
+    pub fn new(workers: usize, queue_capacity: usize) -> std::io::Result<Self> {
+        let workers = workers.max(1);
+        let (sender, receiver) = mpsc::sync_channel::<Job>(queue_capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let counters = Arc::new(Counters::default());
+        for i in 0..workers {
+            let receiver = receiver.clone();
+            let counters = counters.clone();
+            thread::Builder::new().name(format!("zkp-prover-{i}")).spawn(move || loop {
+                // The guard is dropped as soon as a job is taken.
+                let job = receiver.lock().unwrap().recv();
+                let Ok(job) = job else { break };
+                counters.queued.fetch_sub(1, Ordering::Relaxed);
+                counters.active.fetch_add(1, Ordering::Relaxed);
+                job();
+                counters.active.fetch_sub(1, Ordering::Relaxed);
+                counters.completed.fetch_add(1, Ordering::Relaxed);
+            })?;
+        }
+        Ok(Self { sender, workers, queue_capacity, counters })
+    }
+
+    /// Run `job` on a pool thread and wait for its result without blocking
+    /// the caller's executor.
+    pub async fn run<T, F>(&self, job: F) -> Result<T, PoolError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        // Log lines from the proof keep the caller's request context.
+        let span = tracing::Span::current();
+        let job: Job = Box::new(move || {
+            let _entered = span.enter();
+            // A panicking proof must not take the worker thread down with it;
+            // dropping `tx` reports it to the caller as `Aborted`.
+            if let Ok(result) = panic::catch_unwind(AssertUnwindSafe(job)) {
+                let _ = tx.send(result);
+            }
+        });
+        self.counters.queued.fetch_add(1, Ordering::Relaxed);
+        match self.sender.try_send(job) {
+            Ok(()) => {},
+            Err(TrySendError::Full(_)) => {
+                self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+                self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+                return Err(PoolError::Saturated);
+            },
+            Err(TrySendError::Disconnected(_)) => {
+                self.counters.queued.fetch_sub(1, Ordering::Relaxed);
+                return Err(PoolError::Aborted);
+            },
+        }
+        rx.await.map_err(|_| PoolError::Aborted)
+    }
+
+    pub fn status(&self) -> PoolStatus {
+        let queued = self.counters.queued.load(Ordering::Relaxed);
+        let active = self.counters.active.load(Ordering::Relaxed);
+        PoolStatus {
+            workers: self.workers,
+            queue_capacity: self.queue_capacity,
+            queued,
+            active,
+            completed: self.counters.completed.load(Ordering::Relaxed),
+            rejected: self.counters.rejected.load(Ordering::Relaxed),
+            saturated: active >= self.workers && queued >= self.queue_capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    #[actix_web::test]
+    async fn runs_jobs_and_reports_results() {
+        let pool = ProofPool::new(2, 4).unwrap();
+        assert_eq!(pool.run(|| 6 * 7).await, Ok(42));
+        // The worker counts a job just after handing over its result.
+        while pool.status().completed == 0 {
+            std::thread::yield_now();
+        }
+        let status = pool.status();
+        assert_eq!((status.workers, status.completed, status.rejected), (2, 1, 0));
+    }
+
+    #[actix_web::test]
+    async fn rejects_when_queue_is_full() {
+        let pool = Arc::new(ProofPool::new(1, 1).unwrap());
+        let (release, gate) = channel::<()>();
+        let gate = Arc::new(Mutex::new(gate));
+        let spawn_blocked = || {
+            let (pool, gate) = (pool.clone(), gate.clone());
+            actix_web::rt::spawn(async move { pool.run(move || gate.lock().unwrap().recv().unwrap()).await })
+        };
+        let pool_ref = &pool;
+        let wait_until = |done: fn(&PoolStatus) -> bool| async move {
+            while !done(&pool_ref.status()) {
+                actix_web::rt::time::sleep(Duration::from_millis(1)).await;
+            }
+        };
+
+        // One job occupies the worker, the next one fills the queue.
+        let running = spawn_blocked();
+        wait_until(|s| s.active == 1).await;
+        let queued = spawn_blocked();
+        wait_until(|s| s.queued == 1).await;
+
+        assert!(pool.status().saturated);
+        assert_eq!(pool.run(|| ()).await, Err(PoolError::Saturated));
+        release.send(()).unwrap();
+        release.send(()).unwrap();
+        assert_eq!(running.await.unwrap(), Ok(()));
+        assert_eq!(queued.await.unwrap(), Ok(()));
+        assert_eq!(pool.status().rejected, 1);
+    }
+
+    #[actix_web::test]
+    async fn panicking_job_is_aborted_and_worker_survives() {
+        let pool = ProofPool::new(1, 1).unwrap();
+        assert_eq!(pool.run(|| -> u8 { panic!("boom") }).await, Err(PoolError::Aborted));
+        assert_eq!(pool.run(|| 1).await, Ok(1));
+    }
+}
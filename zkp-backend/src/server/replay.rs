@@ -0,0 +1,182 @@
+//! Registry of presented proofs (`replay_store`).
+//!
+//! A binding tag keeps a proof from being accepted in another context, but
+//! nothing in the proof stops it being presented twice in the same one.
+//! Every successful verification (`/v1`, the legacy `/zkp/*` verify routes,
+//! `/zkp/verify-vc` and job verify steps) is recorded here under the SHA-256
+//! of the proof bytes and the binding tag (the nonce for BBS+ presentations),
+//! and its response carries a [`ReplayReport`] with the earlier presentations. `replay_scope` decides
+//! which presentations are `accepted`:
+//!
+//! * `once`: only the first, by anyone;
+//! * `per_verifier`: the first to each verifier, named by `X-Zkp-Verifier`
+//!   (requests without one share a single anonymous verifier);
+//! * `unlimited`: all of them; earlier ones are still reported.
+//!
+//! `verified` keeps meaning "the proof verifies"; callers enforcing the
+//! scope must also check `replay.accepted`.
+
+use std::collections::BTreeMap;
+
+use actix_web::http::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::config::ReplayScope;
+use crate::api::ReplayReport;
+use crate::zk::ZkError;
+
+/// Who a proof is presented to: a DID, address or service name.
+pub const VERIFIER: &str = "x-zkp-verifier";
+const MAX_VERIFIER_LEN: usize = 128;
+
+fn now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// A proof being presented, identified by its bytes and binding tag.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Presentation {
+    key: Vec<u8>,
+    verifier: String,
+}
+
+impl Presentation {
+    pub fn new(proof: &[u8], binding_tag: Option<&[u8]>, verifier: String) -> Self {
+        // The marker byte keeps "no tag" apart from an all-zero tag.
+        let mut key = Sha256::digest(proof).to_vec();
+        match binding_tag {
+            Some(tag) => {
+                key.push(1);
+                key.extend_from_slice(tag);
+            },
+            None => key.push(0),
+        }
+        Self { key, verifier }
+    }
+
+    /// The verifier named by `X-Zkp-Verifier`, or the anonymous one.
+    pub fn verifier(headers: &HeaderMap) -> Result<String, ZkError> {
+        let Some(value) = headers.get(VERIFIER) else {
+            return Ok(String::new());
+        };
+        let verifier = value
+            .to_str()
+            .ok()
+            .map(str::trim)
+            .filter(|v| {
+                !v.is_empty()
+                    && v.len() <= MAX_VERIFIER_LEN
+                    && v.bytes().all(|b| b.is_ascii_alphanumeric() || b"_-.:".contains(&b))
+            })
+            .ok_or_else(|| {
+                ZkError::InvalidParameters(format!("{VERIFIER} must be 1-{MAX_VERIFIER_LEN} of [A-Za-z0-9_.:-]"))
+            })?;
+        Ok(verifier.to_ascii_lowercase())
+    }
+}
+
+/// Earlier presentations of one proof and binding tag.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Presented {
+    first_at: u64,
+    count: u64,
+    by_verifier: BTreeMap<String, u64>,
+}
+
+pub struct ReplayRegistry {
+    presentations: sled::Tree,
+    scope: ReplayScope,
+}
+
+impl ReplayRegistry {
+    /// Open (or create) the registry at `path`.
+    pub fn open(path: impl AsRef<std::path::Path>, scope: ReplayScope) -> sled::Result<Self> {
+        let db = sled::open(path)?;
+        Ok(Self { presentations: db.open_tree("presentations")?, scope })
+    }
+
+    /// Record `presentation` and report the ones before it.
+    pub fn present(&self, presentation: &Presentation) -> sled::Result<ReplayReport> {
+        let now = now();
+        let previous = self.presentations.fetch_and_update(&presentation.key, |old| {
+            let mut presented = old.and_then(|bytes| serde_json::from_slice(bytes).ok()).unwrap_or(Presented {
+                first_at: now,
+                ..Presented::default()
+            });
+            presented.count += 1;
+            *presented.by_verifier.entry(presentation.verifier.clone()).or_default() += 1;
+            Some(serde_json::to_vec(&presented).expect("presentations always serialize"))
+        })?;
+        let previous: Option<Presented> = previous.and_then(|bytes| serde_json::from_slice(&bytes).ok());
+        let previous_presentations = previous.as_ref().map_or(0, |p| p.count);
+        let previous_by_verifier = previous.as_ref().and_then(|p| p.by_verifier.get(&presentation.verifier).copied()).unwrap_or(0);
+        let accepted = match self.scope {
+            ReplayScope::Once => previous_presentations == 0,
+            ReplayScope::PerVerifier => previous_by_verifier == 0,
+            ReplayScope::Unlimited => true,
+        };
+        Ok(ReplayReport {
+            accepted,
+            previous_presentations,
+            previous_by_verifier,
+            first_presented_at: previous.map(|p| p.first_at),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    fn presentations(scope: ReplayScope, verifiers: &[&str]) -> Vec<(bool, u64, u64)> {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = ReplayRegistry::open(dir.path(), scope).unwrap();
+        verifiers
+            .iter()
+            .map(|verifier| {
+                let report = registry.present(&Presentation::new(b"proof", Some(&[7; 32][..]), verifier.to_string())).unwrap();
+                (report.accepted, report.previous_presentations, report.previous_by_verifier)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn scopes_decide_which_presentations_are_accepted() {
+        let verifiers = ["a", "b", "a"];
+        assert_eq!(presentations(ReplayScope::Once, &verifiers), [(true, 0, 0), (false, 1, 0), (false, 2, 1)]);
+        assert_eq!(presentations(ReplayScope::PerVerifier, &verifiers), [(true, 0, 0), (true, 1, 0), (false, 2, 1)]);
+        assert_eq!(presentations(ReplayScope::Unlimited, &verifiers), [(true, 0, 0), (true, 1, 0), (true, 2, 1)]);
+    }
+
+    #[test]
+    fn presentations_are_keyed_by_proof_and_tag_and_persist() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = ReplayRegistry::open(dir.path(), ReplayScope::Once).unwrap();
+        let first = registry.present(&Presentation::new(b"proof", None, String::new())).unwrap();
+        assert!(first.accepted && first.first_presented_at.is_none());
+        assert!(registry.present(&Presentation::new(b"proof", Some(&[0; 32][..]), String::new())).unwrap().accepted);
+        assert!(registry.present(&Presentation::new(b"other", None, String::new())).unwrap().accepted);
+        drop(registry);
+
+        let registry = ReplayRegistry::open(dir.path(), ReplayScope::Once).unwrap();
+        let again = registry.present(&Presentation::new(b"proof", None, String::new())).unwrap();
+        assert!(!again.accepted);
+        assert_eq!(again.previous_presentations, 1);
+        assert!(again.first_presented_at.is_some());
+    }
+
+    #[test]
+    fn verifiers_come_from_the_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(Presentation::verifier(&headers).unwrap(), "");
+        headers.insert(HeaderName::from_static(VERIFIER), HeaderValue::from_static("did:ethr:0xABC"));
+        assert_eq!(Presentation::verifier(&headers).unwrap(), "did:ethr:0xabc");
+        headers.insert(HeaderName::from_static(VERIFIER), HeaderValue::from_static("two words"));
+        assert_eq!(Presentation::verifier(&headers).unwrap_err().code(), "invalid_parameters");
+    }
+}
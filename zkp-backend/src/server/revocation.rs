@@ -0,0 +1,180 @@
+//! Persistent revocation registry (`revocation_store`).
+//!
+//! The accumulator trapdoor is derived from `ZKP_REVOCATION_KEY_HEX`, and the
+//! accumulated elements (hashes of the handles, never the handles) and the
+//! epoch live in a sled database. A restart therefore keeps the published
+//! accumulator value and public key, every witness stays valid, and revoked
+//! handles stay revoked. The public key is stored too, so a store opened
+//! with another key is refused instead of silently forking the accumulator.
+
+use std::sync::{Mutex, MutexGuard};
+
+use ark_bls12_381::Fr;
+
+use crate::zk::revocation_accumulator::{element_from_bytes, element_to_bytes, RevocationRegistry};
+use crate::zk::ZkError;
+
+const EPOCH: &[u8] = b"epoch";
+const PUBLIC_KEY: &[u8] = b"public_key";
+
+pub struct RevocationStore {
+    registry: Mutex<RevocationRegistry>,
+    /// Live elements, keyed by their encoding.
+    members: sled::Tree,
+    /// [`EPOCH`] and [`PUBLIC_KEY`].
+    meta: sled::Tree,
+}
+
+#[derive(Debug)]
+pub enum RevocationError {
+    Registry(ZkError),
+    Store(String),
+}
+
+impl std::fmt::Display for RevocationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Registry(e) => e.fmt(f),
+            Self::Store(e) => write!(f, "revocation store: {e}"),
+        }
+    }
+}
+
+impl From<sled::Error> for RevocationError {
+    fn from(e: sled::Error) -> Self {
+        Self::Store(e.to_string())
+    }
+}
+
+impl RevocationStore {
+    /// Open (or create) the store at `path` for the registry derived from `seed`.
+    pub fn open(path: impl AsRef<std::path::Path>, seed: &[u8]) -> Result<Self, RevocationError> {
+        let db = sled::open(path)?;
+        let (members, meta) = (db.open_tree("members")?, db.open_tree("meta")?);
+        let public_key = RevocationRegistry::from_seed(seed).public_key().to_bytes();
+        match meta.get(PUBLIC_KEY)? {
+            Some(stored) if *stored != *public_key => {
+                return Err(RevocationError::Store("the store was created with another revocation key".into()));
+            },
+            Some(_) => {},
+            None => {
+                meta.insert(PUBLIC_KEY, public_key)?;
+            },
+        }
+        let elements = members
+            .iter()
+            .keys()
+            .map(|key| element_from_bytes(&key?).map_err(RevocationError::Registry))
+            .collect::<Result<Vec<Fr>, _>>()?;
+        let epoch = match meta.get(EPOCH)? {
+            Some(bytes) => u64::from_be_bytes(bytes.as_ref().try_into().map_err(|_| RevocationError::Store("corrupt epoch".into()))?),
+            None => 0,
+        };
+        let registry = RevocationRegistry::restore(seed, elements, epoch).map_err(RevocationError::Registry)?;
+        Ok(Self { registry: Mutex::new(registry), members, meta })
+    }
+
+    /// The registry, for reads; changes go through [`Self::add`] and [`Self::remove`].
+    pub fn registry(&self) -> MutexGuard<'_, RevocationRegistry> {
+        self.registry.lock().expect("revocation registry lock")
+    }
+
+    /// Accumulate `y` and persist it.
+    pub fn add(&self, y: Fr) -> Result<MutexGuard<'_, RevocationRegistry>, RevocationError> {
+        let mut registry = self.registry();
+        registry.add(y).map_err(RevocationError::Registry)?;
+        if let Err(e) = self.persist(&registry, &y, true) {
+            registry.remove(&y).expect("just added");
+            return Err(e);
+        }
+        Ok(registry)
+    }
+
+    /// Remove (revoke) `y` and persist the removal.
+    pub fn remove(&self, y: &Fr) -> Result<MutexGuard<'_, RevocationRegistry>, RevocationError> {
+        let mut registry = self.registry();
+        registry.remove(y).map_err(RevocationError::Registry)?;
+        if let Err(e) = self.persist(&registry, y, false) {
+            registry.add(*y).expect("just removed");
+            return Err(e);
+        }
+        Ok(registry)
+    }
+
+    fn persist(&self, registry: &RevocationRegistry, y: &Fr, added: bool) -> Result<(), RevocationError> {
+        let key = element_to_bytes(y);
+        if added {
+            self.members.insert(key, &[])?;
+        } else {
+            self.members.remove(key)?;
+        }
+        self.meta.insert(EPOCH, &registry.epoch().to_be_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::revocation_accumulator::handle_element;
+
+    #[test]
+    fn registry_survives_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (handle_element("vc:a"), handle_element("vc:b"));
+        let (value, witness) = {
+            let store = RevocationStore::open(dir.path(), &[1; 32]).unwrap();
+            drop(store.add(a).unwrap());
+            drop(store.add(b).unwrap());
+            drop(store.remove(&b).unwrap());
+            let registry = store.registry();
+            (registry.value(), registry.witness(&a).unwrap())
+        };
+
+        let store = RevocationStore::open(dir.path(), &[1; 32]).unwrap();
+        let registry = store.registry();
+        assert_eq!((registry.value(), registry.epoch()), (value, 3));
+        assert!(witness.verify(&registry.public_key(), &registry.value(), &a).is_ok());
+        assert!(!registry.contains(&b), "revocations persist");
+        drop(registry);
+        drop(store);
+
+        assert!(matches!(RevocationStore::open(dir.path(), &[2; 32]), Err(RevocationError::Store(_))));
+    }
+
+    #[actix_web::test]
+    async fn revoked_holders_cannot_prove_against_an_older_epoch() {
+        use actix_web::http::StatusCode;
+        use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+        use actix_web::{web, App};
+        use serde_json::{json, Value};
+
+        use super::super::{revocation_prove, revocation_verify, ProofPool};
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = RevocationStore::open(dir.path(), &[1; 32]).unwrap();
+        drop(store.add(handle_element("vc:a")).unwrap());
+        let witness = hex::encode(store.add(handle_element("vc:b")).unwrap().witness(&handle_element("vc:b")).unwrap().to_bytes());
+        let store = web::Data::new(store);
+        let app = init_service(
+            App::new().app_data(web::Data::new(ProofPool::new(2, 8).unwrap())).app_data(store.clone()).service(revocation_prove).service(revocation_verify),
+        )
+        .await;
+        macro_rules! post {
+            ($path:expr, $body:expr) => {{
+                let resp = call_service(&app, TestRequest::post().uri($path).set_json($body).to_request()).await;
+                (resp.status(), read_body_json::<Value, _>(resp).await)
+            }};
+        }
+
+        let (status, proved) = post!("/zkp/revocation/prove", json!({ "handle": "vc:b", "witness": witness }));
+        assert_eq!((status, proved["epoch"].as_u64()), (StatusCode::OK, Some(2)));
+        let verify = |epoch: Option<u64>| json!({ "commitment": proved["commitment"], "proof": proved["proof"], "epoch": epoch });
+        assert_eq!(post!("/zkp/revocation/verify", verify(Some(2))), (StatusCode::OK, json!({ "verified": true, "epoch": 2 })));
+
+        drop(store.remove(&handle_element("vc:b")).unwrap());
+        let (status, body) = post!("/zkp/revocation/verify", verify(Some(2)));
+        assert_eq!((status, body["code"].as_str()), (StatusCode::BAD_REQUEST, Some("invalid_parameters")));
+        assert_eq!(post!("/zkp/revocation/verify", verify(None)), (StatusCode::OK, json!({ "verified": false, "epoch": 3 })));
+    }
+}
@@ -0,0 +1,335 @@
+//! Structured logging.
+//!
+//! Every request runs inside a `request` span carrying its id, method and
+//! path; handlers add the proof scheme, proof size and time spent on the
+//! proof pool, and the span closes with the status and total time. Output is
+//! one line per event, human-readable or JSON.
+//!
+//! Fields whose name contains a secret-looking segment (`value`, `blinding`,
+//! `tag`, `tx`, ...) are replaced by `[redacted]` by both formatters, so a
+//! `debug!(value = v, ...)` is safe to write. Secrets must not be
+//! interpolated into the message itself.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Instant;
+
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde_json::{Map, Value};
+use tracing::field::{Empty, Field, Visit};
+use tracing::span::Record;
+use tracing::{Event, Instrument, Span, Subscriber};
+use tracing_subscriber::field::RecordFields;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::{FormatTime, SystemTime};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::EnvFilter;
+
+use super::config::{LogFormat, LogLevel};
+use super::metrics;
+
+/// Name segments that mark a field as secret.
+const SECRET_SEGMENTS: &[&str] = &["blinding", "value", "values", "tag", "secret", "witness", "opening", "openings", "tx"];
+
+pub const REDACTED: &str = "[redacted]";
+
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// `binding_tag_hex`, `value`, `blinding` are secret; `proof_size` is not.
+pub fn is_secret(field: &str) -> bool {
+    field.split('_').any(|segment| SECRET_SEGMENTS.contains(&segment))
+}
+
+/// Install the global subscriber. `RUST_LOG` overrides `level`; by default the
+/// zk library only reports warnings.
+pub fn init(level: LogLevel, format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("{},bulletproof_demo::zk=warn", level.as_str())));
+    let builder = tracing_subscriber::fmt().with_env_filter(filter);
+    match format {
+        LogFormat::Pretty => builder.fmt_fields(TextFields).init(),
+        LogFormat::Json => builder.fmt_fields(JsonFields).event_format(JsonLines).init(),
+    }
+}
+
+/// Note the proof scheme and size of the current request, on its span and
+/// in its metrics sample.
+pub fn record_proof(scheme: &'static str, proof_size: usize) {
+    let span = Span::current();
+    span.record("scheme", scheme);
+    span.record("proof_size", proof_size);
+    metrics::record_proof(scheme, proof_size);
+}
+
+/// Middleware (for `App::wrap_fn`) running each request in its own span and
+/// echoing its id in `X-Request-Id`. A well-formed incoming id is kept so
+/// logs can be joined with the caller's.
+pub fn trace_request<S, B>(req: ServiceRequest, srv: &S) -> impl Future<Output = Result<ServiceResponse<B>, actix_web::Error>>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+{
+    let request_id = req
+        .headers()
+        .get(&REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 64 && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'))
+        .map(str::to_owned)
+        .unwrap_or_else(|| {
+            let mut id = [0u8; 8];
+            OsRng.fill_bytes(&mut id);
+            hex::encode(id)
+        });
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %req.method(),
+        path = %req.path(),
+        scheme = Empty,
+        proof_size = Empty,
+        proof_ms = Empty,
+        status = Empty,
+        elapsed_ms = Empty,
+    );
+    let started = Instant::now();
+    let response = span.in_scope(|| srv.call(req));
+    async move {
+        let mut response = response.await?;
+        let status = response.status();
+        let span = Span::current();
+        span.record("status", status.as_u16());
+        span.record("elapsed_ms", started.elapsed().as_millis() as u64);
+        if status.is_server_error() {
+            tracing::error!("request failed");
+        } else {
+            tracing::info!("request completed");
+        }
+        if let Ok(id) = HeaderValue::from_str(&request_id) {
+            response.headers_mut().insert(REQUEST_ID, id);
+        }
+        Ok(response)
+    }
+    .instrument(span)
+}
+
+// ─── Human-readable fields ─────────────────────────────────────────────────
+
+/// `name=value` pairs with secrets redacted.
+pub struct TextFields;
+
+impl<'w> FormatFields<'w> for TextFields {
+    fn format_fields<R: RecordFields>(&self, writer: Writer<'w>, fields: R) -> fmt::Result {
+        let mut visitor = TextVisitor { writer, first: true, result: Ok(()) };
+        fields.record(&mut visitor);
+        visitor.result
+    }
+}
+
+struct TextVisitor<'w> {
+    writer: Writer<'w>,
+    first: bool,
+    result: fmt::Result,
+}
+
+impl Visit for TextVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.record_debug(field, &format_args!("{value}"));
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if self.result.is_err() {
+            return;
+        }
+        let sep = if self.first { "" } else { " " };
+        self.first = false;
+        self.result = match field.name() {
+            "message" => write!(self.writer, "{sep}{value:?}"),
+            name if is_secret(name) => write!(self.writer, "{sep}{name}={REDACTED}"),
+            name => write!(self.writer, "{sep}{name}={value:?}"),
+        };
+    }
+}
+
+// ─── JSON ──────────────────────────────────────────────────────────────────
+
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl JsonVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        let value = if is_secret(field.name()) { Value::from(REDACTED) } else { value };
+        self.0.insert(field.name().to_owned(), value);
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+}
+
+/// Span fields kept as a JSON object so [`JsonLines`] can merge them.
+pub struct JsonFields;
+
+impl<'w> FormatFields<'w> for JsonFields {
+    fn format_fields<R: RecordFields>(&self, mut writer: Writer<'w>, fields: R) -> fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        write!(writer, "{}", Value::Object(visitor.0))
+    }
+
+    fn add_fields(&self, current: &'w mut FormattedFields<Self>, fields: &Record<'_>) -> fmt::Result {
+        let mut visitor = JsonVisitor(serde_json::from_str(&current.fields).unwrap_or_default());
+        fields.record(&mut visitor);
+        current.fields = Value::Object(visitor.0).to_string();
+        Ok(())
+    }
+}
+
+/// One JSON object per event: timestamp, level, target, the event's fields
+/// and the fields of every enclosing span (innermost wins).
+pub struct JsonLines;
+
+impl<S, N> FormatEvent<S, N> for JsonLines
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let mut timestamp = String::new();
+        SystemTime.format_time(&mut Writer::new(&mut timestamp))?;
+        let mut fields = JsonVisitor::default();
+        event.record(&mut fields);
+
+        let mut span_fields = Map::new();
+        let mut spans = Vec::new();
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                spans.push(Value::from(span.name()));
+                if let Some(recorded) = span.extensions().get::<FormattedFields<N>>() {
+                    if let Ok(Value::Object(map)) = serde_json::from_str(&recorded.fields) {
+                        span_fields.extend(map);
+                    }
+                }
+            }
+        }
+
+        let mut line = Map::new();
+        line.insert("timestamp".into(), timestamp.into());
+        line.insert("level".into(), event.metadata().level().as_str().into());
+        line.insert("target".into(), event.metadata().target().into());
+        line.insert("fields".into(), Value::Object(fields.0));
+        if !spans.is_empty() {
+            line.insert("spans".into(), spans.into());
+            line.insert("span".into(), Value::Object(span_fields));
+        }
+        writeln!(writer, "{}", Value::Object(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Capture {
+        fn text(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    fn log_secrets() {
+        let span = tracing::info_span!("request", request_id = "r1", proof_size = Empty);
+        let _guard = span.enter();
+        span.record("proof_size", 672u64);
+        tracing::info!(value = 42u64, blinding_hex = "aa", binding_tag_hex = "bb", verified = true, "committed");
+    }
+
+    #[test]
+    fn secret_fields_are_redacted_in_text() {
+        let out = Capture::default();
+        let writer = out.clone();
+        // No timestamp: its digits could contain the "42" the test looks for.
+        let subscriber = tracing_subscriber::fmt()
+            .fmt_fields(TextFields)
+            .with_ansi(false)
+            .without_time()
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, log_secrets);
+        let text = out.text();
+        assert!(text.contains("committed value=[redacted] blinding_hex=[redacted] binding_tag_hex=[redacted] verified=true"), "{text}");
+        assert!(text.contains("request_id=\"r1\" proof_size=672"), "{text}");
+        assert!(!text.contains("42") && !text.contains("\"aa\""), "{text}");
+    }
+
+    #[test]
+    fn secret_fields_are_redacted_in_json() {
+        let out = Capture::default();
+        let writer = out.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .fmt_fields(JsonFields)
+            .event_format(JsonLines)
+            .with_writer(move || writer.clone())
+            .finish();
+        tracing::subscriber::with_default(subscriber, log_secrets);
+        let line: Value = serde_json::from_str(out.text().trim()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["fields"]["message"], "committed");
+        assert_eq!(line["fields"]["value"], REDACTED);
+        assert_eq!(line["fields"]["binding_tag_hex"], REDACTED);
+        assert_eq!(line["fields"]["verified"], true);
+        assert_eq!(line["span"], serde_json::json!({ "request_id": "r1", "proof_size": 672 }));
+    }
+
+    #[test]
+    fn secret_names() {
+        for name in ["value", "blinding", "blinding_hex", "binding_tag_hex", "secret_key", "values", "tx_hash"] {
+            assert!(is_secret(name), "{name}");
+        }
+        for name in ["proof_size", "verified", "request_id", "commitment", "message"] {
+            assert!(!is_secret(name), "{name}");
+        }
+    }
+}
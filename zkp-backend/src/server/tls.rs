@@ -0,0 +1,313 @@
+//! HTTPS with rustls (`tls_cert` and `tls_key`).
+//!
+//! The certificate chain and its key are PEM files. Both are re-read every
+//! [`RELOAD_INTERVAL`]; when either changed, the new pair is served to new
+//! connections, so a renewed certificate needs no restart. A pair that does
+//! not parse or whose halves do not match is logged and the previous one
+//! kept, which also covers a renewal caught between writing the two files.
+//!
+//! With `tls_client_ca` clients present a certificate signed by one of
+//! those CAs (mutual TLS); this is how the Node `backend/api` service is
+//! meant to call the backend. `tls_client_auth = "required"` refuses the
+//! handshake without one, `"optional"` also lets anonymous clients in. A
+//! verified client certificate counts as authentication for
+//! [`auth`](super::auth) and gets its own [`limits`](super::limits)
+//! buckets. The client CA file is read once, at startup.
+
+use std::any::Any;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use actix_tls::accept::rustls_0_23::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::rt::net::TcpStream;
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::danger::ClientCertVerifier;
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{RootCertStore, ServerConfig};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use super::config::{ClientAuth, Config};
+
+/// How often the certificate and key files are checked for changes.
+pub const RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Connection data of a client whose certificate `tls_client_ca` verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientCertificate {
+    /// SHA-256 of the leaf certificate.
+    pub fingerprint: [u8; 32],
+}
+
+/// `HttpServer::on_connect` hook that records [`ClientCertificate`].
+///
+/// rustls only keeps peer certificates that passed the client verifier, so
+/// anonymous and plain HTTP connections get nothing.
+pub fn on_connect(conn: &dyn Any, data: &mut Extensions) {
+    let Some(stream) = conn.downcast_ref::<TlsStream<TcpStream>>() else {
+        return;
+    };
+    if let Some(leaf) = stream.get_ref().1.peer_certificates().and_then(|chain| chain.first()) {
+        data.insert(ClientCertificate { fingerprint: Sha256::digest(leaf).into() });
+    }
+}
+
+/// The rustls configuration for `config`, and the certificate it serves so
+/// the caller can [`watch`](ReloadingCert::watch) it.
+pub fn server_config(config: &Config) -> Result<(ServerConfig, Arc<ReloadingCert>), String> {
+    let (Some(cert_path), Some(key_path)) = (&config.tls_cert, &config.tls_key) else {
+        return Err("tls_cert and tls_key must be set together".into());
+    };
+    let provider = Arc::new(ring::default_provider());
+    let cert = Arc::new(ReloadingCert::load(cert_path.clone(), key_path.clone(), provider.clone())?);
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?;
+    let builder = match &config.tls_client_ca {
+        None => builder.with_no_client_auth(),
+        Some(path) => builder.with_client_cert_verifier(client_verifier(path, config.tls_client_auth, provider)?),
+    };
+    Ok((builder.with_cert_resolver(cert.clone()), cert))
+}
+
+fn client_verifier(path: &Path, auth: ClientAuth, provider: Arc<CryptoProvider>) -> Result<Arc<dyn ClientCertVerifier>, String> {
+    let context = |e: &dyn fmt::Display| format!("tls_client_ca {}: {e}", path.display());
+    let mut roots = RootCertStore::empty();
+    for ca in CertificateDer::pem_file_iter(path).map_err(|e| context(&e))? {
+        roots.add(ca.map_err(|e| context(&e))?).map_err(|e| context(&e))?;
+    }
+    let builder = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+    let builder = match auth {
+        ClientAuth::Required => builder,
+        ClientAuth::Optional => builder.allow_unauthenticated(),
+    };
+    builder.build().map_err(|e| context(&e))
+}
+
+/// The served certificate, swapped in place when its files change.
+pub struct ReloadingCert {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    provider: Arc<CryptoProvider>,
+    current: RwLock<Loaded>,
+}
+
+struct Loaded {
+    /// File contents the key was built from, to notice changes.
+    pem: (Vec<u8>, Vec<u8>),
+    key: Arc<CertifiedKey>,
+}
+
+impl ReloadingCert {
+    fn load(cert_path: PathBuf, key_path: PathBuf, provider: Arc<CryptoProvider>) -> Result<Self, String> {
+        let pem = read_pair(&cert_path, &key_path)?;
+        let key = Arc::new(certified_key(&provider, &pem)?);
+        Ok(Self { cert_path, key_path, provider, current: RwLock::new(Loaded { pem, key }) })
+    }
+
+    /// Re-read both files; `Ok(true)` when a different pair is now served.
+    pub fn reload(&self) -> Result<bool, String> {
+        let pem = read_pair(&self.cert_path, &self.key_path)?;
+        if self.current.read().unwrap().pem == pem {
+            return Ok(false);
+        }
+        let key = Arc::new(certified_key(&self.provider, &pem)?);
+        *self.current.write().unwrap() = Loaded { pem, key };
+        Ok(true)
+    }
+
+    /// [`reload`](Self::reload) every [`RELOAD_INTERVAL`] on a background thread.
+    pub fn watch(self: Arc<Self>) {
+        std::thread::Builder::new()
+            .name("tls-reload".into())
+            .spawn(move || {
+                let mut last_error = None;
+                loop {
+                    std::thread::sleep(RELOAD_INTERVAL);
+                    match self.reload() {
+                        Ok(reloaded) => {
+                            if reloaded {
+                                info!(cert = %self.cert_path.display(), "TLS certificate reloaded");
+                            }
+                            last_error = None;
+                        },
+                        // Once per distinct problem, not every interval.
+                        Err(e) if last_error.as_ref() != Some(&e) => {
+                            warn!(error = %e, "TLS certificate not reloaded, still serving the previous one");
+                            last_error = Some(e);
+                        },
+                        Err(_) => {},
+                    }
+                }
+            })
+            .expect("failed to spawn the TLS reload thread");
+    }
+}
+
+impl ResolvesServerCert for ReloadingCert {
+    fn resolve(&self, _hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().key.clone())
+    }
+}
+
+// Hand-written so the private key never ends up in a log line.
+impl fmt::Debug for ReloadingCert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReloadingCert").field("cert_path", &self.cert_path).field("key_path", &self.key_path).finish()
+    }
+}
+
+fn read_pair(cert_path: &Path, key_path: &Path) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let read = |path: &Path| std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()));
+    Ok((read(cert_path)?, read(key_path)?))
+}
+
+fn certified_key(provider: &CryptoProvider, (cert_pem, key_pem): &(Vec<u8>, Vec<u8>)) -> Result<CertifiedKey, String> {
+    let chain = CertificateDer::pem_slice_iter(cert_pem)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("tls_cert: {e}"))?;
+    if chain.is_empty() {
+        return Err("tls_cert: no certificate found".into());
+    }
+    let key = PrivateKeyDer::from_pem_slice(key_pem).map_err(|e| format!("tls_key: {e}"))?;
+    CertifiedKey::from_der(chain, key, provider).map_err(|e| format!("tls_cert and tls_key: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{web, App, HttpRequest, HttpServer};
+    use rcgen::{BasicConstraints, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair};
+
+    /// A throwaway CA issuing server and client certificates.
+    struct Pki {
+        ca: rcgen::Certificate,
+        ca_key: KeyPair,
+    }
+
+    impl Pki {
+        fn new() -> Self {
+            let ca_key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(Vec::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            Self { ca: params.self_signed(&ca_key).unwrap(), ca_key }
+        }
+
+        /// PEM certificate and key for `names`, usable as `usage`.
+        fn issue(&self, names: &[&str], usage: ExtendedKeyUsagePurpose) -> (String, String) {
+            let key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(names.iter().map(|n| n.to_string()).collect::<Vec<_>>()).unwrap();
+            params.extended_key_usages = vec![usage];
+            (params.signed_by(&key, &self.ca, &self.ca_key).unwrap().pem(), key.serialize_pem())
+        }
+    }
+
+    /// Write `files` into a fresh directory and point a config at them.
+    fn tls_config(dir: &Path, files: &[(&str, &str)], client_auth: ClientAuth) -> Config {
+        for (name, contents) in files {
+            std::fs::write(dir.join(name), contents).unwrap();
+        }
+        let has = |name: &str| files.iter().any(|(n, _)| *n == name).then(|| dir.join(name));
+        Config {
+            tls_cert: Some(dir.join("cert.pem")),
+            tls_key: Some(dir.join("key.pem")),
+            tls_client_ca: has("ca.pem"),
+            tls_client_auth: client_auth,
+            ..Config::default()
+        }
+    }
+
+    fn served(cert: &ReloadingCert) -> CertificateDer<'static> {
+        cert.current.read().unwrap().key.cert[0].clone()
+    }
+
+    #[test]
+    fn certificates_reload_when_their_files_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let pki = Pki::new();
+        let (cert_pem, key_pem) = pki.issue(&["localhost"], ExtendedKeyUsagePurpose::ServerAuth);
+        let config = tls_config(dir.path(), &[("cert.pem", &cert_pem), ("key.pem", &key_pem)], ClientAuth::Required);
+        let (_, cert) = server_config(&config).unwrap();
+        let first = served(&cert);
+        assert_eq!(cert.reload(), Ok(false));
+
+        // A renewal caught halfway: the new certificate next to the old key.
+        let (new_cert, new_key) = pki.issue(&["localhost"], ExtendedKeyUsagePurpose::ServerAuth);
+        std::fs::write(dir.path().join("cert.pem"), &new_cert).unwrap();
+        let err = cert.reload().unwrap_err();
+        assert!(err.contains("tls_cert and tls_key"), "{err}");
+        assert_eq!(served(&cert), first);
+
+        std::fs::write(dir.path().join("key.pem"), &new_key).unwrap();
+        assert_eq!(cert.reload(), Ok(true));
+        assert_ne!(served(&cert), first);
+
+        std::fs::write(dir.path().join("key.pem"), "not a key").unwrap();
+        assert!(cert.reload().unwrap_err().contains("tls_key"));
+        let config = Config { tls_key: Some(dir.path().join("missing.pem")), ..config };
+        assert!(server_config(&config).is_err());
+    }
+
+    async fn https_server(config: &Config) -> String {
+        let (tls, _) = server_config(config).unwrap();
+        let server = HttpServer::new(|| {
+            App::new().route(
+                "/",
+                web::get().to(|req: HttpRequest| async move {
+                    match req.conn_data::<ClientCertificate>() {
+                        Some(client) => hex::encode(client.fingerprint),
+                        None => "anonymous".into(),
+                    }
+                }),
+            )
+        })
+        .workers(1)
+        .on_connect(on_connect)
+        .listen_rustls_0_23(std::net::TcpListener::bind("127.0.0.1:0").unwrap(), tls)
+        .unwrap();
+        let url = format!("https://localhost:{}/", server.addrs()[0].port());
+        actix_web::rt::spawn(server.run());
+        url
+    }
+
+    fn client(ca_pem: &str, identity: Option<(&str, &str)>) -> reqwest::Client {
+        let mut builder = reqwest::Client::builder()
+            .use_rustls_tls()
+            .tls_built_in_root_certs(false)
+            .add_root_certificate(reqwest::Certificate::from_pem(ca_pem.as_bytes()).unwrap())
+            .resolve("localhost", "127.0.0.1:0".parse().unwrap());
+        if let Some((cert, key)) = identity {
+            builder = builder.identity(reqwest::Identity::from_pem(format!("{cert}{key}").as_bytes()).unwrap());
+        }
+        builder.build().unwrap()
+    }
+
+    #[actix_web::test]
+    async fn client_certificates_identify_the_caller() {
+        let dir = tempfile::tempdir().unwrap();
+        let pki = Pki::new();
+        let ca_pem = pki.ca.pem();
+        let (cert_pem, key_pem) = pki.issue(&["localhost"], ExtendedKeyUsagePurpose::ServerAuth);
+        let (client_cert, client_key) = pki.issue(&["backend-api"], ExtendedKeyUsagePurpose::ClientAuth);
+        let files = [("cert.pem", cert_pem.as_str()), ("key.pem", &key_pem), ("ca.pem", &ca_pem)];
+
+        let url = https_server(&tls_config(dir.path(), &files, ClientAuth::Required)).await;
+        let body = client(&ca_pem, Some((&client_cert, &client_key))).get(&url).send().await.unwrap().text().await.unwrap();
+        let leaf = CertificateDer::from_pem_slice(client_cert.as_bytes()).unwrap();
+        assert_eq!(body, hex::encode(Sha256::digest(&leaf)));
+        assert!(client(&ca_pem, None).get(&url).send().await.is_err());
+
+        // A certificate from another CA is refused even when anonymous clients are welcome.
+        let url = https_server(&tls_config(dir.path(), &files, ClientAuth::Optional)).await;
+        let body = client(&ca_pem, None).get(&url).send().await.unwrap().text().await.unwrap();
+        assert_eq!(body, "anonymous");
+        let (stranger_cert, stranger_key) = Pki::new().issue(&["stranger"], ExtendedKeyUsagePurpose::ClientAuth);
+        assert!(client(&ca_pem, Some((&stranger_cert, &stranger_key))).get(&url).send().await.is_err());
+    }
+}
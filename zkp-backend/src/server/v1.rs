@@ -0,0 +1,805 @@
+//! Versioned `/v1` API.
+//!
+//! The original `/zkp/*` endpoints grew one route per variation (with or
+//! without blinding, binding tag, 4-limb or BP⁺), each with its own body.
+//! `/v1` groups them by resource, takes the bodies from [`api`], and answers
+//! every error with `{ "error", "code" }`. The legacy routes keep working but
+//! carry `Deprecation` and `Link: rel="successor-version"` headers pointing
+//! at their replacement.
+
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::error::{InternalError, JsonPayloadError, PayloadError};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse, Responder};
+use crate::{api, zk};
+use curve25519_dalek_ng::ristretto::CompressedRistretto as NgCompressed;
+use serde_json::{json, Value};
+use tracing::{debug, error};
+
+use super::issued::{Issue, IssuedStore, PurgeError, MAX_LIST_LIMIT};
+use super::proof_pool::ProofPool;
+use super::replay::ReplayRegistry;
+use super::telemetry;
+use super::{issue_context, offload, presentation, presented_response, record_issue, zk_error};
+use api::{
+    binding_tag, blinding, commitments_hex, hex32, proof_bytes, MAX_PROOF_BYTES, TX_HASH_LIMBS,
+    ErrorResponse, IssuedList, IssuedQuery, PurgeResponse, RangeProofRequest, RangeProofResponse, RangeProofVerifyRequest,
+    Scheme, TxHashCommitmentRequest, TxHashCommitmentResponse, TxHashCommitmentVerifyRequest, ValueCommitmentRequest,
+    ValueCommitmentResponse, ValueCommitmentVerifyRequest,
+};
+use zk::ZkError;
+
+/// `Deprecation` value for the legacy routes (RFC 9745): deprecated since 2026-10-18.
+const LEGACY_DEPRECATED_AT: &str = "@1792281600";
+
+/// Legacy route → `/v1` replacement.
+const LEGACY_ROUTES: &[(&str, &str)] = &[
+    ("/zkp/commit-value", "/v1/value-commitments"),
+    ("/zkp/generate-value-commitment", "/v1/value-commitments"),
+    ("/zkp/generate-value-commitment-with-blinding", "/v1/value-commitments"),
+    ("/zkp/generate-value-commitment-with-binding", "/v1/value-commitments"),
+    ("/zkp/verify-value", "/v1/value-commitments/verify"),
+    ("/zkp/verify-value-commitment", "/v1/value-commitments/verify"),
+    ("/zkp/generate", "/v1/tx-hash-commitments"),
+    ("/zkp/commit-tx-hash", "/v1/tx-hash-commitments"),
+    ("/zkp/verify", "/v1/tx-hash-commitments/verify"),
+    ("/zkp/prove_plus", "/v1/range-proofs"),
+    ("/zkp/generate_bp4", "/v1/range-proofs"),
+    ("/zkp/verify_plus", "/v1/range-proofs/verify"),
+    ("/zkp/verify_bp4", "/v1/range-proofs/verify"),
+];
+
+/// The `/v1` replacement for a deprecated route, if `path` is one.
+pub fn successor_of(path: &str) -> Option<&'static str> {
+    LEGACY_ROUTES.iter().find(|(legacy, _)| *legacy == path).map(|(_, successor)| *successor)
+}
+
+/// Add the deprecation headers for a legacy route answered with `headers`.
+pub fn mark_deprecated(headers: &mut HeaderMap, successor: &str) {
+    headers.insert(HeaderName::from_static("deprecation"), HeaderValue::from_static(LEGACY_DEPRECATED_AT));
+    if let Ok(link) = HeaderValue::from_str(&format!("<{successor}>; rel=\"successor-version\"")) {
+        headers.insert(HeaderName::from_static("link"), link);
+    }
+}
+
+/// Mount the `/v1` scope. Its JSON errors get the uniform body, so it
+/// carries its own copy of the body size limit.
+pub fn configure(cfg: &mut web::ServiceConfig, json_limit: usize) {
+    cfg.service(
+        web::scope("/v1")
+            .app_data(web::JsonConfig::default().limit(json_limit).error_handler(|err, _req| {
+                let response = match err {
+                    JsonPayloadError::Overflow { .. }
+                    | JsonPayloadError::OverflowKnownLength { .. }
+                    | JsonPayloadError::Payload(PayloadError::Overflow) => {
+                        HttpResponse::PayloadTooLarge().json(error_body(err.to_string(), "payload_too_large"))
+                    },
+                    _ => HttpResponse::BadRequest().json(error_body(err.to_string(), "invalid_request")),
+                };
+                InternalError::from_response(err, response).into()
+            }))
+            .app_data(web::QueryConfig::default().error_handler(|err, _req| {
+                let response = HttpResponse::BadRequest().json(error_body(err.to_string(), "invalid_request"));
+                InternalError::from_response(err, response).into()
+            }))
+            .service(openapi_document)
+            .service(create_value_commitment)
+            .service(verify_value_commitment)
+            .service(create_tx_hash_commitment)
+            .service(verify_tx_hash_commitment)
+            .service(create_range_proof)
+            .service(verify_range_proof)
+            .service(list_issued)
+            .service(purge_issued)
+            .default_service(web::to(|| async {
+                HttpResponse::NotFound().json(error_body("no such endpoint".into(), "not_found"))
+            })),
+    );
+}
+
+fn error_body(error: String, code: &str) -> ErrorResponse {
+    ErrorResponse { error, code: code.into() }
+}
+
+/// Metric and log label of a range proof scheme: classic Bulletproofs range
+/// proofs over a tx hash are 4-limb R1CS proofs.
+fn scheme_label(scheme: Scheme) -> &'static str {
+    match scheme {
+        Scheme::Bulletproofs => "r1cs_4limb",
+        Scheme::BulletproofsPlus => "bulletproofs_plus",
+    }
+}
+
+/// A freshly generated proof that does not verify is our bug, not the caller's.
+fn self_check_failed(what: &str) -> HttpResponse {
+    error!("{what} failed its own verification");
+    HttpResponse::InternalServerError().json(error_body(format!("{what} failed its own verification"), "self_check_failed"))
+}
+
+// ─── Issued commitment record ──────────────────────────────────────────────
+
+fn issued_store_disabled() -> HttpResponse {
+    HttpResponse::NotFound().json(error_body("the issued commitment store is not enabled".into(), "issued_store_disabled"))
+}
+
+#[get("/issued")]
+async fn list_issued(store: Option<web::Data<IssuedStore>>, query: web::Query<IssuedQuery>) -> impl Responder {
+    let Some(store) = store else {
+        return issued_store_disabled();
+    };
+    match store.list(&query) {
+        Ok(records) => HttpResponse::Ok().json(IssuedList { records }),
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[delete("/issued")]
+async fn purge_issued(store: Option<web::Data<IssuedStore>>, query: web::Query<IssuedQuery>) -> impl Responder {
+    let Some(store) = store else {
+        return issued_store_disabled();
+    };
+    match store.purge(&query) {
+        Ok(purged) => HttpResponse::Ok().json(PurgeResponse { purged }),
+        Err(PurgeError::Invalid(e)) => zk_error(&e),
+        Err(e @ PurgeError::Store(_)) => {
+            error!(error = %e, "purging issued commitments failed");
+            HttpResponse::InternalServerError().json(error_body(e.to_string(), "store_error"))
+        },
+    }
+}
+
+// ─── Value commitments ─────────────────────────────────────────────────────
+
+#[post("/value-commitments")]
+async fn create_value_commitment(
+    pool: web::Data<ProofPool>,
+    store: Option<web::Data<IssuedStore>>,
+    http: HttpRequest,
+    req: web::Json<ValueCommitmentRequest>,
+) -> impl Responder {
+    use zk::pedersen::prove_value_commitment_with_binding_and_range;
+
+    debug!(bit_range = req.bit_range, "creating value commitment");
+    let req = req.into_inner();
+    let inputs = blinding(&req.blinding_hex, "blinding_hex")
+        .and_then(|b| Ok((b, binding_tag(&req.binding_tag_hex)?, issue_context(&store, &http)?)));
+    let (blinding, tag, context) = match inputs {
+        Ok(inputs) => inputs,
+        Err(e) => return zk_error(&e),
+    };
+    let (value, bit_range) = (req.value, req.bit_range);
+    let result = match offload(&pool, move || {
+        prove_value_commitment_with_binding_and_range(value, blinding, tag.as_ref().map(|t| &t[..]), bit_range)
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok((_, _, false)) => self_check_failed("value commitment proof"),
+        Ok((commitment, proof, true)) => {
+            telemetry::record_proof("bulletproofs", proof.len());
+            let response = ValueCommitmentResponse {
+                commitment: hex::encode(commitment.as_bytes()),
+                proof: hex::encode(proof),
+                blinding_hex: hex::encode(blinding.as_bytes()),
+                bit_range,
+            };
+            let issue = Issue {
+                kind: "value_commitment",
+                scheme: "bulletproofs",
+                commitments: vec![response.commitment.clone()],
+                proof: response.proof.clone(),
+                binding_tag: tag,
+            };
+            record_issue(&store, context, issue);
+            HttpResponse::Ok().json(response)
+        },
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[post("/value-commitments/verify")]
+async fn verify_value_commitment(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    req: web::Json<ValueCommitmentVerifyRequest>,
+) -> impl Responder {
+    use zk::pedersen::verify_value_commitment_with_binding_and_range;
+
+    debug!(bit_range = req.bit_range, "verifying value commitment");
+    let inputs = hex32(&req.commitment, "commitment").and_then(|c| {
+        let (proof, tag) = (proof_bytes(&req.proof)?, binding_tag(&req.binding_tag_hex)?);
+        let presentation = presentation(&registry, &http, &proof, tag.as_ref().map(|t| &t[..]))?;
+        Ok((NgCompressed(c), proof, tag, presentation))
+    });
+    let (commitment, proof, tag, presentation) = match inputs {
+        Ok(inputs) => inputs,
+        Err(e) => return zk_error(&e),
+    };
+    let bit_range = req.bit_range;
+    telemetry::record_proof("bulletproofs", proof.len());
+    let result = match offload(&pool, move || {
+        verify_value_commitment_with_binding_and_range(commitment, proof, tag.as_ref().map(|t| &t[..]), bit_range)
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "Value commitment")
+}
+
+// ─── Transaction-hash commitments ──────────────────────────────────────────
+
+#[post("/tx-hash-commitments")]
+async fn create_tx_hash_commitment(
+    pool: web::Data<ProofPool>,
+    store: Option<web::Data<IssuedStore>>,
+    http: HttpRequest,
+    req: web::Json<TxHashCommitmentRequest>,
+) -> impl Responder {
+    use zk::txid_pedersen_proof::prove_txid_commitment_with_binding;
+
+    let inputs = hex32(req.tx_hash.trim(), "tx_hash")
+        .and_then(|h| Ok((h, binding_tag(&req.binding_tag_hex)?, issue_context(&store, &http)?)));
+    let (tx_hash, tag, context) = match inputs {
+        Ok(inputs) => inputs,
+        Err(e) => return zk_error(&e),
+    };
+    let tx_id = curve25519_dalek_ng::scalar::Scalar::from_bytes_mod_order(tx_hash);
+    let result = match offload(&pool, move || prove_txid_commitment_with_binding(tx_id, tag.as_ref().map(|t| &t[..]))).await {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok((_, _, false)) => self_check_failed("TX hash commitment proof"),
+        Ok((commitment, proof, true)) => {
+            telemetry::record_proof("bulletproofs", proof.len());
+            let response = TxHashCommitmentResponse { commitment: hex::encode(commitment.as_bytes()), proof: hex::encode(proof) };
+            let issue = Issue {
+                kind: "tx_hash_commitment",
+                scheme: "bulletproofs",
+                commitments: vec![response.commitment.clone()],
+                proof: response.proof.clone(),
+                binding_tag: tag,
+            };
+            record_issue(&store, context, issue);
+            HttpResponse::Ok().json(response)
+        },
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[post("/tx-hash-commitments/verify")]
+async fn verify_tx_hash_commitment(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    req: web::Json<TxHashCommitmentVerifyRequest>,
+) -> impl Responder {
+    use zk::txid_pedersen_proof::verify_txid_commitment_with_binding;
+
+    let inputs = hex32(&req.commitment, "commitment").and_then(|c| {
+        let (proof, tag) = (proof_bytes(&req.proof)?, binding_tag(&req.binding_tag_hex)?);
+        let presentation = presentation(&registry, &http, &proof, tag.as_ref().map(|t| &t[..]))?;
+        Ok((NgCompressed(c), proof, tag, presentation))
+    });
+    let (commitment, proof, tag, presentation) = match inputs {
+        Ok(inputs) => inputs,
+        Err(e) => return zk_error(&e),
+    };
+    telemetry::record_proof("bulletproofs", proof.len());
+    let result = match offload(&pool, move || {
+        verify_txid_commitment_with_binding(commitment, proof, tag.as_ref().map(|t| &t[..]))
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "TX hash commitment")
+}
+
+// ─── Range proofs over the four 64-bit limbs of a transaction hash ─────────
+
+#[post("/range-proofs")]
+async fn create_range_proof(
+    pool: web::Data<ProofPool>,
+    store: Option<web::Data<IssuedStore>>,
+    http: HttpRequest,
+    req: web::Json<RangeProofRequest>,
+) -> impl Responder {
+    let (tx_hash, context) = match hex32(req.tx_hash.trim(), "tx_hash").and_then(|h| Ok((h, issue_context(&store, &http)?))) {
+        Ok(inputs) => inputs,
+        Err(e) => return zk_error(&e),
+    };
+    let scheme = req.scheme;
+    let result = match offload(&pool, move || -> Result<_, ZkError> {
+        match scheme {
+            Scheme::Bulletproofs => {
+                let (commitments, proof, verified) = zk::txid_pedersen_proof::prove_txid_commitment_4limb(tx_hash)?;
+                let commitments = commitments.iter().map(|c| hex::encode(c.as_bytes())).collect();
+                Ok((commitments, proof, verified))
+            },
+            Scheme::BulletproofsPlus => {
+                let (commitments, proof) = zk::bp_plus_pedersen::prove_txid_commitment(tx_hash)?;
+                let commitments = commitments.iter().map(|c| hex::encode(c.as_bytes())).collect();
+                Ok((commitments, proof, true))
+            },
+        }
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    match result {
+        Ok((_, _, false)) => self_check_failed("range proof"),
+        Ok((commitments, proof, true)) => {
+            telemetry::record_proof(scheme_label(scheme), proof.len());
+            let response = RangeProofResponse { scheme, commitments, proof: hex::encode(proof) };
+            let issue = Issue {
+                kind: "range_proof",
+                scheme: scheme_label(scheme),
+                commitments: response.commitments.clone(),
+                proof: response.proof.clone(),
+                binding_tag: None,
+            };
+            record_issue(&store, context, issue);
+            HttpResponse::Ok().json(response)
+        },
+        Err(e) => zk_error(&e),
+    }
+}
+
+#[post("/range-proofs/verify")]
+async fn verify_range_proof(
+    pool: web::Data<ProofPool>,
+    registry: Option<web::Data<ReplayRegistry>>,
+    http: HttpRequest,
+    req: web::Json<RangeProofVerifyRequest>,
+) -> impl Responder {
+    let inputs = commitments_hex(&req.commitments, TX_HASH_LIMBS..=TX_HASH_LIMBS).and_then(|c| {
+        let proof = proof_bytes(&req.proof)?;
+        let presentation = presentation(&registry, &http, &proof, None)?;
+        Ok((c, proof, presentation))
+    });
+    let (commitments, proof, presentation) = match inputs {
+        Ok(inputs) => inputs,
+        Err(e) => return zk_error(&e),
+    };
+    let scheme = req.scheme;
+    telemetry::record_proof(scheme_label(scheme), proof.len());
+    let result = match offload(&pool, move || match scheme {
+        Scheme::Bulletproofs => {
+            let commitments: Vec<_> = commitments.into_iter().map(NgCompressed).collect();
+            zk::txid_pedersen_proof::verify_txid_commitment_4limb(&commitments, &proof)
+        },
+        Scheme::BulletproofsPlus => {
+            let commitments = commitments.into_iter().map(curve25519_dalek::ristretto::CompressedRistretto).collect();
+            zk::bp_plus_pedersen::verify_txid_commitment(commitments, proof)
+        },
+    })
+    .await
+    {
+        Ok(r) => r,
+        Err(resp) => return resp,
+    };
+    presented_response(&registry, presentation, result, "Range proof")
+}
+
+// ─── OpenAPI ───────────────────────────────────────────────────────────────
+
+#[get("/openapi.json")]
+async fn openapi_document() -> impl Responder {
+    HttpResponse::Ok().json(openapi())
+}
+
+fn schema_ref(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn json_body(schema: &str) -> Value {
+    json!({ "content": { "application/json": { "schema": schema_ref(schema) } } })
+}
+
+/// A POST operation taking `request` and answering `response`, with the
+/// error statuses every proof endpoint shares.
+fn operation(tag: &str, summary: &str, request: &str, response: &str) -> Value {
+    let error = |description: &str| {
+        let mut body = json_body("ErrorResponse");
+        body["description"] = json!(description);
+        body
+    };
+    let mut ok = json_body(response);
+    ok["description"] = json!("Success");
+    json!({ "post": {
+        "tags": [tag],
+        "summary": summary,
+        "requestBody": { "required": true, "content": json_body(request)["content"] },
+        "responses": {
+            "200": ok,
+            "400": error("Malformed request or encoding"),
+            "413": error("Body larger than the configured JSON limit"),
+            "422": error("Well-formed but unprovable or inconsistent input"),
+            "429": error("Client rate limit exceeded; retry after `Retry-After` seconds"),
+            "503": error("Proof workers saturated; retry after `Retry-After` seconds"),
+        },
+    }})
+}
+
+/// `GET` and `DELETE` of the issued commitment record, filtered by query.
+fn issued_operations() -> Value {
+    let error = |description: &str| {
+        let mut body = json_body("ErrorResponse");
+        body["description"] = json!(description);
+        body
+    };
+    let param = |name: &str, schema: Value, description: &str| {
+        json!({ "name": name, "in": "query", "required": false, "schema": schema, "description": description })
+    };
+    let hex32 = json!({ "type": "string", "pattern": "^(0x)?[0-9a-fA-F]{64}$" });
+    let parameters = json!([
+        param("product", json!({ "type": "string", "pattern": "^0x[0-9a-fA-F]{40}$" }), "Product (escrow) address"),
+        param("commitment", hex32.clone(), "One of the record's commitments"),
+        param("binding_tag", hex32, "Binding tag of the proof"),
+        param("stage", json!({ "type": "string" }), "Stage from `X-Zkp-Stage`"),
+        param("before", json!({ "type": "integer", "format": "uint64" }), "Only records issued before this unix time"),
+    ]);
+    let operation = |summary: &str, response: &str, parameters: Value| {
+        let mut ok = json_body(response);
+        ok["description"] = json!("Success");
+        json!({
+            "tags": ["issued"],
+            "summary": summary,
+            "parameters": parameters,
+            "responses": {
+                "200": ok,
+                "400": error("Malformed filter"),
+                "401": error("Authentication required (`auth` is on)"),
+                "404": error("The issued commitment store is not enabled"),
+            },
+        })
+    };
+    let mut list_parameters = parameters.clone();
+    list_parameters.as_array_mut().expect("an array").push(param(
+        "limit",
+        json!({ "type": "integer", "minimum": 1, "maximum": MAX_LIST_LIMIT, "default": 100 }),
+        "Most records returned",
+    ));
+    let mut purge = operation("Purge issued commitments; at least one filter is required", "PurgeResponse", parameters);
+    purge["responses"]["403"] = error("Not an operator key (`auth` is on)");
+    json!({
+        "get": operation("List issued commitments, newest first", "IssuedList", list_parameters),
+        "delete": purge,
+    })
+}
+
+/// OpenAPI 3.0 description of the `/v1` API.
+pub fn openapi() -> Value {
+    let hex = |description: &str| json!({ "type": "string", "pattern": "^(0x)?([0-9a-fA-F]{2})*$", "description": description });
+    let hex32 = |description: &str| json!({ "type": "string", "pattern": "^(0x)?[0-9a-fA-F]{64}$", "description": description });
+    let proof = |description: &str| {
+        let mut schema = hex(description);
+        schema["maxLength"] = json!(2 + 2 * MAX_PROOF_BYTES);
+        schema
+    };
+    let bit_range = json!({ "type": "integer", "enum": [8, 16, 32, 64], "default": 64 });
+    let scheme = json!({
+        "type": "string",
+        "enum": ["bulletproofs", "bulletproofs_plus"],
+        "default": "bulletproofs_plus",
+        "description": "`bulletproofs`: classic R1CS proof over four limb commitments; `bulletproofs_plus`: aggregated BP⁺ range proof, each limb in [0, 2^64)",
+    });
+    let commitments = json!({ "type": "array", "items": hex32("Compressed Ristretto point"), "minItems": 4, "maxItems": 4 });
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "ZKP backend",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Pedersen commitments and Bulletproofs range proofs. All binary values are hex; a 0x prefix is accepted.",
+        },
+        "servers": [{ "url": "/v1" }],
+        "paths": {
+            "/value-commitments": operation("value-commitments", "Commit to a value and prove it lies in [0, 2^bit_range)", "ValueCommitmentRequest", "ValueCommitmentResponse"),
+            "/value-commitments/verify": operation("value-commitments", "Verify a value commitment range proof", "ValueCommitmentVerifyRequest", "VerifyResponse"),
+            "/tx-hash-commitments": operation("tx-hash-commitments", "Commit to a transaction hash and prove knowledge of it", "TxHashCommitmentRequest", "TxHashCommitmentResponse"),
+            "/tx-hash-commitments/verify": operation("tx-hash-commitments", "Verify a transaction hash commitment proof", "TxHashCommitmentVerifyRequest", "VerifyResponse"),
+            "/range-proofs": operation("range-proofs", "Prove the four 64-bit limbs of a transaction hash", "RangeProofRequest", "RangeProofResponse"),
+            "/range-proofs/verify": operation("range-proofs", "Verify a limb range proof", "RangeProofVerifyRequest", "VerifyResponse"),
+            "/issued": issued_operations(),
+            "/openapi.json": { "get": {
+                "summary": "This document",
+                "responses": { "200": { "description": "OpenAPI document", "content": { "application/json": {} } } },
+            }},
+        },
+        "components": { "schemas": {
+            "ValueCommitmentRequest": {
+                "type": "object",
+                "required": ["value"],
+                "properties": {
+                    "value": { "type": "integer", "format": "uint64", "minimum": 0 },
+                    "blinding_hex": hex32("Blinding factor; random if omitted"),
+                    "binding_tag_hex": hex32("Tag bound into the proof transcript"),
+                    "bit_range": bit_range,
+                },
+            },
+            "ValueCommitmentResponse": {
+                "type": "object",
+                "required": ["commitment", "proof", "blinding_hex", "bit_range"],
+                "properties": {
+                    "commitment": hex32("Compressed Ristretto point"),
+                    "proof": proof("Range proof"),
+                    "blinding_hex": hex32("Blinding factor used"),
+                    "bit_range": bit_range,
+                },
+            },
+            "ValueCommitmentVerifyRequest": {
+                "type": "object",
+                "required": ["commitment", "proof"],
+                "properties": {
+                    "commitment": hex32("Compressed Ristretto point"),
+                    "proof": proof("Range proof"),
+                    "binding_tag_hex": hex32("Tag the proof was bound to"),
+                    "bit_range": bit_range,
+                },
+            },
+            "TxHashCommitmentRequest": {
+                "type": "object",
+                "required": ["tx_hash"],
+                "properties": {
+                    "tx_hash": hex32("Transaction hash"),
+                    "binding_tag_hex": hex32("Tag bound into the proof transcript"),
+                },
+            },
+            "TxHashCommitmentResponse": {
+                "type": "object",
+                "required": ["commitment", "proof"],
+                "properties": {
+                    "commitment": hex32("Compressed Ristretto point"),
+                    "proof": proof("R1CS proof"),
+                },
+            },
+            "TxHashCommitmentVerifyRequest": {
+                "type": "object",
+                "required": ["commitment", "proof"],
+                "properties": {
+                    "commitment": hex32("Compressed Ristretto point"),
+                    "proof": proof("R1CS proof"),
+                    "binding_tag_hex": hex32("Tag the proof was bound to"),
+                },
+            },
+            "RangeProofRequest": {
+                "type": "object",
+                "required": ["tx_hash"],
+                "properties": { "tx_hash": hex32("Transaction hash"), "scheme": scheme },
+            },
+            "RangeProofResponse": {
+                "type": "object",
+                "required": ["scheme", "commitments", "proof"],
+                "properties": { "scheme": scheme, "commitments": commitments, "proof": proof("Proof") },
+            },
+            "RangeProofVerifyRequest": {
+                "type": "object",
+                "required": ["commitments", "proof"],
+                "properties": { "scheme": scheme, "commitments": commitments, "proof": proof("Proof") },
+            },
+            "IssuedRecord": {
+                "type": "object",
+                "required": ["id", "kind", "scheme", "commitments", "proof", "issued_at"],
+                "properties": {
+                    "id": { "type": "string", "description": "Record id; ids increase with time" },
+                    "kind": { "type": "string", "enum": ["value_commitment", "tx_hash_commitment", "range_proof"] },
+                    "scheme": { "type": "string", "enum": ["bulletproofs", "r1cs_4limb", "bulletproofs_plus"] },
+                    "commitments": { "type": "array", "items": hex32("Compressed Ristretto point") },
+                    "proof": proof("Proof as issued"),
+                    "binding_tag": hex32("Tag bound into the proof transcript"),
+                    "product": { "type": "string", "description": "Product (escrow) address, lower case" },
+                    "stage": { "type": "string" },
+                    "issued_at": { "type": "integer", "format": "uint64", "description": "Unix seconds" },
+                },
+            },
+            "IssuedList": {
+                "type": "object",
+                "required": ["records"],
+                "properties": { "records": { "type": "array", "items": schema_ref("IssuedRecord") } },
+            },
+            "PurgeResponse": {
+                "type": "object",
+                "required": ["purged"],
+                "properties": { "purged": { "type": "integer", "description": "Records removed" } },
+            },
+            "VerifyResponse": {
+                "type": "object",
+                "required": ["verified"],
+                "properties": {
+                    "verified": { "type": "boolean", "description": "false only when the proof does not verify" },
+                    "replay": schema_ref("ReplayReport"),
+                },
+            },
+            "ReplayReport": {
+                "type": "object",
+                "description": "Earlier presentations of a verified proof under the same binding tag; present when the server keeps a replay registry",
+                "required": ["accepted", "previous_presentations", "previous_by_verifier"],
+                "properties": {
+                    "accepted": { "type": "boolean", "description": "Whether the server's replay scope (once, per_verifier, unlimited) allows this presentation" },
+                    "previous_presentations": { "type": "integer", "description": "Earlier presentations to any verifier" },
+                    "previous_by_verifier": { "type": "integer", "description": "Earlier presentations to the verifier named by `X-Zkp-Verifier`" },
+                    "first_presented_at": { "type": "integer", "format": "uint64", "description": "Unix seconds of the first presentation" },
+                },
+            },
+            "ErrorResponse": {
+                "type": "object",
+                "required": ["error", "code"],
+                "properties": {
+                    "error": { "type": "string" },
+                    "code": { "type": "string", "description": "Stable machine-readable error code" },
+                },
+            },
+        }},
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{call_service, init_service, read_body_json, TestRequest};
+    use actix_web::App;
+
+    macro_rules! app {
+        () => {
+            init_service(App::new().app_data(web::Data::new(ProofPool::new(2, 8).unwrap())).configure(|cfg| configure(cfg, 1 << 20))).await
+        };
+    }
+
+    /// POST `body` to `path`, returning the status and JSON body.
+    macro_rules! post {
+        ($app:expr, $path:expr, $body:expr) => {{
+            let resp = call_service(&$app, TestRequest::post().uri($path).set_json($body).to_request()).await;
+            (resp.status(), read_body_json::<Value, _>(resp).await)
+        }};
+    }
+
+    #[actix_web::test]
+    async fn value_commitment_round_trip() {
+        let app = app!();
+        let tag = "11".repeat(32);
+        let (status, created) = post!(app, "/v1/value-commitments", json!({ "value": 200, "bit_range": 8, "binding_tag_hex": tag }));
+        assert_eq!(status, StatusCode::OK);
+        let created: ValueCommitmentResponse = serde_json::from_value(created).unwrap();
+
+        let verify = |tag: String| json!({ "commitment": created.commitment, "proof": created.proof, "bit_range": 8, "binding_tag_hex": tag });
+        assert_eq!(post!(app, "/v1/value-commitments/verify", verify(tag)), (StatusCode::OK, json!({ "verified": true })));
+        assert_eq!(post!(app, "/v1/value-commitments/verify", verify("22".repeat(32))), (StatusCode::OK, json!({ "verified": false })));
+    }
+
+    #[actix_web::test]
+    async fn range_proofs_round_trip_for_both_schemes() {
+        let app = app!();
+        for scheme in ["bulletproofs", "bulletproofs_plus"] {
+            let (status, created) = post!(app, "/v1/range-proofs", json!({ "tx_hash": format!("0x{}", "ab".repeat(32)), "scheme": scheme }));
+            assert_eq!(status, StatusCode::OK, "{scheme}");
+            assert_eq!(created["scheme"], scheme);
+            let (_, verified) = post!(app, "/v1/range-proofs/verify", created);
+            assert_eq!(verified, json!({ "verified": true }), "{scheme}");
+        }
+    }
+
+    #[actix_web::test]
+    async fn errors_share_one_shape() {
+        let app = app!();
+        let (status, body) = post!(app, "/v1/tx-hash-commitments", json!({ "tx_hash": "xyz" }));
+        assert_eq!((status, body["code"].as_str()), (StatusCode::BAD_REQUEST, Some("invalid_parameters")));
+        let (status, body) = post!(app, "/v1/tx-hash-commitments", json!({ "hash": "00" }));
+        assert_eq!((status, body["code"].as_str()), (StatusCode::BAD_REQUEST, Some("invalid_request")));
+        let (status, body) = post!(app, "/v1/value-commitments", json!({ "value": 1, "blinding_hex": "00".repeat(1 << 20) }));
+        assert_eq!((status, body["code"].as_str()), (StatusCode::PAYLOAD_TOO_LARGE, Some("payload_too_large")));
+        let (status, body) = post!(app, "/v1/nothing-here", json!({}));
+        assert_eq!((status, body["code"].as_str()), (StatusCode::NOT_FOUND, Some("not_found")));
+    }
+
+    #[actix_web::test]
+    async fn issued_commitments_are_recorded_and_queryable() {
+        let (status, body) = {
+            let app = app!();
+            let resp = call_service(&app, TestRequest::get().uri("/v1/issued").to_request()).await;
+            (resp.status(), read_body_json::<Value, _>(resp).await)
+        };
+        assert_eq!((status, body["code"].as_str()), (StatusCode::NOT_FOUND, Some("issued_store_disabled")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(ProofPool::new(2, 8).unwrap()))
+                .app_data(web::Data::new(IssuedStore::open(dir.path()).unwrap()))
+                .configure(|cfg| configure(cfg, 1 << 20)),
+        )
+        .await;
+        let product = "0x00000000000000000000000000000000000000AA";
+        let create = |path: &str, body: Value| {
+            TestRequest::post()
+                .uri(path)
+                .insert_header(("X-Zkp-Product", product))
+                .insert_header(("X-Zkp-Stage", "purchase"))
+                .set_json(body)
+                .to_request()
+        };
+        let resp = call_service(&app, create("/v1/value-commitments", json!({ "value": 7, "bit_range": 8, "binding_tag_hex": "11".repeat(32) }))).await;
+        let created: ValueCommitmentResponse = read_body_json(resp).await;
+        let resp = call_service(&app, create("/v1/range-proofs", json!({ "tx_hash": "ab".repeat(32) }))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp = call_service(&app, TestRequest::post().uri("/v1/value-commitments").insert_header(("X-Zkp-Stage", "no spaces")).set_json(json!({ "value": 1 })).to_request()).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let list = |query: String| TestRequest::get().uri(&format!("/v1/issued{query}")).to_request();
+        let all: IssuedList = read_body_json(call_service(&app, list(String::new())).await).await;
+        assert_eq!(all.records.iter().map(|r| r.kind.as_str()).collect::<Vec<_>>(), ["range_proof", "value_commitment"]);
+        let record = &all.records[1];
+        assert_eq!((record.commitments.as_slice(), record.stage.as_deref()), (std::slice::from_ref(&created.commitment), Some("purchase")));
+        assert_eq!(record.product.as_deref(), Some(product.to_lowercase().as_str()));
+        assert!(!serde_json::to_string(record).unwrap().contains(&created.blinding_hex));
+
+        let by_tag: IssuedList = read_body_json(call_service(&app, list(format!("?binding_tag={}", "11".repeat(32)))).await).await;
+        assert_eq!(by_tag.records.len(), 1);
+        let resp = call_service(&app, list("?limit=zero".into())).await;
+        assert_eq!((resp.status(), read_body_json::<Value, _>(resp).await["code"].as_str()), (StatusCode::BAD_REQUEST, Some("invalid_request")));
+
+        let purge = |query: &str| TestRequest::delete().uri(&format!("/v1/issued{query}")).to_request();
+        let resp = call_service(&app, purge("")).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let purged: Value = read_body_json(call_service(&app, purge(&format!("?product={product}"))).await).await;
+        assert_eq!(purged, json!({ "purged": 2 }));
+    }
+
+    #[actix_web::test]
+    async fn verifications_report_earlier_presentations() {
+        use crate::api::{ReplayReport, VerifyResponse};
+        use super::super::config::ReplayScope;
+
+        let dir = tempfile::tempdir().unwrap();
+        let app = init_service(
+            App::new()
+                .app_data(web::Data::new(ProofPool::new(2, 8).unwrap()))
+                .app_data(web::Data::new(ReplayRegistry::open(dir.path(), ReplayScope::PerVerifier).unwrap()))
+                .configure(|cfg| configure(cfg, 1 << 20)),
+        )
+        .await;
+        let tag = "11".repeat(32);
+        let (_, created) = post!(app, "/v1/tx-hash-commitments", json!({ "tx_hash": "ab".repeat(32), "binding_tag_hex": tag }));
+        let verify = |verifier: &str, tag: &str| {
+            TestRequest::post()
+                .uri("/v1/tx-hash-commitments/verify")
+                .insert_header(("X-Zkp-Verifier", verifier))
+                .set_json(json!({ "commitment": created["commitment"], "proof": created["proof"], "binding_tag_hex": tag }))
+                .to_request()
+        };
+        let replay = |body: Value| serde_json::from_value::<VerifyResponse>(body).unwrap().replay.map(|r: ReplayReport| (r.accepted, r.previous_presentations, r.previous_by_verifier));
+
+        assert_eq!(replay(read_body_json(call_service(&app, verify("alice", &tag)).await).await), Some((true, 0, 0)));
+        assert_eq!(replay(read_body_json(call_service(&app, verify("bob", &tag)).await).await), Some((true, 1, 0)));
+        assert_eq!(replay(read_body_json(call_service(&app, verify("alice", &tag)).await).await), Some((false, 2, 1)));
+        // Failed verifications are neither recorded nor reported.
+        let failed: Value = read_body_json(call_service(&app, verify("carol", &"22".repeat(32))).await).await;
+        assert_eq!(failed, json!({ "verified": false }));
+        let resp = call_service(&app, verify("not valid", &tag)).await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn openapi_references_resolve_and_legacy_routes_have_successors() {
+        let doc = openapi();
+        let text = doc.to_string();
+        for name in text.split("#/components/schemas/").skip(1).map(|s| s.split('"').next().unwrap()) {
+            assert!(doc["components"]["schemas"].get(name).is_some(), "dangling schema reference {name}");
+        }
+        for (_, successor) in LEGACY_ROUTES {
+            let path = successor.strip_prefix("/v1").unwrap();
+            assert!(doc["paths"].get(path).is_some(), "{successor} is not documented");
+        }
+        assert_eq!(successor_of("/zkp/verify_bp4"), Some("/v1/range-proofs/verify"));
+        assert_eq!(successor_of("/zkp/jobs"), None);
+    }
+}
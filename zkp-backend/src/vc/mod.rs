@@ -0,0 +1,573 @@
+//! Zero-knowledge proofs carried by a product Verifiable Credential.
+//!
+//! The frontend stores the price range proof at
+//! `credentialSubject.price.zkpProof` (`price` may itself be a JSON string),
+//! and the transaction hash commitments of later stages at
+//! `credentialSubject.purchaseTxHashCommitment` and
+//! `credentialSubject.txHashCommitment` (delivery). Each one has
+//! `commitment`, `proof` and an optional `bindingTag`. [`verify_proofs`]
+//! checks every proof that is present, the way
+//! `frontend/src/utils/verifyZKP.js` does one endpoint at a time.
+//!
+//! A stored tag only shows what the proof was bound to, not that it belongs
+//! to this credential. Where the credential says what the tag commits to
+//! (`price.zkpProof.bindingContext`, checked against the product contract,
+//! previous credential, schema version and issuer chain), the expected tag
+//! is recomputed as `frontend/src/utils/commitmentUtils.js` derives it and
+//! each proof is verified against that; older credentials without a context
+//! are verified against their stored tags, and their checks say so.
+//!
+//! A transaction hash commitment whose tag was not recomputed could have been
+//! copied from another credential with the same stored tag, so a report with
+//! one is never `verified`: its [`VcStatus`] is `unbound` even when every
+//! check passes.
+//!
+//! [`verify`] adds the issuer and holder signatures ([`signature`]).
+
+pub mod signature;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::api::{ReplayReport, TxHashCommitmentVerifyRequest, ValueCommitmentVerifyRequest};
+use crate::eth::{encode_uint, keccak256, parse_address, Address};
+
+/// `proofType` of price proofs that are 64-bit value range proofs. Older
+/// credentials without it (and without the protocol below) carry tx-hash
+/// style R1CS proofs.
+pub const RANGE_PROOF_TYPE: &str = "zkRangeProof-v1";
+const RANGE_PROOF_PROTOCOL: &str = "bulletproofs-pedersen";
+
+/// A commitment, its proof and the tag the proof is bound to, as found in
+/// the credential.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddedProof {
+    pub commitment: String,
+    pub proof: String,
+    pub binding_tag: Option<String>,
+}
+
+impl EmbeddedProof {
+    fn from_json(value: &Value, what: &str) -> Result<Self, String> {
+        let field = |name: &str| value.get(name).and_then(Value::as_str).filter(|s| !s.is_empty());
+        let (Some(commitment), Some(proof)) = (field("commitment"), field("proof")) else {
+            return Err(format!("{what} needs a commitment and a proof"));
+        };
+        Ok(Self { commitment: commitment.into(), proof: proof.into(), binding_tag: field("bindingTag").map(String::from) })
+    }
+}
+
+/// The price proof and whether it is a value range proof.
+pub fn price_proof(vc: &Value) -> Result<(EmbeddedProof, bool), String> {
+    let price = &vc["credentialSubject"]["price"];
+    let parsed;
+    let price = match price.as_str() {
+        Some(text) => {
+            parsed = serde_json::from_str::<Value>(text).unwrap_or(Value::Null);
+            &parsed
+        },
+        None => price,
+    };
+    let zkp = &price["zkpProof"];
+    if zkp.is_null() {
+        return Err("no price proof at credentialSubject.price.zkpProof".into());
+    }
+    let range = zkp["proofType"] == RANGE_PROOF_TYPE || zkp["protocol"] == RANGE_PROOF_PROTOCOL;
+    Ok((EmbeddedProof::from_json(zkp, "price.zkpProof")?, range))
+}
+
+/// `credentialSubject.<field>`, if the credential has one.
+pub fn tx_hash_commitment(vc: &Value, field: &str) -> Result<Option<EmbeddedProof>, String> {
+    match &vc["credentialSubject"][field] {
+        Value::Null => Ok(None),
+        value => EmbeddedProof::from_json(value, field).map(Some),
+    }
+}
+
+// ─── Binding tags ──────────────────────────────────────────────────────────
+
+/// What a price proof is bound to (`generateBindingTag`): the product's
+/// chain, escrow and id, the credential stage and schema, and from stage 2
+/// on the CID of the previous credential.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceBinding {
+    pub chain_id: u64,
+    pub escrow: Address,
+    pub product_id: u64,
+    pub stage: u8,
+    pub schema_version: String,
+    pub previous_vc_cid: Option<String>,
+}
+
+impl PriceBinding {
+    /// `solidityPackedKeccak256` of the context, `zkp-bind-v2` with a
+    /// previous credential and `zkp-bind-v1` without.
+    pub fn tag(&self) -> [u8; 32] {
+        let version: &[u8] = if self.previous_vc_cid.is_some() { b"zkp-bind-v2" } else { b"zkp-bind-v1" };
+        let mut packed = [version, &encode_uint(self.chain_id), &self.escrow, &encode_uint(self.product_id), &[self.stage]].concat();
+        packed.extend_from_slice(self.schema_version.as_bytes());
+        packed.extend_from_slice(self.previous_vc_cid.as_deref().unwrap_or_default().as_bytes());
+        keccak256(&packed)
+    }
+
+    /// The context stored with the price proof, if any, after checking it
+    /// against the rest of the credential.
+    pub fn from_credential(vc: &Value, zkp: &Value) -> Result<Option<Self>, String> {
+        let context = &zkp["bindingContext"];
+        if context.is_null() {
+            return Ok(None);
+        }
+        let binding = Self {
+            chain_id: uint(&context["chainId"], "bindingContext.chainId")?,
+            escrow: address(&context["escrowAddr"], "bindingContext.escrowAddr")?,
+            product_id: uint(&context["productId"], "bindingContext.productId")?,
+            stage: u8::try_from(uint(&context["stage"], "bindingContext.stage")?)
+                .map_err(|_| "bindingContext.stage must be below 256".to_string())?,
+            schema_version: text(&context["schemaVersion"]).unwrap_or("1.0").into(),
+            previous_vc_cid: text(&context["previousVCCid"]).map(String::from),
+        };
+
+        let subject = &vc["credentialSubject"];
+        if !subject["subjectDetails"]["productContract"].is_null() {
+            let contract = address(&subject["subjectDetails"]["productContract"], "subjectDetails.productContract")?;
+            if contract != binding.escrow {
+                return Err("bindingContext.escrowAddr is not the credential's product contract".into());
+            }
+        }
+        if let Some(previous) = &binding.previous_vc_cid {
+            if text(&subject["previousCredential"]) != Some(previous) {
+                return Err("bindingContext.previousVCCid is not the credential's previousCredential".into());
+            }
+        }
+        if text(&vc["schemaVersion"]).is_some_and(|version| version != binding.schema_version) {
+            return Err("bindingContext.schemaVersion is not the credential's schemaVersion".into());
+        }
+        let issuer = text(&vc["issuer"]["id"]).or(text(&vc["issuer"])).and_then(did_ethr);
+        if issuer.and_then(|(chain, _)| chain).is_some_and(|chain| chain != binding.chain_id) {
+            return Err("bindingContext.chainId is not the chain of the issuer DID".into());
+        }
+        Ok(Some(binding))
+    }
+}
+
+/// `generateTxHashCommitmentBindingTag`: one tag for the purchase and
+/// delivery transaction commitments of a product and buyer.
+pub fn tx_hash_binding_tag(chain_id: u64, escrow: &Address, product_id: u64, buyer: &Address) -> [u8; 32] {
+    keccak256(&[&b"tx-hash-bind-v1"[..], &encode_uint(chain_id), escrow, &encode_uint(product_id), buyer].concat())
+}
+
+fn text(value: &Value) -> Option<&str> {
+    value.as_str().filter(|s| !s.is_empty())
+}
+
+/// A JavaScript number or decimal string, as the frontend stores ids.
+fn uint(value: &Value, what: &str) -> Result<u64, String> {
+    value
+        .as_u64()
+        .or_else(|| value.as_str()?.trim().parse().ok())
+        .ok_or_else(|| format!("{what} must be an unsigned integer"))
+}
+
+fn address(value: &Value, what: &str) -> Result<Address, String> {
+    let text = value.as_str().ok_or_else(|| format!("{what} must be an address"))?;
+    parse_address(text.trim()).map_err(|e| format!("{what}: {e}"))
+}
+
+/// The chain id (when numeric) and address of `did:ethr:[<chain>:]0x…`.
+pub fn did_ethr(did: &str) -> Option<(Option<u64>, Address)> {
+    let rest = did.strip_prefix("did:ethr:")?;
+    let (chain, account) = match rest.rsplit_once(':') {
+        Some((chain, account)) => (chain.parse().ok(), account),
+        None => (None, rest),
+    };
+    Some((chain, parse_address(account).ok()?))
+}
+
+/// How a proof's binding tag was established.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Binding {
+    /// Recomputed from the credential; a swapped-in proof cannot pass.
+    Recomputed,
+    /// Taken from the credential as stored; the credential has no context
+    /// to recompute it from.
+    Stored,
+    /// The proof is bound to no tag.
+    Unbound,
+}
+
+/// The tag to verify against: the recomputed one, which a stored tag must
+/// match, or else the stored one.
+fn expected_tag(stored: Option<&str>, recomputed: Option<[u8; 32]>) -> Result<(Option<String>, Binding), String> {
+    let stored = stored.map(|t| t.trim().trim_start_matches("0x").to_ascii_lowercase());
+    match (stored, recomputed) {
+        (Some(stored), Some(tag)) if stored != hex::encode(tag) => Err(format!(
+            "stored binding tag 0x{stored} is not 0x{}, the one recomputed from the credential",
+            hex::encode(tag)
+        )),
+        (_, Some(tag)) => Ok((Some(hex::encode(tag)), Binding::Recomputed)),
+        (Some(stored), None) => Ok((Some(stored), Binding::Stored)),
+        (None, None) => Ok((None, Binding::Unbound)),
+    }
+}
+
+// ─── Verification ──────────────────────────────────────────────────────────
+
+/// The outcome of a whole report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VcStatus {
+    /// Every check passed and every proof is bound to this credential.
+    Verified,
+    /// Every check passed, but a proof (the price proof or a transaction hash
+    /// commitment) is only bound to its stored tag, or to none, and could
+    /// belong to another credential.
+    Unbound,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VcReport {
+    /// Whether `status` is [`VcStatus::Verified`].
+    pub verified: bool,
+    pub status: VcStatus,
+    pub checks: Vec<VcCheck>,
+}
+
+impl VcReport {
+    fn new(checks: Vec<VcCheck>) -> Self {
+        let status = if !checks.iter().all(|c| c.verified) {
+            VcStatus::Failed
+        } else if checks.iter().any(|c| c.binding.is_some_and(|b| b != Binding::Recomputed)) {
+            VcStatus::Unbound
+        } else {
+            VcStatus::Verified
+        };
+        Self { verified: status == VcStatus::Verified, status, checks }
+    }
+}
+
+/// Check names and credential subject fields of the transaction hash commitments.
+const TX_HASH_COMMITMENTS: [(&str, &str); 2] = [
+    ("purchase_tx_hash_commitment", "purchaseTxHashCommitment"),
+    ("delivery_tx_hash_commitment", "txHashCommitment"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VcCheck {
+    pub name: &'static str,
+    pub verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// For proof checks: how the tag was established, and the tag itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binding: Option<Binding>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binding_tag: Option<String>,
+    /// For signature checks: the recovered signer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// For proof checks: the proof, which a replay registry records.
+    #[serde(skip)]
+    pub proof: Option<Vec<u8>>,
+    /// Earlier presentations of a verified proof, when the server keeps a
+    /// replay registry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay: Option<ReplayReport>,
+}
+
+impl VcCheck {
+    fn new(name: &'static str, result: Result<(), String>) -> Self {
+        let error = result.err();
+        Self { name, verified: error.is_none(), error, binding: None, binding_tag: None, signer: None, proof: None, replay: None }
+    }
+
+    /// Verify `proof` against the tag [`expected_tag`] settles on.
+    fn proof(
+        name: &'static str,
+        proof: &EmbeddedProof,
+        tag: Result<(Option<String>, Binding), String>,
+        verify: impl FnOnce(Option<String>) -> Result<(), String>,
+    ) -> Self {
+        match tag {
+            Ok((tag, binding)) => {
+                let bytes = hex::decode(proof.proof.trim_start_matches("0x")).ok();
+                Self { binding: Some(binding), binding_tag: tag.clone(), proof: bytes, ..Self::new(name, verify(tag)) }
+            },
+            Err(e) => Self::new(name, Err(e)),
+        }
+    }
+}
+
+fn verified(result: Result<crate::api::VerifyResponse, crate::zk::ZkError>) -> Result<(), String> {
+    match result {
+        Ok(response) if response.verified => Ok(()),
+        Ok(_) => Err("proof does not verify for this commitment and binding tag".into()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn verify_tx_hash(proof: EmbeddedProof, tag: Option<String>) -> Result<(), String> {
+    let request = TxHashCommitmentVerifyRequest { commitment: proof.commitment, proof: proof.proof, binding_tag_hex: tag };
+    verified(request.verify())
+}
+
+/// The price proof's `zkpProof` object, with `price` parsed if it is a string.
+fn price_zkp(vc: &Value) -> Value {
+    let price = &vc["credentialSubject"]["price"];
+    match price.as_str() {
+        Some(text) => serde_json::from_str::<Value>(text).unwrap_or(Value::Null)["zkpProof"].take(),
+        None => price["zkpProof"].clone(),
+    }
+}
+
+/// Verify the price proof (always required) and the transaction hash
+/// commitments that are present, each against its recomputed binding tag
+/// where the credential allows; purchase and delivery commitments must
+/// also share their tag.
+pub fn verify_proofs(vc: &Value) -> VcReport {
+    let price_binding = PriceBinding::from_credential(vc, &price_zkp(vc));
+    let price = match price_proof(vc) {
+        Ok((proof, range)) => {
+            let tag = price_binding
+                .clone()
+                .and_then(|binding| expected_tag(proof.binding_tag.as_deref(), binding.map(|b| b.tag())));
+            VcCheck::proof("price_proof", &proof, tag, |tag| {
+                if range {
+                    let request = ValueCommitmentVerifyRequest {
+                        commitment: proof.commitment.clone(),
+                        proof: proof.proof.clone(),
+                        binding_tag_hex: tag,
+                        bit_range: 64,
+                    };
+                    verified(request.verify())
+                } else {
+                    verify_tx_hash(proof.clone(), tag)
+                }
+            })
+        },
+        Err(e) => VcCheck::new("price_proof", Err(e)),
+    };
+    let mut checks = vec![price];
+
+    // Transaction commitments are bound to the product and its buyer; a
+    // context the price check rejected is reported there, not again here.
+    let buyer = text(&vc["holder"]["id"]).or(text(&vc["credentialSubject"]["id"])).and_then(did_ethr);
+    let tx_tag = match (price_binding.ok().flatten(), buyer) {
+        (Some(p), Some((_, buyer))) => Some(tx_hash_binding_tag(p.chain_id, &p.escrow, p.product_id, &buyer)),
+        _ => None,
+    };
+    let mut tags = Vec::new();
+    for (name, field) in TX_HASH_COMMITMENTS {
+        match tx_hash_commitment(vc, field) {
+            Ok(None) => {},
+            Ok(Some(proof)) => {
+                let tag = expected_tag(proof.binding_tag.as_deref(), tx_tag);
+                let check = VcCheck::proof(name, &proof, tag, |tag| verify_tx_hash(proof.clone(), tag));
+                tags.push(check.binding_tag.clone());
+                checks.push(check);
+            },
+            Err(e) => checks.push(VcCheck::new(name, Err(e))),
+        }
+    }
+    if let [purchase, delivery] = &tags[..] {
+        let linked = match (purchase, delivery) {
+            (Some(a), Some(b)) if a == b => Ok(()),
+            (Some(_), Some(_)) => Err("purchase and delivery binding tags differ".into()),
+            _ => Err("purchase and delivery commitments need binding tags to be linked".into()),
+        };
+        checks.push(VcCheck::new("tx_hash_commitments_linked", linked));
+    }
+    VcReport::new(checks)
+}
+
+/// Verify the proofs and then the signatures of `vc`.
+pub fn verify(vc: &Value, options: &signature::SignatureOptions) -> VcReport {
+    let mut checks = verify_proofs(vc).checks;
+    checks.extend(signature::verify_signatures(vc, options));
+    VcReport::new(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{TxHashCommitmentRequest, ValueCommitmentRequest};
+    use serde_json::json;
+
+    fn tag(byte: u8) -> Option<String> {
+        Some(format!("0x{}", hex::encode([byte; 32])))
+    }
+
+    fn tx_commitment(tx: u8, binding_tag: Option<String>) -> Value {
+        let resp = TxHashCommitmentRequest { tx_hash: hex::encode([tx; 32]), binding_tag_hex: binding_tag.clone() }.prove().unwrap();
+        json!({ "commitment": resp.commitment, "proof": resp.proof, "bindingTag": binding_tag, "protocol": "bulletproofs-pedersen" })
+    }
+
+    fn credential() -> Value {
+        let price = ValueCommitmentRequest { value: 1500, blinding_hex: None, binding_tag_hex: tag(1), bit_range: 64 }.prove().unwrap();
+        let zkp = json!({ "commitment": price.commitment, "proof": price.proof, "bindingTag": tag(1), "proofType": RANGE_PROOF_TYPE });
+        json!({
+            "credentialSubject": {
+                // Stored as a string, as the frontend does.
+                "price": json!({ "hidden": true, "zkpProof": zkp }).to_string(),
+                "purchaseTxHashCommitment": tx_commitment(0x11, tag(2)),
+                "txHashCommitment": tx_commitment(0x22, tag(2)),
+            }
+        })
+    }
+
+    fn check<'a>(report: &'a VcReport, name: &str) -> &'a VcCheck {
+        report.checks.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[test]
+    fn every_embedded_proof_is_checked() {
+        let mut vc = credential();
+        let report = verify_proofs(&vc);
+        assert!(report.checks.iter().all(|c| c.verified), "{report:?}");
+        assert_eq!(report.checks.len(), 4);
+
+        vc["credentialSubject"]["txHashCommitment"] = tx_commitment(0x22, tag(3));
+        let report = verify_proofs(&vc);
+        assert_eq!((report.verified, report.status), (false, VcStatus::Failed));
+        assert!(check(&report, "delivery_tx_hash_commitment").verified);
+        assert!(!check(&report, "tx_hash_commitments_linked").verified);
+
+        vc["credentialSubject"]["txHashCommitment"]["bindingTag"] = json!(tag(2));
+        let report = verify_proofs(&vc);
+        assert!(!check(&report, "delivery_tx_hash_commitment").verified);
+    }
+
+    #[test]
+    fn missing_and_malformed_proofs_fail_their_check() {
+        let report = verify_proofs(&json!({ "credentialSubject": { "price": "{}" } }));
+        assert!(!report.verified);
+        assert_eq!(report.checks.len(), 1);
+        assert!(check(&report, "price_proof").error.as_deref().unwrap().contains("no price proof"));
+
+        let mut vc = credential();
+        vc["credentialSubject"]["purchaseTxHashCommitment"] = json!({ "commitment": "00" });
+        vc["credentialSubject"]["txHashCommitment"] = Value::Null;
+        let report = verify_proofs(&vc);
+        assert!(check(&report, "price_proof").verified);
+        assert!(check(&report, "purchase_tx_hash_commitment").error.as_deref().unwrap().contains("needs a commitment"));
+        assert_eq!(report.checks.len(), 2);
+    }
+
+    const ESCROW: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+    const BUYER: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+
+    fn binding(stage: u8, previous: Option<&str>) -> PriceBinding {
+        PriceBinding {
+            chain_id: 11155111,
+            escrow: parse_address(ESCROW).unwrap(),
+            product_id: 7,
+            stage,
+            schema_version: "1.0".into(),
+            previous_vc_cid: previous.map(String::from),
+        }
+    }
+
+    #[test]
+    fn binding_tags_match_the_frontend() {
+        // solidityPackedKeccak256 results from ethers.
+        assert_eq!(hex::encode(binding(0, None).tag()), "4a5596efdd61278ccd24c9f55f451bceb9cf5634de63b255e47e941e1357b15c");
+        assert_eq!(
+            hex::encode(binding(2, Some("QmStage2Cid")).tag()),
+            "2c7c4d6b880666d1f8645e0f97654c60d8eb169b67ff47bde0fc96a7e50e8dac"
+        );
+        let tag = tx_hash_binding_tag(11155111, &parse_address(ESCROW).unwrap(), 7, &parse_address(BUYER).unwrap());
+        assert_eq!(hex::encode(tag), "55a7cba15c987d6221d721f329d1c1c7c0866aaf2c334f08f003b9fb5d8f9246");
+    }
+
+    /// A stage 3 credential as the frontend builds it, with every tag derived
+    /// from its context.
+    fn bound_credential() -> Value {
+        let price_tag = Some(hex::encode(binding(2, Some("QmStage2Cid")).tag()));
+        let price = ValueCommitmentRequest { value: 1500, blinding_hex: None, binding_tag_hex: price_tag.clone(), bit_range: 64 }.prove().unwrap();
+        let tx_tag = tx_hash_binding_tag(11155111, &parse_address(ESCROW).unwrap(), 7, &parse_address(BUYER).unwrap());
+        let tx_tag = Some(format!("0x{}", hex::encode(tx_tag)));
+        let zkp = json!({
+            "commitment": price.commitment,
+            "proof": price.proof,
+            "bindingTag": price_tag,
+            "proofType": RANGE_PROOF_TYPE,
+            "bindingContext": { "chainId": 11155111, "escrowAddr": ESCROW, "productId": "7", "stage": 2, "schemaVersion": "1.0", "previousVCCid": "QmStage2Cid" },
+        });
+        json!({
+            "schemaVersion": "1.0",
+            "issuer": { "id": "did:ethr:11155111:0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC", "name": "Seller" },
+            "holder": { "id": format!("did:ethr:11155111:{BUYER}"), "name": "Buyer" },
+            "credentialSubject": {
+                "previousCredential": "QmStage2Cid",
+                "subjectDetails": { "productContract": ESCROW },
+                "price": json!({ "hidden": true, "zkpProof": zkp }).to_string(),
+                "purchaseTxHashCommitment": tx_commitment(0x11, tx_tag.clone()),
+                "txHashCommitment": tx_commitment(0x22, tx_tag),
+            }
+        })
+    }
+
+    #[test]
+    fn binding_tags_are_recomputed_from_the_credential() {
+        let vc = bound_credential();
+        let report = verify_proofs(&vc);
+        assert_eq!((report.verified, report.status), (true, VcStatus::Verified), "{report:?}");
+        assert!(report.checks.iter().filter(|c| c.binding.is_some()).all(|c| c.binding == Some(Binding::Recomputed)));
+
+        // A context that disagrees with the credential is rejected.
+        let mut moved = vc.clone();
+        moved["credentialSubject"]["subjectDetails"]["productContract"] = json!(BUYER);
+        let report = verify_proofs(&moved);
+        assert!(check(&report, "price_proof").error.as_deref().unwrap().contains("product contract"));
+
+        // So is a stored tag other than the recomputed one, even for a proof bound to it.
+        let mut swapped = vc.clone();
+        swapped["credentialSubject"]["purchaseTxHashCommitment"] = tx_commitment(0x11, tag(9));
+        let report = verify_proofs(&swapped);
+        assert!(check(&report, "purchase_tx_hash_commitment").error.as_deref().unwrap().contains("recomputed"));
+
+        // And a proof without a tag where the credential implies one.
+        let mut unbound = vc;
+        unbound["credentialSubject"]["txHashCommitment"] = tx_commitment(0x22, None);
+        let report = verify_proofs(&unbound);
+        let delivery = check(&report, "delivery_tx_hash_commitment");
+        assert_eq!((delivery.verified, delivery.binding), (false, Some(Binding::Recomputed)));
+    }
+
+    #[test]
+    fn credentials_without_context_use_their_stored_tags() {
+        let mut vc = credential();
+        vc["credentialSubject"]["purchaseTxHashCommitment"] = Value::Null;
+        vc["credentialSubject"]["txHashCommitment"] = Value::Null;
+        let report = verify_proofs(&vc);
+        assert!(report.checks.iter().all(|c| c.verified));
+        assert_eq!(check(&report, "price_proof").binding, Some(Binding::Stored));
+        // The stored tag is all that binds the price proof, so it is not verified either.
+        assert_eq!((report.verified, report.status), (false, VcStatus::Unbound));
+        assert_eq!(did_ethr("did:ethr:0x5FbDB2315678afecb367f032d93F642f64180aa3").map(|(chain, _)| chain), Some(None));
+        assert_eq!(did_ethr("did:ethr:sepolia:0x5FbDB2315678afecb367f032d93F642f64180aa3").map(|(chain, _)| chain), Some(None));
+    }
+
+    #[test]
+    fn stored_tx_hash_tags_are_never_verified() {
+        let report = verify_proofs(&credential());
+        assert!(report.checks.iter().all(|c| c.verified));
+        assert_eq!((report.verified, report.status), (false, VcStatus::Unbound));
+
+        // A commitment swapped in from another credential with the same stored
+        // tag passes every check, which is why the report is not verified.
+        let mut swapped = credential();
+        swapped["credentialSubject"]["txHashCommitment"] = tx_commitment(0x33, tag(2));
+        let report = verify_proofs(&swapped);
+        assert!(report.checks.iter().all(|c| c.verified));
+        assert_eq!((report.verified, report.status), (false, VcStatus::Unbound));
+
+        // Likewise an untagged one.
+        swapped["credentialSubject"]["purchaseTxHashCommitment"] = Value::Null;
+        swapped["credentialSubject"]["txHashCommitment"] = tx_commitment(0x33, None);
+        let report = verify_proofs(&swapped);
+        assert_eq!(check(&report, "delivery_tx_hash_commitment").binding, Some(Binding::Unbound));
+        assert_eq!((report.verified, report.status), (false, VcStatus::Unbound));
+    }
+}
@@ -0,0 +1,520 @@
+//! EIP-712 signatures of a product Verifiable Credential.
+//!
+//! `frontend/src/utils/signVcWithMetamask.js` signs the credential as typed
+//! data in the domain `{ name: "VC", version: "1.0", chainId }`, with the
+//! escrow as `verifyingContract` when it is known, and appends
+//! `{ verificationMethod: "did:ethr:<chain>:<signer>", jws, payloadHash }` to
+//! `proof`. Credentials signed before `schemaVersion` was added to the
+//! `Credential` type have no `schemaVersion` and are checked with the old
+//! types. [`verify_signatures`] follows `backend/api/verifyVC.js`: the same
+//! payload preparation, the same domains and types tried in the same order,
+//! and the recovered signer compared with the DID of the issuer or holder.
+
+use k256::ecdsa::SigningKey;
+use serde_json::{json, Map, Value};
+
+use super::VcCheck;
+use crate::eth::{
+    self, address_of, eip712_hash, encode_uint, format_address, hash_struct, keccak256, parse_address, parse_signature, Address,
+    Eip712Domain,
+};
+
+/// Chain of signatures whose verification method and party DID name none
+/// (`VC_CHAIN_ID` in `verifyVC.js`): Sepolia.
+pub const DEFAULT_CHAIN_ID: u64 = 11155111;
+
+const PARTY: &str = "Party(string id,string name)";
+const CERTIFICATE: &str = "Certificate(string name,string cid)";
+const SUBJECT: &str = "CredentialSubject(string id,string productName,string batch,uint256 quantity,\
+string previousCredential,string[] componentCredentials,Certificate certificateCredential,string price)";
+const CREDENTIAL_V0: &str = "Credential(string id,string[] @context,string[] type,\
+Party issuer,Party holder,string issuanceDate,CredentialSubject credentialSubject)";
+const CREDENTIAL_V1: &str = "Credential(string id,string[] @context,string[] type,string schemaVersion,\
+Party issuer,Party holder,string issuanceDate,CredentialSubject credentialSubject)";
+
+/// The `Credential` type a signature was made over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Types {
+    /// Without `schemaVersion`, for credentials that have none.
+    V0,
+    /// With `schemaVersion`, as credentials are signed today.
+    V1,
+}
+
+/// Who signed: the seller (`issuer`) or the buyer (`holder`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Issuer,
+    Holder,
+}
+
+impl Role {
+    /// The credential field naming the party.
+    pub fn field(self) -> &'static str {
+        match self {
+            Self::Issuer => "issuer",
+            Self::Holder => "holder",
+        }
+    }
+
+    fn check(self) -> &'static str {
+        match self {
+            Self::Issuer => "issuer_signature",
+            Self::Holder => "holder_signature",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureOptions {
+    /// Chain when neither the proof nor the party DID names one.
+    pub chain_id: u64,
+    /// The escrow the signatures may be bound to.
+    pub verifying_contract: Option<Address>,
+    /// Certificates are signed by their issuer alone.
+    pub certificate: bool,
+}
+
+impl Default for SignatureOptions {
+    fn default() -> Self {
+        Self { chain_id: DEFAULT_CHAIN_ID, verifying_contract: None, certificate: false }
+    }
+}
+
+pub fn domain(chain_id: u64, verifying_contract: Option<Address>) -> Eip712Domain {
+    Eip712Domain { name: "VC".into(), version: "1.0".into(), chain_id: Some(chain_id), verifying_contract }
+}
+
+// ─── Payload ───────────────────────────────────────────────────────────────
+
+/// JavaScript truthiness, which decides the defaults below.
+fn truthy(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => false,
+        Value::Number(n) => n.as_f64() != Some(0.0),
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+/// The credential as it was signed, and the types it was signed with: the
+/// price as a JSON string, empty defaults for the optional subject fields
+/// and lower-cased party ids. Fields outside the types (`proof`, `vcHash`,
+/// `transactionId`, the commitments) are never hashed, so they are left in.
+pub fn payload(vc: &Value) -> (Value, Types) {
+    let mut payload = vc.clone();
+    let types = if truthy(&vc["schemaVersion"]) { Types::V1 } else { Types::V0 };
+    if !payload.is_object() {
+        return (payload, types);
+    }
+    if !payload["credentialSubject"].is_object() {
+        payload["credentialSubject"] = Value::Object(Map::new());
+    }
+    let subject = &mut payload["credentialSubject"];
+    if truthy(&subject["price"]) && !subject["price"].is_string() {
+        // preserve_order keeps the keys where the signer had them.
+        subject["price"] = Value::String(subject["price"].to_string());
+    }
+    if !truthy(&subject["certificateCredential"]) {
+        subject["certificateCredential"] = json!({ "name": "", "cid": "" });
+    }
+    if subject["previousCredential"].is_null() {
+        subject["previousCredential"] = json!("");
+    }
+    if !subject["componentCredentials"].is_array() {
+        subject["componentCredentials"] = json!([]);
+    }
+    for party in ["issuer", "holder", "credentialSubject"] {
+        if let Some(Value::String(id)) = payload.get_mut(party).and_then(|p| p.get_mut("id")) {
+            *id = id.to_lowercase();
+        }
+    }
+    (payload, types)
+}
+
+fn string(value: &Value, what: &str) -> Result<[u8; 32], String> {
+    value.as_str().map(|s| keccak256(s.as_bytes())).ok_or_else(|| format!("{what} must be a string"))
+}
+
+fn strings(value: &Value, what: &str) -> Result<[u8; 32], String> {
+    let items = value.as_array().ok_or_else(|| format!("{what} must be an array of strings"))?;
+    let hashes = items.iter().map(|item| string(item, what)).collect::<Result<Vec<_>, _>>()?;
+    Ok(keccak256(&hashes.concat()))
+}
+
+/// A `uint256` as ethers accepts one: a safe integer, or a decimal or `0x`
+/// hex string.
+fn uint256(value: &Value, what: &str) -> Result<[u8; 32], String> {
+    const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_991.0;
+    let invalid = || format!("{what} must be an unsigned integer below 2^256");
+    if let Some(n) = value.as_u64() {
+        return Ok(encode_uint(n));
+    }
+    if let Some(n) = value.as_f64().filter(|n| n.fract() == 0.0 && (0.0..=MAX_SAFE_INTEGER).contains(n)) {
+        return Ok(encode_uint(n as u64));
+    }
+    let text = value.as_str().map(str::trim).filter(|s| !s.is_empty()).ok_or_else(invalid)?;
+    let mut word = [0u8; 32];
+    if let Some(digits) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        if digits.is_empty() || digits.len() > 64 {
+            return Err(invalid());
+        }
+        let bytes = hex::decode(format!("{digits:0>64}")).map_err(|_| invalid())?;
+        word.copy_from_slice(&bytes);
+        return Ok(word);
+    }
+    for digit in text.bytes() {
+        if !digit.is_ascii_digit() {
+            return Err(invalid());
+        }
+        let mut carry = u16::from(digit - b'0');
+        for byte in word.iter_mut().rev() {
+            let next = u16::from(*byte) * 10 + carry;
+            *byte = next as u8;
+            carry = next >> 8;
+        }
+        if carry != 0 {
+            return Err(invalid());
+        }
+    }
+    Ok(word)
+}
+
+fn party(value: &Value, what: &str) -> Result<[u8; 32], String> {
+    let members = [string(&value["id"], &format!("{what}.id"))?, string(&value["name"], &format!("{what}.name"))?];
+    Ok(hash_struct(PARTY, &members))
+}
+
+/// `hashStruct(Credential)` of a prepared payload.
+pub fn struct_hash(payload: &Value, types: Types) -> Result<[u8; 32], String> {
+    let subject = &payload["credentialSubject"];
+    let certificate = &subject["certificateCredential"];
+    let certificate = hash_struct(CERTIFICATE, &[
+        string(&certificate["name"], "certificateCredential.name")?,
+        string(&certificate["cid"], "certificateCredential.cid")?,
+    ]);
+    let subject = hash_struct(&format!("{SUBJECT}{CERTIFICATE}"), &[
+        string(&subject["id"], "credentialSubject.id")?,
+        string(&subject["productName"], "credentialSubject.productName")?,
+        string(&subject["batch"], "credentialSubject.batch")?,
+        uint256(&subject["quantity"], "credentialSubject.quantity")?,
+        string(&subject["previousCredential"], "credentialSubject.previousCredential")?,
+        strings(&subject["componentCredentials"], "credentialSubject.componentCredentials")?,
+        certificate,
+        string(&subject["price"], "credentialSubject.price")?,
+    ]);
+
+    let mut members = vec![
+        string(&payload["id"], "id")?,
+        strings(&payload["@context"], "@context")?,
+        strings(&payload["type"], "type")?,
+    ];
+    let credential = match types {
+        Types::V0 => CREDENTIAL_V0,
+        Types::V1 => {
+            members.push(string(&payload["schemaVersion"], "schemaVersion")?);
+            CREDENTIAL_V1
+        },
+    };
+    members.extend([
+        party(&payload["issuer"], "issuer")?,
+        party(&payload["holder"], "holder")?,
+        string(&payload["issuanceDate"], "issuanceDate")?,
+        subject,
+    ]);
+    // Referenced types follow the primary one in alphabetical order.
+    Ok(hash_struct(&format!("{credential}{CERTIFICATE}{SUBJECT}{PARTY}"), &members))
+}
+
+/// The digest a wallet signs for `payload` in `domain`.
+pub fn digest(payload: &Value, types: Types, domain: &Eip712Domain) -> Result<[u8; 32], String> {
+    Ok(eip712_hash(&domain.separator(), &struct_hash(payload, types)?))
+}
+
+// ─── Signing and verification ──────────────────────────────────────────────
+
+/// Sign `vc` as `signVcWithMetamask.js` does, returning the entry to append
+/// to its `proof` array (without `created`).
+pub fn sign(vc: &Value, key: &SigningKey, role: Role, chain_id: u64, verifying_contract: Option<Address>) -> Result<Value, String> {
+    let (mut payload, _) = payload(vc);
+    if !truthy(&payload["schemaVersion"]) {
+        payload["schemaVersion"] = json!("1.0");
+    }
+    let digest = digest(&payload, Types::V1, &domain(chain_id, verifying_contract))?;
+    let signer = hex::encode(address_of(key.verifying_key()));
+    Ok(json!({
+        "type": "EcdsaSecp256k1Signature2019",
+        "proofPurpose": "assertionMethod",
+        "verificationMethod": format!("did:ethr:{chain_id}:0x{signer}"),
+        "jws": format!("0x{}", hex::encode(eth::sign(key, &digest))),
+        "payloadHash": format!("0x{}", hex::encode(digest)),
+        "role": if role == Role::Issuer { "seller" } else { "holder" },
+    }))
+}
+
+/// `proof` entries, or the values of a legacy `proofs` object.
+fn proofs(vc: &Value) -> Vec<&Value> {
+    match (&vc["proof"], &vc["proofs"]) {
+        (Value::Array(proofs), _) => proofs.iter().collect(),
+        (_, Value::Object(proofs)) => proofs.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The chain of `did:ethr:<chain>:<account>`, when it is numeric.
+fn chain_id(identifier: Option<&str>) -> Option<u64> {
+    let parts: Vec<&str> = identifier?.split(':').collect();
+    if parts.len() < 4 {
+        return None;
+    }
+    parts[2].parse().ok()
+}
+
+/// Recover the signer of `proof` in each domain and type set `verifyVC.js`
+/// tries, and accept the first that is `expected`.
+fn verify_proof(proof: &Value, payload: &Value, types: Types, chain_id: u64, options: &SignatureOptions, expected: &Address) -> Result<Address, String> {
+    let signature = parse_signature(proof["jws"].as_str().unwrap_or_default()).map_err(|e| e.to_string())?;
+    let attempts = match types {
+        Types::V0 => vec![(Types::V0, None)],
+        Types::V1 => {
+            let mut attempts = vec![(Types::V1, None)];
+            if options.verifying_contract.is_some() {
+                attempts.push((Types::V1, options.verifying_contract));
+            }
+            attempts.push((Types::V0, None));
+            attempts
+        },
+    };
+    let mut recovered = Vec::new();
+    for (types, contract) in attempts {
+        let digest = digest(payload, types, &domain(chain_id, contract))?;
+        let signer = eth::recover(&digest, &signature).map_err(|e| e.to_string())?;
+        if signer != *expected {
+            recovered.push(format_address(&signer));
+            continue;
+        }
+        let declared = proof["payloadHash"].as_str().map(|h| h.trim_start_matches("0x").to_ascii_lowercase());
+        if declared.is_some_and(|declared| declared != hex::encode(digest)) {
+            return Err(format!("payloadHash is not 0x{}, the EIP-712 hash of the signed credential", hex::encode(digest)));
+        }
+        return Ok(signer);
+    }
+    Err(format!("signed by {}, not {}", recovered.join(" or "), format_address(expected)))
+}
+
+/// The account a `did:ethr` (or a bare address) names: its last segment.
+fn did_account(did: &str) -> Result<Address, String> {
+    let account = did.rsplit(':').next().unwrap_or_default();
+    let account = account.split('#').next().unwrap_or_default();
+    parse_address(account).map_err(|e| format!("{did} does not name an account: {e}"))
+}
+
+/// Check the signature of the issuer or holder of `vc`.
+pub fn verify_signature(vc: &Value, role: Role, options: &SignatureOptions) -> Result<Address, String> {
+    let (payload, types) = payload(vc);
+    let did = payload[role.field()]["id"].as_str().filter(|id| !id.is_empty());
+    let did = did.ok_or_else(|| format!("the credential has no {}.id", role.field()))?;
+    // The signer is the party's own account; the proof only has to name it.
+    let expected = did_account(did)?;
+    let names_party = |method: &str| method.starts_with("did:ethr:") && did_account(method).is_ok_and(|a| a == expected);
+    let proof = proofs(vc)
+        .into_iter()
+        .find(|p| p["verificationMethod"].as_str().is_some_and(|m| names_party(&m.to_lowercase())))
+        .ok_or_else(|| format!("no proof is signed by {did}"))?;
+    let method = proof["verificationMethod"].as_str().unwrap_or_default().to_lowercase();
+
+    // The holder's chain defaults to the issuer's, as in verifyVC.js.
+    let issuer_chain = || {
+        let issuer = payload["issuer"]["id"].as_str().unwrap_or_default();
+        let method = proofs(vc).into_iter().find_map(|p| p["verificationMethod"].as_str().filter(|m| m.to_lowercase().contains(issuer)));
+        chain_id(method).or(chain_id(Some(issuer))).unwrap_or(options.chain_id)
+    };
+    let chain = chain_id(Some(&method)).or(chain_id(Some(did)));
+    let chain = match role {
+        Role::Issuer => chain.unwrap_or(options.chain_id),
+        Role::Holder => chain.unwrap_or_else(issuer_chain),
+    };
+    verify_proof(proof, &payload, types, chain, options, &expected)
+}
+
+/// One check per signature the credential needs: the issuer's, and the
+/// holder's unless it is a certificate.
+pub fn verify_signatures(vc: &Value, options: &SignatureOptions) -> Vec<VcCheck> {
+    let roles: &[Role] = if options.certificate { &[Role::Issuer] } else { &[Role::Issuer, Role::Holder] };
+    roles
+        .iter()
+        .map(|&role| match verify_signature(vc, role, options) {
+            Ok(signer) => VcCheck { signer: Some(format_address(&signer)), ..VcCheck::new(role.check(), Ok(())) },
+            Err(e) => VcCheck::new(role.check(), Err(e)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ISSUER: &str = "0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC";
+    const HOLDER: &str = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+    const ESCROW: &str = "0x5FbDB2315678afecb367f032d93F642f64180aa3";
+
+    /// The credential `signVcWithMetamask.js` signed for the fixtures below,
+    /// with its price left an object whose keys are out of order.
+    fn unsigned(schema_version: bool) -> Value {
+        let mut vc = json!({
+            "@context": ["https://www.w3.org/2018/credentials/v1"],
+            "id": "https://example.edu/credentials/58473",
+            "type": ["VerifiableCredential", "SupplyChainCredential"],
+            "schemaVersion": "1.0",
+            "issuer": { "id": format!("did:ethr:11155111:{ISSUER}"), "name": "Seller" },
+            "holder": { "id": format!("did:ethr:11155111:{HOLDER}"), "name": "Buyer" },
+            "issuanceDate": "2025-01-15T10:00:00.000Z",
+            "credentialSubject": {
+                "id": format!("did:ethr:11155111:{HOLDER}"),
+                "productName": "Battery Module",
+                "batch": "B-42",
+                "quantity": 3,
+                "previousCredential": "QmStage2Cid",
+                "componentCredentials": ["QmCellA", "QmCellB"],
+                "price": { "zkpProof": { "proofType": "zkRangeProof-v1", "commitment": "ab", "proof": "cd" }, "hidden": true },
+                "transactionId": "0x1234",
+                "vcHash": "0xdead",
+            },
+        });
+        if !schema_version {
+            vc.as_object_mut().unwrap().remove("schemaVersion");
+        }
+        vc
+    }
+
+    /// `[issuer jws, holder jws, payloadHash]` from ethers 6.15.
+    fn signed(schema_version: bool, [issuer, holder, payload_hash]: [&str; 3]) -> Value {
+        let mut vc = unsigned(schema_version);
+        let proof = |address: &str, jws: &str| {
+            json!({ "verificationMethod": format!("did:ethr:11155111:{}", address.to_lowercase()), "jws": jws, "payloadHash": payload_hash })
+        };
+        vc["proof"] = json!([proof(ISSUER, issuer), proof(HOLDER, holder)]);
+        vc
+    }
+
+    /// Signed by `signVcWithMetamask.js` without a contract.
+    fn new_fixture() -> Value {
+        signed(true, [
+            "0xa69c7c8b0996f2eef71ca827f651c3758a587947f9cb101f360c2fba82e2348a35ace4641ace75d9bb4dddacdf9399a58ee7698b9927ac9a6e28d73024719c441c",
+            "0x7ed9ed36a883eb3e8cd8b8865c76e872c49a18f860412071e353a11e84e695df5a2234309c772da75e8d5a14c3cac6284edfb1fa2811034e3ba721dada794f791c",
+            "0xcb99f980d223b006cea4d7457d08b2955e2a4cd9460399187098fd4754a33675",
+        ])
+    }
+
+    /// Signed by `signVcWithMetamask.js` with the escrow as verifyingContract.
+    fn contract_fixture() -> Value {
+        signed(true, [
+            "0x1ea8db8d02f3f3d5142cfa9a260dfe6fde997dde6712314b4642becd6fed8e3376b9f83097915d23b762728fb4871b3ec38dbb508e35b2b012fa9475fb7d546a1b",
+            "0x5ead9ce05b449a8c366df74dfada2dbd110e2821372547e43ac7876ba2c5fb703131e791866f7dea1a70869cccc9beb58d47a8d1a900d71165f6e8a34b52019b1c",
+            "0xe2c154302ae1949cd58c5e2542f640b3f967db3eda6c7bf2e9e8178f5f090867",
+        ])
+    }
+
+    /// Signed with the old types, before credentials had a schemaVersion.
+    fn old_fixture() -> Value {
+        signed(false, [
+            "0xc59540a7dcc5ceccd20ac6b4cc9ce1830174db4870f704aee73edc712a74986e5f033f0234ac2c11c69bc9cd0282b88806c40d8ed5431874a89eb070c5856ad91c",
+            "0xcdd17f035f46b5b9ab9103d48ad39b745be8bc54950b566e3163415d6de8789953fa4aedc5b86662fe7df9ca929d8547492f57980abe586df07efe52f00520641b",
+            "0x8b6746edc21eb7854422a506121255bf47c2cf4be140683a29a6a7e80fc68e44",
+        ])
+    }
+
+    fn signers(vc: &Value, options: &SignatureOptions) -> Vec<Result<String, String>> {
+        [Role::Issuer, Role::Holder].map(|role| verify_signature(vc, role, options).map(|a| format_address(&a))).to_vec()
+    }
+
+    #[test]
+    fn frontend_signatures_recover_their_signers() {
+        let both = vec![Ok(ISSUER.to_string()), Ok(HOLDER.to_string())];
+        let escrow = SignatureOptions { verifying_contract: Some(parse_address(ESCROW).unwrap()), ..Default::default() };
+        assert_eq!(signers(&new_fixture(), &SignatureOptions::default()), both);
+        assert_eq!(signers(&new_fixture(), &escrow), both);
+        assert_eq!(signers(&contract_fixture(), &escrow), both);
+        assert_eq!(signers(&old_fixture(), &SignatureOptions::default()), both);
+
+        // Bound to an escrow the verifier was not told about.
+        assert!(signers(&contract_fixture(), &SignatureOptions::default()).iter().all(Result::is_err));
+        // A schemaVersion added later falls back to the old types; removing
+        // one never reaches the new types.
+        let mut relabelled = old_fixture();
+        relabelled["schemaVersion"] = json!("1.0");
+        assert!(signers(&relabelled, &SignatureOptions::default())[0].as_ref().is_ok());
+        let mut downgraded = new_fixture();
+        downgraded.as_object_mut().unwrap().remove("schemaVersion");
+        assert!(signers(&downgraded, &SignatureOptions::default())[0].as_ref().unwrap_err().contains("signed by"));
+    }
+
+    #[test]
+    fn tampering_breaks_the_signatures() {
+        let mut vc = new_fixture();
+        vc["credentialSubject"]["quantity"] = json!("0x3");
+        vc["issuer"]["id"] = json!(format!("did:ethr:11155111:{}", ISSUER.to_lowercase()));
+        assert!(signers(&vc, &SignatureOptions::default()).iter().all(Result::is_ok), "equivalent values hash alike");
+
+        vc["credentialSubject"]["batch"] = json!("B-43");
+        assert!(verify_signature(&vc, Role::Issuer, &SignatureOptions::default()).unwrap_err().contains(&format!("not {ISSUER}")));
+
+        let mut swapped = new_fixture();
+        swapped["holder"]["id"] = json!(format!("did:ethr:11155111:{ISSUER}"));
+        assert!(verify_signature(&swapped, Role::Holder, &SignatureOptions::default()).is_err());
+
+        let mut stale = new_fixture();
+        stale["proof"][0]["payloadHash"] = json!(format!("0x{}", "00".repeat(32)));
+        assert!(verify_signature(&stale, Role::Issuer, &SignatureOptions::default()).unwrap_err().contains("payloadHash"));
+
+        let unsigned = unsigned(true);
+        assert!(verify_signature(&unsigned, Role::Issuer, &SignatureOptions::default()).unwrap_err().contains("no proof"));
+    }
+
+    #[test]
+    fn a_verification_method_cannot_name_another_signer() {
+        // An attacker signs an edited credential and names the issuer's DID
+        // inside its own verificationMethod.
+        let attacker = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let mut forged = unsigned(true);
+        forged["credentialSubject"]["batch"] = json!("B-43");
+        let mut proof = sign(&forged, &attacker, Role::Issuer, DEFAULT_CHAIN_ID, None).unwrap();
+        let account = format_address(&address_of(attacker.verifying_key()));
+        proof["verificationMethod"] = json!(format!("did:ethr:11155111:{ISSUER}:{account}"));
+        forged["proof"] = json!([proof]);
+        let err = verify_signature(&forged, Role::Issuer, &SignatureOptions::default()).unwrap_err();
+        assert!(err.contains("no proof is signed by"), "{err}");
+
+        // Naming the issuer's account outright still needs the issuer's key.
+        forged["proof"][0]["verificationMethod"] = json!(format!("did:ethr:11155111:{ISSUER}"));
+        let err = verify_signature(&forged, Role::Issuer, &SignatureOptions::default()).unwrap_err();
+        assert!(err.contains(&format!("signed by {account}")), "{err}");
+    }
+
+    #[test]
+    fn signing_matches_the_frontend() {
+        // Hardhat account #2, the issuer above.
+        let key = SigningKey::from_slice(&hex::decode("5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a").unwrap()).unwrap();
+        let proof = sign(&unsigned(true), &key, Role::Issuer, DEFAULT_CHAIN_ID, Some(parse_address(ESCROW).unwrap())).unwrap();
+        assert_eq!(proof["jws"], contract_fixture()["proof"][0]["jws"]);
+        assert_eq!(proof["verificationMethod"], contract_fixture()["proof"][0]["verificationMethod"]);
+
+        let mut certificate = unsigned(true);
+        certificate["proof"] = json!([proof]);
+        let options = SignatureOptions { verifying_contract: Some(parse_address(ESCROW).unwrap()), certificate: true, ..Default::default() };
+        let checks = verify_signatures(&certificate, &options);
+        assert_eq!(checks.len(), 1);
+        assert_eq!((checks[0].name, checks[0].verified, checks[0].signer.as_deref()), ("issuer_signature", true, Some(ISSUER)));
+    }
+
+    #[test]
+    fn quantities_are_uint256() {
+        let word = |v: Value| uint256(&v, "quantity").map(hex::encode);
+        let three = Ok(hex::encode(encode_uint(3)));
+        assert_eq!([word(json!(3)), word(json!(3.0)), word(json!("3")), word(json!("0x03"))], [three.clone(), three.clone(), three.clone(), three]);
+        assert_eq!(word(json!("115792089237316195423570985008687907853269984665640564039457584007913129639935")), Ok("ff".repeat(32)));
+        for bad in [json!(-1), json!(1.5), json!(""), json!("1e3"), json!("115792089237316195423570985008687907853269984665640564039457584007913129639936")] {
+            assert!(word(bad).is_err());
+        }
+    }
+}
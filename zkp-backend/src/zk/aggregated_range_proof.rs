@@ -0,0 +1,577 @@
+//! Multi-party aggregated range proof via the Bulletproofs dealer/party protocol.
+//!
+//! Every party (seller, transporter, buyer, …) keeps its own amount and blinding
+//! and only hands protocol messages to the dealer, so the server never learns a
+//! value. The output is one aggregated `RangeProof` over all commitments.
+//!
+//! Both sides are replayed from stored inputs instead of holding the borrowed
+//! typestate objects from `bulletproofs::range_proof_mpc` across requests:
+//! * the dealer is deterministic given the transcript and the messages, so an
+//!   [`AggregationSession`] just stores what it has received;
+//! * a [`PartySecret`] carries an RNG seed, so each round re-derives the same
+//!   bit/poly blindings it used in the previous round.
+//!
+//! The protocol needs a power-of-two number of parties. Sessions with e.g. three
+//! parties are padded with dummy parties (value 0) that the dealer runs itself.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bulletproofs::range_proof_mpc::dealer::Dealer;
+use bulletproofs::range_proof_mpc::messages::{
+    BitChallenge, BitCommitment, PolyChallenge, PolyCommitment, ProofShare,
+};
+use bulletproofs::range_proof_mpc::party::Party;
+use bulletproofs::range_proof_mpc::MPCError;
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+use curve25519_dalek_ng::ristretto::CompressedRistretto;
+use curve25519_dalek_ng::scalar::Scalar;
+use merlin::Transcript;
+use rand::rngs::{OsRng, StdRng};
+use rand::{RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use super::generators::GeneratorRegistry;
+use super::ZkError;
+
+const LABEL: &[u8] = b"AggregatedRangeProof";
+
+/// Upper bound on parties per session (aggregation size 16 → 16 × 64 generators).
+pub const MAX_PARTIES: usize = 16;
+
+/// Errors raised while driving an aggregation session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AggregationError {
+    /// Party count is zero or above [`MAX_PARTIES`].
+    InvalidPartyCount(usize),
+    /// Bit range is not one of 8, 16, 32, 64.
+    InvalidBitRange(usize),
+    /// Party index is outside `0..parties`.
+    InvalidPartyIndex(usize),
+    /// Message does not belong to the current round.
+    WrongPhase { expected: SessionPhase, actual: SessionPhase },
+    /// The party already submitted a message for this round.
+    DuplicateMessage(usize),
+    /// The dealer or party rejected a message.
+    Protocol(MPCError),
+}
+
+impl fmt::Display for AggregationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidPartyCount(n) => write!(f, "invalid party count {n}: must be 1..={MAX_PARTIES}"),
+            Self::InvalidBitRange(n) => write!(f, "invalid bit range {n}: must be 8, 16, 32 or 64"),
+            Self::InvalidPartyIndex(j) => write!(f, "invalid party index {j}"),
+            Self::WrongPhase { expected, actual } => {
+                write!(f, "session is in phase {actual:?}, message requires {expected:?}")
+            }
+            Self::DuplicateMessage(j) => write!(f, "party {j} already submitted a message for this round"),
+            Self::Protocol(e) => write!(f, "protocol error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for AggregationError {}
+
+/// Public parameters every party needs to produce its messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregationParams {
+    /// Bits per value (8, 16, 32 or 64).
+    pub bit_range: usize,
+    /// Number of slots in the aggregated proof (next power of two ≥ parties).
+    pub aggregation_size: usize,
+}
+
+impl AggregationParams {
+    pub fn new(parties: usize, bit_range: usize) -> Result<Self, ZkError> {
+        if parties == 0 || parties > MAX_PARTIES {
+            return Err(AggregationError::InvalidPartyCount(parties).into());
+        }
+        check_bit_range(bit_range)?;
+        Ok(Self { bit_range, aggregation_size: parties.next_power_of_two() })
+    }
+
+    fn generators(&self) -> (&'static PedersenGens, Arc<BulletproofGens>) {
+        let gens = GeneratorRegistry::shared();
+        (gens.pedersen(), gens.bulletproofs(self.bit_range, self.aggregation_size))
+    }
+}
+
+/// A party's secret opening plus the seed that makes its rounds reproducible.
+///
+/// The seed must stay private and must not be reused across sessions.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct PartySecret {
+    value: u64,
+    blinding: Scalar,
+    seed: [u8; 32],
+}
+
+impl PartySecret {
+    /// New party secret with a fresh random protocol seed.
+    pub fn new(value: u64, blinding: Scalar) -> Self {
+        let mut seed = [0u8; 32];
+        OsRng.fill_bytes(&mut seed);
+        Self::from_parts(value, blinding, seed)
+    }
+
+    /// Rebuild a party secret from a previously stored seed.
+    pub fn from_parts(value: u64, blinding: Scalar, seed: [u8; 32]) -> Self {
+        Self { value, blinding, seed }
+    }
+
+    /// Pedersen commitment to this party's value.
+    pub fn commitment(&self) -> CompressedRistretto {
+        GeneratorRegistry::shared().pedersen().commit(Scalar::from(self.value), self.blinding).compress()
+    }
+
+    /// Round 1: commit to the bits of the value at slot `position`.
+    pub fn bit_commitment(
+        &self,
+        params: &AggregationParams,
+        position: usize,
+    ) -> Result<BitCommitment, ZkError> {
+        Ok(self.replay(params, position, None)?.0)
+    }
+
+    /// Round 2: answer the dealer's bit challenge.
+    pub fn poly_commitment(
+        &self,
+        params: &AggregationParams,
+        position: usize,
+        bit_challenge: &BitChallenge,
+    ) -> Result<PolyCommitment, ZkError> {
+        let (_, poly, _) = self.replay(params, position, Some((bit_challenge, None)))?;
+        Ok(poly.expect("poly commitment is produced once a bit challenge is supplied"))
+    }
+
+    /// Round 3: answer the dealer's poly challenge with this party's proof share.
+    pub fn proof_share(
+        &self,
+        params: &AggregationParams,
+        position: usize,
+        bit_challenge: &BitChallenge,
+        poly_challenge: &PolyChallenge,
+    ) -> Result<ProofShare, ZkError> {
+        let (_, _, share) =
+            self.replay(params, position, Some((bit_challenge, Some(poly_challenge))))?;
+        Ok(share.expect("proof share is produced once both challenges are supplied"))
+    }
+
+    fn replay(
+        &self,
+        params: &AggregationParams,
+        position: usize,
+        challenges: Option<(&BitChallenge, Option<&PolyChallenge>)>,
+    ) -> Result<(BitCommitment, Option<PolyCommitment>, Option<ProofShare>), ZkError> {
+        let (pc_gens, bp_gens) = params.generators();
+        let mut rng = StdRng::from_seed(self.seed);
+        let party = Party::new(&bp_gens, pc_gens, self.value, self.blinding, params.bit_range)?;
+        let (party, bit_commitment) = party.assign_position_with_rng(position, &mut rng)?;
+        let Some((bit_challenge, poly_challenge)) = challenges else {
+            return Ok((bit_commitment, None, None));
+        };
+        let (party, poly_commitment) = party.apply_challenge_with_rng(bit_challenge, &mut rng);
+        let Some(poly_challenge) = poly_challenge else {
+            return Ok((bit_commitment, Some(poly_commitment), None));
+        };
+        let share = party.apply_challenge(poly_challenge)?;
+        Ok((bit_commitment, Some(poly_commitment), Some(share)))
+    }
+}
+
+/// Extract the value commitment `V_j` from a party's bit commitment.
+pub fn bit_commitment_value(bit_commitment: &BitCommitment) -> CompressedRistretto {
+    // `BitCommitment` keeps its fields crate-private; the serde form is the only public view.
+    #[derive(Deserialize)]
+    struct View {
+        #[serde(rename = "V_j")]
+        v_j: CompressedRistretto,
+    }
+    serde_json::to_value(bit_commitment)
+        .and_then(serde_json::from_value::<View>)
+        .map(|view| view.v_j)
+        .expect("BitCommitment always serializes its V_j field")
+}
+
+/// Round the session is waiting on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPhase {
+    AwaitingBitCommitments,
+    AwaitingPolyCommitments,
+    AwaitingProofShares,
+    Complete,
+}
+
+/// Snapshot of a session, safe to hand to any participant.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionStatus {
+    pub phase: SessionPhase,
+    pub parties: usize,
+    pub params: AggregationParams,
+    /// Parties that have submitted a message for the current round.
+    pub received: Vec<usize>,
+    pub bit_challenge: Option<BitChallenge>,
+    pub poly_challenge: Option<PolyChallenge>,
+    /// Hex commitments for all slots (parties first, then padding), once complete.
+    pub commitments: Option<Vec<String>>,
+    /// Hex aggregated range proof, once complete.
+    pub proof: Option<String>,
+}
+
+/// Dealer-side state of one aggregation.
+pub struct AggregationSession {
+    parties: usize,
+    params: AggregationParams,
+    binding_tag: Option<Vec<u8>>,
+    padding_seed: [u8; 32],
+    bit_commitments: Vec<Option<BitCommitment>>,
+    poly_commitments: Vec<Option<PolyCommitment>>,
+    proof_shares: Vec<Option<ProofShare>>,
+    bit_challenge: Option<BitChallenge>,
+    poly_challenge: Option<PolyChallenge>,
+    commitments: Option<Vec<CompressedRistretto>>,
+    proof: Option<RangeProof>,
+    created_at: Instant,
+}
+
+impl AggregationSession {
+    /// Open a session for `parties` values of `bit_range` bits, optionally bound to a VC context.
+    pub fn new(
+        parties: usize,
+        bit_range: usize,
+        binding_tag: Option<&[u8]>,
+    ) -> Result<Self, ZkError> {
+        let params = AggregationParams::new(parties, bit_range)?;
+        let mut padding_seed = [0u8; 32];
+        OsRng.fill_bytes(&mut padding_seed);
+        Ok(Self {
+            parties,
+            params,
+            binding_tag: binding_tag.map(<[u8]>::to_vec),
+            padding_seed,
+            bit_commitments: vec![None; parties],
+            poly_commitments: vec![None; parties],
+            proof_shares: vec![None; parties],
+            bit_challenge: None,
+            poly_challenge: None,
+            commitments: None,
+            proof: None,
+            created_at: Instant::now(),
+        })
+    }
+
+    pub fn params(&self) -> AggregationParams {
+        self.params
+    }
+
+    pub fn created_at(&self) -> Instant {
+        self.created_at
+    }
+
+    pub fn phase(&self) -> SessionPhase {
+        if self.proof.is_some() {
+            SessionPhase::Complete
+        } else if self.poly_challenge.is_some() {
+            SessionPhase::AwaitingProofShares
+        } else if self.bit_challenge.is_some() {
+            SessionPhase::AwaitingPolyCommitments
+        } else {
+            SessionPhase::AwaitingBitCommitments
+        }
+    }
+
+    pub fn status(&self) -> SessionStatus {
+        fn filled<T>(slots: &[Option<T>]) -> Vec<usize> {
+            slots.iter().enumerate().filter(|(_, s)| s.is_some()).map(|(j, _)| j).collect()
+        }
+        let received = match self.phase() {
+            SessionPhase::AwaitingBitCommitments => filled(&self.bit_commitments),
+            SessionPhase::AwaitingPolyCommitments => filled(&self.poly_commitments),
+            SessionPhase::AwaitingProofShares => filled(&self.proof_shares),
+            SessionPhase::Complete => (0..self.parties).collect(),
+        };
+        SessionStatus {
+            phase: self.phase(),
+            parties: self.parties,
+            params: self.params,
+            received,
+            bit_challenge: self.bit_challenge,
+            poly_challenge: self.poly_challenge,
+            commitments: self
+                .commitments
+                .as_ref()
+                .map(|coms| coms.iter().map(|c| hex::encode(c.as_bytes())).collect()),
+            proof: self.proof.as_ref().map(|p| hex::encode(p.to_bytes())),
+        }
+    }
+
+    /// Aggregated proof and all slot commitments, once every party has sent its share.
+    pub fn result(&self) -> Option<(Vec<CompressedRistretto>, Vec<u8>)> {
+        Some((self.commitments.clone()?, self.proof.as_ref()?.to_bytes()))
+    }
+
+    /// Round 1. Returns the bit challenge once the last party has committed.
+    pub fn submit_bit_commitment(
+        &mut self,
+        party: usize,
+        bit_commitment: BitCommitment,
+    ) -> Result<Option<BitChallenge>, ZkError> {
+        self.accept(party, SessionPhase::AwaitingBitCommitments)?;
+        store(&mut self.bit_commitments, party, bit_commitment)?;
+        if self.bit_commitments.iter().all(Option::is_some) {
+            self.bit_challenge = Some(self.replay_dealer()?.0);
+        }
+        Ok(self.bit_challenge)
+    }
+
+    /// Round 2. Returns the poly challenge once the last party has answered.
+    pub fn submit_poly_commitment(
+        &mut self,
+        party: usize,
+        poly_commitment: PolyCommitment,
+    ) -> Result<Option<PolyChallenge>, ZkError> {
+        self.accept(party, SessionPhase::AwaitingPolyCommitments)?;
+        store(&mut self.poly_commitments, party, poly_commitment)?;
+        if self.poly_commitments.iter().all(Option::is_some) {
+            self.poly_challenge = self.replay_dealer()?.1;
+        }
+        Ok(self.poly_challenge)
+    }
+
+    /// Round 3. Returns the aggregated proof once the last share arrives.
+    ///
+    /// The dealer verifies the assembled proof; a bad share is reported as
+    /// [`MPCError::MalformedProofShares`] and the slot is cleared so the party can resend.
+    pub fn submit_proof_share(
+        &mut self,
+        party: usize,
+        proof_share: ProofShare,
+    ) -> Result<Option<Vec<u8>>, ZkError> {
+        self.accept(party, SessionPhase::AwaitingProofShares)?;
+        store(&mut self.proof_shares, party, proof_share)?;
+        if self.proof_shares.iter().all(Option::is_some) {
+            match self.replay_dealer() {
+                Ok((_, _, proof)) => self.proof = proof,
+                Err(ZkError::Aggregation(AggregationError::Protocol(MPCError::MalformedProofShares { bad_shares }))) => {
+                    for &j in bad_shares.iter().filter(|&&j| j < self.parties) {
+                        self.proof_shares[j] = None;
+                    }
+                    return Err(MPCError::MalformedProofShares { bad_shares }.into());
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(self.proof.as_ref().map(RangeProof::to_bytes))
+    }
+
+    fn accept(&self, party: usize, expected: SessionPhase) -> Result<(), AggregationError> {
+        if party >= self.parties {
+            return Err(AggregationError::InvalidPartyIndex(party));
+        }
+        let actual = self.phase();
+        if actual != expected {
+            return Err(AggregationError::WrongPhase { expected, actual });
+        }
+        Ok(())
+    }
+
+    fn padding_party(&self, position: usize) -> PartySecret {
+        let seed: [u8; 32] = Sha256::new()
+            .chain_update(self.padding_seed)
+            .chain_update((position as u64).to_le_bytes())
+            .finalize()
+            .into();
+        let blinding = Scalar::random(&mut StdRng::from_seed(seed));
+        PartySecret::from_parts(0, blinding, seed)
+    }
+
+    /// Run the dealer over everything received so far (plus padding parties),
+    /// advancing as far as the stored messages allow.
+    fn replay_dealer(
+        &mut self,
+    ) -> Result<(BitChallenge, Option<PolyChallenge>, Option<RangeProof>), ZkError> {
+        let params = self.params;
+        let (pc_gens, bp_gens) = params.generators();
+        let mut transcript = Transcript::new(LABEL);
+        if let Some(binding) = &self.binding_tag {
+            transcript.append_message(b"bind", binding);
+        }
+        let padding: Vec<PartySecret> =
+            (self.parties..params.aggregation_size).map(|j| self.padding_party(j)).collect();
+
+        let dealer = Dealer::new(&bp_gens, pc_gens, &mut transcript, params.bit_range, params.aggregation_size)?;
+
+        let mut bit_commitments: Vec<BitCommitment> = self.bit_commitments.iter().flatten().copied().collect();
+        for (offset, pad) in padding.iter().enumerate() {
+            bit_commitments.push(pad.bit_commitment(&params, self.parties + offset)?);
+        }
+        self.commitments = Some(bit_commitments.iter().map(bit_commitment_value).collect());
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments)?;
+
+        if self.poly_commitments.iter().any(Option::is_none) {
+            return Ok((bit_challenge, None, None));
+        }
+        let mut poly_commitments: Vec<PolyCommitment> = self.poly_commitments.iter().flatten().copied().collect();
+        for (offset, pad) in padding.iter().enumerate() {
+            poly_commitments.push(pad.poly_commitment(&params, self.parties + offset, &bit_challenge)?);
+        }
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments)?;
+
+        if self.proof_shares.iter().any(Option::is_none) {
+            return Ok((bit_challenge, Some(poly_challenge), None));
+        }
+        let mut shares: Vec<ProofShare> = self.proof_shares.iter().flatten().cloned().collect();
+        for (offset, pad) in padding.iter().enumerate() {
+            shares.push(pad.proof_share(&params, self.parties + offset, &bit_challenge, &poly_challenge)?);
+        }
+        let proof = dealer.receive_shares(&shares)?;
+        Ok((bit_challenge, Some(poly_challenge), Some(proof)))
+    }
+}
+
+fn store<T>(slots: &mut [Option<T>], party: usize, message: T) -> Result<(), AggregationError> {
+    if slots[party].is_some() {
+        return Err(AggregationError::DuplicateMessage(party));
+    }
+    slots[party] = Some(message);
+    Ok(())
+}
+
+/// The bit ranges Bulletproofs supports.
+fn check_bit_range(bit_range: usize) -> Result<(), ZkError> {
+    match bit_range {
+        8 | 16 | 32 | 64 => Ok(()),
+        _ => Err(AggregationError::InvalidBitRange(bit_range).into()),
+    }
+}
+
+/// Verifies an aggregated range proof over all slot commitments (including padding).
+pub fn verify_aggregated_range_proof(
+    commitments: &[CompressedRistretto],
+    proof_bytes: &[u8],
+    bit_range: usize,
+    binding_tag: Option<&[u8]>,
+) -> Result<(), ZkError> {
+    if !commitments.len().is_power_of_two() || commitments.len() > MAX_PARTIES.next_power_of_two() {
+        return Err(ZkError::InvalidParameters(format!(
+            "commitment count must be a power of two up to {}, got {}",
+            MAX_PARTIES.next_power_of_two(),
+            commitments.len()
+        )));
+    }
+    // Before any generators are built for it.
+    check_bit_range(bit_range)?;
+    let proof = RangeProof::from_bytes(proof_bytes).map_err(|_| ZkError::ProofDeserialization("range proof"))?;
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(bit_range, commitments.len());
+    let mut transcript = Transcript::new(LABEL);
+    if let Some(binding) = binding_tag {
+        transcript.append_message(b"bind", binding);
+    }
+    proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript, commitments, bit_range)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_session(values: &[u64], bit_range: usize, binding_tag: Option<&[u8]>) -> (Vec<CompressedRistretto>, Vec<u8>) {
+        let mut session = AggregationSession::new(values.len(), bit_range, binding_tag).unwrap();
+        let params = session.params();
+        let secrets: Vec<PartySecret> = values
+            .iter()
+            .map(|&v| PartySecret::new(v, Scalar::random(&mut OsRng)))
+            .collect();
+
+        let mut bit_challenge = None;
+        for (j, secret) in secrets.iter().enumerate() {
+            bit_challenge = session.submit_bit_commitment(j, secret.bit_commitment(&params, j).unwrap()).unwrap();
+        }
+        let bit_challenge = bit_challenge.expect("all parties committed");
+
+        let mut poly_challenge = None;
+        for (j, secret) in secrets.iter().enumerate() {
+            let poly = secret.poly_commitment(&params, j, &bit_challenge).unwrap();
+            poly_challenge = session.submit_poly_commitment(j, poly).unwrap();
+        }
+        let poly_challenge = poly_challenge.expect("all parties answered");
+
+        let mut proof = None;
+        for (j, secret) in secrets.iter().enumerate() {
+            let share = secret.proof_share(&params, j, &bit_challenge, &poly_challenge).unwrap();
+            proof = session.submit_proof_share(j, share).unwrap();
+        }
+        assert_eq!(session.phase(), SessionPhase::Complete);
+
+        let (commitments, bytes) = session.result().unwrap();
+        assert_eq!(proof.unwrap(), bytes);
+        for (j, secret) in secrets.iter().enumerate() {
+            assert_eq!(commitments[j], secret.commitment(), "slot {j} must carry the party's commitment");
+        }
+        (commitments, bytes)
+    }
+
+    #[test]
+    fn three_party_aggregation_verifies() {
+        let binding = [0x11u8; 32];
+        let (commitments, proof) = run_session(&[1_000_000, 25_000, 500_000], 64, Some(&binding));
+        assert_eq!(commitments.len(), 4, "three parties are padded to four slots");
+        assert_eq!(verify_aggregated_range_proof(&commitments, &proof, 64, Some(&binding)), Ok(()));
+        assert_eq!(verify_aggregated_range_proof(&commitments, &proof, 64, Some(&[0x22u8; 32])), Err(ZkError::TranscriptMismatch));
+        assert_eq!(verify_aggregated_range_proof(&commitments, &proof, 64, None), Err(ZkError::TranscriptMismatch));
+    }
+
+    #[test]
+    fn swapped_commitments_do_not_verify() {
+        let (mut commitments, proof) = run_session(&[7, 8], 32, None);
+        commitments.swap(0, 1);
+        assert!(verify_aggregated_range_proof(&commitments, &proof, 32, None).is_err());
+    }
+
+    #[test]
+    fn out_of_order_and_duplicate_messages_are_rejected() {
+        let secret = PartySecret::new(42, Scalar::random(&mut OsRng));
+
+        // A poly commitment from a finished first round of a one-party session.
+        let mut solo = AggregationSession::new(1, 64, None).unwrap();
+        let solo_params = solo.params();
+        let solo_bc = secret.bit_commitment(&solo_params, 0).unwrap();
+        let challenge = solo.submit_bit_commitment(0, solo_bc).unwrap().unwrap();
+        let poly = secret.poly_commitment(&solo_params, 0, &challenge).unwrap();
+
+        let mut session = AggregationSession::new(2, 64, None).unwrap();
+        let bc = secret.bit_commitment(&session.params(), 0).unwrap();
+        assert!(matches!(session.submit_bit_commitment(2, bc), Err(ZkError::Aggregation(AggregationError::InvalidPartyIndex(2)))));
+        assert!(matches!(session.submit_bit_commitment(0, bc), Ok(None)));
+        assert!(matches!(session.submit_bit_commitment(0, bc), Err(ZkError::Aggregation(AggregationError::DuplicateMessage(0)))));
+        assert!(matches!(
+            session.submit_poly_commitment(0, poly),
+            Err(ZkError::Aggregation(AggregationError::WrongPhase {
+                expected: SessionPhase::AwaitingPolyCommitments,
+                actual: SessionPhase::AwaitingBitCommitments,
+            }))
+        ));
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert!(matches!(AggregationSession::new(0, 64, None), Err(ZkError::Aggregation(AggregationError::InvalidPartyCount(0)))));
+        assert!(matches!(AggregationSession::new(MAX_PARTIES + 1, 64, None), Err(ZkError::Aggregation(AggregationError::InvalidPartyCount(_)))));
+        assert!(matches!(AggregationSession::new(3, 48, None), Err(ZkError::Aggregation(AggregationError::InvalidBitRange(48)))));
+
+        let (commitments, proof) = run_session(&[7, 8], 32, None);
+        for bit_range in [0, 48, 128, usize::MAX] {
+            assert_eq!(
+                verify_aggregated_range_proof(&commitments, &proof, bit_range, None),
+                Err(ZkError::Aggregation(AggregationError::InvalidBitRange(bit_range)))
+            );
+        }
+    }
+}
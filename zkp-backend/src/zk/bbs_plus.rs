@@ -0,0 +1,608 @@
+//! BBS+ signatures over BLS12-381 for selectively disclosable VC attributes.
+//!
+//! EIP-712 signatures cover the whole payload, so a VC presentation reveals
+//! everything that was signed. With BBS+ the issuer signs each
+//! `credentialSubject` attribute as a separate message, and the holder derives
+//! zero-knowledge presentations that reveal only the attributes they choose
+//! while still proving the issuer signed all of them.
+//!
+//! Scheme: BBS+ as in Camenisch–Drijvers–Lehmann, "Anonymous Attestation Using
+//! the Strong Diffie Hellman Assumption Revisited" (2016), §4.3–4.5.
+//! * keys: `x ← Zp`, `W = g2·x`
+//! * sign: `B = g1 + h0·s + Σ hᵢ·mᵢ`, `A = B·1/(e+x)`, signature `(A, e, s)`
+//! * presentation: randomise `A' = A·r1`, `Ā = A'·(-e) + B·r1`, `d = B·r1 - h0·r2`
+//!   and prove knowledge of `e, r2, r3 = 1/r1, s' = s - r2·r3` and the hidden
+//!   messages with a Fiat–Shamir Schnorr proof; the verifier also checks
+//!   `e(A', W) = e(Ā, g2)`.
+//!
+//! Message generators `h0..hL` come from hash-to-curve, so nobody knows their
+//! discrete logs.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use ark_bls12_381::{g1, Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::hashing::curve_maps::wb::WBMap;
+use ark_ec::hashing::map_to_curve_hasher::MapToCurveBasedHasher;
+use ark_ec::hashing::HashToCurve;
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::field_hashers::{DefaultFieldHasher, HashToField};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+use super::ZkError;
+
+const GENERATOR_DST: &[u8] = b"EVBATTERY-BBS+-BLS12381G1_XMD:SHA-256_SSWU_RO_H2G_";
+const MESSAGE_DST: &[u8] = b"EVBATTERY-BBS+-MESSAGE-TO-SCALAR";
+const CHALLENGE_DST: &[u8] = b"EVBATTERY-BBS+-CHALLENGE";
+const KEYGEN_DST: &[u8] = b"EVBATTERY-BBS+-KEYGEN";
+
+/// Maximum attributes in one credential.
+pub const MAX_ATTRIBUTES: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BbsError {
+    /// The credential has no attributes, or more than [`MAX_ATTRIBUTES`].
+    InvalidAttributeCount(usize),
+    /// A requested or revealed attribute is not in the credential.
+    UnknownAttribute(String),
+    /// The signature does not verify against the attributes.
+    InvalidSignature,
+    /// Two revealed attributes claim the same index.
+    DuplicateIndex(usize),
+}
+
+impl fmt::Display for BbsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidAttributeCount(n) => write!(f, "credential must have 1..={MAX_ATTRIBUTES} attributes, got {n}"),
+            Self::UnknownAttribute(name) => write!(f, "unknown attribute {name:?}"),
+            Self::InvalidSignature => write!(f, "signature does not verify against the credential attributes"),
+            Self::DuplicateIndex(i) => write!(f, "more than one revealed attribute has index {i}"),
+        }
+    }
+}
+
+impl std::error::Error for BbsError {}
+
+// ─── Encoding helpers (shared with the revocation accumulator) ───────────────
+
+/// Hash arbitrary bytes to a scalar with a domain separation tag.
+pub(crate) fn hash_to_scalar(dst: &[u8], msg: &[u8]) -> Fr {
+    let hasher = <DefaultFieldHasher<Sha256> as HashToField<Fr>>::new(dst);
+    hasher.hash_to_field(msg, 1)[0]
+}
+
+/// Hash arbitrary bytes to a G1 point with unknown discrete log.
+pub(crate) fn hash_to_g1(dst: &[u8], msg: &[u8]) -> G1Affine {
+    MapToCurveBasedHasher::<G1Projective, DefaultFieldHasher<Sha256>, WBMap<g1::Config>>::new(dst)
+        .and_then(|h| h.hash(msg))
+        .expect("hash-to-curve for BLS12-381 G1 is always available")
+}
+
+pub(crate) fn to_bytes<T: CanonicalSerialize>(value: &T) -> Vec<u8> {
+    let mut out = Vec::with_capacity(value.compressed_size());
+    value.serialize_compressed(&mut out).expect("serializing into a Vec cannot fail");
+    out
+}
+
+pub(crate) fn from_bytes<T: CanonicalDeserialize>(bytes: &[u8], err: ZkError) -> Result<T, ZkError> {
+    // `deserialize_compressed` checks points are on the curve and in the prime-order
+    // subgroup, and that scalars are canonical.
+    T::deserialize_compressed(bytes).map_err(|_| err)
+}
+
+/// `h0` (for the blinding `s`) followed by one generator per message.
+fn generators(message_count: usize) -> Vec<G1Affine> {
+    (0..=message_count as u64).map(|i| hash_to_g1(GENERATOR_DST, &i.to_be_bytes())).collect()
+}
+
+fn msm(bases: &[G1Affine], scalars: &[Fr]) -> G1Projective {
+    G1Projective::msm(bases, scalars).expect("bases and scalars have equal length")
+}
+
+/// `B = g1 + h0·s + Σ hᵢ·mᵢ`.
+fn signed_point(gens: &[G1Affine], s: &Fr, messages: &[Fr]) -> G1Projective {
+    G1Projective::generator() + gens[0] * s + msm(&gens[1..], messages)
+}
+
+// ─── Keys ────────────────────────────────────────────────────────────────────
+
+/// Issuer secret key `x`.
+#[derive(Clone)]
+pub struct SecretKey(Fr);
+
+/// Issuer public key `W = g2·x`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicKey(G2Affine);
+
+impl SecretKey {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    /// Derive a key deterministically from seed material (at least 32 bytes of entropy).
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self(hash_to_scalar(KEYGEN_DST, seed))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        let x: Fr = from_bytes(bytes, ZkError::NonCanonicalScalar("secret key"))?;
+        if x.is_zero() {
+            return Err(ZkError::NonCanonicalScalar("secret key"));
+        }
+        Ok(Self(x))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(&self.0)
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey((G2Projective::generator() * self.0).into_affine())
+    }
+}
+
+impl PublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        let w: G2Affine = from_bytes(bytes, ZkError::MalformedPoint("public key"))?;
+        if w.is_zero() {
+            return Err(ZkError::MalformedPoint("public key"));
+        }
+        Ok(Self(w))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(&self.0)
+    }
+}
+
+// ─── Signatures over scalar messages ─────────────────────────────────────────
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct Signature {
+    a: G1Affine,
+    e: Fr,
+    s: Fr,
+}
+
+impl Signature {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        from_bytes(bytes, ZkError::ProofDeserialization("BBS+ signature"))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(self)
+    }
+}
+
+/// Sign `messages` (one scalar per attribute).
+pub fn sign<R: RngCore + CryptoRng>(sk: &SecretKey, messages: &[Fr], rng: &mut R) -> Signature {
+    let gens = generators(messages.len());
+    loop {
+        let e = Fr::rand(rng);
+        let s = Fr::rand(rng);
+        // e + x = 0 happens with negligible probability; resample rather than panic.
+        let Some(inv) = (e + sk.0).inverse() else { continue };
+        let a = (signed_point(&gens, &s, messages) * inv).into_affine();
+        return Signature { a, e, s };
+    }
+}
+
+/// Check `e(A, W + g2·e) = e(B, g2)`.
+pub fn verify(pk: &PublicKey, messages: &[Fr], signature: &Signature) -> Result<(), ZkError> {
+    if signature.a.is_zero() {
+        return Err(BbsError::InvalidSignature.into());
+    }
+    let gens = generators(messages.len());
+    let b = signed_point(&gens, &signature.s, messages);
+    let lhs = Bls12_381::pairing(signature.a, pk.0 + G2Projective::generator() * signature.e);
+    let rhs = Bls12_381::pairing(b, G2Affine::generator());
+    if lhs != rhs {
+        return Err(BbsError::InvalidSignature.into());
+    }
+    Ok(())
+}
+
+/// Zero-knowledge proof of a signature on partially revealed messages.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SignatureProof {
+    a_prime: G1Affine,
+    a_bar: G1Affine,
+    d: G1Affine,
+    c: Fr,
+    e_hat: Fr,
+    r2_hat: Fr,
+    r3_hat: Fr,
+    s_hat: Fr,
+    /// Responses for hidden messages, in ascending index order.
+    m_hat: Vec<Fr>,
+}
+
+impl SignatureProof {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        from_bytes(bytes, ZkError::ProofDeserialization("BBS+ signature proof"))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(self)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn challenge(
+    pk: &PublicKey,
+    a_prime: &G1Affine,
+    a_bar: &G1Affine,
+    d: &G1Affine,
+    t1: &G1Affine,
+    t2: &G1Affine,
+    message_count: usize,
+    revealed: &BTreeMap<usize, Fr>,
+    nonce: &[u8],
+) -> Fr {
+    let mut transcript = Vec::new();
+    for point in [a_prime, a_bar, d, t1, t2] {
+        transcript.extend(to_bytes(point));
+    }
+    transcript.extend(pk.to_bytes());
+    transcript.extend((message_count as u64).to_be_bytes());
+    for (&i, m) in revealed {
+        transcript.extend((i as u64).to_be_bytes());
+        transcript.extend(to_bytes(m));
+    }
+    transcript.extend((nonce.len() as u64).to_be_bytes());
+    transcript.extend(nonce);
+    hash_to_scalar(CHALLENGE_DST, &transcript)
+}
+
+/// Prove knowledge of `signature` on `messages`, revealing only the indices in `revealed`.
+///
+/// `nonce` (presentation header) is chosen by the verifier to prevent replay.
+pub fn prove<R: RngCore + CryptoRng>(
+    pk: &PublicKey,
+    signature: &Signature,
+    messages: &[Fr],
+    revealed: &BTreeSet<usize>,
+    nonce: &[u8],
+    rng: &mut R,
+) -> Result<SignatureProof, ZkError> {
+    if let Some(&i) = revealed.iter().find(|&&i| i >= messages.len()) {
+        return Err(BbsError::UnknownAttribute(format!("#{i}")).into());
+    }
+    let gens = generators(messages.len());
+    let h0 = gens[0];
+    let hidden: Vec<usize> = (0..messages.len()).filter(|i| !revealed.contains(i)).collect();
+
+    let b = signed_point(&gens, &signature.s, messages);
+    let r1 = loop {
+        let r = Fr::rand(rng);
+        if !r.is_zero() {
+            break r;
+        }
+    };
+    let r2 = Fr::rand(rng);
+    let r3 = r1.inverse().expect("r1 is non-zero");
+
+    let a_prime = (signature.a * r1).into_affine();
+    let a_bar = (a_prime * (-signature.e) + b * r1).into_affine();
+    let d = (b * r1 - h0 * r2).into_affine();
+    let s_prime = signature.s - r2 * r3;
+
+    // Schnorr commitments for
+    //   Ā - d            = A'·(-e) + h0·r2
+    //   g1 + Σᵣ hᵢ·mᵢ    = d·r3 - h0·s' - Σₕ hⱼ·mⱼ
+    let e_tilde = Fr::rand(rng);
+    let r2_tilde = Fr::rand(rng);
+    let r3_tilde = Fr::rand(rng);
+    let s_tilde = Fr::rand(rng);
+    let m_tilde: Vec<Fr> = hidden.iter().map(|_| Fr::rand(rng)).collect();
+
+    let hidden_gens: Vec<G1Affine> = hidden.iter().map(|&j| gens[j + 1]).collect();
+    let t1 = (a_prime * (-e_tilde) + h0 * r2_tilde).into_affine();
+    let t2 = (d * r3_tilde - h0 * s_tilde - msm(&hidden_gens, &m_tilde)).into_affine();
+
+    let revealed_messages = revealed.iter().map(|&i| (i, messages[i])).collect();
+    let c = challenge(pk, &a_prime, &a_bar, &d, &t1, &t2, messages.len(), &revealed_messages, nonce);
+
+    Ok(SignatureProof {
+        a_prime,
+        a_bar,
+        d,
+        c,
+        e_hat: e_tilde + c * signature.e,
+        r2_hat: r2_tilde + c * r2,
+        r3_hat: r3_tilde + c * r3,
+        s_hat: s_tilde + c * s_prime,
+        m_hat: hidden.iter().zip(&m_tilde).map(|(&j, mt)| *mt + c * messages[j]).collect(),
+    })
+}
+
+/// Verify a [`SignatureProof`] for `message_count` messages of which `revealed` are disclosed.
+pub fn verify_proof(
+    pk: &PublicKey,
+    proof: &SignatureProof,
+    message_count: usize,
+    revealed: &BTreeMap<usize, Fr>,
+    nonce: &[u8],
+) -> Result<(), ZkError> {
+    if revealed.keys().any(|&i| i >= message_count) || proof.m_hat.len() + revealed.len() != message_count {
+        return Err(ZkError::InvalidParameters(format!(
+            "proof hides {} and reveals {} of {message_count} attributes",
+            proof.m_hat.len(),
+            revealed.len()
+        )));
+    }
+    if proof.a_prime.is_zero() {
+        return Err(ZkError::TranscriptMismatch);
+    }
+    if Bls12_381::pairing(proof.a_prime, pk.0) != Bls12_381::pairing(proof.a_bar, G2Affine::generator()) {
+        return Err(ZkError::TranscriptMismatch);
+    }
+
+    let gens = generators(message_count);
+    let h0 = gens[0];
+    let hidden_gens: Vec<G1Affine> =
+        (0..message_count).filter(|i| !revealed.contains_key(i)).map(|j| gens[j + 1]).collect();
+    let revealed_gens: Vec<G1Affine> = revealed.keys().map(|&i| gens[i + 1]).collect();
+    let revealed_msgs: Vec<Fr> = revealed.values().copied().collect();
+    let c = proof.c;
+
+    let t1 = (proof.a_prime * (-proof.e_hat) + h0 * proof.r2_hat - (proof.a_bar.into_group() - proof.d) * c)
+        .into_affine();
+    let public_part = G1Projective::generator() + msm(&revealed_gens, &revealed_msgs);
+    let t2 = (proof.d * proof.r3_hat - h0 * proof.s_hat - msm(&hidden_gens, &proof.m_hat) - public_part * c)
+        .into_affine();
+
+    if challenge(pk, &proof.a_prime, &proof.a_bar, &proof.d, &t1, &t2, message_count, revealed, nonce) != c {
+        return Err(ZkError::TranscriptMismatch);
+    }
+    Ok(())
+}
+
+// ─── VC attribute layer ──────────────────────────────────────────────────────
+
+/// Map each `credentialSubject` attribute to a message scalar.
+///
+/// Attributes are ordered by name (the index of each is its position), and
+/// each message commits to both name and canonical JSON value, so a revealed
+/// value cannot be presented under a different attribute name.
+pub fn encode_attributes(subject: &Map<String, Value>) -> Result<Vec<(String, Fr)>, ZkError> {
+    if subject.is_empty() || subject.len() > MAX_ATTRIBUTES {
+        return Err(BbsError::InvalidAttributeCount(subject.len()).into());
+    }
+    let mut sorted: Vec<(&String, &Value)> = subject.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    Ok(sorted.into_iter().map(|(name, value)| (name.clone(), attribute_message(name, value))).collect())
+}
+
+fn attribute_message(name: &str, value: &Value) -> Fr {
+    let mut msg = Vec::with_capacity(name.len() + 64);
+    msg.extend((name.len() as u64).to_be_bytes());
+    msg.extend(name.as_bytes());
+    canonical_json(value, &mut msg);
+    hash_to_scalar(MESSAGE_DST, &msg)
+}
+
+/// Compact JSON with object keys sorted at every depth, whatever order the
+/// map keeps them in (serde_json's `preserve_order` keeps insertion order).
+fn canonical_json(value: &Value, out: &mut Vec<u8>) {
+    match value {
+        Value::Object(fields) => {
+            let mut sorted: Vec<(&String, &Value)> = fields.iter().collect();
+            sorted.sort_by(|a, b| a.0.cmp(b.0));
+            out.push(b'{');
+            for (i, (key, value)) in sorted.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                out.extend(Value::String(key.clone()).to_string().as_bytes());
+                out.push(b':');
+                canonical_json(value, out);
+            }
+            out.push(b'}');
+        },
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                canonical_json(item, out);
+            }
+            out.push(b']');
+        },
+        scalar => out.extend(scalar.to_string().as_bytes()),
+    }
+}
+
+/// Sign every attribute of a `credentialSubject`.
+pub fn issue_credential<R: RngCore + CryptoRng>(
+    sk: &SecretKey,
+    subject: &Map<String, Value>,
+    rng: &mut R,
+) -> Result<Signature, ZkError> {
+    let messages: Vec<Fr> = encode_attributes(subject)?.into_iter().map(|(_, m)| m).collect();
+    Ok(sign(sk, &messages, rng))
+}
+
+/// An attribute disclosed in a presentation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RevealedAttribute {
+    pub index: usize,
+    pub value: Value,
+}
+
+/// Selective-disclosure presentation of a BBS+-signed credential.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Presentation {
+    pub attribute_count: usize,
+    pub revealed: BTreeMap<String, RevealedAttribute>,
+    /// Hex-encoded [`SignatureProof`].
+    pub proof: String,
+}
+
+/// Derive a presentation revealing only the attributes named in `reveal`.
+pub fn derive_presentation<R: RngCore + CryptoRng>(
+    pk: &PublicKey,
+    subject: &Map<String, Value>,
+    signature: &Signature,
+    reveal: &[String],
+    nonce: &[u8],
+    rng: &mut R,
+) -> Result<Presentation, ZkError> {
+    let attributes = encode_attributes(subject)?;
+    let messages: Vec<Fr> = attributes.iter().map(|(_, m)| *m).collect();
+    verify(pk, &messages, signature)?;
+
+    let mut revealed = BTreeMap::new();
+    let mut indices = BTreeSet::new();
+    for name in reveal {
+        let index = attributes
+            .iter()
+            .position(|(n, _)| n == name)
+            .ok_or_else(|| BbsError::UnknownAttribute(name.clone()))?;
+        indices.insert(index);
+        revealed.insert(name.clone(), RevealedAttribute { index, value: subject[name].clone() });
+    }
+
+    let proof = prove(pk, signature, &messages, &indices, nonce, rng)?;
+    Ok(Presentation { attribute_count: messages.len(), revealed, proof: hex::encode(proof.to_bytes()) })
+}
+
+/// Verify a presentation against the issuer key and the verifier's `nonce`.
+///
+/// A well-formed presentation that doesn't verify is a [`ZkError::TranscriptMismatch`].
+pub fn verify_presentation(pk: &PublicKey, presentation: &Presentation, nonce: &[u8]) -> Result<(), ZkError> {
+    if presentation.attribute_count == 0 || presentation.attribute_count > MAX_ATTRIBUTES {
+        return Err(BbsError::InvalidAttributeCount(presentation.attribute_count).into());
+    }
+    let proof_bytes = hex::decode(&presentation.proof)
+        .map_err(|_| ZkError::ProofDeserialization("BBS+ signature proof"))?;
+    let proof = SignatureProof::from_bytes(&proof_bytes)?;
+
+    let mut revealed = BTreeMap::new();
+    for (name, attr) in &presentation.revealed {
+        if revealed.insert(attr.index, attribute_message(name, &attr.value)).is_some() {
+            return Err(BbsError::DuplicateIndex(attr.index).into());
+        }
+    }
+    verify_proof(pk, &proof, presentation.attribute_count, &revealed, nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use serde_json::json;
+
+    fn subject() -> Map<String, Value> {
+        json!({
+            "id": "did:ethr:1337:0xabc",
+            "productName": "Battery Pack A",
+            "batch": "B-2024-07",
+            "quantity": 12,
+            "price": "{\"hidden\":true}",
+            "certificateCredential": { "name": "ISO", "cid": "bafy..." }
+        })
+        .as_object()
+        .unwrap()
+        .clone()
+    }
+
+    #[test]
+    fn signature_roundtrip() {
+        let sk = SecretKey::generate(&mut OsRng);
+        let pk = sk.public_key();
+        let messages: Vec<Fr> = (0..4u64).map(Fr::from).collect();
+        let sig = sign(&sk, &messages, &mut OsRng);
+        assert_eq!(verify(&pk, &messages, &sig), Ok(()));
+
+        let mut tampered = messages.clone();
+        tampered[2] = Fr::from(99u64);
+        assert_eq!(verify(&pk, &tampered, &sig), Err(BbsError::InvalidSignature.into()));
+        assert!(verify(&SecretKey::generate(&mut OsRng).public_key(), &messages, &sig).is_err());
+
+        assert_eq!(Signature::from_bytes(&sig.to_bytes()).unwrap(), sig);
+        assert_eq!(PublicKey::from_bytes(&pk.to_bytes()).unwrap(), pk);
+    }
+
+    #[test]
+    fn selective_disclosure_roundtrip() {
+        let sk = SecretKey::generate(&mut OsRng);
+        let pk = sk.public_key();
+        let subject = subject();
+        let sig = issue_credential(&sk, &subject, &mut OsRng).unwrap();
+
+        let reveal = vec!["productName".to_string(), "batch".to_string()];
+        let p = derive_presentation(&pk, &subject, &sig, &reveal, b"verifier-nonce", &mut OsRng).unwrap();
+        assert_eq!(p.attribute_count, 6);
+        assert_eq!(p.revealed.len(), 2);
+        assert!(!p.revealed.contains_key("price"));
+        assert_eq!(verify_presentation(&pk, &p, b"verifier-nonce"), Ok(()));
+
+        // Wrong nonce, wrong key and altered values must all fail.
+        assert_eq!(verify_presentation(&pk, &p, b"other-nonce"), Err(ZkError::TranscriptMismatch));
+        let other_pk = SecretKey::generate(&mut OsRng).public_key();
+        assert_eq!(verify_presentation(&other_pk, &p, b"verifier-nonce"), Err(ZkError::TranscriptMismatch));
+        let mut forged = p.clone();
+        forged.revealed.get_mut("batch").unwrap().value = json!("B-1999-01");
+        assert_eq!(verify_presentation(&pk, &forged, b"verifier-nonce"), Err(ZkError::TranscriptMismatch));
+        // Presenting a revealed value under another attribute's name fails too.
+        let mut renamed = p.clone();
+        let attr = renamed.revealed.remove("batch").unwrap();
+        renamed.revealed.insert("id".into(), attr);
+        assert_eq!(verify_presentation(&pk, &renamed, b"verifier-nonce"), Err(ZkError::TranscriptMismatch));
+    }
+
+    #[test]
+    fn reveal_all_and_none() {
+        let sk = SecretKey::generate(&mut OsRng);
+        let pk = sk.public_key();
+        let subject = subject();
+        let sig = issue_credential(&sk, &subject, &mut OsRng).unwrap();
+
+        let all: Vec<String> = subject.keys().cloned().collect();
+        for reveal in [all, vec![]] {
+            let p = derive_presentation(&pk, &subject, &sig, &reveal, b"n", &mut OsRng).unwrap();
+            assert_eq!(verify_presentation(&pk, &p, b"n"), Ok(()));
+        }
+    }
+
+    #[test]
+    fn attribute_messages_ignore_key_order() {
+        let sorted = json!({ "cid": "bafy...", "name": "ISO", "tags": [{ "a": 1, "b": [true, null] }] });
+        let shuffled: Value = serde_json::from_str(r#"{"tags":[{"b":[true,null],"a":1}],"name":"ISO","cid":"bafy..."}"#).unwrap();
+        assert_eq!(attribute_message("certificateCredential", &shuffled), attribute_message("certificateCredential", &sorted));
+
+        let mut canonical = Vec::new();
+        canonical_json(&shuffled, &mut canonical);
+        assert_eq!(canonical, br#"{"cid":"bafy...","name":"ISO","tags":[{"a":1,"b":[true,null]}]}"#);
+    }
+
+    #[test]
+    fn rejects_bad_inputs() {
+        let sk = SecretKey::generate(&mut OsRng);
+        let pk = sk.public_key();
+        let subject = subject();
+        let sig = issue_credential(&sk, &subject, &mut OsRng).unwrap();
+
+        let err = derive_presentation(&pk, &subject, &sig, &["vin".to_string()], b"n", &mut OsRng).unwrap_err();
+        assert_eq!(err, BbsError::UnknownAttribute("vin".into()).into());
+
+        let mut altered = subject.clone();
+        altered.insert("quantity".into(), json!(13));
+        let err = derive_presentation(&pk, &altered, &sig, &[], b"n", &mut OsRng).unwrap_err();
+        assert_eq!(err, BbsError::InvalidSignature.into());
+
+        assert_eq!(encode_attributes(&Map::new()), Err(BbsError::InvalidAttributeCount(0).into()));
+        assert_eq!(PublicKey::from_bytes(&[0u8; 96]), Err(ZkError::MalformedPoint("public key")));
+    }
+}
@@ -9,21 +9,22 @@ use rand::rngs::OsRng;
 use rand::{RngCore, CryptoRng};
 use tari_bulletproofs_plus::{
     commitment_opening::CommitmentOpening,
-    generators::pedersen_gens::ExtensionDegree,
-    range_parameters::RangeParameters,
     range_proof::VerifyAction,
     range_statement::RangeStatement,
     range_witness::RangeWitness,
-    ristretto::{create_pedersen_gens_with_extension_degree, RistrettoRangeProof},
+    ristretto::RistrettoRangeProof,
 };
 
+use super::generators::GeneratorRegistry;
+use super::ZkError;
+
 const LABEL: &[u8]   = b"TxID-BP+-256bit";
 const LIMB_BITS: usize = 64;            // each limb ∈ [0, 2⁶⁴)
 const LIMBS: usize     = 4;             // 4 × 64 = 256 bits
 
 /// Produce a BP⁺ proof for a full 32-byte tx-hash.
 /// Returns `(commitments, proof bytes)`.
-pub fn prove_txid_commitment(hash: [u8; 32]) -> (Vec<CompressedRistretto>, Vec<u8>) {
+pub fn prove_txid_commitment(hash: [u8; 32]) -> Result<(Vec<CompressedRistretto>, Vec<u8>), ZkError> {
     let mut rng = OsRng;
     prove_txid_commitment_with_rng(hash, &mut rng)
 }
@@ -34,7 +35,7 @@ pub fn prove_txid_commitment(hash: [u8; 32]) -> (Vec<CompressedRistretto>, Vec<u
 pub fn prove_txid_commitment_with_rng<R: RngCore + CryptoRng>(
     hash: [u8; 32],
     rng: &mut R,
-) -> (Vec<CompressedRistretto>, Vec<u8>) {
+) -> Result<(Vec<CompressedRistretto>, Vec<u8>), ZkError> {
     // 1️⃣ split hash into four little-endian 64-bit limbs
     let limbs: [u64; LIMBS] = [
         u64::from_le_bytes(hash[0..8].try_into().unwrap()),
@@ -43,71 +44,55 @@ pub fn prove_txid_commitment_with_rng<R: RngCore + CryptoRng>(
         u64::from_le_bytes(hash[24..32].try_into().unwrap()),
     ];
 
-    // 2️⃣ generators & parameters
-    // Note: These are created each time (same as BP's approach for consistency)
-    // In production, they could be cached, but for benchmarking we match BP's behavior
-    let pc_gens = create_pedersen_gens_with_extension_degree(ExtensionDegree::DefaultPedersen);
-    let params  = RangeParameters::init(LIMB_BITS, LIMBS, pc_gens.clone()).unwrap();
+    // 2️⃣ generators & parameters (shared, built once per process)
+    let params  = GeneratorRegistry::shared().bulletproofs_plus(LIMB_BITS, LIMBS)?;
 
     // 3️⃣ commit each limb
-    let mut commitments = Vec::with_capacity(LIMBS);
+    let mut points      = Vec::with_capacity(LIMBS);
     let mut openings    = Vec::with_capacity(LIMBS);
     for &limb in &limbs {
         let blind = Scalar::random(rng);
-        let com = pc_gens.commit(&Scalar::from(limb), &[blind]).unwrap();
-        commitments.push(com.compress());
+        points.push(params.pc_gens().commit(&Scalar::from(limb), &[blind])?);
         openings.push(CommitmentOpening::new(limb, vec![blind]));
     }
+    let commitments = points.iter().map(|p| p.compress()).collect();
 
     // 4️⃣ witness & statement
-    let witness   = RangeWitness::init(openings).unwrap();
-    let statement = RangeStatement::init(
-        params,
-        commitments.iter().map(|c| c.decompress().unwrap()).collect(),
-        vec![None; LIMBS],
-        None
-    ).unwrap();
+    let witness   = RangeWitness::init(openings)?;
+    let statement = RangeStatement::init((*params).clone(), points, vec![None; LIMBS], None)?;
 
     // 5️⃣ prove
     // Note: BP+ requires RNG during proof generation (unlike BP which uses pre-computed blinding)
     // This is a fundamental difference in the APIs
     let mut transcript = Transcript::new(LABEL);
-    let proof = RistrettoRangeProof::prove_with_rng(&mut transcript, &statement, &witness, rng).unwrap();
+    let proof = RistrettoRangeProof::prove_with_rng(&mut transcript, &statement, &witness, rng)?;
 
-    (commitments, proof.to_bytes())
+    Ok((commitments, proof.to_bytes()))
 }
 
 /// Verify a BP⁺ proof produced above.
+#[allow(clippy::useless_vec)]
 pub fn verify_txid_commitment(
     commitments: Vec<CompressedRistretto>,
     proof_bytes: Vec<u8>,
-) -> bool {
+) -> Result<(), ZkError> {
     if commitments.len() != LIMBS {
-        return false;
+        return Err(ZkError::InvalidParameters(format!("expected {LIMBS} commitments, got {}", commitments.len())));
     }
     // deserialize proof
-    let proof = match RistrettoRangeProof::from_bytes(&proof_bytes) {
-        Ok(p) => p,
-        Err(_) => return false,
-    };
+    let proof = RistrettoRangeProof::from_bytes(&proof_bytes)
+        .map_err(|_| ZkError::ProofDeserialization("BP+ range proof"))?;
 
-    // params
-    let pc_gens = create_pedersen_gens_with_extension_degree(ExtensionDegree::DefaultPedersen);
-    let params  = RangeParameters::init(LIMB_BITS, LIMBS, pc_gens).unwrap();
+    // params; the shared copy carries the precomputed verification tables
+    let params  = GeneratorRegistry::shared().bulletproofs_plus(LIMB_BITS, LIMBS)?;
 
     // decompress commitments; bail if malformed
-    let decompressed: Option<Vec<_>> = commitments.iter().map(|c| c.decompress()).collect();
-    let decompressed = match decompressed {
-        Some(v) => v,
-        None => return false,
-    };
-
-    let statement = RangeStatement::init(
-        params,
-        decompressed,
-        vec![None; LIMBS],
-        None
-    ).unwrap();
+    let decompressed = commitments
+        .iter()
+        .map(|c| c.decompress().ok_or(ZkError::MalformedPoint("commitment")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let statement = RangeStatement::init((*params).clone(), decompressed, vec![None; LIMBS], None)?;
 
     let transcript = Transcript::new(LABEL);
     RistrettoRangeProof::verify_batch(
@@ -115,8 +100,8 @@ pub fn verify_txid_commitment(
         &vec![statement],
         &vec![proof],
         VerifyAction::VerifyOnly,
-    )
-    .is_ok()
+    )?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -128,15 +113,15 @@ mod tests {
     fn valid_bp_plus_roundtrip() {
         let mut hash = [0u8; 32];
         rand::thread_rng().fill_bytes(&mut hash);
-        let (coms, p) = prove_txid_commitment(hash);
-        assert!(verify_txid_commitment(coms, p));
+        let (coms, p) = prove_txid_commitment(hash).unwrap();
+        assert_eq!(verify_txid_commitment(coms, p), Ok(()));
     }
 
     #[test]
     fn invalid_bp_plus_proof() {
         let hash = [1u8; 32];
-        let (coms, mut p) = prove_txid_commitment(hash);
+        let (coms, mut p) = prove_txid_commitment(hash).unwrap();
         p[5] ^= 0xAB; // corrupt proof
-        assert!(!verify_txid_commitment(coms, p));
+        assert!(verify_txid_commitment(coms, p).is_err());
     }
 }
\ No newline at end of file
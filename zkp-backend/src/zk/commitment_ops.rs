@@ -0,0 +1,90 @@
+//! Homomorphic arithmetic on Pedersen value commitments.
+//!
+//! `C(v, r) = v·B + r·B_blinding`, so `C(a, r) + C(b, s) = C(a + b, r + s)` and
+//! `k·C(v, r) = C(k·v, k·r)`. Holders who know the openings apply the same
+//! operation to their blindings to be able to open (or prove over) the result.
+//!
+//! Inputs are compressed Ristretto points as produced by [`super::pedersen`];
+//! anything that isn't a canonical encoding of a valid point is rejected.
+
+use curve25519_dalek_ng::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek_ng::scalar::Scalar;
+
+use super::ZkError;
+
+/// Decompress `bytes`, accepting only the canonical encoding of a valid point.
+pub fn decompress_canonical(bytes: &[u8; 32]) -> Option<RistrettoPoint> {
+    let point = CompressedRistretto(*bytes).decompress()?;
+    // `decompress` already rejects non-canonical field elements; the round-trip
+    // makes the canonicity guarantee explicit rather than an implementation detail.
+    (point.compress().as_bytes() == bytes).then_some(point)
+}
+
+fn operand(bytes: &[u8; 32], which: &'static str) -> Result<RistrettoPoint, ZkError> {
+    decompress_canonical(bytes).ok_or(ZkError::MalformedPoint(which))
+}
+
+/// `lhs + rhs`: commitment to the sum of the committed values.
+pub fn add_commitments(lhs: &[u8; 32], rhs: &[u8; 32]) -> Result<CompressedRistretto, ZkError> {
+    Ok((operand(lhs, "lhs")? + operand(rhs, "rhs")?).compress())
+}
+
+/// `lhs - rhs`: commitment to the difference of the committed values.
+pub fn sub_commitments(lhs: &[u8; 32], rhs: &[u8; 32]) -> Result<CompressedRistretto, ZkError> {
+    Ok((operand(lhs, "lhs")? - operand(rhs, "rhs")?).compress())
+}
+
+/// `factor · commitment`: commitment to the committed value times `factor`.
+pub fn scale_commitment(commitment: &[u8; 32], factor: u64) -> Result<CompressedRistretto, ZkError> {
+    Ok((operand(commitment, "commitment")? * Scalar::from(factor)).compress())
+}
+
+/// Blinding of `add_commitments(C(_, lhs), C(_, rhs))`.
+pub fn add_blindings(lhs: &Scalar, rhs: &Scalar) -> Scalar {
+    lhs + rhs
+}
+
+/// Blinding of `sub_commitments(C(_, lhs), C(_, rhs))`.
+pub fn sub_blindings(lhs: &Scalar, rhs: &Scalar) -> Scalar {
+    lhs - rhs
+}
+
+/// Blinding of `scale_commitment(C(_, blinding), factor)`.
+pub fn scale_blinding(blinding: &Scalar, factor: u64) -> Scalar {
+    blinding * Scalar::from(factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bulletproofs::PedersenGens;
+
+    fn commit(value: u64, blinding: Scalar) -> [u8; 32] {
+        PedersenGens::default().commit(Scalar::from(value), blinding).compress().to_bytes()
+    }
+
+    #[test]
+    fn arithmetic_matches_openings() {
+        let (r, s) = (Scalar::from(11u64), Scalar::from(29u64));
+        let (a, b) = (commit(1000, r), commit(250, s));
+
+        assert_eq!(add_commitments(&a, &b).unwrap().to_bytes(), commit(1250, add_blindings(&r, &s)));
+        assert_eq!(sub_commitments(&a, &b).unwrap().to_bytes(), commit(750, sub_blindings(&r, &s)));
+        assert_eq!(scale_commitment(&b, 4).unwrap().to_bytes(), commit(1000, scale_blinding(&s, 4)));
+    }
+
+    #[test]
+    fn rejects_non_canonical_points() {
+        let valid = commit(1, Scalar::one());
+        // 2^255 - 1 is not a canonical field element.
+        let mut non_canonical = [0xffu8; 32];
+        non_canonical[31] = 0x7f;
+        // Ristretto encodings must be "non-negative" (low bit clear).
+        let mut negative = valid;
+        negative[0] |= 1;
+
+        assert_eq!(add_commitments(&non_canonical, &valid), Err(ZkError::MalformedPoint("lhs")));
+        assert_eq!(sub_commitments(&valid, &negative), Err(ZkError::MalformedPoint("rhs")));
+        assert_eq!(scale_commitment(&negative, 2), Err(ZkError::MalformedPoint("commitment")));
+    }
+}
@@ -0,0 +1,173 @@
+//! Error type shared by every fallible function in [`crate::zk`].
+//!
+//! Cross-cutting failures (bad encodings, proofs that don't verify, generator
+//! limits) have their own variants; scheme-specific failures are wrapped in
+//! the owning module's error enum. [`ZkError::code`] gives a stable,
+//! machine-readable identifier for API responses.
+
+use std::fmt;
+
+use bulletproofs::r1cs::R1CSError;
+use bulletproofs::range_proof_mpc::MPCError;
+use bulletproofs::ProofError;
+
+use super::aggregated_range_proof::AggregationError;
+use super::bbs_plus::BbsError;
+use super::r1cs_statement::StatementError;
+use super::revocation_accumulator::AccumulatorError;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZkError {
+    /// Bytes are not the canonical encoding of a valid group element (`what`).
+    MalformedPoint(&'static str),
+    /// Bytes are not the canonical encoding of a scalar (`what`).
+    NonCanonicalScalar(&'static str),
+    /// Proof bytes could not be decoded (`what`).
+    ProofDeserialization(&'static str),
+    /// A well-formed proof does not verify against the transcript it was
+    /// checked with: wrong commitment, binding tag, statement or nonce.
+    TranscriptMismatch,
+    /// A value does not fit in the requested number of bits.
+    RangeOverflow { bits: usize },
+    /// The proof needs more generators than are available.
+    GeneratorCapacity(String),
+    /// Arguments are well-formed but unsupported (bit range, lengths, counts).
+    InvalidParameters(String),
+    Aggregation(AggregationError),
+    Statement(StatementError),
+    Credential(BbsError),
+    Revocation(AccumulatorError),
+}
+
+impl ZkError {
+    /// Stable identifier for the error kind, safe to match on in clients.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MalformedPoint(_) => "malformed_point",
+            Self::NonCanonicalScalar(_) => "non_canonical_scalar",
+            Self::ProofDeserialization(_) => "proof_deserialization",
+            Self::TranscriptMismatch => "transcript_mismatch",
+            Self::RangeOverflow { .. } => "range_overflow",
+            Self::GeneratorCapacity(_) => "generator_capacity",
+            Self::InvalidParameters(_) => "invalid_parameters",
+            Self::Aggregation(_) => "aggregation_session",
+            Self::Statement(StatementError::Unsatisfied { .. }) => "statement_unsatisfied",
+            Self::Statement(_) => "invalid_statement",
+            Self::Credential(_) => "credential",
+            Self::Revocation(_) => "revocation",
+        }
+    }
+
+    /// HTTP status the server answers this error with: 400 for unusable
+    /// input, 409 for conflicting session or registry state, 422 for
+    /// well-formed input that cannot be proven or verified.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            Self::Statement(StatementError::Unsatisfied { .. }) => 422,
+            Self::MalformedPoint(_)
+            | Self::NonCanonicalScalar(_)
+            | Self::ProofDeserialization(_)
+            | Self::InvalidParameters(_)
+            | Self::Statement(_)
+            | Self::Credential(_) => 400,
+            Self::Aggregation(
+                AggregationError::InvalidPartyCount(_) | AggregationError::InvalidBitRange(_) | AggregationError::InvalidPartyIndex(_),
+            ) => 400,
+            Self::Aggregation(_) | Self::Revocation(AccumulatorError::AlreadyMember | AccumulatorError::NotMember) => 409,
+            Self::TranscriptMismatch
+            | Self::RangeOverflow { .. }
+            | Self::GeneratorCapacity(_)
+            | Self::Revocation(AccumulatorError::InvalidWitness) => 422,
+        }
+    }
+}
+
+impl fmt::Display for ZkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MalformedPoint(what) => write!(f, "{what} is not a valid canonical point encoding"),
+            Self::NonCanonicalScalar(what) => write!(f, "{what} is not a canonical scalar"),
+            Self::ProofDeserialization(what) => write!(f, "{what} could not be decoded"),
+            Self::TranscriptMismatch => {
+                write!(f, "proof does not verify for this commitment, binding tag or statement")
+            }
+            Self::RangeOverflow { bits } => write!(f, "value does not fit in {bits} bits"),
+            Self::GeneratorCapacity(detail) => write!(f, "insufficient generators: {detail}"),
+            Self::InvalidParameters(detail) => write!(f, "invalid parameters: {detail}"),
+            Self::Aggregation(e) => e.fmt(f),
+            Self::Statement(e) => e.fmt(f),
+            Self::Credential(e) => e.fmt(f),
+            Self::Revocation(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ZkError {}
+
+impl From<AggregationError> for ZkError {
+    fn from(e: AggregationError) -> Self {
+        Self::Aggregation(e)
+    }
+}
+
+impl From<StatementError> for ZkError {
+    fn from(e: StatementError) -> Self {
+        Self::Statement(e)
+    }
+}
+
+impl From<BbsError> for ZkError {
+    fn from(e: BbsError) -> Self {
+        Self::Credential(e)
+    }
+}
+
+impl From<AccumulatorError> for ZkError {
+    fn from(e: AccumulatorError) -> Self {
+        Self::Revocation(e)
+    }
+}
+
+impl From<R1CSError> for ZkError {
+    fn from(e: R1CSError) -> Self {
+        match e {
+            R1CSError::VerificationError => Self::TranscriptMismatch,
+            R1CSError::FormatError => Self::ProofDeserialization("R1CS proof"),
+            R1CSError::InvalidGeneratorsLength => Self::GeneratorCapacity(e.to_string()),
+            R1CSError::MissingAssignment | R1CSError::GadgetError { .. } => Self::InvalidParameters(e.to_string()),
+        }
+    }
+}
+
+impl From<ProofError> for ZkError {
+    fn from(e: ProofError) -> Self {
+        match e {
+            ProofError::VerificationError => Self::TranscriptMismatch,
+            ProofError::FormatError => Self::ProofDeserialization("range proof"),
+            ProofError::InvalidGeneratorsLength => Self::GeneratorCapacity(e.to_string()),
+            ProofError::ProvingError(mpc) => mpc.into(),
+            ProofError::WrongNumBlindingFactors | ProofError::InvalidBitsize | ProofError::InvalidAggregation => {
+                Self::InvalidParameters(e.to_string())
+            }
+        }
+    }
+}
+
+impl From<MPCError> for ZkError {
+    fn from(e: MPCError) -> Self {
+        match e {
+            MPCError::InvalidGeneratorsLength => Self::GeneratorCapacity(e.to_string()),
+            e => Self::Aggregation(AggregationError::Protocol(e)),
+        }
+    }
+}
+
+impl From<tari_bulletproofs_plus::errors::ProofError> for ZkError {
+    fn from(e: tari_bulletproofs_plus::errors::ProofError) -> Self {
+        use tari_bulletproofs_plus::errors::ProofError as PlusError;
+        match e {
+            PlusError::VerificationFailed(_) => Self::TranscriptMismatch,
+            e => Self::InvalidParameters(e.to_string()),
+        }
+    }
+}
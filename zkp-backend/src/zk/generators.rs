@@ -0,0 +1,175 @@
+//! Process-wide cache of Bulletproofs and BP⁺ generators.
+//!
+//! Generator vectors are deterministic, so every proof of a given shape can
+//! share one copy. Building them (and, for BP⁺, their multiscalar
+//! precomputation tables) dominates the cost of a small proof, so the
+//! registry builds each (scheme, bit range, aggregation size) once and hands
+//! out `Arc`s afterwards.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use bulletproofs::{BulletproofGens, PedersenGens};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use serde::{Deserialize, Serialize};
+use tari_bulletproofs_plus::{
+    generators::pedersen_gens::ExtensionDegree,
+    range_parameters::RangeParameters,
+    ristretto::create_pedersen_gens_with_extension_degree,
+};
+
+use super::ZkError;
+
+/// Proof system a set of generators belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Scheme {
+    /// Classic Bulletproofs (range proofs and R1CS), dalek-ng.
+    Bulletproofs,
+    /// Bulletproofs⁺ range proofs with precomputed verification tables.
+    BulletproofsPlus,
+}
+
+/// Shape of a cached generator set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct GeneratorKey {
+    pub scheme: Scheme,
+    /// Generators per party: the bit range for range proofs, the padded
+    /// multiplication count for R1CS.
+    pub bit_range: usize,
+    pub aggregation_size: usize,
+}
+
+impl GeneratorKey {
+    pub const fn bulletproofs(bit_range: usize, aggregation_size: usize) -> Self {
+        Self { scheme: Scheme::Bulletproofs, bit_range, aggregation_size }
+    }
+
+    pub const fn bulletproofs_plus(bit_range: usize, aggregation_size: usize) -> Self {
+        Self { scheme: Scheme::BulletproofsPlus, bit_range, aggregation_size }
+    }
+}
+
+/// Shapes used by the built-in proofs, worth building before the first request.
+pub const DEFAULT_SHAPES: &[GeneratorKey] = &[
+    GeneratorKey::bulletproofs(64, 1),
+    GeneratorKey::bulletproofs(64, 4),
+    GeneratorKey::bulletproofs_plus(64, 4),
+];
+
+type PlusParameters = RangeParameters<RistrettoPoint>;
+
+pub struct GeneratorRegistry {
+    pc_gens: PedersenGens,
+    bulletproofs: RwLock<HashMap<(usize, usize), Arc<BulletproofGens>>>,
+    bulletproofs_plus: RwLock<HashMap<(usize, usize), Arc<PlusParameters>>>,
+}
+
+impl GeneratorRegistry {
+    pub fn new() -> Self {
+        Self {
+            pc_gens: PedersenGens::default(),
+            bulletproofs: RwLock::default(),
+            bulletproofs_plus: RwLock::default(),
+        }
+    }
+
+    /// The registry shared by every prover and verifier in the process.
+    pub fn shared() -> &'static Self {
+        static SHARED: OnceLock<GeneratorRegistry> = OnceLock::new();
+        SHARED.get_or_init(Self::new)
+    }
+
+    /// Pedersen generators for classic Bulletproofs commitments.
+    pub fn pedersen(&self) -> &PedersenGens {
+        &self.pc_gens
+    }
+
+    /// Classic Bulletproofs generators for `bit_range` × `aggregation_size`.
+    pub fn bulletproofs(&self, bit_range: usize, aggregation_size: usize) -> Arc<BulletproofGens> {
+        let key = (bit_range, aggregation_size);
+        if let Some(gens) = self.bulletproofs.read().unwrap().get(&key) {
+            return gens.clone();
+        }
+        let gens = Arc::new(BulletproofGens::new(bit_range, aggregation_size));
+        self.bulletproofs.write().unwrap().entry(key).or_insert(gens).clone()
+    }
+
+    /// BP⁺ range parameters, including the interleaved precomputation tables
+    /// the verifier's multiscalar multiplication runs on.
+    pub fn bulletproofs_plus(&self, bit_range: usize, aggregation_size: usize) -> Result<Arc<PlusParameters>, ZkError> {
+        let key = (bit_range, aggregation_size);
+        if let Some(params) = self.bulletproofs_plus.read().unwrap().get(&key) {
+            return Ok(params.clone());
+        }
+        let pc_gens = create_pedersen_gens_with_extension_degree(ExtensionDegree::DefaultPedersen);
+        let params = RangeParameters::init(bit_range, aggregation_size, pc_gens)
+            .map_err(|e| ZkError::GeneratorCapacity(e.to_string()))?;
+        Ok(self.bulletproofs_plus.write().unwrap().entry(key).or_insert(Arc::new(params)).clone())
+    }
+
+    /// Build the generators for `shapes` ahead of time.
+    pub fn warm_up(&self, shapes: &[GeneratorKey]) -> Result<(), ZkError> {
+        for key in shapes {
+            match key.scheme {
+                Scheme::Bulletproofs => {
+                    self.bulletproofs(key.bit_range, key.aggregation_size);
+                },
+                Scheme::BulletproofsPlus => {
+                    self.bulletproofs_plus(key.bit_range, key.aggregation_size)?;
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Shapes currently cached, sorted.
+    pub fn cached(&self) -> Vec<GeneratorKey> {
+        let classic = self.bulletproofs.read().unwrap().keys().map(|&(b, n)| GeneratorKey::bulletproofs(b, n)).collect::<Vec<_>>();
+        let plus = self.bulletproofs_plus.read().unwrap().keys().map(|&(b, n)| GeneratorKey::bulletproofs_plus(b, n)).collect::<Vec<_>>();
+        let mut keys = [classic, plus].concat();
+        keys.sort();
+        keys
+    }
+}
+
+impl Default for GeneratorRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for GeneratorRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneratorRegistry").field("cached", &self.cached()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_shape_is_built_once() {
+        let registry = GeneratorRegistry::new();
+        let a = registry.bulletproofs(64, 1);
+        let b = registry.bulletproofs(64, 1);
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &registry.bulletproofs(32, 1)));
+
+        let p = registry.bulletproofs_plus(64, 4).unwrap();
+        assert!(Arc::ptr_eq(&p, &registry.bulletproofs_plus(64, 4).unwrap()));
+        assert_eq!(
+            registry.cached(),
+            vec![GeneratorKey::bulletproofs(32, 1), GeneratorKey::bulletproofs(64, 1), GeneratorKey::bulletproofs_plus(64, 4)]
+        );
+    }
+
+    #[test]
+    fn unsupported_plus_shape_is_an_error() {
+        let registry = GeneratorRegistry::new();
+        assert!(matches!(registry.bulletproofs_plus(63, 1), Err(ZkError::GeneratorCapacity(_))));
+        assert!(registry.cached().is_empty());
+    }
+}
@@ -1,5 +1,15 @@
 pub mod txid_pedersen_proof;
 pub mod bp_plus_pedersen;
 pub mod pedersen; 
+pub mod aggregated_range_proof;
+pub mod r1cs_statement;
+pub mod commitment_ops;
+pub mod bbs_plus;
+pub mod revocation_accumulator;
+pub mod error;
+pub mod generators;
+pub mod self_test;
+
+pub use error::ZkError;
 
 
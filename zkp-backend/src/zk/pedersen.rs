@@ -1,19 +1,24 @@
 use bulletproofs::r1cs::{ConstraintSystem, Prover, Verifier};
-use bulletproofs::{BulletproofGens, PedersenGens};
 use bulletproofs::RangeProof;
 use curve25519_dalek_ng::ristretto::CompressedRistretto;
 use curve25519_dalek_ng::scalar::Scalar;
 use merlin::Transcript;
 use rand::rngs::OsRng;
 use rand::RngCore;
+use tracing::{debug, warn};
 
-pub fn prove_equal_42() {
+use super::commitment_ops::decompress_canonical;
+use super::generators::GeneratorRegistry;
+use super::ZkError;
+
+pub fn prove_equal_42() -> Result<(), ZkError> {
     // Step 1: Secret value
     let secret_value: u64 = 42;
 
     // Step 2: Generators
-    let pc_gens = PedersenGens::default();
-    let bp_gens = BulletproofGens::new(64, 1);
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(64, 1);
     let mut rng = OsRng;
 
     // Step 3: Prover commits to secret
@@ -33,7 +38,7 @@ pub fn prove_equal_42() {
         prover.constrain(var - Scalar::from(42u64));
 
         // Create proof
-        let proof = prover.prove(&bp_gens).unwrap();
+        let proof = prover.prove(&bp_gens)?;
         (proof, com)
     };
 
@@ -43,16 +48,15 @@ pub fn prove_equal_42() {
         let mut verifier = Verifier::new(&mut verifier_transcript);
         let var = verifier.commit(committed_value);
         verifier.constrain(var - Scalar::from(42u64));
-        verifier.verify(&proof, &pc_gens, &bp_gens).is_ok()
+        verifier.verify(&proof, &pc_gens, &bp_gens)
     };
 
-    println!("✅ Proof verified? {}", verified);
+    debug!(verified = verified.is_ok(), "demo proof checked");
+    Ok(verified?)
 }
 
 /// Proves knowledge of a value such that Pedersen(value, r) == commitment and value in [0, 2^64)
-pub fn prove_value_commitment(value: u64) -> (CompressedRistretto, Vec<u8>, bool) {
-    println!("▶️ Running: Bulletproofs range proof for value using Pedersen commitment");
-
+pub fn prove_value_commitment(value: u64) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
     let mut rng = OsRng;
 
     // Generate random blinding factor
@@ -68,7 +72,7 @@ pub fn prove_value_commitment(value: u64) -> (CompressedRistretto, Vec<u8>, bool
 pub fn prove_value_commitment_with_blinding(
     value: u64,
     blinding: Scalar,
-) -> (CompressedRistretto, Vec<u8>, bool) {
+) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
     // Call with no binding tag for backward compatibility
     prove_value_commitment_with_binding(value, blinding, None)
 }
@@ -82,9 +86,17 @@ pub fn prove_value_commitment_with_binding_and_range(
     blinding: Scalar,
     binding_tag: Option<&[u8]>,
     bit_range: usize,
-) -> (CompressedRistretto, Vec<u8>, bool) {
-    let pc_gens = PedersenGens::default();
-    let bp_gens = BulletproofGens::new(bit_range, 1); // variable bit range, 1 party
+) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
+    if !matches!(bit_range, 8 | 16 | 32 | 64) {
+        return Err(ZkError::InvalidParameters(format!("bit range {bit_range} must be 8, 16, 32 or 64")));
+    }
+    // The prover would happily produce a proof that can never verify.
+    if bit_range < 64 && value >> bit_range != 0 {
+        return Err(ZkError::RangeOverflow { bits: bit_range });
+    }
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(bit_range, 1); // variable bit range, 1 party
 
     // Prover phase: use RangeProof API with provided blinding and binding tag
     let mut transcript = Transcript::new(b"ValueRangeProof");
@@ -101,7 +113,7 @@ pub fn prove_value_commitment_with_binding_and_range(
         value,
         &blinding,
         bit_range,
-    ).expect("Range proof generation should not fail");
+    )?;
     let proof_bytes = proof.to_bytes();
 
     // Verifier phase (optional, for sanity check)
@@ -121,7 +133,7 @@ pub fn prove_value_commitment_with_binding_and_range(
         bit_range,
     ).is_ok();
 
-    (commitment, proof_bytes, verified)
+    Ok((commitment, proof_bytes, verified))
 }
 
 /// Proves knowledge of a value with a specific blinding factor and binding tag
@@ -130,15 +142,12 @@ pub fn prove_value_commitment_with_binding(
     value: u64,
     blinding: Scalar,
     binding_tag: Option<&[u8]>,
-) -> (CompressedRistretto, Vec<u8>, bool) {
-    if binding_tag.is_some() {
-        println!("▶️ Running: Bulletproofs range proof with binding tag");
-    } else {
-        println!("▶️ Running: Bulletproofs range proof for value with provided blinding factor");
-    }
+) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
+    debug!(bound = binding_tag.is_some(), "proving 64-bit value commitment");
 
-    let pc_gens = PedersenGens::default();
-    let bp_gens = BulletproofGens::new(64, 1); // 64-bit range, 1 party
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(64, 1); // 64-bit range, 1 party
 
     // Prover phase: use RangeProof API with provided blinding and binding tag
     let mut transcript = Transcript::new(b"ValueRangeProof");
@@ -146,7 +155,6 @@ pub fn prove_value_commitment_with_binding(
     // ✅ Add binding tag to transcript if provided
     if let Some(binding) = binding_tag {
         transcript.append_message(b"bind", binding);
-        println!("   📎 Binding tag added to transcript: {} bytes", binding.len());
     }
     
     let (proof, commitment) = RangeProof::prove_single(
@@ -156,7 +164,7 @@ pub fn prove_value_commitment_with_binding(
         value,
         &blinding,
         64,
-    ).expect("Range proof generation should not fail");
+    )?;
     let proof_bytes = proof.to_bytes();
     // commitment is already CompressedRistretto
 
@@ -177,16 +185,18 @@ pub fn prove_value_commitment_with_binding(
         64,
     ).is_ok();
 
-    println!("✅ ZK Range proof of value commitment verified? {}", verified);
+    if !verified {
+        warn!("value range proof failed its own verification");
+    }
 
-    (commitment, proof_bytes, verified)
+    Ok((commitment, proof_bytes, verified))
 }
 
 /// Verifies the proof of a value commitment
 pub fn verify_value_commitment(
     commitment: CompressedRistretto,
     proof_bytes: Vec<u8>
-) -> bool {
+) -> Result<(), ZkError> {
     // Call with no binding tag for backward compatibility
     verify_value_commitment_with_binding(commitment, proof_bytes, None)
 }
@@ -196,9 +206,27 @@ pub fn verify_value_commitment_with_binding(
     commitment: CompressedRistretto,
     proof_bytes: Vec<u8>,
     binding_tag: Option<&[u8]>,
-) -> bool {
-    let pc_gens = PedersenGens::default();
-    let bp_gens = BulletproofGens::new(64, 1);
+) -> Result<(), ZkError> {
+    verify_value_commitment_with_binding_and_range(commitment, proof_bytes, binding_tag, 64)
+}
+
+/// Verifies the proof of a value commitment with binding tag and bit range
+/// bit_range must match the one the proof was generated with
+pub fn verify_value_commitment_with_binding_and_range(
+    commitment: CompressedRistretto,
+    proof_bytes: Vec<u8>,
+    binding_tag: Option<&[u8]>,
+    bit_range: usize,
+) -> Result<(), ZkError> {
+    if !matches!(bit_range, 8 | 16 | 32 | 64) {
+        return Err(ZkError::InvalidParameters(format!("bit range {bit_range} must be 8, 16, 32 or 64")));
+    }
+    if decompress_canonical(commitment.as_bytes()).is_none() {
+        return Err(ZkError::MalformedPoint("commitment"));
+    }
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(bit_range, 1);
     let mut transcript = Transcript::new(b"ValueRangeProof");
     
     // ✅ Add binding tag to verification transcript if provided
@@ -206,18 +234,17 @@ pub fn verify_value_commitment_with_binding(
         transcript.append_message(b"bind", binding);
     }
     
-    let proof = match RangeProof::from_bytes(&proof_bytes) {
-        Ok(p) => p,
-        Err(_) => return false,
-    };
+    let proof = RangeProof::from_bytes(&proof_bytes)
+        .map_err(|_| ZkError::ProofDeserialization("range proof"))?;
     RangeProof::verify_single(
         &proof,
         &bp_gens,
         &pc_gens,
         &mut transcript,
         &commitment,
-        64,
-    ).is_ok()
+        bit_range,
+    )?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -227,19 +254,19 @@ mod value_commitment_tests {
     #[test]
     fn test_valid_value_commitment_proof() {
         let value = 123456u64;
-        let (commitment, proof_bytes, verified) = prove_value_commitment(value);
+        let (commitment, proof_bytes, verified) = prove_value_commitment(value).unwrap();
         assert!(verified, "Proof should verify locally");
-        assert!(verify_value_commitment(commitment, proof_bytes), "Proof should verify with public verifier");
+        assert!(verify_value_commitment(commitment, proof_bytes).is_ok(), "Proof should verify with public verifier");
     }
 
     #[test]
     fn test_invalid_value_commitment_proof() {
         let value1 = 123456u64;
         let value2 = 654321u64;
-        let (commitment1, proof_bytes, _) = prove_value_commitment(value1);
+        let (_commitment1, proof_bytes, _) = prove_value_commitment(value1).unwrap();
         // Try to verify proof_bytes against a different commitment
-        let (commitment2, _, _) = prove_value_commitment(value2);
-        assert!(!verify_value_commitment(commitment2, proof_bytes), "Proof for value1 should not verify for commitment2");
+        let (commitment2, _, _) = prove_value_commitment(value2).unwrap();
+        assert_eq!(verify_value_commitment(commitment2, proof_bytes), Err(ZkError::TranscriptMismatch), "Proof for value1 should not verify for commitment2");
     }
 
     #[test]
@@ -250,9 +277,9 @@ mod value_commitment_tests {
         let blinding = Scalar::from_bytes_mod_order(blinding_bytes);
 
         // Generate commitment twice with same blinding
-        let (commitment1, proof1_bytes, verified1) = prove_value_commitment_with_blinding(value, blinding);
+        let (commitment1, proof1_bytes, verified1) = prove_value_commitment_with_blinding(value, blinding).unwrap();
         let blinding2 = Scalar::from_bytes_mod_order(blinding_bytes);
-        let (commitment2, proof2_bytes, verified2) = prove_value_commitment_with_blinding(value, blinding2);
+        let (commitment2, proof2_bytes, verified2) = prove_value_commitment_with_blinding(value, blinding2).unwrap();
 
         // Both should verify
         assert!(verified1, "First proof should verify");
@@ -263,8 +290,8 @@ mod value_commitment_tests {
 
         // Proofs should be different (transcript randomness), but both should verify
         assert_ne!(proof1_bytes, proof2_bytes, "Proofs should be different due to transcript");
-        assert!(verify_value_commitment(commitment1, proof1_bytes), "First proof should verify");
-        assert!(verify_value_commitment(commitment2, proof2_bytes), "Second proof should verify");
+        assert!(verify_value_commitment(commitment1, proof1_bytes).is_ok(), "First proof should verify");
+        assert!(verify_value_commitment(commitment2, proof2_bytes).is_ok(), "Second proof should verify");
     }
 
     #[test]
@@ -275,20 +302,20 @@ mod value_commitment_tests {
         let binding_tag = b"test-binding-tag-32-bytes-long!!";
 
         // Test 1: Generate proof with binding tag
-        let (commitment, proof_bytes, verified) = prove_value_commitment_with_binding(value, blinding, Some(binding_tag));
+        let (commitment, proof_bytes, verified) = prove_value_commitment_with_binding(value, blinding, Some(binding_tag)).unwrap();
         assert!(verified, "Proof with binding tag should verify");
 
         // Test 2: Verify proof with correct binding tag
-        let verified_correct = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), Some(binding_tag));
+        let verified_correct = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), Some(binding_tag)).is_ok();
         assert!(verified_correct, "Proof should verify with correct binding tag");
 
         // Test 3: Verify proof with wrong binding tag (should fail)
         let wrong_binding_tag = b"wrong-binding-tag-32-bytes-long!!";
-        let verified_wrong = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), Some(wrong_binding_tag));
+        let verified_wrong = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), Some(wrong_binding_tag)).is_ok();
         assert!(!verified_wrong, "Proof should not verify with wrong binding tag");
 
         // Test 4: Verify proof without binding tag (should fail if generated with binding tag)
-        let verified_no_tag = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), None);
+        let verified_no_tag = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), None).is_ok();
         assert!(!verified_no_tag, "Proof generated with binding tag should not verify without binding tag");
     }
 
@@ -299,11 +326,32 @@ mod value_commitment_tests {
         let blinding = Scalar::from_bytes_mod_order(blinding_bytes);
 
         // Test: Generate proof without binding tag (backward compatible)
-        let (commitment, proof_bytes, verified) = prove_value_commitment_with_binding(value, blinding, None);
+        let (commitment, proof_bytes, verified) = prove_value_commitment_with_binding(value, blinding, None).unwrap();
         assert!(verified, "Proof without binding tag should verify");
 
         // Test: Verify proof without binding tag (backward compatible)
-        let verified_no_tag = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), None);
+        let verified_no_tag = verify_value_commitment_with_binding(commitment, proof_bytes.clone(), None).is_ok();
         assert!(verified_no_tag, "Proof generated without binding tag should verify without binding tag");
     }
+
+    #[test]
+    fn test_range_overflow_is_an_error() {
+        let blinding = Scalar::from_bytes_mod_order([0x42u8; 32]);
+        assert_eq!(
+            prove_value_commitment_with_binding_and_range(1 << 40, blinding, None, 32).unwrap_err(),
+            ZkError::RangeOverflow { bits: 32 }
+        );
+        assert!(matches!(
+            prove_value_commitment_with_binding_and_range(1, blinding, None, 48),
+            Err(ZkError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn test_verification_uses_the_proof_bit_range() {
+        let blinding = Scalar::from_bytes_mod_order([0x42u8; 32]);
+        let (commitment, proof_bytes, _) = prove_value_commitment_with_binding_and_range(200, blinding, None, 8).unwrap();
+        assert!(verify_value_commitment_with_binding_and_range(commitment, proof_bytes.clone(), None, 8).is_ok());
+        assert!(verify_value_commitment_with_binding(commitment, proof_bytes, None).is_err());
+    }
 }
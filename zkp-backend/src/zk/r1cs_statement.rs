@@ -0,0 +1,448 @@
+//! Declarative R1CS statements: callers describe a relation in JSON and we
+//! compile it to a `bulletproofs::r1cs` circuit, instead of hand-writing a new
+//! prover for every rule (sum of components, markup, fee split, …).
+//!
+//! ```json
+//! {
+//!   "variables": ["price", "fee", "total"],
+//!   "constants": { "fee_pct": 10 },
+//!   "linear": [
+//!     { "label": "total = price + fee",
+//!       "lhs": [{ "var": "total" }],
+//!       "rhs": [{ "var": "price" }, { "var": "fee" }] }
+//!   ],
+//!   "multiplications": [
+//!     { "label": "fee = 10% of price",
+//!       "left":  [{ "var": "price" }],
+//!       "right": [{ "var": "fee_pct" }],
+//!       "output": [{ "var": "fee", "coeff": 100 }] }
+//!   ]
+//! }
+//! ```
+//!
+//! `variables` are committed (the prover supplies openings, the verifier only
+//! sees Pedersen commitments); `constants` are public. A term is `coeff · name`,
+//! where `name` is a variable or constant, or just `coeff` when `var` is omitted.
+//! Linear constraints require `Σ lhs == Σ rhs`; multiplication gates require
+//! `(Σ left) · (Σ right) == Σ output`.
+//!
+//! Proofs are bound to the SHA-256 of the statement's canonical JSON, so a proof
+//! for one statement never verifies under another.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+use bulletproofs::r1cs::{ConstraintSystem, LinearCombination, Prover, R1CSProof, Variable, Verifier};
+use bulletproofs::BulletproofGens;
+use curve25519_dalek_ng::ristretto::CompressedRistretto;
+use curve25519_dalek_ng::scalar::Scalar;
+use merlin::Transcript;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::commitment_ops::decompress_canonical;
+use super::generators::GeneratorRegistry;
+use super::ZkError;
+
+const LABEL: &[u8] = b"DeclarativeR1CS";
+
+/// Maximum committed variables per statement.
+pub const MAX_VARIABLES: usize = 64;
+/// Maximum public constants per statement.
+pub const MAX_CONSTANTS: usize = 64;
+/// Maximum linear constraints plus multiplication gates.
+pub const MAX_CONSTRAINTS: usize = 256;
+/// Maximum terms in a single expression.
+pub const MAX_TERMS: usize = 64;
+/// Maximum length of a variable or constant name.
+pub const MAX_NAME_LEN: usize = 64;
+
+/// `coeff · var`, or a bare constant `coeff` when `var` is omitted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Term {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub var: Option<String>,
+    #[serde(default = "one")]
+    pub coeff: i64,
+}
+
+fn one() -> i64 { 1 }
+
+/// `Σ lhs == Σ rhs`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinearConstraint {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub lhs: Vec<Term>,
+    #[serde(default)]
+    pub rhs: Vec<Term>,
+}
+
+/// `(Σ left) · (Σ right) == Σ output`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MultiplicationGate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub left: Vec<Term>,
+    pub right: Vec<Term>,
+    pub output: Vec<Term>,
+}
+
+/// A relation over committed variables and public constants.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Statement {
+    pub variables: Vec<String>,
+    #[serde(default)]
+    pub constants: BTreeMap<String, u64>,
+    #[serde(default)]
+    pub linear: Vec<LinearConstraint>,
+    #[serde(default)]
+    pub multiplications: Vec<MultiplicationGate>,
+}
+
+/// Value and blinding of one committed variable.
+#[derive(Debug, Clone, Copy)]
+pub struct Opening {
+    pub value: u64,
+    pub blinding: Scalar,
+}
+
+/// Output of [`prove_statement`]; commitments follow `statement.variables` order.
+#[derive(Debug, Clone)]
+pub struct StatementProof {
+    pub statement_hash: [u8; 32],
+    pub commitments: Vec<CompressedRistretto>,
+    pub proof: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatementError {
+    /// A size limit was exceeded (`what`, limit).
+    TooLarge(&'static str, usize),
+    InvalidName(String),
+    DuplicateName(String),
+    UnknownName(String),
+    MissingOpening(String),
+    /// The supplied openings violate a constraint (`kind`, index, label).
+    Unsatisfied { kind: &'static str, index: usize, label: Option<String> },
+    WrongCommitmentCount { expected: usize, actual: usize },
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooLarge(what, limit) => write!(f, "statement has too many {what} (limit {limit})"),
+            Self::InvalidName(name) => write!(f, "invalid name {name:?}: use 1..={MAX_NAME_LEN} of [A-Za-z0-9_]"),
+            Self::DuplicateName(name) => write!(f, "name {name:?} is declared more than once"),
+            Self::UnknownName(name) => write!(f, "name {name:?} is neither a variable nor a constant"),
+            Self::MissingOpening(name) => write!(f, "no opening supplied for variable {name:?}"),
+            Self::Unsatisfied { kind, index, label } => match label {
+                Some(label) => write!(f, "{kind} constraint #{index} ({label}) is not satisfied by the openings"),
+                None => write!(f, "{kind} constraint #{index} is not satisfied by the openings"),
+            },
+            Self::WrongCommitmentCount { expected, actual } => {
+                write!(f, "expected {expected} commitments, got {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StatementError {}
+
+impl Statement {
+    /// Checks size limits and that every name is declared exactly once.
+    pub fn validate(&self) -> Result<(), ZkError> {
+        if self.variables.len() > MAX_VARIABLES {
+            return Err(StatementError::TooLarge("variables", MAX_VARIABLES).into());
+        }
+        if self.constants.len() > MAX_CONSTANTS {
+            return Err(StatementError::TooLarge("constants", MAX_CONSTANTS).into());
+        }
+        if self.linear.len() + self.multiplications.len() > MAX_CONSTRAINTS {
+            return Err(StatementError::TooLarge("constraints", MAX_CONSTRAINTS).into());
+        }
+
+        let mut names = HashSet::new();
+        for name in self.variables.iter().chain(self.constants.keys()) {
+            let valid = !name.is_empty()
+                && name.len() <= MAX_NAME_LEN
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                return Err(StatementError::InvalidName(name.clone()).into());
+            }
+            if !names.insert(name.as_str()) {
+                return Err(StatementError::DuplicateName(name.clone()).into());
+            }
+        }
+
+        let exprs = self.linear.iter().flat_map(|c| [&c.lhs, &c.rhs]).chain(
+            self.multiplications.iter().flat_map(|g| [&g.left, &g.right, &g.output]),
+        );
+        for expr in exprs {
+            if expr.len() > MAX_TERMS {
+                return Err(StatementError::TooLarge("terms in one expression", MAX_TERMS).into());
+            }
+            for name in expr.iter().filter_map(|t| t.var.as_ref()) {
+                if !names.contains(name.as_str()) {
+                    return Err(StatementError::UnknownName(name.clone()).into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// SHA-256 of the canonical JSON encoding (struct field order, sorted constants).
+    pub fn hash(&self) -> [u8; 32] {
+        let canonical = serde_json::to_vec(self).expect("statement always serializes");
+        Sha256::digest(canonical).into()
+    }
+
+    fn generators(&self) -> Arc<BulletproofGens> {
+        GeneratorRegistry::shared().bulletproofs(self.multiplications.len().next_power_of_two(), 1)
+    }
+
+    /// Build the constraint system on `cs`, with `vars` holding the committed variables.
+    fn synthesize<CS: ConstraintSystem>(&self, cs: &mut CS, vars: &HashMap<&str, Variable>) {
+        let lc = |expr: &[Term]| -> LinearCombination {
+            expr.iter()
+                .map(|t| {
+                    let coeff = scalar_from_i64(t.coeff);
+                    match t.var.as_deref() {
+                        Some(name) => match vars.get(name) {
+                            Some(&var) => (var, coeff),
+                            None => (Variable::One(), coeff * Scalar::from(self.constants[name])),
+                        },
+                        None => (Variable::One(), coeff),
+                    }
+                })
+                .collect()
+        };
+        for c in &self.linear {
+            cs.constrain(lc(&c.lhs) - lc(&c.rhs));
+        }
+        for g in &self.multiplications {
+            let (_, _, out) = cs.multiply(lc(&g.left), lc(&g.right));
+            cs.constrain(out - lc(&g.output));
+        }
+    }
+
+    /// Evaluate every constraint on the openings so an unsatisfiable witness gets
+    /// a precise error rather than a proof that silently fails to verify.
+    fn check_satisfied(&self, openings: &HashMap<&str, Scalar>) -> Result<(), StatementError> {
+        let eval = |expr: &[Term]| -> Scalar {
+            expr.iter()
+                .map(|t| {
+                    let value = match t.var.as_deref() {
+                        Some(name) => openings
+                            .get(name)
+                            .copied()
+                            .unwrap_or_else(|| Scalar::from(self.constants[name])),
+                        None => Scalar::one(),
+                    };
+                    scalar_from_i64(t.coeff) * value
+                })
+                .sum()
+        };
+        for (index, c) in self.linear.iter().enumerate() {
+            if eval(&c.lhs) != eval(&c.rhs) {
+                return Err(StatementError::Unsatisfied { kind: "linear", index, label: c.label.clone() });
+            }
+        }
+        for (index, g) in self.multiplications.iter().enumerate() {
+            if eval(&g.left) * eval(&g.right) != eval(&g.output) {
+                return Err(StatementError::Unsatisfied { kind: "multiplication", index, label: g.label.clone() });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn scalar_from_i64(x: i64) -> Scalar {
+    if x < 0 {
+        -Scalar::from(x.unsigned_abs())
+    } else {
+        Scalar::from(x as u64)
+    }
+}
+
+fn transcript(statement_hash: &[u8; 32], binding_tag: Option<&[u8]>) -> Transcript {
+    let mut transcript = Transcript::new(LABEL);
+    transcript.append_message(b"statement", statement_hash);
+    if let Some(binding) = binding_tag {
+        transcript.append_message(b"bind", binding);
+    }
+    transcript
+}
+
+/// Prove that the openings satisfy `statement`.
+pub fn prove_statement(
+    statement: &Statement,
+    openings: &HashMap<String, Opening>,
+    binding_tag: Option<&[u8]>,
+) -> Result<StatementProof, ZkError> {
+    statement.validate()?;
+    let mut values = HashMap::with_capacity(statement.variables.len());
+    for name in &statement.variables {
+        let opening = openings.get(name).ok_or_else(|| StatementError::MissingOpening(name.clone()))?;
+        values.insert(name.as_str(), Scalar::from(opening.value));
+    }
+    statement.check_satisfied(&values)?;
+
+    let statement_hash = statement.hash();
+    let pc_gens = *GeneratorRegistry::shared().pedersen();
+    let bp_gens = statement.generators();
+    let mut transcript = transcript(&statement_hash, binding_tag);
+    let mut prover = Prover::new(&pc_gens, &mut transcript);
+
+    let mut commitments = Vec::with_capacity(statement.variables.len());
+    let mut vars = HashMap::with_capacity(statement.variables.len());
+    for name in &statement.variables {
+        let opening = &openings[name];
+        let (com, var) = prover.commit(Scalar::from(opening.value), opening.blinding);
+        commitments.push(com);
+        vars.insert(name.as_str(), var);
+    }
+    statement.synthesize(&mut prover, &vars);
+    let proof = prover.prove(&bp_gens)?;
+
+    Ok(StatementProof { statement_hash, commitments, proof: proof.to_bytes() })
+}
+
+/// Verify a proof for `statement`.
+///
+/// A statement that doesn't hash to `statement_hash`, like a proof that
+/// doesn't verify, is a [`ZkError::TranscriptMismatch`]: the hash is part of
+/// the transcript.
+pub fn verify_statement(
+    statement: &Statement,
+    statement_hash: &[u8; 32],
+    commitments: &[CompressedRistretto],
+    proof_bytes: &[u8],
+    binding_tag: Option<&[u8]>,
+) -> Result<(), ZkError> {
+    statement.validate()?;
+    if &statement.hash() != statement_hash {
+        return Err(ZkError::TranscriptMismatch);
+    }
+    if commitments.len() != statement.variables.len() {
+        return Err(StatementError::WrongCommitmentCount {
+            expected: statement.variables.len(),
+            actual: commitments.len(),
+        }
+        .into());
+    }
+    if commitments.iter().any(|c| decompress_canonical(c.as_bytes()).is_none()) {
+        return Err(ZkError::MalformedPoint("commitment"));
+    }
+    let proof = R1CSProof::from_bytes(proof_bytes)?;
+
+    let pc_gens = *GeneratorRegistry::shared().pedersen();
+    let bp_gens = statement.generators();
+    let mut transcript = transcript(statement_hash, binding_tag);
+    let mut verifier = Verifier::new(&mut transcript);
+    let vars = statement
+        .variables
+        .iter()
+        .zip(commitments)
+        .map(|(name, &com)| (name.as_str(), verifier.commit(com)))
+        .collect();
+    statement.synthesize(&mut verifier, &vars);
+    verifier.verify(&proof, &pc_gens, &bp_gens)?;
+    Ok(())
+}
+
+/// Random blinding for callers that don't need to reopen the commitment later.
+pub fn random_blinding() -> Scalar {
+    Scalar::random(&mut OsRng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee_statement() -> Statement {
+        serde_json::from_value(serde_json::json!({
+            "variables": ["price", "fee", "total"],
+            "constants": { "fee_pct": 10 },
+            "linear": [
+                { "label": "total = price + fee",
+                  "lhs": [{ "var": "total" }],
+                  "rhs": [{ "var": "price" }, { "var": "fee" }] }
+            ],
+            "multiplications": [
+                { "label": "fee = 10% of price",
+                  "left": [{ "var": "price" }],
+                  "right": [{ "var": "fee_pct" }],
+                  "output": [{ "var": "fee", "coeff": 100 }] }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn openings(price: u64, fee: u64, total: u64) -> HashMap<String, Opening> {
+        [("price", price), ("fee", fee), ("total", total)]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), Opening { value, blinding: random_blinding() }))
+            .collect()
+    }
+
+    #[test]
+    fn satisfied_statement_roundtrip() {
+        let statement = fee_statement();
+        let binding = [0x42u8; 32];
+        let p = prove_statement(&statement, &openings(1000, 100, 1100), Some(&binding)).unwrap();
+        assert_eq!(p.commitments.len(), 3);
+        assert_eq!(verify_statement(&statement, &p.statement_hash, &p.commitments, &p.proof, Some(&binding)), Ok(()));
+        assert_eq!(verify_statement(&statement, &p.statement_hash, &p.commitments, &p.proof, None), Err(ZkError::TranscriptMismatch));
+    }
+
+    #[test]
+    fn unsatisfied_constraint_is_named() {
+        let err = prove_statement(&fee_statement(), &openings(1000, 100, 1200), None).unwrap_err();
+        assert_eq!(
+            err,
+            ZkError::Statement(StatementError::Unsatisfied {
+                kind: "linear",
+                index: 0,
+                label: Some("total = price + fee".into())
+            })
+        );
+        let err = prove_statement(&fee_statement(), &openings(1000, 90, 1090), None).unwrap_err();
+        assert!(matches!(err, ZkError::Statement(StatementError::Unsatisfied { kind: "multiplication", index: 0, .. })));
+    }
+
+    #[test]
+    fn proof_is_bound_to_statement() {
+        let statement = fee_statement();
+        let p = prove_statement(&statement, &openings(1000, 100, 1100), None).unwrap();
+
+        let mut other = statement.clone();
+        other.constants.insert("unused".into(), 7);
+        assert_eq!(
+            verify_statement(&other, &p.statement_hash, &p.commitments, &p.proof, None),
+            Err(ZkError::TranscriptMismatch)
+        );
+        assert_eq!(verify_statement(&other, &other.hash(), &p.commitments, &p.proof, None), Err(ZkError::TranscriptMismatch));
+    }
+
+    #[test]
+    fn rejects_malformed_statements() {
+        let mut s = fee_statement();
+        s.linear[0].lhs.push(Term { var: Some("missing".into()), coeff: 1 });
+        assert_eq!(s.validate(), Err(StatementError::UnknownName("missing".into()).into()));
+
+        let mut s = fee_statement();
+        s.constants.insert("price".into(), 1);
+        assert_eq!(s.validate(), Err(StatementError::DuplicateName("price".into()).into()));
+
+        let mut s = fee_statement();
+        s.variables = (0..=MAX_VARIABLES).map(|i| format!("v{i}")).collect();
+        assert_eq!(s.validate(), Err(StatementError::TooLarge("variables", MAX_VARIABLES).into()));
+    }
+}
@@ -0,0 +1,471 @@
+//! Pairing-based accumulator for privacy-preserving VC revocation.
+//!
+//! The registry accumulates one element per live credential handle. Revoking
+//! removes the element, which changes the accumulator value and invalidates
+//! that handle's witness, without ever publishing which handle was revoked.
+//!
+//! Scheme: Nguyen's dynamic accumulator over BLS12-381 (CT-RSA 2005), with
+//! the trapdoor held by the registry.
+//! * keys: `α ← Zp`, `Q = g2·α`
+//! * value: `V`, starting at a hash-to-curve point
+//! * add `y`: `V ← V·(y+α)`; remove `y`: `V ← V·1/(y+α)`
+//! * witness for `y`: `C = V·1/(y+α)`, valid iff `e(C, Q + g2·y) = e(V, g2)`
+//!
+//! Non-revocation proof: the holder commits to `y` as `E = g1·y + h·r`,
+//! randomises the witness as `C' = C·r1`, `C̄ = C'·(-y) + V·r1` (so that
+//! `e(C', Q) = e(C̄, g2)`), and proves knowledge of `y, r1, r` consistent with
+//! both `C̄` and `E` with a Fiat–Shamir Schnorr proof. The verifier learns only
+//! that the committed handle is in the current accumulator.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine, G2Projective};
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ff::{Field, PrimeField, UniformRand, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use rand::{CryptoRng, RngCore};
+
+use super::bbs_plus::{from_bytes as decode, hash_to_g1, hash_to_scalar, to_bytes};
+use super::ZkError;
+
+const HANDLE_DST: &[u8] = b"EVBATTERY-ACCUMULATOR-HANDLE-TO-SCALAR";
+const KEYGEN_DST: &[u8] = b"EVBATTERY-ACCUMULATOR-KEYGEN";
+const CHALLENGE_DST: &[u8] = b"EVBATTERY-ACCUMULATOR-CHALLENGE";
+const POINT_DST: &[u8] = b"EVBATTERY-ACCUMULATOR-BLS12381G1_XMD:SHA-256_SSWU_RO_H2G_";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccumulatorError {
+    /// The handle is already accumulated.
+    AlreadyMember,
+    /// The handle is not accumulated (never added, or revoked).
+    NotMember,
+    /// The witness does not open the accumulator value (stale or for another handle).
+    InvalidWitness,
+}
+
+impl fmt::Display for AccumulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyMember => write!(f, "credential handle is already in the accumulator"),
+            Self::NotMember => write!(f, "credential handle is not in the accumulator"),
+            Self::InvalidWitness => write!(f, "membership witness does not match the accumulator value"),
+        }
+    }
+}
+
+impl std::error::Error for AccumulatorError {}
+
+/// Accumulator element for a credential handle (e.g. the VC id).
+pub fn handle_element(handle: &str) -> Fr {
+    hash_to_scalar(HANDLE_DST, handle.as_bytes())
+}
+
+/// Canonical encoding of an accumulator element, for persisting a registry.
+pub fn element_to_bytes(y: &Fr) -> Vec<u8> {
+    to_bytes(y)
+}
+
+pub fn element_from_bytes(bytes: &[u8]) -> Result<Fr, ZkError> {
+    decode(bytes, ZkError::NonCanonicalScalar("accumulator element"))
+}
+
+/// Second generator for handle commitments, independent of `g1`.
+fn commitment_generator() -> G1Affine {
+    hash_to_g1(POINT_DST, b"commitment-h")
+}
+
+/// `E = g1·y + h·r`: Pedersen commitment to a handle element.
+pub fn commit_element(y: &Fr, blinding: &Fr) -> G1Affine {
+    (G1Projective::generator() * y + commitment_generator() * blinding).into_affine()
+}
+
+pub fn random_blinding<R: RngCore + CryptoRng>(rng: &mut R) -> Fr {
+    Fr::rand(rng)
+}
+
+/// Blinding from 32 little-endian bytes, reduced mod the group order.
+pub fn blinding_from_bytes(bytes: &[u8; 32]) -> Fr {
+    Fr::from_le_bytes_mod_order(bytes)
+}
+
+pub fn blinding_to_bytes(blinding: &Fr) -> Vec<u8> {
+    to_bytes(blinding)
+}
+
+/// Published registry key `Q = g2·α`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccumulatorPublicKey(G2Affine);
+
+impl AccumulatorPublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        let q: G2Affine = decode(bytes, ZkError::MalformedPoint("accumulator public key"))?;
+        if q.is_zero() {
+            return Err(ZkError::MalformedPoint("accumulator public key"));
+        }
+        Ok(Self(q))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(&self.0)
+    }
+}
+
+/// Accumulator value `V`, the quantity anchored on chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccumulatorValue(G1Affine);
+
+impl AccumulatorValue {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        let v: G1Affine = decode(bytes, ZkError::MalformedPoint("accumulator value"))?;
+        if v.is_zero() {
+            return Err(ZkError::MalformedPoint("accumulator value"));
+        }
+        Ok(Self(v))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(&self.0)
+    }
+}
+
+/// Membership witness `C` for one handle against one accumulator value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MembershipWitness(G1Affine);
+
+impl MembershipWitness {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        Ok(Self(decode(bytes, ZkError::MalformedPoint("membership witness"))?))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(&self.0)
+    }
+
+    /// `e(C, Q + g2·y) = e(V, g2)`.
+    pub fn verify(&self, pk: &AccumulatorPublicKey, value: &AccumulatorValue, y: &Fr) -> Result<(), ZkError> {
+        if Bls12_381::pairing(self.0, pk.0 + G2Projective::generator() * y)
+            != Bls12_381::pairing(value.0, G2Affine::generator())
+        {
+            return Err(AccumulatorError::InvalidWitness.into());
+        }
+        Ok(())
+    }
+
+    /// Update after `added` was accumulated into `old_value`: `C' = C·(y' - y) + V`.
+    pub fn update_on_add(&self, y: &Fr, added: &Fr, old_value: &AccumulatorValue) -> Self {
+        Self((self.0 * (*added - y) + old_value.0).into_affine())
+    }
+
+    /// Update after `removed` was taken out, giving `new_value`: `C' = (C - V')·1/(y' - y)`.
+    ///
+    /// Fails if `removed` is the witness's own element: that handle was revoked.
+    pub fn update_on_remove(&self, y: &Fr, removed: &Fr, new_value: &AccumulatorValue) -> Result<Self, ZkError> {
+        let inv = (*removed - y).inverse().ok_or(AccumulatorError::NotMember)?;
+        Ok(Self(((self.0.into_group() - new_value.0) * inv).into_affine()))
+    }
+}
+
+/// Registry state: trapdoor, current value and the set of live elements.
+///
+/// Only hashed elements are kept, never the handles themselves.
+pub struct RevocationRegistry {
+    alpha: Fr,
+    public_key: AccumulatorPublicKey,
+    value: AccumulatorValue,
+    members: HashSet<Fr>,
+    epoch: u64,
+}
+
+impl RevocationRegistry {
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    /// Derive the trapdoor deterministically from seed material.
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let alpha = hash_to_scalar(KEYGEN_DST, seed);
+        let public_key = AccumulatorPublicKey((G2Projective::generator() * alpha).into_affine());
+        // Start from a point nobody knows the discrete log of.
+        let value = AccumulatorValue(hash_to_g1(POINT_DST, &to_bytes(&public_key.0)));
+        Self { alpha, public_key, value, members: HashSet::new(), epoch: 0 }
+    }
+
+    /// Rebuild a registry from its seed, live elements and epoch. The value
+    /// does not depend on the order elements were added in.
+    pub fn restore(seed: &[u8], members: impl IntoIterator<Item = Fr>, epoch: u64) -> Result<Self, ZkError> {
+        let mut registry = Self::from_seed(seed);
+        for y in members {
+            registry.add(y)?;
+        }
+        registry.epoch = epoch;
+        Ok(registry)
+    }
+
+    pub fn public_key(&self) -> AccumulatorPublicKey {
+        self.public_key
+    }
+
+    pub fn value(&self) -> AccumulatorValue {
+        self.value
+    }
+
+    /// Incremented on every add or remove.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    pub fn contains(&self, y: &Fr) -> bool {
+        self.members.contains(y)
+    }
+
+    /// Accumulate `y`, returning its witness against the new value.
+    pub fn add(&mut self, y: Fr) -> Result<MembershipWitness, ZkError> {
+        // y = -α would zero the accumulator; it is as unlikely as guessing α.
+        if self.members.contains(&y) || (y + self.alpha).is_zero() {
+            return Err(AccumulatorError::AlreadyMember.into());
+        }
+        let witness = MembershipWitness(self.value.0);
+        self.value = AccumulatorValue((self.value.0 * (y + self.alpha)).into_affine());
+        self.members.insert(y);
+        self.epoch += 1;
+        Ok(witness)
+    }
+
+    /// Remove `y` from the accumulator (revoke).
+    pub fn remove(&mut self, y: &Fr) -> Result<(), ZkError> {
+        if !self.members.remove(y) {
+            return Err(AccumulatorError::NotMember.into());
+        }
+        let inv = (*y + self.alpha).inverse().expect("members never equal -α");
+        self.value = AccumulatorValue((self.value.0 * inv).into_affine());
+        self.epoch += 1;
+        Ok(())
+    }
+
+    /// Witness for `y` against the current value.
+    pub fn witness(&self, y: &Fr) -> Result<MembershipWitness, ZkError> {
+        if !self.members.contains(y) {
+            return Err(AccumulatorError::NotMember.into());
+        }
+        let inv = (*y + self.alpha).inverse().expect("members never equal -α");
+        Ok(MembershipWitness((self.value.0 * inv).into_affine()))
+    }
+}
+
+/// Zero-knowledge proof that the element committed in `E` is accumulated in `V`.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct NonRevocationProof {
+    c_prime: G1Affine,
+    c_bar: G1Affine,
+    c: Fr,
+    y_hat: Fr,
+    r1_hat: Fr,
+    r_hat: Fr,
+}
+
+impl NonRevocationProof {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ZkError> {
+        decode(bytes, ZkError::ProofDeserialization("non-revocation proof"))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        to_bytes(self)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn challenge(
+    pk: &AccumulatorPublicKey,
+    value: &AccumulatorValue,
+    commitment: &G1Affine,
+    c_prime: &G1Affine,
+    c_bar: &G1Affine,
+    t1: &G1Affine,
+    t2: &G1Affine,
+    nonce: &[u8],
+) -> Fr {
+    let mut transcript = pk.to_bytes();
+    for point in [&value.0, commitment, c_prime, c_bar, t1, t2] {
+        transcript.extend(to_bytes(point));
+    }
+    transcript.extend((nonce.len() as u64).to_be_bytes());
+    transcript.extend(nonce);
+    hash_to_scalar(CHALLENGE_DST, &transcript)
+}
+
+/// Prove that `y` (committed as `commit_element(y, blinding)`) is in `value`.
+///
+/// `nonce` is chosen by the verifier (or is a binding tag) to prevent replay.
+pub fn prove_non_revocation<R: RngCore + CryptoRng>(
+    pk: &AccumulatorPublicKey,
+    value: &AccumulatorValue,
+    y: &Fr,
+    witness: &MembershipWitness,
+    blinding: &Fr,
+    nonce: &[u8],
+    rng: &mut R,
+) -> Result<(G1Affine, NonRevocationProof), ZkError> {
+    // A stale or foreign witness would only yield a proof that fails to verify.
+    witness.verify(pk, value, y)?;
+    let h = commitment_generator();
+    let commitment = commit_element(y, blinding);
+
+    let r1 = loop {
+        let r = Fr::rand(rng);
+        if !r.is_zero() {
+            break r;
+        }
+    };
+    let c_prime = (witness.0 * r1).into_affine();
+    let c_bar = (c_prime * (-*y) + value.0 * r1).into_affine();
+
+    // Schnorr commitments for  C̄ = C'·(-y) + V·r1  and  E = g1·y + h·r.
+    let y_tilde = Fr::rand(rng);
+    let r1_tilde = Fr::rand(rng);
+    let r_tilde = Fr::rand(rng);
+    let t1 = (c_prime * (-y_tilde) + value.0 * r1_tilde).into_affine();
+    let t2 = (G1Projective::generator() * y_tilde + h * r_tilde).into_affine();
+
+    let c = challenge(pk, value, &commitment, &c_prime, &c_bar, &t1, &t2, nonce);
+    let proof = NonRevocationProof {
+        c_prime,
+        c_bar,
+        c,
+        y_hat: y_tilde + c * y,
+        r1_hat: r1_tilde + c * r1,
+        r_hat: r_tilde + c * blinding,
+    };
+    Ok((commitment, proof))
+}
+
+/// Verify a [`NonRevocationProof`] for `commitment` against `value`.
+pub fn verify_non_revocation(
+    pk: &AccumulatorPublicKey,
+    value: &AccumulatorValue,
+    commitment: &G1Affine,
+    proof: &NonRevocationProof,
+    nonce: &[u8],
+) -> Result<(), ZkError> {
+    if proof.c_prime.is_zero() {
+        return Err(ZkError::TranscriptMismatch);
+    }
+    if Bls12_381::pairing(proof.c_prime, pk.0) != Bls12_381::pairing(proof.c_bar, G2Affine::generator()) {
+        return Err(ZkError::TranscriptMismatch);
+    }
+    let c = proof.c;
+    let t1 = (proof.c_prime * (-proof.y_hat) + value.0 * proof.r1_hat - proof.c_bar * c).into_affine();
+    let t2 = (G1Projective::generator() * proof.y_hat + commitment_generator() * proof.r_hat - *commitment * c)
+        .into_affine();
+    if challenge(pk, value, commitment, &proof.c_prime, &proof.c_bar, &t1, &t2, nonce) != c {
+        return Err(ZkError::TranscriptMismatch);
+    }
+    Ok(())
+}
+
+/// Decode a handle commitment `E` as produced by [`commit_element`].
+pub fn commitment_from_bytes(bytes: &[u8]) -> Result<G1Affine, ZkError> {
+    decode(bytes, ZkError::MalformedPoint("handle commitment"))
+}
+
+pub fn commitment_to_bytes(commitment: &G1Affine) -> Vec<u8> {
+    to_bytes(commitment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn witnesses_track_adds_and_removes() {
+        let mut registry = RevocationRegistry::generate(&mut OsRng);
+        let pk = registry.public_key();
+        let (a, b, c) = (handle_element("vc:a"), handle_element("vc:b"), handle_element("vc:c"));
+
+        registry.add(a).unwrap();
+        let mut witness = registry.witness(&a).unwrap();
+        assert!(witness.verify(&pk, &registry.value(), &a).is_ok());
+
+        let before_add = registry.value();
+        registry.add(b).unwrap();
+        assert_eq!(witness.verify(&pk, &registry.value(), &a), Err(AccumulatorError::InvalidWitness.into()));
+        witness = witness.update_on_add(&a, &b, &before_add);
+        assert!(witness.verify(&pk, &registry.value(), &a).is_ok());
+        assert_eq!(witness, registry.witness(&a).unwrap());
+
+        registry.add(c).unwrap();
+        registry.remove(&b).unwrap();
+        let fresh = registry.witness(&a).unwrap();
+        assert!(fresh.verify(&pk, &registry.value(), &a).is_ok());
+        assert_eq!(registry.witness(&b), Err(AccumulatorError::NotMember.into()));
+        assert_eq!(registry.add(a), Err(AccumulatorError::AlreadyMember.into()));
+
+        let removed_value = {
+            registry.remove(&c).unwrap();
+            registry.value()
+        };
+        assert_eq!(fresh.update_on_remove(&a, &c, &removed_value), Ok(registry.witness(&a).unwrap()));
+        assert_eq!(registry.epoch(), 5);
+    }
+
+    #[test]
+    fn restored_registries_keep_value_and_witnesses() {
+        let seed = [7u8; 32];
+        let mut registry = RevocationRegistry::from_seed(&seed);
+        let (a, b, c) = (handle_element("vc:a"), handle_element("vc:b"), handle_element("vc:c"));
+        for y in [a, b, c] {
+            registry.add(y).unwrap();
+        }
+        registry.remove(&b).unwrap();
+        let witness = registry.witness(&a).unwrap();
+
+        let restored = RevocationRegistry::restore(&seed, [c, a], registry.epoch()).unwrap();
+        assert_eq!((restored.value(), restored.public_key(), restored.epoch()), (registry.value(), registry.public_key(), 4));
+        assert!(witness.verify(&restored.public_key(), &restored.value(), &a).is_ok());
+        assert!(!restored.contains(&b));
+        assert_eq!(element_from_bytes(&element_to_bytes(&a)), Ok(a));
+    }
+
+    #[test]
+    fn non_revocation_roundtrip() {
+        let mut registry = RevocationRegistry::generate(&mut OsRng);
+        let pk = registry.public_key();
+        let y = handle_element("urn:uuid:battery-vc-1");
+        registry.add(y).unwrap();
+        registry.add(handle_element("urn:uuid:battery-vc-2")).unwrap();
+        let value = registry.value();
+        let witness = registry.witness(&y).unwrap();
+        let blinding = random_blinding(&mut OsRng);
+
+        let (commitment, proof) = prove_non_revocation(&pk, &value, &y, &witness, &blinding, b"nonce", &mut OsRng).unwrap();
+        assert_eq!(commitment, commit_element(&y, &blinding));
+        let proof = NonRevocationProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert_eq!(verify_non_revocation(&pk, &value, &commitment, &proof, b"nonce"), Ok(()));
+        assert_eq!(verify_non_revocation(&pk, &value, &commitment, &proof, b"other"), Err(ZkError::TranscriptMismatch));
+
+        // A proof for a different commitment must not verify.
+        let other = commit_element(&y, &random_blinding(&mut OsRng));
+        assert!(verify_non_revocation(&pk, &value, &other, &proof, b"nonce").is_err());
+
+        // After revocation the old proof fails against the new value and no new proof can be made.
+        registry.remove(&y).unwrap();
+        let revoked_value = registry.value();
+        assert!(verify_non_revocation(&pk, &revoked_value, &commitment, &proof, b"nonce").is_err());
+        assert_eq!(
+            prove_non_revocation(&pk, &revoked_value, &y, &witness, &blinding, b"nonce", &mut OsRng).unwrap_err(),
+            AccumulatorError::InvalidWitness.into()
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Known-answer self-test.
+//!
+//! Fixed commitment vectors catch a dependency upgrade that silently changes
+//! the generators (every commitment already handed out would stop opening);
+//! one prove/verify round trip per scheme catches a broken prover or
+//! verifier, and a wrong binding tag must still be rejected.
+
+use curve25519_dalek_ng::scalar::Scalar as NgScalar;
+use serde::Serialize;
+
+use super::generators::GeneratorRegistry;
+use super::{bp_plus_pedersen, pedersen, txid_pedersen_proof, ZkError};
+
+/// Opening behind both commitment vectors.
+const VALUE: u64 = 42;
+const BLINDING: u64 = 7;
+
+/// `VALUE·B + BLINDING·B̃` with the classic Bulletproofs Pedersen generators.
+const PEDERSEN_VECTOR: &str = "a69ed12fb9c42f06a8c6ff8b535a781b613f46c7944d013c078eb0b5f3745c44";
+
+/// The same opening with the BP⁺ Pedersen generators.
+const BULLETPROOFS_PLUS_VECTOR: &str = "703d04aa9801c28a35f9a8f8d6adc4785369dcea58da1374d75572eb4c4a9320";
+
+const TAG: [u8; 32] = [0x5a; 32];
+const OTHER_TAG: [u8; 32] = [0xa5; 32];
+const TX_HASH: [u8; 32] = [0x3c; 32];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    pub passed: bool,
+    pub checks: Vec<SelfTestCheck>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Run every check. Takes a few tens of milliseconds once the generators
+/// are warm.
+pub fn run() -> SelfTestReport {
+    let checks: Vec<SelfTestCheck> = [
+        ("pedersen_vector", pedersen_vector as fn() -> Result<(), String>),
+        ("bulletproofs_plus_vector", bulletproofs_plus_vector),
+        ("value_commitment", value_commitment),
+        ("tx_hash_commitment", tx_hash_commitment),
+        ("bulletproofs_plus", bulletproofs_plus),
+    ]
+    .into_iter()
+    .map(|(name, check)| {
+        let error = check().err();
+        SelfTestCheck { name, passed: error.is_none(), error }
+    })
+    .collect();
+    SelfTestReport { passed: checks.iter().all(|c| c.passed), checks }
+}
+
+fn expect_vector(what: &str, actual: &[u8], expected: &str) -> Result<(), String> {
+    let actual = hex::encode(actual);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("{what} is {actual}, expected {expected}"))
+    }
+}
+
+fn expect_mismatch(result: Result<(), ZkError>) -> Result<(), String> {
+    match result {
+        Err(ZkError::TranscriptMismatch) => Ok(()),
+        Ok(()) => Err("proof verified under the wrong binding tag".into()),
+        Err(e) => Err(format!("wrong binding tag: expected a failed verification, got {e}")),
+    }
+}
+
+fn pedersen_vector() -> Result<(), String> {
+    let commitment = GeneratorRegistry::shared().pedersen().commit(NgScalar::from(VALUE), NgScalar::from(BLINDING));
+    expect_vector("commitment", commitment.compress().as_bytes(), PEDERSEN_VECTOR)
+}
+
+fn bulletproofs_plus_vector() -> Result<(), String> {
+    use curve25519_dalek::scalar::Scalar;
+
+    let params = GeneratorRegistry::shared().bulletproofs_plus(64, 4).map_err(|e| e.to_string())?;
+    let commitment = params.pc_gens().commit(&Scalar::from(VALUE), &[Scalar::from(BLINDING)]).map_err(|e| e.to_string())?;
+    expect_vector("commitment", commitment.compress().as_bytes(), BULLETPROOFS_PLUS_VECTOR)
+}
+
+/// A 64-bit range proof over the vector's opening: the commitment must be
+/// the vector itself.
+fn value_commitment() -> Result<(), String> {
+    let (commitment, proof, _) =
+        pedersen::prove_value_commitment_with_binding_and_range(VALUE, NgScalar::from(BLINDING), Some(&TAG), 64)
+            .map_err(|e| e.to_string())?;
+    expect_vector("value commitment", commitment.as_bytes(), PEDERSEN_VECTOR)?;
+    pedersen::verify_value_commitment_with_binding_and_range(commitment, proof.clone(), Some(&TAG), 64)
+        .map_err(|e| e.to_string())?;
+    expect_mismatch(pedersen::verify_value_commitment_with_binding_and_range(commitment, proof, Some(&OTHER_TAG), 64))
+}
+
+fn tx_hash_commitment() -> Result<(), String> {
+    let (commitment, proof, _) =
+        txid_pedersen_proof::prove_txid_commitment_with_binding(NgScalar::from_bytes_mod_order(TX_HASH), Some(&TAG))
+            .map_err(|e| e.to_string())?;
+    txid_pedersen_proof::verify_txid_commitment_with_binding(commitment, proof.clone(), Some(&TAG))
+        .map_err(|e| e.to_string())?;
+    expect_mismatch(txid_pedersen_proof::verify_txid_commitment_with_binding(commitment, proof, Some(&OTHER_TAG)))
+}
+
+fn bulletproofs_plus() -> Result<(), String> {
+    let (commitments, proof) = bp_plus_pedersen::prove_txid_commitment(TX_HASH).map_err(|e| e.to_string())?;
+    bp_plus_pedersen::verify_txid_commitment(commitments.clone(), proof.clone()).map_err(|e| e.to_string())?;
+    let mut tampered = commitments;
+    tampered.swap(0, 1);
+    match bp_plus_pedersen::verify_txid_commitment(tampered, proof) {
+        Err(_) => Ok(()),
+        Ok(()) => Err("proof verified against reordered commitments".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_checks_pass() {
+        let report = run();
+        assert!(report.passed, "{report:#?}");
+        assert_eq!(report.checks.len(), 5);
+    }
+}
@@ -1,5 +1,4 @@
 use bulletproofs::r1cs::{Prover, Verifier};
-use bulletproofs::{BulletproofGens, PedersenGens};
 use bulletproofs::r1cs::R1CSProof;
 use curve25519_dalek_ng::ristretto::CompressedRistretto;
 use curve25519_dalek_ng::scalar::Scalar;
@@ -7,89 +6,73 @@ use merlin::Transcript;
 use rand::rngs::OsRng;
 use rand::RngCore;
 use hex::FromHex;
+use tracing::{debug, trace, warn};
+
+use super::commitment_ops::decompress_canonical;
+use super::generators::GeneratorRegistry;
+use super::ZkError;
 
 /// Proves knowledge of a transaction ID preimage such that Pedersen(tx_id, r) == commitment
 /// This version supports optional binding tag for linking commitments
 pub fn prove_txid_commitment_with_binding(
     tx_id: Scalar,
     binding_tag: Option<&[u8]>,
-) -> (CompressedRistretto, Vec<u8>, bool) {
-    if binding_tag.is_some() {
-        println!("\u{25B6}\u{FE0F} [ZKP] Running: Bulletproof-based ZKP for tx_id with binding tag");
-        println!("   [ZKP] Binding tag: {} bytes", binding_tag.unwrap().len());
-    } else {
-        println!("\u{25B6}\u{FE0F} [ZKP] Running: Bulletproof-based ZKP for tx_id using Pedersen commitment (no binding tag)");
-    }
-
-    println!("   [ZKP] Initializing generators...");
-    let pc_gens = PedersenGens::default();
-    let bp_gens = BulletproofGens::new(64, 1);
+) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
+    debug!(bound = binding_tag.is_some(), "proving tx id commitment");
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(64, 1);
     let mut rng = OsRng;
 
-    println!("   [ZKP] Generating random blinding factor...");
     let mut bytes = [0u8; 64];
     rng.fill_bytes(&mut bytes);
     let blinding_r = Scalar::from_bytes_mod_order_wide(&bytes);
 
     // ✍️ Prover Phase
-    println!("   [ZKP] Starting prover phase...");
     let mut transcript = Transcript::new(b"TxIDPedersenZKP");
     
     // ✅ Add binding tag to transcript if provided (Feature 2: Linkable Commitment)
     if let Some(binding) = binding_tag {
         transcript.append_message(b"bind", binding);
-        println!("   [ZKP] ✅ Binding tag added to prover transcript: {} bytes", binding.len());
-    } else {
-        println!("   [ZKP] No binding tag in prover transcript");
     }
     
     let mut prover = Prover::new(&pc_gens, &mut transcript);
     let (_com_var, _) = prover.commit(tx_id, blinding_r);
-    println!("   [ZKP] Committed tx_id to prover");
     
-    println!("   [ZKP] Generating proof...");
-    let proof = prover.prove(&bp_gens).unwrap();
+    let proof = prover.prove(&bp_gens)?;
     let proof_bytes = proof.to_bytes();
     let commitment = pc_gens.commit(tx_id, blinding_r).compress();
-    println!("   [ZKP] ✅ Proof generated: {} bytes, commitment: {} bytes", proof_bytes.len(), commitment.as_bytes().len());
+    trace!(proof_size = proof_bytes.len(), "tx id proof generated");
 
     // 🔍 Verifier Phase
-    println!("   [ZKP] Starting verifier phase...");
     let mut transcript = Transcript::new(b"TxIDPedersenZKP");
     
     // ✅ Add binding tag to verification transcript if provided
     if let Some(binding) = binding_tag {
         transcript.append_message(b"bind", binding);
-        println!("   [ZKP] ✅ Binding tag added to verifier transcript: {} bytes", binding.len());
-    } else {
-        println!("   [ZKP] No binding tag in verifier transcript");
     }
     
     let mut verifier = Verifier::new(&mut transcript);
     let _var = verifier.commit(commitment);
-    println!("   [ZKP] Committed to verifier");
     
-    println!("   [ZKP] Verifying proof...");
     let verified = verifier.verify(&proof, &pc_gens, &bp_gens).is_ok();
 
-    if verified {
-        println!("\u{2705} [ZKP] ✅ ZK Proof of tx_id preimage VERIFIED");
-    } else {
-        println!("\u{274C} [ZKP] ❌ ZK Proof of tx_id preimage VERIFICATION FAILED");
+    if !verified {
+        warn!("tx id proof failed its own verification");
     }
 
-    (commitment, proof_bytes, verified)
+    Ok((commitment, proof_bytes, verified))
 }
 
 /// Proves knowledge of a transaction ID preimage such that Pedersen(tx_id, r) == commitment
 /// Backward compatible version without binding tag
-pub fn prove_txid_commitment(tx_id: Scalar) -> (CompressedRistretto, Vec<u8>, bool) {
+pub fn prove_txid_commitment(tx_id: Scalar) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
     prove_txid_commitment_with_binding(tx_id, None)
 }
 
 /// Convenience wrapper: takes Ethereum tx hash as hex string and proves it
 /// Backward compatible version without binding tag
-pub fn prove_txid_commitment_from_hex(txid_hex: &str) -> (CompressedRistretto, Vec<u8>, bool) {
+pub fn prove_txid_commitment_from_hex(txid_hex: &str) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
     prove_txid_commitment_from_hex_with_binding(txid_hex, None)
 }
 
@@ -98,34 +81,18 @@ pub fn prove_txid_commitment_from_hex(txid_hex: &str) -> (CompressedRistretto, V
 pub fn prove_txid_commitment_from_hex_with_binding(
     txid_hex: &str,
     binding_tag: Option<&[u8]>,
-) -> (CompressedRistretto, Vec<u8>, bool) {
-    println!("[ZKP] prove_txid_commitment_from_hex_with_binding called");
-    println!("   [ZKP] TX hash (hex): {}", txid_hex);
-    println!("   [ZKP] Binding tag: {}", if binding_tag.is_some() { "provided" } else { "not provided" });
-    
+) -> Result<(CompressedRistretto, Vec<u8>, bool), ZkError> {
     let hex_str = txid_hex.strip_prefix("0x").unwrap_or(txid_hex);
-    println!("   [ZKP] Parsing hex string (length: {})...", hex_str.len());
-    
-    let bytes = match <[u8; 32]>::from_hex(hex_str) {
-        Ok(b) => {
-            println!("   [ZKP] ✅ TX hash parsed successfully: {} bytes", b.len());
-            b
-        },
-        Err(e) => {
-            println!("   [ZKP] ❌ Failed to parse TX hash: {:?}", e);
-            panic!("Invalid tx hash: {:?}", e);
-        },
-    };
-    
+    let bytes = <[u8; 32]>::from_hex(hex_str)
+        .map_err(|_| ZkError::InvalidParameters("tx hash must be 32 bytes of hex".into()))?;
     let tx_scalar = Scalar::from_bytes_mod_order(bytes);
-    println!("   [ZKP] Converted to scalar, calling prove_txid_commitment_with_binding...");
     prove_txid_commitment_with_binding(tx_scalar, binding_tag)
 }
 
 /// Proves knowledge of a 256-bit transaction ID preimage such that the commitments to all 4 limbs are valid
 /// Returns (Vec<CompressedRistretto>, proof bytes, verified)
-pub fn prove_txid_commitment_4limb(txid_bytes: [u8; 32]) -> (Vec<CompressedRistretto>, Vec<u8>, bool) {
-    use bulletproofs::r1cs::ConstraintSystem;
+pub fn prove_txid_commitment_4limb(txid_bytes: [u8; 32]) -> Result<(Vec<CompressedRistretto>, Vec<u8>, bool), ZkError> {
+    use bulletproofs::r1cs::{ConstraintSystem, LinearCombination};
     use curve25519_dalek_ng::scalar::Scalar;
     // Split into 4 limbs
     let limbs: [u64; 4] = [
@@ -134,8 +101,9 @@ pub fn prove_txid_commitment_4limb(txid_bytes: [u8; 32]) -> (Vec<CompressedRistr
         u64::from_le_bytes(txid_bytes[16..24].try_into().unwrap()),
         u64::from_le_bytes(txid_bytes[24..32].try_into().unwrap()),
     ];
-    let pc_gens = PedersenGens::default();
-    let bp_gens = BulletproofGens::new(64, 4); // 4 parties, 64 bits each
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(64, 4); // 4 parties, 64 bits each
     let mut rng = OsRng;
     let mut transcript = Transcript::new(b"TxIDPedersenZKP4Limb");
     let mut prover = Prover::new(&pc_gens, &mut transcript);
@@ -148,24 +116,39 @@ pub fn prove_txid_commitment_4limb(txid_bytes: [u8; 32]) -> (Vec<CompressedRistr
         let (com, var) = prover.commit(Scalar::from(limb), blind);
         commitments.push(com);
         vars.push(var);
-        // Optionally, constrain range here if you want to prove it's in [0, 2^64)
-        prover.constrain(var - Scalar::from(limb));
+        // Must mirror the verifier's constraint exactly; constraining against the
+        // limb itself would both leak it and never verify.
+        prover.constrain(LinearCombination::from(var) - var);
     }
     // No additional constraints: just prove knowledge of all 4 limbs
-    let proof = prover.prove(&bp_gens).unwrap();
+    let proof = prover.prove(&bp_gens)?;
     let proof_bytes = proof.to_bytes();
-    // Verifier phase
+    let verified = verify_txid_commitment_4limb(&commitments, &proof_bytes).is_ok();
+    Ok((commitments, proof_bytes, verified))
+}
+
+/// Verifies a proof produced by [`prove_txid_commitment_4limb`]
+#[allow(clippy::eq_op)]
+pub fn verify_txid_commitment_4limb(commitments: &[CompressedRistretto], proof_bytes: &[u8]) -> Result<(), ZkError> {
+    use bulletproofs::r1cs::ConstraintSystem;
+    if commitments.len() != 4 {
+        return Err(ZkError::InvalidParameters(format!("expected 4 limb commitments, got {}", commitments.len())));
+    }
+    if commitments.iter().any(|c| decompress_canonical(c.as_bytes()).is_none()) {
+        return Err(ZkError::MalformedPoint("commitment"));
+    }
+    let proof = R1CSProof::from_bytes(proof_bytes).map_err(|_| ZkError::ProofDeserialization("R1CS proof"))?;
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(64, 4);
     let mut transcript = Transcript::new(b"TxIDPedersenZKP4Limb");
     let mut verifier = Verifier::new(&mut transcript);
-    let mut v_vars = Vec::with_capacity(4);
-    for &com in &commitments {
+    for &com in commitments {
         let var = verifier.commit(com);
-        v_vars.push(var);
         // Optionally, constrain range here if you want to prove it's in [0, 2^64)
         verifier.constrain(var - var); // always zero, just to keep structure
     }
-    let verified = verifier.verify(&R1CSProof::from_bytes(&proof_bytes).unwrap(), &pc_gens, &bp_gens).is_ok();
-    (commitments, proof_bytes, verified)
+    Ok(verifier.verify(&proof, &pc_gens, &bp_gens)?)
 }
 
 /// Verifies the proof of a transaction ID preimage
@@ -173,7 +156,7 @@ pub fn prove_txid_commitment_4limb(txid_bytes: [u8; 32]) -> (Vec<CompressedRistr
 pub fn verify_txid_commitment(
     commitment: CompressedRistretto,
     proof_bytes: Vec<u8>
-) -> bool {
+) -> Result<(), ZkError> {
     verify_txid_commitment_with_binding(commitment, proof_bytes, None)
 }
 
@@ -183,55 +166,28 @@ pub fn verify_txid_commitment_with_binding(
     commitment: CompressedRistretto,
     proof_bytes: Vec<u8>,
     binding_tag: Option<&[u8]>,
-) -> bool {
-    if binding_tag.is_some() {
-        println!("[ZKP] [VERIFY] Verifying TX hash commitment with binding tag ({} bytes)", binding_tag.unwrap().len());
-    } else {
-        println!("[ZKP] [VERIFY] Verifying TX hash commitment without binding tag (backward compatible)");
+) -> Result<(), ZkError> {
+    debug!(bound = binding_tag.is_some(), proof_size = proof_bytes.len(), "verifying tx id commitment");
+    if decompress_canonical(commitment.as_bytes()).is_none() {
+        return Err(ZkError::MalformedPoint("commitment"));
     }
-    
-    println!("   [ZKP] [VERIFY] Initializing generators...");
-    let pc_gens = PedersenGens::default();
-    let bp_gens = BulletproofGens::new(64, 1);
+
+    let gens = GeneratorRegistry::shared();
+    let pc_gens = *gens.pedersen();
+    let bp_gens = gens.bulletproofs(64, 1);
     let mut transcript = Transcript::new(b"TxIDPedersenZKP");
 
     // ✅ Add binding tag to verification transcript if provided
     if let Some(binding) = binding_tag {
         transcript.append_message(b"bind", binding);
-        println!("   [ZKP] [VERIFY] ✅ Binding tag added to verification transcript: {} bytes", binding.len());
-    } else {
-        println!("   [ZKP] [VERIFY] No binding tag in verification transcript");
     }
 
-    println!("   [ZKP] [VERIFY] Creating verifier and committing...");
     let mut verifier = Verifier::new(&mut transcript);
     let _var = verifier.commit(commitment);
 
-    println!("   [ZKP] [VERIFY] Parsing proof bytes ({} bytes)...", proof_bytes.len());
-    let proof = match R1CSProof::from_bytes(&proof_bytes) {
-        Ok(p) => {
-            println!("   [ZKP] [VERIFY] ✅ Proof parsed successfully");
-            p
-        },
-        Err(e) => {
-            println!("   [ZKP] [VERIFY] ❌ Failed to parse proof: {:?}", e);
-            return false;
-        },
-    };
-    
-    println!("   [ZKP] [VERIFY] Running verification...");
-    let result = verifier.verify(&proof, &pc_gens, &bp_gens);
-    
-    match result {
-        Ok(_) => {
-            println!("   [ZKP] [VERIFY] ✅ Verification SUCCESS");
-            true
-        },
-        Err(e) => {
-            println!("   [ZKP] [VERIFY] ❌ Verification FAILED: {:?}", e);
-            false
-        },
-    }
+    let proof = R1CSProof::from_bytes(&proof_bytes).map_err(|_| ZkError::ProofDeserialization("R1CS proof"))?;
+    verifier.verify(&proof, &pc_gens, &bp_gens)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -241,33 +197,51 @@ mod tests {
     #[test]
     fn test_valid_txid_proof() {
         let tx_id = Scalar::from(123456u64);
-        let (commitment, proof_bytes, verified) = prove_txid_commitment(tx_id);
+        let (commitment, proof_bytes, verified) = prove_txid_commitment(tx_id).unwrap();
         assert!(verified);
-        assert!(verify_txid_commitment(commitment, proof_bytes));
+        assert_eq!(verify_txid_commitment(commitment, proof_bytes), Ok(()));
     }
 
     #[test]
     fn test_invalid_txid_proof() {
         let tx_id = Scalar::from(123456u64);
-        let (_, proof_bytes, _) = prove_txid_commitment(tx_id);
+        let (_, proof_bytes, _) = prove_txid_commitment(tx_id).unwrap();
 
         // Fake commitment to simulate mismatch
-        let fake_commitment = PedersenGens::default().commit(Scalar::from(999999u64), Scalar::zero()).compress();
+        let fake_commitment = GeneratorRegistry::shared().pedersen().commit(Scalar::from(999999u64), Scalar::zero()).compress();
 
         let result = verify_txid_commitment(fake_commitment, proof_bytes);
-        assert!(!result);
+        assert_eq!(result, Err(ZkError::TranscriptMismatch));
     }
 
     #[test]
     fn test_malformed_proof_bytes() {
         let tx_id = Scalar::from(123456u64);
-        let (commitment, _, _) = prove_txid_commitment(tx_id);
+        let (commitment, _, _) = prove_txid_commitment(tx_id).unwrap();
         let malformed = vec![0u8; 10];
-        assert!(
-            !verify_txid_commitment(commitment, malformed),
+        assert_eq!(
+            verify_txid_commitment(commitment, malformed),
+            Err(ZkError::ProofDeserialization("R1CS proof")),
             "Malformed proof bytes should not verify"
         );
     }
+
+    #[test]
+    fn test_invalid_tx_hash_hex_is_an_error() {
+        assert!(matches!(prove_txid_commitment_from_hex("0x1234"), Err(ZkError::InvalidParameters(_))));
+        assert!(matches!(
+            verify_txid_commitment(CompressedRistretto([0xff; 32]), vec![0u8; 64]),
+            Err(ZkError::MalformedPoint("commitment"))
+        ));
+    }
+
+    #[test]
+    fn test_4limb_proof_verifies() {
+        let (commitments, proof_bytes, verified) = prove_txid_commitment_4limb([0xab; 32]).unwrap();
+        assert!(verified);
+        assert_eq!(verify_txid_commitment_4limb(&commitments, &proof_bytes), Ok(()));
+        assert!(verify_txid_commitment_4limb(&commitments[..3], &proof_bytes).is_err());
+    }
 }
 
 
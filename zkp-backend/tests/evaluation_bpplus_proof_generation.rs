@@ -2,6 +2,8 @@
 //! Measures time to generate a BP+ range proof for transaction IDs (4 × 64-bit limbs)
 //! Structured identically to BP generation test for fair comparison
 
+#![allow(unused_imports, clippy::needless_borrows_for_generic_args, clippy::manual_is_multiple_of)]
+
 use bulletproof_demo::zk::bp_plus_pedersen::{prove_txid_commitment, verify_txid_commitment};
 use std::time::Instant;
 use sha2::{Sha256, Digest};
@@ -142,7 +144,7 @@ mod tests {
         use bulletproof_demo::zk::bp_plus_pedersen::prove_txid_commitment_with_rng;
         use rand::thread_rng;
         let mut warmup_rng = thread_rng();
-        let _ = prove_txid_commitment_with_rng(tx_hash, &mut warmup_rng);
+        let _ = prove_txid_commitment_with_rng(tx_hash, &mut warmup_rng).unwrap();
 
         for i in 0..RUNS {
             let total_start = Instant::now();
@@ -185,7 +187,7 @@ mod tests {
             let proof_start = Instant::now();
             use bulletproof_demo::zk::bp_plus_pedersen::prove_txid_commitment_with_rng;
             let mut proof_rng = thread_rng();
-            let (_commitments2, _proof_bytes) = prove_txid_commitment_with_rng(tx_hash, &mut proof_rng);
+            let (_commitments2, _proof_bytes) = prove_txid_commitment_with_rng(tx_hash, &mut proof_rng).unwrap();
             let proof_time = proof_start.elapsed();
             proof_times.push(proof_time.as_nanos() as f64 / 1_000_000.0);
 
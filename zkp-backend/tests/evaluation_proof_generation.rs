@@ -1,6 +1,8 @@
 //! Test 3.2: Proof Generation Time
 //! Measures time to generate a range proof with breakdown of operations
 
+#![allow(clippy::needless_borrows_for_generic_args, clippy::manual_is_multiple_of)]
+
 use bulletproof_demo::zk::pedersen::prove_value_commitment_with_binding_and_range;
 use curve25519_dalek_ng::scalar::Scalar;
 use std::time::Instant;
@@ -157,7 +159,7 @@ mod tests {
                 blinding,
                 Some(&binding_tag),
                 BIT_RANGE,
-            );
+            ).unwrap();
             let proof_time = proof_start.elapsed();
             proof_times.push(proof_time.as_nanos() as f64 / 1_000_000.0);
 
@@ -1,6 +1,8 @@
 //! Test 3.1: Proof Size Measurement
 //! Measures Bulletproofs proof size for different value ranges (32-bit and 64-bit)
 
+#![allow(clippy::len_zero)]
+
 use bulletproof_demo::zk::pedersen::prove_value_commitment_with_binding_and_range;
 use curve25519_dalek_ng::scalar::Scalar;
 use rand::rngs::OsRng;
@@ -28,7 +30,7 @@ fn main() {
         blinding,
         Some(binding_tag),
         32,
-    );
+    ).unwrap();
     
     let commitment_size_32 = 32; // CompressedRistretto is always 32 bytes
     let proof_size_32 = proof_bytes_32.len();
@@ -48,7 +50,7 @@ fn main() {
         blinding,
         Some(binding_tag),
         64,
-    );
+    ).unwrap();
     
     let commitment_size_64 = 32; // CompressedRistretto is always 32 bytes
     let proof_size_64 = proof_bytes_64.len();
@@ -98,7 +100,7 @@ mod tests {
             blinding,
             Some(binding_tag),
             32,
-        );
+        ).unwrap();
         
         let commitment_size_32 = 32; // CompressedRistretto is always 32 bytes
         let proof_size_32 = proof_bytes_32.len();
@@ -118,7 +120,7 @@ mod tests {
             blinding,
             Some(binding_tag),
             64,
-        );
+        ).unwrap();
         
         let commitment_size_64 = 32; // CompressedRistretto is always 32 bytes
         let proof_size_64 = proof_bytes_64.len();
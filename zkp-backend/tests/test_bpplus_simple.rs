@@ -17,14 +17,14 @@ fn test_bpplus_simple() {
     
     println!("Test 1: Using OsRng (production version)...");
     let start = Instant::now();
-    let (commitments1, proof_bytes1) = prove_txid_commitment(tx_hash);
+    let (commitments1, proof_bytes1) = prove_txid_commitment(tx_hash).unwrap();
     let gen_time1 = start.elapsed();
     println!("  Proof generated in {:.3} ms", gen_time1.as_nanos() as f64 / 1_000_000.0);
     println!("  Proof size: {} bytes", proof_bytes1.len());
     println!("  Commitments: {} ({} bytes total)", commitments1.len(), commitments1.len() * 32);
     
     let start = Instant::now();
-    let verified1 = verify_txid_commitment(commitments1.clone(), proof_bytes1.clone());
+    let verified1 = verify_txid_commitment(commitments1.clone(), proof_bytes1.clone()).is_ok();
     let verify_time1 = start.elapsed();
     println!("  Proof verified in {:.3} ms", verify_time1.as_nanos() as f64 / 1_000_000.0);
     println!("  Verified: {}\n", verified1);
@@ -34,14 +34,14 @@ fn test_bpplus_simple() {
     println!("Test 2: Using ThreadRng (faster for testing)...");
     let mut rng = thread_rng();
     let start = Instant::now();
-    let (commitments2, proof_bytes2) = prove_txid_commitment_with_rng(tx_hash, &mut rng);
+    let (commitments2, proof_bytes2) = prove_txid_commitment_with_rng(tx_hash, &mut rng).unwrap();
     let gen_time2 = start.elapsed();
     println!("  Proof generated in {:.3} ms", gen_time2.as_nanos() as f64 / 1_000_000.0);
     println!("  Proof size: {} bytes", proof_bytes2.len());
     println!("  Commitments: {} ({} bytes total)", commitments2.len(), commitments2.len() * 32);
     
     let start = Instant::now();
-    let verified2 = verify_txid_commitment(commitments2, proof_bytes2);
+    let verified2 = verify_txid_commitment(commitments2, proof_bytes2).is_ok();
     let verify_time2 = start.elapsed();
     println!("  Proof verified in {:.3} ms", verify_time2.as_nanos() as f64 / 1_000_000.0);
     println!("  Verified: {}\n", verified2);
@@ -8,6 +8,8 @@
 //!         -H "Content-Type: application/json" \
 //!         -d '{"tx_hash":"0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef"}'
 
+#![allow(clippy::doc_overindented_list_items)]
+
 // This test file documents the API endpoint
 // Actual API testing should be done manually or with integration tests
 
@@ -0,0 +1,106 @@
+//! The `zkp` binary: JSON in, JSON out, and its exit codes.
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+use bulletproof_demo::vc::signature::{sign, Role, DEFAULT_CHAIN_ID};
+use k256::ecdsa::SigningKey;
+use serde_json::{json, Value};
+
+fn zkp(args: &[&str], stdin: Option<&str>) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_zkp"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin.unwrap_or_default().as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn json_of(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes).unwrap()
+}
+
+#[test]
+fn commitments_verify_offline() {
+    let tag = "ab".repeat(32);
+    let out = zkp(&["commit-value", "--value", "42", "--bit-range", "16", "--binding-tag-hex", &tag], None);
+    assert!(out.status.success());
+    let commitment = String::from_utf8(out.stdout).unwrap();
+
+    // The commit output is a valid verify body; flags override its fields.
+    let out = zkp(&["verify-value", "-i", "-", "--binding-tag-hex", &tag], Some(&commitment));
+    assert_eq!((out.status.code(), json_of(&out.stdout)), (Some(0), json!({ "verified": true })));
+    let out = zkp(&["verify-value", "-i", "-"], Some(&commitment));
+    assert_eq!((out.status.code(), json_of(&out.stdout)), (Some(1), json!({ "verified": false })));
+
+    let out = zkp(&["verify-value", "-i", "-", "--proof", "zz"], Some(&commitment));
+    assert_eq!(out.status.code(), Some(2));
+    assert_eq!(json_of(&out.stderr)["code"], "invalid_parameters");
+    let out = zkp(&["commit-tx-hash"], None);
+    assert_eq!(out.status.code(), Some(2));
+    assert_eq!(json_of(&out.stderr)["code"], "invalid_request");
+}
+
+/// Sign as a Hardhat account the way the frontend does.
+fn signed_by(vc: &Value, key: &str, role: Role) -> Value {
+    let key = SigningKey::from_slice(&hex::decode(key).unwrap()).unwrap();
+    sign(vc, &key, role, DEFAULT_CHAIN_ID, None).unwrap()
+}
+
+#[test]
+fn credentials_report_each_proof_and_signature() {
+    let tag = format!("0x{}", "cd".repeat(32));
+    let out = zkp(&["commit-value", "--value", "1500", "--binding-tag-hex", &tag], None);
+    let price = json_of(&out.stdout);
+    let mut vc = json!({
+        "@context": ["https://www.w3.org/2018/credentials/v1"],
+        "id": "urn:uuid:1",
+        "type": ["VerifiableCredential"],
+        "schemaVersion": "1.0",
+        "issuer": { "id": "did:ethr:11155111:0x3C44CdDdB6a900fa2b585dd299e03d12FA4293BC", "name": "Seller" },
+        "holder": { "id": "did:ethr:11155111:0x70997970C51812dc3A010C7d01b50e0d17dc79C8", "name": "Buyer" },
+        "issuanceDate": "2025-01-15T10:00:00.000Z",
+        "credentialSubject": {
+            "id": "did:ethr:11155111:0x70997970C51812dc3A010C7d01b50e0d17dc79C8",
+            "productName": "Battery Module",
+            "batch": "B-42",
+            "quantity": 1,
+            "price": { "hidden": true, "zkpProof": {
+                "commitment": price["commitment"], "proof": price["proof"], "bindingTag": tag, "proofType": "zkRangeProof-v1",
+            } },
+        }
+    });
+    let unsigned = vc.to_string();
+    vc["proof"] = json!([
+        signed_by(&vc, "5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a", Role::Issuer),
+        signed_by(&vc, "59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d", Role::Holder),
+    ]);
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), vc.to_string()).unwrap();
+
+    // Every check passes, but without a product contract the price proof is
+    // only bound to its stored tag, so the credential is not verified.
+    let out = zkp(&["verify-vc", file.path().to_str().unwrap()], None);
+    assert_eq!(out.status.code(), Some(1));
+    let report = json_of(&out.stdout);
+    assert_eq!(report["status"], "unbound");
+    let checks = report["checks"].as_array().unwrap();
+    let names: Vec<(Value, Value)> = checks.iter().map(|c| (c["name"].clone(), c["verified"].clone())).collect();
+    assert_eq!(names, [(json!("price_proof"), json!(true)), (json!("issuer_signature"), json!(true)), (json!("holder_signature"), json!(true))]);
+
+    let out = zkp(&["verify-vc", "-"], Some(&unsigned));
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(json_of(&out.stdout)["checks"][1]["verified"], false);
+
+    let mut tampered = vc.clone();
+    tampered["credentialSubject"]["price"]["zkpProof"]["bindingTag"] = json!(format!("0x{}", "00".repeat(32)));
+    let out = zkp(&["verify-vc", "-"], Some(&tampered.to_string()));
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(json_of(&out.stdout)["status"], "failed");
+
+    let out = zkp(&["verify-vc", "-", "--contract", "0x1234"], Some(&vc.to_string()));
+    assert_eq!((out.status.code(), json_of(&out.stderr)["code"].as_str()), (Some(2), Some("invalid_address")));
+}
@@ -0,0 +1,78 @@
+//! The prover and verifier for the browser.
+//!
+//! Each function takes the same object as the JSON body of its `/v1`
+//! endpoint and returns the same response object, so proofs made here
+//! verify on the server and vice versa. Failures throw an `Error` whose
+//! `code` is the server's error code.
+//!
+//! ```js
+//! import init, { commitValue, verifyValue } from "zkp-wasm";
+//! await init();
+//! const c = commitValue({ value: 1500, binding_tag_hex: tag });
+//! verifyValue({ ...c, binding_tag_hex: tag }).verified; // true
+//! ```
+
+use bulletproof_demo::api::{
+    RangeProofRequest, RangeProofVerifyRequest, TxHashCommitmentRequest, TxHashCommitmentVerifyRequest,
+    ValueCommitmentRequest, ValueCommitmentVerifyRequest,
+};
+use bulletproof_demo::zk::ZkError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+fn error(message: &str, code: &str) -> JsValue {
+    let error = js_sys::Error::new(message);
+    js_sys::Reflect::set(&error, &"code".into(), &code.into()).expect("Error objects are extensible");
+    error.into()
+}
+
+fn zk_error(e: ZkError) -> JsValue {
+    error(&e.to_string(), e.code())
+}
+
+fn request<T: DeserializeOwned>(body: JsValue) -> Result<T, JsValue> {
+    serde_wasm_bindgen::from_value(body).map_err(|e| error(&e.to_string(), "invalid_request"))
+}
+
+fn response(body: impl Serialize) -> Result<JsValue, JsValue> {
+    // Plain objects rather than Maps, exactly as the server's JSON parses.
+    Ok(body.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).expect("response bodies always serialize"))
+}
+
+/// Commit to a value with a range proof (`POST /v1/value-commitments`).
+#[wasm_bindgen(js_name = commitValue)]
+pub fn commit_value(body: JsValue) -> Result<JsValue, JsValue> {
+    response(request::<ValueCommitmentRequest>(body)?.prove().map_err(zk_error)?)
+}
+
+/// Verify a value commitment's range proof (`POST /v1/value-commitments/verify`).
+#[wasm_bindgen(js_name = verifyValue)]
+pub fn verify_value(body: JsValue) -> Result<JsValue, JsValue> {
+    response(request::<ValueCommitmentVerifyRequest>(body)?.verify().map_err(zk_error)?)
+}
+
+/// Commit to a transaction hash (`POST /v1/tx-hash-commitments`).
+#[wasm_bindgen(js_name = commitTxHash)]
+pub fn commit_tx_hash(body: JsValue) -> Result<JsValue, JsValue> {
+    response(request::<TxHashCommitmentRequest>(body)?.prove().map_err(zk_error)?)
+}
+
+/// Verify a transaction hash commitment (`POST /v1/tx-hash-commitments/verify`).
+#[wasm_bindgen(js_name = verifyTxHash)]
+pub fn verify_tx_hash(body: JsValue) -> Result<JsValue, JsValue> {
+    response(request::<TxHashCommitmentVerifyRequest>(body)?.verify().map_err(zk_error)?)
+}
+
+/// Range-prove the four limbs of a transaction hash (`POST /v1/range-proofs`).
+#[wasm_bindgen(js_name = proveRange)]
+pub fn prove_range(body: JsValue) -> Result<JsValue, JsValue> {
+    response(request::<RangeProofRequest>(body)?.prove().map_err(zk_error)?)
+}
+
+/// Verify a four-limb range proof, Bulletproofs+ unless `scheme` says
+/// otherwise (`POST /v1/range-proofs/verify`).
+#[wasm_bindgen(js_name = verifyRange)]
+pub fn verify_range(body: JsValue) -> Result<JsValue, JsValue> {
+    response(request::<RangeProofVerifyRequest>(body)?.verify().map_err(zk_error)?)
+}
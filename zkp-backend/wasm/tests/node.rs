@@ -0,0 +1,76 @@
+//! Run with `wasm-pack test --node` (or `cargo test --target
+//! wasm32-unknown-unknown` with `wasm-bindgen-test-runner` installed).
+//!
+//! `server_proofs.json` was made by the native `zkp` CLI: value 42 with
+//! blinding 7, transaction hash `3c…3c` and binding tag `5a…5a`, the
+//! inputs of the server's self-test.
+
+#![cfg(target_arch = "wasm32")]
+
+use js_sys::{Reflect, JSON};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+use zkp_wasm::*;
+
+const TAG: &str = "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a";
+/// The server's self-test commitment to 42 with blinding 7.
+const PEDERSEN_VECTOR: &str = "a69ed12fb9c42f06a8c6ff8b535a781b613f46c7944d013c078eb0b5f3745c44";
+
+fn server_proof(name: &str) -> JsValue {
+    let fixtures = JSON::parse(include_str!("server_proofs.json")).unwrap();
+    Reflect::get(&fixtures, &name.into()).unwrap()
+}
+
+fn get(object: &JsValue, field: &str) -> JsValue {
+    Reflect::get(object, &field.into()).unwrap()
+}
+
+fn with(object: &JsValue, field: &str, value: &str) -> JsValue {
+    let copy = JSON::parse(&JSON::stringify(object).unwrap().as_string().unwrap()).unwrap();
+    Reflect::set(&copy, &field.into(), &value.into()).unwrap();
+    copy
+}
+
+fn verified(result: Result<JsValue, JsValue>) -> bool {
+    get(&result.unwrap(), "verified").as_bool().unwrap()
+}
+
+#[wasm_bindgen_test]
+fn server_proofs_verify_in_wasm() {
+    let value = server_proof("value_commitment");
+    assert!(verified(verify_value(value.clone())));
+    assert!(!verified(verify_value(with(&value, "binding_tag_hex", &"00".repeat(32)))));
+
+    let tx_hash = server_proof("tx_hash_commitment");
+    assert!(verified(verify_tx_hash(tx_hash.clone())));
+    assert!(!verified(verify_tx_hash(with(&tx_hash, "binding_tag_hex", &"00".repeat(32)))));
+
+    assert!(verified(verify_range(server_proof("range_proof"))));
+}
+
+#[wasm_bindgen_test]
+fn wasm_commitments_match_the_server() {
+    let request = JSON::parse(&format!(
+        r#"{{ "value": 42, "blinding_hex": "07{}", "binding_tag_hex": "{TAG}" }}"#,
+        "00".repeat(31)
+    ))
+    .unwrap();
+    let response = commit_value(request).unwrap();
+    assert_eq!(get(&response, "commitment").as_string().unwrap(), PEDERSEN_VECTOR);
+    assert!(verified(verify_value(with(&response, "binding_tag_hex", TAG))));
+
+    let request = JSON::parse(&format!(r#"{{ "tx_hash": "{}", "binding_tag_hex": "{TAG}" }}"#, "3c".repeat(32))).unwrap();
+    let response = commit_tx_hash(request).unwrap();
+    assert!(verified(verify_tx_hash(with(&response, "binding_tag_hex", TAG))));
+
+    let request = JSON::parse(&format!(r#"{{ "tx_hash": "{}" }}"#, "3c".repeat(32))).unwrap();
+    assert!(verified(verify_range(prove_range(request).unwrap())));
+}
+
+#[wasm_bindgen_test]
+fn errors_carry_the_server_codes() {
+    let error = verify_value(with(&server_proof("value_commitment"), "proof", "zz")).unwrap_err();
+    assert_eq!(get(&error, "code").as_string().unwrap(), "invalid_parameters");
+    let error = commit_tx_hash(JSON::parse("{}").unwrap()).unwrap_err();
+    assert_eq!(get(&error, "code").as_string().unwrap(), "invalid_request");
+}